@@ -23,6 +23,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(AiOptions {
             backend: Some(Backend::vertex_ai("europe-west4")),
             use_limited_use_app_check_tokens: Some(true),
+            ..Default::default()
         }),
     )
     .await?;
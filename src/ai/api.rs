@@ -2,7 +2,6 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, LazyLock, Mutex};
 
-use async_trait::async_trait;
 use serde_json::{json, Value};
 
 use crate::ai::backend::{Backend, BackendType};
@@ -11,6 +10,8 @@ use crate::ai::error::{internal_error, invalid_argument, AiError, AiErrorCode, A
 use crate::ai::helpers::{decode_instance_identifier, encode_instance_identifier};
 use crate::ai::public_types::{AiOptions, AiRuntimeOptions};
 use crate::ai::requests::{ApiSettings, PreparedRequest, RequestFactory, RequestOptions, Task};
+use crate::ai::transport::{AiByteStream, AiHttpTransport, ReqwestTransport};
+use crate::ai::types::GenerateContentRequest;
 use crate::app;
 use crate::app::{FirebaseApp, FirebaseOptions};
 use crate::app_check::FirebaseAppCheckInternal;
@@ -61,24 +62,6 @@ impl CacheKey {
 
 static AI_OVERRIDES: LazyLock<Mutex<HashMap<CacheKey, Arc<AiService>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
 
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-trait AiHttpTransport: Send + Sync {
-    async fn send(&self, request: PreparedRequest) -> AiResult<Value>;
-}
-
-struct ReqwestTransport {
-    client: reqwest::Client,
-}
-
-impl Default for ReqwestTransport {
-    fn default() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-        }
-    }
-}
-
 #[cfg(test)]
 #[derive(Default)]
 struct TestTokenOverrides {
@@ -87,59 +70,6 @@ struct TestTokenOverrides {
     limited_app_check: Option<String>,
 }
 
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-impl AiHttpTransport for ReqwestTransport {
-    async fn send(&self, request: PreparedRequest) -> AiResult<Value> {
-        let builder = request
-            .into_reqwest(&self.client)
-            .map_err(|err| internal_error(format!("failed to encode AI request: {err}")))?;
-
-        let response = builder
-            .send()
-            .await
-            .map_err(|err| AiError::new(AiErrorCode::FetchError, format!("failed to send AI request: {err}"), None))?;
-
-        let status = response.status();
-        let bytes = response.bytes().await.map_err(|err| {
-            AiError::new(AiErrorCode::FetchError, format!("failed to read AI response body: {err}"), None)
-        })?;
-
-        let parsed = serde_json::from_slice::<Value>(&bytes);
-
-        if !status.is_success() {
-            let mut data = CustomErrorData::default().with_status(status.as_u16());
-            if let Some(reason) = status.canonical_reason() {
-                data = data.with_status_text(reason);
-            }
-
-            return match parsed {
-                Ok(json) => {
-                    let message = AiService::extract_error_message(&json)
-                        .unwrap_or_else(|| format!("AI endpoint returned HTTP {status}"));
-                    Err(AiError::new(AiErrorCode::FetchError, message, Some(data.with_response(json))))
-                }
-                Err(_) => {
-                    let raw = String::from_utf8_lossy(&bytes).to_string();
-                    Err(AiError::new(
-                        AiErrorCode::FetchError,
-                        format!("AI endpoint returned HTTP {status}"),
-                        Some(data.with_response(json!({ "raw": raw }))),
-                    ))
-                }
-            };
-        }
-
-        parsed.map_err(|err| {
-            AiError::new(
-                AiErrorCode::ParseFailed,
-                format!("failed to parse AI response JSON: {err}"),
-                None,
-            )
-        })
-    }
-}
-
 #[derive(Clone, Debug, PartialEq)]
 pub struct GenerateTextRequest {
     pub prompt: String,
@@ -206,8 +136,12 @@ impl AiService {
         *self.inner.options.lock().unwrap() = options;
     }
 
-    #[cfg(test)]
-    fn set_transport_for_tests(&self, transport: Arc<dyn AiHttpTransport>) {
+    /// Replaces the HTTP transport used for subsequent REST requests.
+    ///
+    /// The service is shared per app and backend, so the transport applies to every handle
+    /// returned by [`get_ai`]; requests already in flight finish on the previous transport.
+    /// [`AiOptions::with_transport`] installs one while resolving the service.
+    pub fn set_transport(&self, transport: Arc<dyn AiHttpTransport>) {
         *self.inner.transport.lock().unwrap() = transport;
     }
 
@@ -409,24 +343,18 @@ impl AiService {
         Ok(GenerateTextResponse { text, model })
     }
 
-    async fn execute_prepared_request(&self, prepared: PreparedRequest) -> AiResult<Value> {
+    pub(crate) async fn execute_prepared_request(&self, prepared: PreparedRequest) -> AiResult<Value> {
         let transport = self.inner.transport.lock().unwrap().clone();
         transport.send(prepared).await
     }
 
+    pub(crate) async fn execute_streaming_request(&self, prepared: PreparedRequest) -> AiResult<AiByteStream> {
+        let transport = self.inner.transport.lock().unwrap().clone();
+        transport.send_stream(prepared).await
+    }
+
     fn build_generate_text_body(prompt: &str) -> Value {
-        json!({
-            "contents": [
-                {
-                    "role": "user",
-                    "parts": [
-                        {
-                            "text": prompt,
-                        }
-                    ]
-                }
-            ]
-        })
+        serde_json::to_value(GenerateContentRequest::from(prompt)).expect("prompt serialization should never fail")
     }
 
     fn extract_text_from_response(response: &Value) -> Option<String> {
@@ -464,19 +392,6 @@ impl AiService {
             .and_then(|value| value.as_str())
             .map(|value| value.to_string())
     }
-
-    fn extract_error_message(value: &Value) -> Option<String> {
-        if let Some(error) = value.get("error") {
-            if let Some(message) = error.get("message").and_then(|v| v.as_str()) {
-                return Some(message.to_string());
-            }
-        }
-
-        value
-            .get("message")
-            .and_then(|v| v.as_str())
-            .map(|message| message.to_string())
-    }
 }
 
 #[derive(Debug)]
@@ -578,6 +493,8 @@ pub fn register_ai_component() {
 /// When `options` is provided the backend identifier is encoded using the same
 /// rules as `encodeInstanceIdentifier` from the JavaScript SDK so that separate
 /// backend configurations create independent service instances.
+/// A transport set with [`AiOptions::with_transport`] is installed on the returned instance,
+/// including instances that were already cached.
 ///
 /// # Examples
 ///
@@ -597,6 +514,7 @@ pub fn register_ai_component() {
 ///     Some(AiOptions {
 ///         backend: Some(Backend::vertex_ai("us-central1")),
 ///         use_limited_use_app_check_tokens: Some(false),
+///         ..Default::default()
 ///     }),
 /// )
 /// .await
@@ -619,9 +537,17 @@ pub async fn get_ai(app: Option<FirebaseApp>, options: Option<AiOptions>) -> AiR
         use_limited_use_app_check_tokens: options.limited_use_app_check(),
     };
 
+    let transport = options.transport.get();
+    let configure = |service: &AiService| {
+        service.set_options(runtime_options.clone());
+        if let Some(transport) = &transport {
+            service.set_transport(transport.clone());
+        }
+    };
+
     let cache_key = CacheKey::new(app.name(), &identifier);
     if let Some(service) = Cache::get(&cache_key) {
-        service.set_options(runtime_options.clone());
+        configure(&service);
         return Ok(service);
     }
 
@@ -631,7 +557,7 @@ pub async fn get_ai(app: Option<FirebaseApp>, options: Option<AiOptions>) -> AiR
         .get_immediate_with_options::<AiService>(Some(&identifier), true)
         .map_err(|err| internal_error(err.to_string()))?
     {
-        service.set_options(runtime_options.clone());
+        configure(&service);
         Cache::insert(cache_key.clone(), service.clone());
         return Ok(service);
     }
@@ -644,7 +570,7 @@ pub async fn get_ai(app: Option<FirebaseApp>, options: Option<AiOptions>) -> AiR
         Some(&cache_key.identifier),
     ) {
         Ok(service) => {
-            service.set_options(runtime_options.clone());
+            configure(&service);
             Cache::insert(cache_key.clone(), service.clone());
             Ok(service)
         }
@@ -653,7 +579,7 @@ pub async fn get_ai(app: Option<FirebaseApp>, options: Option<AiOptions>) -> AiR
                 .get_immediate_with_options::<AiService>(Some(&cache_key.identifier), true)
                 .map_err(|err| internal_error(err.to_string()))?
             {
-                service.set_options(runtime_options.clone());
+                configure(&service);
                 Cache::insert(cache_key.clone(), service.clone());
                 Ok(service)
             } else {
@@ -661,11 +587,12 @@ pub async fn get_ai(app: Option<FirebaseApp>, options: Option<AiOptions>) -> AiR
                 let fallback = Arc::new(AiService::new(
                     app.clone(),
                     backend,
-                    runtime_options,
+                    runtime_options.clone(),
                     None,
                     container.get_provider("auth-internal"),
                     container.get_provider("app-check-internal"),
                 ));
+                configure(&fallback);
                 Cache::insert(cache_key.clone(), fallback.clone());
                 Ok(fallback)
             }
@@ -679,44 +606,35 @@ pub async fn get_ai_service(app: Option<FirebaseApp>) -> AiResult<Arc<AiService>
     get_ai(app, None).await
 }
 
+/// Transport double shared by the AI unit tests: replays queued responses and records requests.
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ai::backend::Backend;
-    use crate::ai::error::AiErrorCode;
-    use crate::ai::public_types::AiOptions;
-    use crate::app::initialize_app;
-    use crate::app::{FirebaseAppSettings, FirebaseOptions};
-    use async_trait::async_trait;
-    use serde_json::json;
+pub(crate) mod testing {
     use std::collections::VecDeque;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
 
-    fn unique_settings() -> FirebaseAppSettings {
-        static COUNTER: AtomicUsize = AtomicUsize::new(0);
-        FirebaseAppSettings {
-            name: Some(format!("ai-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
-            ..Default::default()
-        }
-    }
+    use async_trait::async_trait;
+    use serde_json::Value;
+
+    use crate::ai::error::{internal_error, AiResult};
+    use crate::ai::requests::PreparedRequest;
+    use crate::ai::transport::AiHttpTransport;
 
     #[derive(Clone, Default)]
-    struct TestTransport {
+    pub(crate) struct TestTransport {
         responses: Arc<Mutex<VecDeque<AiResult<Value>>>>,
         requests: Arc<Mutex<Vec<PreparedRequest>>>,
     }
 
     impl TestTransport {
-        fn new() -> Self {
+        pub(crate) fn new() -> Self {
             Self::default()
         }
 
-        fn push_response(&self, response: AiResult<Value>) {
+        pub(crate) fn push_response(&self, response: AiResult<Value>) {
             self.responses.lock().unwrap().push_back(response);
         }
 
-        fn take_requests(&self) -> Vec<PreparedRequest> {
+        pub(crate) fn take_requests(&self) -> Vec<PreparedRequest> {
             self.requests.lock().unwrap().clone()
         }
     }
@@ -733,6 +651,28 @@ mod tests {
                 .unwrap_or_else(|| Err(internal_error("no stub response configured")))
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::api::testing::TestTransport;
+    use crate::ai::backend::Backend;
+    use crate::ai::error::AiErrorCode;
+    use crate::ai::public_types::AiOptions;
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn unique_settings() -> FirebaseAppSettings {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("ai-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
 
     #[tokio::test(flavor = "current_thread")]
     async fn generate_text_includes_backend_info() {
@@ -757,7 +697,7 @@ mod tests {
         };
         let app = initialize_app(options, Some(unique_settings())).await.unwrap();
         let ai = get_ai_service(Some(app)).await.unwrap();
-        ai.set_transport_for_tests(Arc::new(transport.clone()));
+        ai.set_transport(Arc::new(transport.clone()));
         let response = ai
             .generate_text(GenerateTextRequest {
                 prompt: "Hello AI".to_string(),
@@ -801,15 +741,18 @@ mod tests {
 
         let ai = get_ai(
             Some(app),
-            Some(AiOptions {
-                backend: Some(Backend::google_ai()),
-                use_limited_use_app_check_tokens: Some(true),
-            }),
+            Some(
+                AiOptions {
+                    backend: Some(Backend::google_ai()),
+                    use_limited_use_app_check_tokens: Some(true),
+                    ..Default::default()
+                }
+                .with_transport(Arc::new(transport.clone())),
+            ),
         )
         .await
         .unwrap();
 
-        ai.set_transport_for_tests(Arc::new(transport.clone()));
         ai.override_tokens_for_tests(None, Some("standard-token".into()), Some("limited-token".into()));
 
         let response = ai
@@ -862,6 +805,7 @@ mod tests {
             Some(AiOptions {
                 backend: Some(Backend::google_ai()),
                 use_limited_use_app_check_tokens: None,
+                ..Default::default()
             }),
         )
         .await
@@ -872,6 +816,7 @@ mod tests {
             Some(AiOptions {
                 backend: Some(Backend::vertex_ai("europe-west4")),
                 use_limited_use_app_check_tokens: Some(true),
+                ..Default::default()
             }),
         )
         .await
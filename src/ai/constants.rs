@@ -12,6 +12,9 @@ pub const DEFAULT_DOMAIN: &str = "firebasevertexai.googleapis.com";
 /// Default API version used by Firebase AI (`packages/ai/src/constants.ts`).
 pub const DEFAULT_API_VERSION: &str = "v1beta";
 
+/// Service prefix of the Live API WebSocket endpoints (`packages/ai/src/constants.ts`).
+pub const LIVE_SERVICE_NAME: &str = "google.firebase.vertexai";
+
 /// Default fetch timeout (in milliseconds) used by the JS SDK (`packages/ai/src/constants.ts`).
 pub const DEFAULT_FETCH_TIMEOUT_MS: u64 = 180_000;
 
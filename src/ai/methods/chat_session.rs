@@ -0,0 +1,596 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use async_lock::{Mutex as AsyncMutex, MutexGuardArc};
+use futures::stream::{Stream, StreamExt};
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::methods::chat_session_helpers::{format_new_content, validate_chat_history};
+use crate::ai::methods::function_calling::FunctionHandlers;
+use crate::ai::models::generative_model::GenerativeModel;
+use crate::ai::stream::GenerateContentStream;
+use crate::ai::types::{
+    Content, GenerateContentRequest, GenerateContentResponse, GenerationConfig, Part, Role, SafetySetting,
+    StartChatParams, Tool, ToolConfig,
+};
+
+/// A message sent to a [`ChatSession`]: text, a part, or a list of parts.
+///
+/// A message made only of [`PartData::FunctionResponse`](crate::ai::PartData::FunctionResponse)
+/// parts is sent with the `function` role; anything else is sent as the user.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    pub parts: Vec<Part>,
+}
+
+impl From<&str> for ChatMessage {
+    fn from(text: &str) -> Self {
+        Self {
+            parts: vec![Part::text(text)],
+        }
+    }
+}
+
+impl From<String> for ChatMessage {
+    fn from(text: String) -> Self {
+        Self {
+            parts: vec![Part::text(text)],
+        }
+    }
+}
+
+impl From<Part> for ChatMessage {
+    fn from(part: Part) -> Self {
+        Self { parts: vec![part] }
+    }
+}
+
+impl From<Vec<Part>> for ChatMessage {
+    fn from(parts: Vec<Part>) -> Self {
+        Self { parts }
+    }
+}
+
+/// A multi-turn conversation with a [`GenerativeModel`].
+///
+/// Port of the Firebase JS SDK `ChatSession` class (`packages/ai/src/methods/chat-session.ts`).
+/// The session keeps the conversation history and sends it with every message. Only turns that
+/// produced a usable candidate are appended, so a failed or blocked message can simply be retried.
+/// Sends are serialised: a message waits until the previous one (including a streamed reply) has
+/// completed. Clones share the same history.
+#[derive(Clone, Debug)]
+pub struct ChatSession {
+    model: GenerativeModel,
+    generation_config: Option<GenerationConfig>,
+    safety_settings: Vec<SafetySetting>,
+    system_instruction: Option<Content>,
+    tools: Vec<Tool>,
+    tool_config: Option<ToolConfig>,
+    history: Arc<AsyncMutex<Vec<Content>>>,
+}
+
+impl ChatSession {
+    pub(crate) fn new(model: GenerativeModel, params: StartChatParams) -> AiResult<Self> {
+        validate_chat_history(&params.history)?;
+        Ok(Self {
+            model,
+            generation_config: params.generation_config,
+            safety_settings: params.safety_settings,
+            system_instruction: params.system_instruction,
+            tools: params.tools,
+            tool_config: params.tool_config,
+            history: Arc::new(AsyncMutex::new(params.history)),
+        })
+    }
+
+    /// Returns the conversation so far, waiting for an in-flight message to complete.
+    ///
+    /// The result can be passed back through [`StartChatParams::with_history`] to restore the
+    /// session later.
+    pub async fn history(&self) -> Vec<Content> {
+        self.history.lock().await.clone()
+    }
+
+    /// Sends `message` with the conversation history and records the exchange on success.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/invalid-content` for malformed messages and propagates request failures; the
+    /// history is left untouched in both cases.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use firebase_rs_sdk::ai::{AiService, GenerativeModel, StartChatParams};
+    /// # async fn example(ai: Arc<AiService>) -> firebase_rs_sdk::ai::AiResult<()> {
+    /// let model = GenerativeModel::new(ai, "gemini-2.0-flash", None)?;
+    /// let chat = model.start_chat(StartChatParams::new())?;
+    /// chat.send_message("Hi, I have two dogs.").await?;
+    /// let reply = chat.send_message("How many paws are in my house?").await?;
+    /// println!("{}", reply.text()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_message(&self, message: impl Into<ChatMessage>) -> AiResult<GenerateContentResponse> {
+        let mut history = self.history.lock().await;
+        let content = format_new_content(message.into().parts)?;
+        self.send_content(&mut history, content).await
+    }
+
+    /// Sends `message` and answers the model's function calls with `handlers` until it replies
+    /// without calling a function.
+    ///
+    /// Every exchange, including the function calls and their responses, is recorded in the
+    /// history. The session stays locked for the whole exchange, and on error the history is
+    /// restored to what it was before the call.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/invalid-argument` when the model calls a function without a registered handler,
+    /// `AI/response-error` when it is still calling functions after
+    /// [`FunctionHandlers::max_round_trips`] exchanges, and propagates handler and request errors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use firebase_rs_sdk::ai::{AiResult, ChatSession, FunctionHandlers};
+    /// # async fn example(chat: ChatSession) -> AiResult<()> {
+    /// use serde_json::json;
+    ///
+    /// let handlers = FunctionHandlers::new().register("get_weather", |args| async move {
+    ///     let city = args.get("city").and_then(|city| city.as_str()).unwrap_or_default().to_string();
+    ///     Ok(json!({ "city": city, "forecast": "sunny" }))
+    /// });
+    /// let reply = chat.send_message_with_functions("Weather in Paris?", &handlers).await?;
+    /// println!("{}", reply.text()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_message_with_functions(
+        &self,
+        message: impl Into<ChatMessage>,
+        handlers: &FunctionHandlers,
+    ) -> AiResult<GenerateContentResponse> {
+        let mut history = self.history.lock().await;
+        let committed = history.len();
+        let result = self
+            .exchange_function_calls(&mut history, message.into(), handlers)
+            .await;
+        if result.is_err() {
+            // Never leave a dangling function call (or response) in the history.
+            history.truncate(committed);
+        }
+        result
+    }
+
+    async fn exchange_function_calls(
+        &self,
+        history: &mut Vec<Content>,
+        message: ChatMessage,
+        handlers: &FunctionHandlers,
+    ) -> AiResult<GenerateContentResponse> {
+        let mut content = format_new_content(message.parts)?;
+        let mut round_trips = 0;
+        loop {
+            let response = self.send_content(history, content).await?;
+            let calls = response.function_calls()?;
+            if calls.is_empty() {
+                return Ok(response);
+            }
+            if round_trips == handlers.max_round_trips() {
+                return Err(AiError::new(
+                    AiErrorCode::ResponseError,
+                    format!(
+                        "Model was still calling functions after {} round trips",
+                        handlers.max_round_trips()
+                    ),
+                    None,
+                ));
+            }
+            round_trips += 1;
+            content = Content::new(Role::Function, handlers.dispatch(calls).await?);
+        }
+    }
+
+    /// Streams the reply to `message`.
+    ///
+    /// The exchange is appended to the history once the returned stream has been fully consumed
+    /// without errors; dropping it early discards the turn. Other sends wait until then.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`send_message`](Self::send_message); errors reported mid-stream are yielded by the
+    /// stream itself.
+    pub async fn send_message_stream(&self, message: impl Into<ChatMessage>) -> AiResult<ChatStream> {
+        let history = self.history.lock_arc().await;
+        let content = format_new_content(message.into().parts)?;
+        let request = self.build_request(&history, content.clone())?;
+        let inner = self.model.generate_content_stream(request).await?;
+        Ok(ChatStream {
+            inner,
+            pending: Some((history, content)),
+            failed: false,
+        })
+    }
+
+    async fn send_content(&self, history: &mut Vec<Content>, content: Content) -> AiResult<GenerateContentResponse> {
+        let request = self.build_request(history, content.clone())?;
+        let response = self.model.generate_content(request).await?;
+        record_turn(history, content, &response);
+        Ok(response)
+    }
+
+    fn build_request(&self, history: &[Content], content: Content) -> AiResult<GenerateContentRequest> {
+        let mut contents = history.to_vec();
+        contents.push(content);
+        validate_chat_history(&contents)?;
+        Ok(GenerateContentRequest {
+            contents,
+            system_instruction: self.system_instruction.clone(),
+            generation_config: self.generation_config.clone(),
+            safety_settings: self.safety_settings.clone(),
+            tools: self.tools.clone(),
+            tool_config: self.tool_config.clone(),
+        })
+    }
+}
+
+/// Appends the sent content and the model reply when the response has a usable candidate.
+fn record_turn(history: &mut Vec<Content>, content: Content, response: &GenerateContentResponse) {
+    let candidate = response.candidates.first().filter(|candidate| {
+        !candidate.content.parts.is_empty() && !candidate.finish_reason.is_some_and(|reason| reason.is_blocked())
+    });
+    match candidate {
+        Some(candidate) => {
+            history.push(content);
+            history.push(Content::new(Role::Model, candidate.content.parts.clone()));
+        }
+        None => log::warn!("sendMessage() was unsuccessful; the turn was not added to the chat history"),
+    }
+}
+
+/// Streamed reply of [`ChatSession::send_message_stream`].
+///
+/// Holds the session's history lock until the stream ends or is dropped.
+pub struct ChatStream {
+    inner: GenerateContentStream,
+    pending: Option<(MutexGuardArc<Vec<Content>>, Content)>,
+    failed: bool,
+}
+
+impl ChatStream {
+    /// Returns the chunks received so far merged into a single response.
+    pub fn aggregated(&self) -> &GenerateContentResponse {
+        self.inner.aggregated()
+    }
+
+    /// Drains the remaining chunks, records the turn and returns the aggregated response.
+    ///
+    /// # Errors
+    ///
+    /// Propagates the first stream error and returns `AI/response-error` when the prompt was
+    /// blocked.
+    pub async fn response(mut self) -> AiResult<GenerateContentResponse> {
+        while let Some(chunk) = self.next().await {
+            chunk?;
+        }
+        let aggregated = self.inner.aggregated();
+        match aggregated.prompt_block_error() {
+            Some(err) => Err(err),
+            None => Ok(aggregated.clone()),
+        }
+    }
+}
+
+impl Stream for ChatStream {
+    type Item = AiResult<GenerateContentResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let item = match this.inner.poll_next_unpin(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(item) => item,
+        };
+        match &item {
+            Some(Err(_)) => {
+                this.failed = true;
+                this.pending = None;
+            }
+            Some(Ok(_)) => {}
+            None => {
+                if let Some((mut history, content)) = this.pending.take() {
+                    if !this.failed {
+                        record_turn(&mut history, content, this.inner.aggregated());
+                    }
+                }
+            }
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl std::fmt::Debug for ChatStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChatStream")
+            .field("inner", &self.inner)
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::api::testing::TestTransport;
+    use crate::ai::api::AiService;
+    use crate::ai::requests::RequestOptions;
+    use crate::ai::types::FunctionDeclaration;
+    use crate::ai::types::ModelParams;
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn unique_settings() -> FirebaseAppSettings {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("chat-session-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn init_service() -> Arc<AiService> {
+        let options = FirebaseOptions {
+            api_key: Some("api".into()),
+            project_id: Some("project".into()),
+            app_id: Some("app".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        crate::ai::get_ai_service(Some(app)).await.unwrap()
+    }
+
+    async fn model_for(server: &MockServer) -> GenerativeModel {
+        let service = init_service().await;
+        let request_options = RequestOptions {
+            timeout: Some(Duration::from_secs(5)),
+            base_url: Some(server.base_url()),
+        };
+        GenerativeModel::new(service, "gemini-pro", Some(request_options)).unwrap()
+    }
+
+    fn turn(role: &str, text: &str) -> Value {
+        json!({ "role": role, "parts": [{ "text": text }] })
+    }
+
+    fn reply(text: &str) -> Value {
+        json!({ "candidates": [{ "content": turn("model", text), "finishReason": "STOP" }] })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn concurrent_sends_are_serialised_and_recorded() {
+        let server = start_mock_server();
+        let first = server.mock(|when, then| {
+            when.method(POST)
+                .path_contains(":generateContent")
+                .json_body(json!({ "contents": [turn("user", "first")] }));
+            then.status(200).json_body(reply("one"));
+        });
+        let second = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent").json_body(json!({
+                "contents": [turn("user", "first"), turn("model", "one"), turn("user", "second")]
+            }));
+            then.status(200).json_body(reply("two"));
+        });
+
+        let chat = model_for(&server).await.start_chat(StartChatParams::new()).unwrap();
+        let (one, two) = futures::future::join(chat.send_message("first"), chat.send_message("second")).await;
+        assert_eq!(one.unwrap().text().unwrap(), "one");
+        assert_eq!(two.unwrap().text().unwrap(), "two");
+        first.assert();
+        second.assert();
+
+        let history = chat.history().await;
+        let texts: Vec<String> = history.iter().map(|content| content.text().unwrap()).collect();
+        assert_eq!(texts, ["first", "one", "second", "two"]);
+        assert_eq!(history[1].role, Role::Model);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn failed_and_blocked_turns_are_not_recorded() {
+        let server = start_mock_server();
+        let mut failing = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent");
+            then.status(500).json_body(json!({ "error": { "message": "boom" } }));
+        });
+
+        let chat = model_for(&server).await.start_chat(StartChatParams::new()).unwrap();
+        assert_eq!(chat.send_message("hi").await.unwrap_err().code(), AiErrorCode::FetchError);
+        assert!(chat.history().await.is_empty());
+
+        failing.delete();
+        let _blocked = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent");
+            then.status(200).json_body(json!({
+                "candidates": [{ "content": turn("model", "partial"), "finishReason": "SAFETY" }]
+            }));
+        });
+        chat.send_message("hi").await.unwrap();
+        assert!(chat.history().await.is_empty());
+
+        let accepted = chat
+            .send_message(vec![Part::text("a"), Part::file_data("image/png", "gs://b/o")])
+            .await;
+        assert!(accepted.is_ok());
+        let err = chat.send_message(Vec::<Part>::new()).await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::InvalidContent);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn restored_history_is_validated_and_sent() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path_contains(":streamGenerateContent")
+                .query_param("alt", "sse")
+                .json_body(json!({
+                    "contents": [turn("user", "hello"), turn("model", "hi"), turn("user", "again")],
+                    "systemInstruction": turn("system", "be nice")
+                }));
+            then.status(200)
+                .header("content-type", "text/event-stream")
+                .body(concat!(
+                    "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"hey \"}]}}]}\n\n",
+                    "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"there\"}]}}]}\n\n"
+                ));
+        });
+
+        let model = model_for(&server).await;
+        let err = model
+            .start_chat(StartChatParams::new().with_history(vec![Content::model(vec![Part::text("hi")])]))
+            .unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::InvalidContent);
+
+        let history = vec![
+            Content::user(vec![Part::text("hello")]),
+            Content::model(vec![Part::text("hi")]),
+        ];
+        let params = StartChatParams::new()
+            .with_history(history)
+            .with_system_instruction(Content::system("be nice"));
+        let chat = model.start_chat(params).unwrap();
+
+        let stream = chat.send_message_stream("again").await.unwrap();
+        let response = stream.response().await.unwrap();
+        mock.assert();
+        assert_eq!(response.text().unwrap(), "hey there");
+
+        let history = chat.history().await;
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[3].text().unwrap(), "hey there");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn dropped_stream_discards_turn() {
+        let server = start_mock_server();
+        let _mock = server.mock(|when, then| {
+            when.method(POST).path_contains(":streamGenerateContent");
+            then.status(200)
+                .body("data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"x\"}]}}]}\n\n");
+        });
+
+        let chat = model_for(&server).await.start_chat(StartChatParams::new()).unwrap();
+        let mut stream = chat.send_message_stream("hi").await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        drop(stream);
+        assert!(chat.history().await.is_empty());
+    }
+
+    fn function_call_reply(name: &str, args: Value) -> Value {
+        json!({ "candidates": [{ "content": {
+            "role": "model",
+            "parts": [{ "functionCall": { "id": "call-1", "name": name, "args": args } }]
+        } }] })
+    }
+
+    async fn tool_chat(transport: &TestTransport) -> ChatSession {
+        let service = init_service().await;
+        service.set_transport(Arc::new(transport.clone()));
+        let tool = Tool::functions(vec![FunctionDeclaration::new("add", "Adds two numbers")]);
+        let params = ModelParams::new("gemini-pro")
+            .with_tools(vec![tool])
+            .with_tool_config(ToolConfig::auto());
+        let model = GenerativeModel::from_params(service, params, None).unwrap();
+        model.start_chat(StartChatParams::new()).unwrap()
+    }
+
+    fn adder() -> FunctionHandlers {
+        FunctionHandlers::new().register("add", |args| async move {
+            let a = args["a"].as_i64().unwrap();
+            let b = args["b"].as_i64().unwrap();
+            Ok(json!(a + b))
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn function_calls_are_dispatched_and_fed_back() {
+        let transport = TestTransport::new();
+        transport.push_response(Ok(function_call_reply("add", json!({ "a": 2, "b": 3 }))));
+        transport.push_response(Ok(reply("2 + 3 = 5")));
+
+        let chat = tool_chat(&transport).await;
+        let response = chat
+            .send_message_with_functions("What is 2 + 3?", &adder())
+            .await
+            .unwrap();
+        assert_eq!(response.text().unwrap(), "2 + 3 = 5");
+
+        let requests = transport.take_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].body["tools"],
+            json!([{ "functionDeclarations": [{ "name": "add", "description": "Adds two numbers" }] }])
+        );
+        assert_eq!(
+            requests[0].body["toolConfig"],
+            json!({ "functionCallingConfig": { "mode": "AUTO" } })
+        );
+        assert_eq!(
+            requests[1].body["contents"][2],
+            json!({ "role": "function", "parts": [{ "functionResponse": {
+                "id": "call-1", "name": "add", "response": { "result": 5 }
+            } }] })
+        );
+
+        let roles: Vec<Role> = chat.history().await.iter().map(|content| content.role).collect();
+        assert_eq!(roles, [Role::User, Role::Model, Role::Function, Role::Model]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn function_calling_stops_after_max_round_trips() {
+        let transport = TestTransport::new();
+        for _ in 0..3 {
+            transport.push_response(Ok(function_call_reply("add", json!({ "a": 1, "b": 1 }))));
+        }
+
+        let chat = tool_chat(&transport).await;
+        let handlers = adder().with_max_round_trips(2);
+        let err = chat.send_message_with_functions("loop", &handlers).await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ResponseError);
+        assert_eq!(transport.take_requests().len(), 3);
+        assert!(chat.history().await.is_empty());
+
+        transport.push_response(Ok(reply("hello")));
+        chat.send_message("hi").await.unwrap();
+        assert_eq!(transport.take_requests()[0].body["contents"].as_array().unwrap().len(), 1);
+        assert_eq!(chat.history().await.len(), 2);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn failed_function_dispatch_restores_history() {
+        let transport = TestTransport::new();
+        transport.push_response(Ok(reply("hello")));
+        let chat = tool_chat(&transport).await;
+        chat.send_message("hi").await.unwrap();
+        let before = chat.history().await;
+
+        transport.push_response(Ok(function_call_reply("unknown", json!({}))));
+        let err = chat.send_message_with_functions("again", &adder()).await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::InvalidArgument);
+        assert!(err.message().contains("unknown"));
+        assert_eq!(chat.history().await, before);
+
+        let failing = FunctionHandlers::new().register("add", |_args| async move {
+            Err(AiError::new(AiErrorCode::Error, "adder is down", None))
+        });
+        transport.push_response(Ok(function_call_reply("add", json!({ "a": 1, "b": 1 }))));
+        let err = chat.send_message_with_functions("add", &failing).await.unwrap_err();
+        assert_eq!(err.message(), "adder is down");
+        assert_eq!(chat.history().await, before);
+    }
+}
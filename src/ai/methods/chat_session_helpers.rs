@@ -0,0 +1,183 @@
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::types::{Content, Part, PartData, Role};
+
+/// Part kinds accepted for each role, named after their wire fields.
+///
+/// Ported from `VALID_PARTS_PER_ROLE` in `packages/ai/src/methods/chat-session-helpers.ts`; `fileData`
+/// is also accepted from users and `inlineData` from the model (image output). Model turns may also
+/// carry [`PartData::Unknown`] parts such as code execution or thought signatures.
+fn valid_parts(role: Role) -> &'static [&'static str] {
+    match role {
+        Role::User => &["text", "inlineData", "fileData"],
+        Role::Function => &["functionResponse"],
+        Role::Model => &["text", "inlineData", "functionCall", "unknown"],
+        Role::System => &["text"],
+    }
+}
+
+/// Roles that may directly precede `role` in a conversation.
+fn valid_previous_roles(role: Role) -> &'static [Role] {
+    match role {
+        Role::User | Role::Function => &[Role::Model],
+        Role::Model => &[Role::User, Role::Function],
+        Role::System => &[],
+    }
+}
+
+fn part_kind(part: &Part) -> &'static str {
+    match part.data {
+        PartData::Text(_) => "text",
+        PartData::InlineData(_) => "inlineData",
+        PartData::FileData(_) => "fileData",
+        PartData::FunctionCall(_) => "functionCall",
+        PartData::FunctionResponse(_) => "functionResponse",
+        PartData::Unknown(_) => "unknown",
+    }
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Model => "model",
+        Role::Function => "function",
+        Role::System => "system",
+    }
+}
+
+fn invalid_content(message: impl Into<String>) -> AiError {
+    AiError::new(AiErrorCode::InvalidContent, message, None)
+}
+
+/// Wraps the parts of a new chat message in a [`Content`] with the `user` or `function` role.
+///
+/// Mirrors `formatNewContent` in `packages/ai/src/requests/request-helpers.ts`: a message made only
+/// of function responses is sent with the `function` role, and mixing both kinds is rejected.
+pub(crate) fn format_new_content(parts: Vec<Part>) -> AiResult<Content> {
+    if parts.is_empty() {
+        return Err(invalid_content("Chat messages must contain at least one part."));
+    }
+    let responses = parts
+        .iter()
+        .filter(|part| matches!(part.data, PartData::FunctionResponse(_)))
+        .count();
+    match responses {
+        0 => Ok(Content::user(parts)),
+        count if count == parts.len() => Ok(Content::new(Role::Function, parts)),
+        _ => Err(invalid_content(
+            "Within a single message, FunctionResponse cannot be mixed with other type of part in the request for sending chat message.",
+        )),
+    }
+}
+
+/// Checks that `history` starts with a user turn, alternates roles correctly and only uses the
+/// part kinds allowed for each role.
+///
+/// Ported from `validateChatHistory` in `packages/ai/src/methods/chat-session-helpers.ts`.
+pub(crate) fn validate_chat_history(history: &[Content]) -> AiResult<()> {
+    let mut previous: Option<Role> = None;
+    for content in history {
+        let role = content.role;
+        if previous.is_none() && role != Role::User {
+            return Err(invalid_content(format!(
+                "First Content should be with role 'user', got {}",
+                role_name(role)
+            )));
+        }
+        if content.parts.is_empty() {
+            return Err(invalid_content("Each Content should have at least one part"));
+        }
+        let allowed = valid_parts(role);
+        if let Some(part) = content.parts.iter().find(|part| !allowed.contains(&part_kind(part))) {
+            return Err(invalid_content(format!(
+                "Content with role '{}' can't contain '{}' part",
+                role_name(role),
+                part_kind(part)
+            )));
+        }
+        if let Some(previous) = previous {
+            let valid = valid_previous_roles(role);
+            if !valid.contains(&previous) {
+                let names: Vec<&str> = valid.iter().map(|role| role_name(*role)).collect();
+                return Err(invalid_content(format!(
+                    "Content with role '{}' can't follow '{}'. Valid previous roles: {:?}",
+                    role_name(role),
+                    role_name(previous),
+                    names
+                )));
+            }
+        }
+        previous = Some(role);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::{FunctionCall, FunctionResponse};
+    use serde_json::json;
+
+    fn function_response() -> Part {
+        Part::function_response(FunctionResponse {
+            id: None,
+            name: "lookup".into(),
+            response: json!({ "ok": true }),
+        })
+    }
+
+    #[test]
+    fn format_new_content_selects_role() {
+        assert_eq!(format_new_content(vec![Part::text("hi")]).unwrap().role, Role::User);
+        assert_eq!(format_new_content(vec![function_response()]).unwrap().role, Role::Function);
+
+        let err = format_new_content(vec![Part::text("hi"), function_response()]).unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::InvalidContent);
+        assert!(format_new_content(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn validate_accepts_function_calling_turns() {
+        let history = vec![
+            Content::user(vec![Part::text("weather?")]),
+            Content::model(vec![Part::function_call(FunctionCall {
+                name: "lookup".into(),
+                ..Default::default()
+            })]),
+            Content::new(Role::Function, vec![function_response()]),
+            Content::model(vec![Part::text("sunny")]),
+        ];
+        validate_chat_history(&history).unwrap();
+        validate_chat_history(&[]).unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_invalid_histories() {
+        let cases = [
+            (
+                vec![Content::model(vec![Part::text("hi")])],
+                "First Content should be with role 'user'",
+            ),
+            (vec![Content::user(Vec::new())], "at least one part"),
+            (
+                vec![Content::user(vec![function_response()])],
+                "can't contain 'functionResponse'",
+            ),
+            (
+                vec![
+                    Content::user(vec![Part::text("a")]),
+                    Content::user(vec![Part::text("b")]),
+                ],
+                "Content with role 'user' can't follow 'user'",
+            ),
+            (
+                vec![Content::user(vec![Part::text("a")]), Content::system("b")],
+                "can't follow 'user'",
+            ),
+        ];
+        for (history, expected) in cases {
+            let err = validate_chat_history(&history).unwrap_err();
+            assert_eq!(err.code(), AiErrorCode::InvalidContent);
+            assert!(err.message().contains(expected), "{}", err.message());
+        }
+    }
+}
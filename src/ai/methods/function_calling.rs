@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use serde_json::{json, Map, Value};
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::types::{FunctionCall, FunctionResponse, Part};
+
+/// Number of model/function exchanges allowed per message when none is configured.
+pub const DEFAULT_MAX_FUNCTION_ROUND_TRIPS: usize = 10;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type FunctionHandlerFuture = BoxFuture<'static, AiResult<Value>>;
+#[cfg(target_arch = "wasm32")]
+pub type FunctionHandlerFuture = LocalBoxFuture<'static, AiResult<Value>>;
+
+type Handler = Arc<dyn Fn(Map<String, Value>) -> FunctionHandlerFuture + Send + Sync>;
+
+/// Async Rust handlers for the functions declared to the model, keyed by function name.
+///
+/// Used by [`ChatSession::send_message_with_functions`](crate::ai::ChatSession::send_message_with_functions)
+/// to answer the model's function calls. A handler receives the call arguments and returns the
+/// response payload; values that are not JSON objects are wrapped as `{"result": value}` because
+/// the API only accepts objects.
+#[derive(Clone)]
+pub struct FunctionHandlers {
+    handlers: HashMap<String, Handler>,
+    max_round_trips: usize,
+}
+
+impl Default for FunctionHandlers {
+    fn default() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            max_round_trips: DEFAULT_MAX_FUNCTION_ROUND_TRIPS,
+        }
+    }
+}
+
+impl FunctionHandlers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the handler invoked when the model calls `name`, replacing any previous one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn register<F, Fut>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(Map<String, Value>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AiResult<Value>> + Send + 'static,
+    {
+        self.handlers
+            .insert(name.into(), Arc::new(move |args| handler(args).boxed()));
+        self
+    }
+
+    /// Registers the handler invoked when the model calls `name`, replacing any previous one.
+    #[cfg(target_arch = "wasm32")]
+    pub fn register<F, Fut>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(Map<String, Value>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AiResult<Value>> + 'static,
+    {
+        self.handlers
+            .insert(name.into(), Arc::new(move |args| handler(args).boxed_local()));
+        self
+    }
+
+    /// Caps how many times the model may call functions before answering; defaults to
+    /// [`DEFAULT_MAX_FUNCTION_ROUND_TRIPS`].
+    pub fn with_max_round_trips(mut self, max_round_trips: usize) -> Self {
+        self.max_round_trips = max_round_trips;
+        self
+    }
+
+    pub fn max_round_trips(&self) -> usize {
+        self.max_round_trips
+    }
+
+    /// Runs the handlers for `calls` concurrently and returns the responses in call order.
+    pub(crate) async fn dispatch(&self, calls: Vec<FunctionCall>) -> AiResult<Vec<Part>> {
+        let pending = calls.into_iter().map(|call| {
+            let handler = self.handlers.get(&call.name).cloned();
+            async move {
+                let handler = handler.ok_or_else(|| {
+                    AiError::new(
+                        AiErrorCode::InvalidArgument,
+                        format!("No handler registered for function '{}'", call.name),
+                        None,
+                    )
+                })?;
+                let response = match handler(call.args).await? {
+                    Value::Object(object) => Value::Object(object),
+                    other => json!({ "result": other }),
+                };
+                Ok(Part::function_response(FunctionResponse {
+                    id: call.id,
+                    name: call.name,
+                    response,
+                }))
+            }
+        });
+        futures::future::try_join_all(pending).await
+    }
+}
+
+impl fmt::Debug for FunctionHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.handlers.keys().collect();
+        names.sort();
+        f.debug_struct("FunctionHandlers")
+            .field("functions", &names)
+            .field("max_round_trips", &self.max_round_trips)
+            .finish()
+    }
+}
@@ -0,0 +1,296 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use async_lock::Mutex as AsyncMutex;
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::methods::chat_session::ChatMessage;
+use crate::ai::methods::chat_session_helpers::format_new_content;
+use crate::ai::types::{FunctionResponse, GenerativeContentBlob, LiveServerMessage};
+use crate::ai::websocket::{ServerMessages, WebSocketConnection, CLOSE_NORMAL};
+
+/// A bidirectional conversation with a Live model over a WebSocket.
+///
+/// Port of `packages/ai/src/methods/live-session.ts`. Created by
+/// [`LiveGenerativeModel::connect`](crate::ai::LiveGenerativeModel::connect) once the setup
+/// handshake has completed. Sends may be issued while [`receive`](Self::receive) is being
+/// consumed; the server interleaves content, tool calls and turn boundaries on one stream.
+pub struct LiveSession {
+    connection: WebSocketConnection,
+    incoming: AsyncMutex<ServerMessages>,
+    closed: AtomicBool,
+}
+
+impl std::fmt::Debug for LiveSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveSession")
+            .field("closed", &self.is_closed())
+            .finish_non_exhaustive()
+    }
+}
+
+impl LiveSession {
+    pub(crate) fn new(connection: WebSocketConnection, incoming: ServerMessages) -> Self {
+        Self {
+            connection,
+            incoming: AsyncMutex::new(incoming),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` once [`close`](Self::close) has been called.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Sends a user turn; `turn_complete` tells the model to start answering.
+    pub async fn send(&self, message: impl Into<ChatMessage>, turn_complete: bool) -> AiResult<()> {
+        let content = format_new_content(message.into().parts)?;
+        self.send_json(json!({
+            "clientContent": {
+                "turns": [content],
+                "turnComplete": turn_complete,
+            }
+        }))
+        .await
+    }
+
+    /// Sends a complete text turn.
+    pub async fn send_text(&self, text: &str) -> AiResult<()> {
+        self.send(text, true).await
+    }
+
+    /// Streams realtime media, such as 16 kHz PCM audio (`audio/pcm`) or JPEG video frames.
+    pub async fn send_media_chunks(&self, chunks: Vec<GenerativeContentBlob>) -> AiResult<()> {
+        self.send_json(json!({ "realtimeInput": { "mediaChunks": chunks } }))
+            .await
+    }
+
+    /// Answers the function calls of a [`LiveServerMessage::ToolCall`].
+    pub async fn send_function_responses(&self, responses: Vec<FunctionResponse>) -> AiResult<()> {
+        self.send_json(json!({ "toolResponse": { "functionResponses": responses } }))
+            .await
+    }
+
+    /// Returns the messages sent by the server.
+    ///
+    /// The stream ends when the server closes the connection. An abnormal closure yields a final
+    /// `AI/fetch-error`. Unknown message kinds are logged and skipped.
+    pub fn receive(&self) -> impl Stream<Item = AiResult<LiveServerMessage>> + '_ {
+        stream::unfold(Some(self), |state| async move {
+            let session = state?;
+            if session.is_closed() {
+                return Some((Err(session_closed()), None));
+            }
+            let mut incoming = session.incoming.lock().await;
+            loop {
+                match incoming.next().await? {
+                    Ok(value) => match parse_server_message(value) {
+                        Ok(Some(message)) => return Some((Ok(message), Some(session))),
+                        Ok(None) => continue,
+                        Err(err) => return Some((Err(err), Some(session))),
+                    },
+                    Err(err) => return Some((Err(err), None)),
+                }
+            }
+        })
+    }
+
+    /// Closes the connection. Further sends fail with `AI/session-closed`.
+    pub async fn close(&self) -> AiResult<()> {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.connection.close(CLOSE_NORMAL, "Client closed session.").await
+    }
+
+    async fn send_json(&self, message: Value) -> AiResult<()> {
+        if self.is_closed() {
+            return Err(session_closed());
+        }
+        self.connection.send(message.to_string()).await
+    }
+}
+
+fn session_closed() -> AiError {
+    AiError::new(
+        AiErrorCode::SessionClosed,
+        "This LiveSession has been closed and cannot be used.",
+        None,
+    )
+}
+
+fn parse_server_message(value: Value) -> AiResult<Option<LiveServerMessage>> {
+    fn field<T: DeserializeOwned>(value: &Value, key: &str) -> AiResult<T> {
+        serde_json::from_value(value[key].clone()).map_err(|err| {
+            AiError::new(
+                AiErrorCode::ParseFailed,
+                format!("Failed to parse '{key}' message: {err}"),
+                None,
+            )
+        })
+    }
+
+    if value.get("serverContent").is_some() {
+        Ok(Some(LiveServerMessage::ServerContent(field(&value, "serverContent")?)))
+    } else if value.get("toolCall").is_some() {
+        Ok(Some(LiveServerMessage::ToolCall(field(&value, "toolCall")?)))
+    } else if value.get("toolCallCancellation").is_some() {
+        Ok(Some(LiveServerMessage::ToolCallCancellation(field(
+            &value,
+            "toolCallCancellation",
+        )?)))
+    } else {
+        log::warn!("Received an unexpected message from the Live API: {value}");
+        Ok(None)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::ai::backend::Backend;
+    use crate::ai::models::live_generative_model::LiveGenerativeModel;
+    use crate::ai::public_types::AiOptions;
+    use crate::ai::requests::RequestOptions;
+    use crate::ai::types::{LiveModelParams, LiveServerToolCallCancellation};
+    use crate::ai::websocket::testing::{recv_json, send_json, serve_once};
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn unique_settings() -> FirebaseAppSettings {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("live-session-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn model_for(base_url: String) -> LiveGenerativeModel {
+        let options = FirebaseOptions {
+            api_key: Some("api".into()),
+            project_id: Some("project".into()),
+            app_id: Some("app".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let service = crate::ai::get_ai(
+            Some(app),
+            Some(AiOptions {
+                backend: Some(Backend::google_ai()),
+                use_limited_use_app_check_tokens: None,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let request_options = RequestOptions {
+            timeout: None,
+            base_url: Some(base_url),
+        };
+        LiveGenerativeModel::new(service, LiveModelParams::new("gemini-live"), Some(request_options)).unwrap()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn exchanges_turns_tool_calls_and_media() {
+        let (base_url, server) = serve_once(|_, mut socket| async move {
+            recv_json(&mut socket).await;
+            send_json(&mut socket, json!({ "setupComplete": {} })).await;
+
+            assert_eq!(
+                recv_json(&mut socket).await,
+                json!({ "clientContent": {
+                    "turns": [{ "role": "user", "parts": [{ "text": "Is A1 in stock?" }] }],
+                    "turnComplete": true
+                } })
+            );
+            send_json(
+                &mut socket,
+                json!({ "serverContent": { "modelTurn": { "role": "model", "parts": [{ "text": "Checking" }] } } }),
+            )
+            .await;
+            send_json(&mut socket, json!({ "usageMetadata": { "totalTokenCount": 3 } })).await;
+            send_json(
+                &mut socket,
+                json!({ "toolCall": { "functionCalls": [{ "id": "call-1", "name": "stock", "args": { "sku": "A1" } }] } }),
+            )
+            .await;
+            send_json(&mut socket, json!({ "toolCallCancellation": { "functionIds": ["call-0"] } })).await;
+
+            assert_eq!(
+                recv_json(&mut socket).await,
+                json!({ "toolResponse": { "functionResponses": [
+                    { "id": "call-1", "name": "stock", "response": { "count": 4 } }
+                ] } })
+            );
+            let media = recv_json(&mut socket).await;
+            assert_eq!(media["realtimeInput"]["mediaChunks"][0]["mimeType"], "audio/pcm");
+            send_json(&mut socket, json!({ "serverContent": { "turnComplete": true } })).await;
+
+            loop {
+                match socket.next().await {
+                    Some(Ok(Message::Close(Some(frame)))) => {
+                        assert_eq!(u16::from(frame.code), CLOSE_NORMAL);
+                        assert_eq!(frame.reason, "Client closed session.");
+                        break;
+                    }
+                    Some(Ok(_)) => continue,
+                    other => panic!("expected a close frame, got {other:?}"),
+                }
+            }
+        })
+        .await;
+
+        let session = model_for(base_url).await.connect().await.unwrap();
+        session.send_text("Is A1 in stock?").await.unwrap();
+
+        let first: Vec<_> = session.receive().take(2).collect().await;
+        match &first[0] {
+            Ok(LiveServerMessage::ServerContent(content)) => {
+                assert_eq!(content.model_turn.as_ref().unwrap().text().as_deref(), Some("Checking"));
+                assert!(!content.turn_complete);
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+        let call = match &first[1] {
+            Ok(LiveServerMessage::ToolCall(call)) => call.function_calls[0].clone(),
+            other => panic!("unexpected message: {other:?}"),
+        };
+        assert_eq!(call.args["sku"], "A1");
+
+        session
+            .send_function_responses(vec![FunctionResponse {
+                id: call.id,
+                name: call.name,
+                response: json!({ "count": 4 }),
+            }])
+            .await
+            .unwrap();
+        session
+            .send_media_chunks(vec![GenerativeContentBlob::from_bytes("audio/pcm", &[0, 1, 2, 3])])
+            .await
+            .unwrap();
+
+        let rest: Vec<_> = session.receive().take(2).collect().await;
+        assert_eq!(
+            rest[0].as_ref().unwrap(),
+            &LiveServerMessage::ToolCallCancellation(LiveServerToolCallCancellation {
+                function_ids: vec!["call-0".into()]
+            })
+        );
+        assert!(matches!(&rest[1], Ok(LiveServerMessage::ServerContent(content)) if content.turn_complete));
+
+        session.close().await.unwrap();
+        assert!(session.is_closed());
+        let err = session.send_text("again").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::SessionClosed);
+        let received: Vec<_> = session.receive().collect().await;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].as_ref().unwrap_err().code(), AiErrorCode::SessionClosed);
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::stream;
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::stream::GenerateContentStream;
+use crate::ai::types::{
+    Content, FinishReason, GenerateContentCandidate, GenerateContentRequest, GenerateContentResponse, InferenceMode,
+    Part,
+};
+
+/// On-device model used by a hybrid [`GenerativeModel`](crate::ai::GenerativeModel).
+///
+/// Rust counterpart of the JS SDK `ChromeAdapter` (`packages/ai/src/types/chrome-adapter.ts`):
+/// instead of Chrome's built-in Prompt API, applications plug in their own local model. Requests
+/// arrive with the model defaults (generation config, system instruction, tools) already applied.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait LocalModelAdapter: Send + Sync {
+    /// Returns whether the local model can handle `request` right now.
+    async fn is_available(&self, request: &GenerateContentRequest) -> bool;
+
+    async fn generate_content(&self, request: GenerateContentRequest) -> AiResult<GenerateContentResponse>;
+
+    /// Streams the response; build the result with [`GenerateContentStream::from_chunks`].
+    async fn generate_content_stream(&self, request: GenerateContentRequest) -> AiResult<GenerateContentStream>;
+}
+
+/// Runs a request on the device or in the cloud according to `mode`.
+///
+/// Mirrors `callCloudOrDevice` in `packages/ai/src/requests/hybrid-helpers.ts`. With
+/// [`InferenceMode::PreferOnDevice`], adapter errors are logged and the request is retried in the
+/// cloud.
+pub(crate) async fn call_local_or_cloud<T, D, DF, C, CF>(
+    mode: InferenceMode,
+    adapter: Option<&Arc<dyn LocalModelAdapter>>,
+    request: &GenerateContentRequest,
+    on_device: D,
+    in_cloud: C,
+) -> AiResult<T>
+where
+    D: FnOnce(Arc<dyn LocalModelAdapter>) -> DF,
+    DF: Future<Output = AiResult<T>>,
+    C: FnOnce() -> CF,
+    CF: Future<Output = AiResult<T>>,
+{
+    match (mode, adapter) {
+        (InferenceMode::OnlyInCloud, _) | (InferenceMode::PreferOnDevice, None) => in_cloud().await,
+        (InferenceMode::OnlyOnDevice, None) => Err(on_device_unavailable()),
+        (InferenceMode::OnlyOnDevice, Some(adapter)) => {
+            if adapter.is_available(request).await {
+                on_device(adapter.clone()).await
+            } else {
+                Err(on_device_unavailable())
+            }
+        }
+        (InferenceMode::PreferOnDevice, Some(adapter)) => {
+            if !adapter.is_available(request).await {
+                return in_cloud().await;
+            }
+            match on_device(adapter.clone()).await {
+                Ok(response) => Ok(response),
+                Err(err) => {
+                    log::warn!("On-device inference failed, falling back to the cloud: {err}");
+                    in_cloud().await
+                }
+            }
+        }
+    }
+}
+
+fn on_device_unavailable() -> AiError {
+    AiError::new(
+        AiErrorCode::Unsupported,
+        "Inference mode is OnlyOnDevice, but an on-device model is not available.",
+        None,
+    )
+}
+
+/// Deterministic [`LocalModelAdapter`] for tests and examples.
+///
+/// Answers every request with the same text (streamed word by word), or fails every request, and
+/// records the requests it receives. Clones share their state.
+///
+/// ```
+/// use std::sync::Arc;
+/// use firebase_rs_sdk::ai::{AiService, AiResult, GenerativeModel, InferenceMode, TestLocalModelAdapter};
+///
+/// # async fn example(ai: Arc<AiService>) -> AiResult<()> {
+/// let local = TestLocalModelAdapter::replying("Hello from the device");
+/// let model = GenerativeModel::new(ai, "gemini-2.0-flash", None)?
+///     .with_local_model(Arc::new(local.clone()))
+///     .with_inference_mode(InferenceMode::OnlyOnDevice);
+/// assert_eq!(model.generate_content("Hi").await?.text()?, "Hello from the device");
+/// assert_eq!(local.requests().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TestLocalModelAdapter {
+    available: bool,
+    reply: Result<String, String>,
+    requests: Arc<Mutex<Vec<GenerateContentRequest>>>,
+}
+
+impl fmt::Debug for TestLocalModelAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestLocalModelAdapter")
+            .field("available", &self.available)
+            .field("reply", &self.reply)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TestLocalModelAdapter {
+    /// An available adapter that answers with `text`.
+    pub fn replying(text: impl Into<String>) -> Self {
+        Self {
+            available: true,
+            reply: Ok(text.into()),
+            requests: Arc::default(),
+        }
+    }
+
+    /// An available adapter whose generations fail with `AI/error` and `message`.
+    pub fn failing(message: impl Into<String>) -> Self {
+        Self {
+            reply: Err(message.into()),
+            ..Self::replying("")
+        }
+    }
+
+    /// An adapter that reports itself as unavailable.
+    pub fn unavailable() -> Self {
+        Self {
+            available: false,
+            ..Self::replying("")
+        }
+    }
+
+    /// Returns the requests received by `generate_content` and `generate_content_stream`.
+    pub fn requests(&self) -> Vec<GenerateContentRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn record(&self, request: GenerateContentRequest) -> AiResult<String> {
+        self.requests.lock().unwrap().push(request);
+        self.reply
+            .clone()
+            .map_err(|message| AiError::new(AiErrorCode::Error, message, None))
+    }
+}
+
+fn text_response(text: &str, finish_reason: Option<FinishReason>) -> GenerateContentResponse {
+    GenerateContentResponse {
+        candidates: vec![GenerateContentCandidate {
+            content: Content::model(vec![Part::text(text)]),
+            finish_reason,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl LocalModelAdapter for TestLocalModelAdapter {
+    async fn is_available(&self, _request: &GenerateContentRequest) -> bool {
+        self.available
+    }
+
+    async fn generate_content(&self, request: GenerateContentRequest) -> AiResult<GenerateContentResponse> {
+        let text = self.record(request)?;
+        Ok(text_response(&text, Some(FinishReason::Stop)))
+    }
+
+    async fn generate_content_stream(&self, request: GenerateContentRequest) -> AiResult<GenerateContentStream> {
+        let text = self.record(request)?;
+        let words: Vec<&str> = text.split_inclusive(' ').collect();
+        let chunks: Vec<AiResult<GenerateContentResponse>> = words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                let finish_reason = (index + 1 == words.len()).then_some(FinishReason::Stop);
+                Ok(text_response(word, finish_reason))
+            })
+            .collect();
+        Ok(GenerateContentStream::from_chunks(stream::iter(chunks)))
+    }
+}
@@ -0,0 +1,5 @@
+pub mod chat_session;
+mod chat_session_helpers;
+pub mod function_calling;
+pub mod live_session;
+pub mod local_model_adapter;
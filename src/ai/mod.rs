@@ -4,9 +4,14 @@ mod backend;
 mod constants;
 mod error;
 mod helpers;
+mod methods;
 mod models;
 mod public_types;
 mod requests;
+mod stream;
+mod transport;
+mod types;
+mod websocket;
 
 #[doc(inline)]
 pub use api::{get_ai, get_ai_service, register_ai_component, AiService, GenerateTextRequest, GenerateTextResponse};
@@ -17,11 +22,56 @@ pub use backend::{Backend, BackendType, GoogleAiBackend, VertexAiBackend};
 #[doc(inline)]
 pub use error::{internal_error, invalid_argument, AiError, AiErrorCode, AiResult, CustomErrorData, ErrorDetails};
 
+#[doc(inline)]
+pub use methods::chat_session::{ChatMessage, ChatSession, ChatStream};
+
+#[doc(inline)]
+pub use methods::function_calling::{FunctionHandlerFuture, FunctionHandlers, DEFAULT_MAX_FUNCTION_ROUND_TRIPS};
+
+#[doc(inline)]
+pub use methods::live_session::LiveSession;
+
+#[doc(inline)]
+pub use methods::local_model_adapter::{LocalModelAdapter, TestLocalModelAdapter};
+
+#[doc(inline)]
+pub use models::embedding_model::EmbeddingModel;
+
 #[doc(inline)]
 pub use models::generative_model::GenerativeModel;
 
 #[doc(inline)]
-pub use public_types::{AiOptions, AiRuntimeOptions};
+pub use models::imagen_model::ImagenModel;
+
+#[doc(inline)]
+pub use models::live_generative_model::LiveGenerativeModel;
+
+#[doc(inline)]
+pub use public_types::{AiOptions, AiRuntimeOptions, AiTransportOption};
 
 #[doc(inline)]
 pub use requests::{HttpMethod, PreparedRequest, RequestOptions};
+
+#[doc(inline)]
+pub use stream::GenerateContentStream;
+
+#[doc(inline)]
+pub use transport::{
+    AiByteStream, AiHttpTransport, ReqwestTransport, RetrySettings, TransportEvent, TransportObserver,
+    TransportRecorder,
+};
+
+#[doc(inline)]
+pub use types::{
+    BatchEmbedContentsResponse, BlockReason, Citation, CitationDate, CitationMetadata, Content, ContentEmbedding,
+    CountTokensResponse, EmbedContentRequest, EmbedContentResponse, EmbeddingTaskType, FileData, FinishReason,
+    FunctionCall, FunctionCallingConfig, FunctionCallingMode, FunctionDeclaration, FunctionResponse,
+    GenerateContentCandidate, GenerateContentRequest, GenerateContentResponse, GenerationConfig, GenerativeContentBlob,
+    HarmBlockMethod, HarmBlockThreshold, HarmCategory, HarmProbability, HarmSeverity, ImagenAspectRatio,
+    ImagenGcsImage, ImagenGenerationConfig, ImagenGenerationResponse, ImagenImageFormat, ImagenInlineImage,
+    ImagenModelParams, ImagenPersonFilterLevel, ImagenSafetyFilterLevel, ImagenSafetySettings, InferenceMode,
+    LiveGenerationConfig, LiveModelParams, LiveServerContent, LiveServerMessage, LiveServerToolCall,
+    LiveServerToolCallCancellation, Modality, ModalityTokenCount, ModelParams, Part, PartData, PrebuiltVoiceConfig,
+    PromptFeedback, ResponseModality, Role, SafetyRating, SafetySetting, Schema, SchemaType, SpeechConfig,
+    StartChatParams, Tool, ToolConfig, Transcription, UsageMetadata, VoiceConfig,
+};
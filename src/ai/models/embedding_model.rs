@@ -0,0 +1,359 @@
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+use crate::ai::api::AiService;
+use crate::ai::backend::BackendType;
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::models::generative_model::normalize_model_name;
+use crate::ai::requests::{RequestOptions, Task};
+use crate::ai::types::{
+    BatchEmbedContentsResponse, ContentEmbedding, EmbedContentRequest, EmbedContentResponse, PartData,
+};
+
+/// Model that turns content into embedding vectors, e.g. for retrieval indexes.
+///
+/// The Gemini Developer API exposes `embedContent`/`batchEmbedContents`; on Vertex AI the same
+/// calls are mapped onto the `predict` endpoint of the text embedding models, which only accept
+/// text content.
+#[derive(Clone, Debug)]
+pub struct EmbeddingModel {
+    service: Arc<AiService>,
+    model: String,
+    default_request_options: Option<RequestOptions>,
+}
+
+impl EmbeddingModel {
+    /// Creates an embedding model bound to `service`, e.g. `text-embedding-004`.
+    pub fn new(
+        service: Arc<AiService>,
+        model_name: impl Into<String>,
+        request_options: Option<RequestOptions>,
+    ) -> AiResult<Self> {
+        let model_name = model_name.into();
+        if model_name.trim().is_empty() {
+            return Err(AiError::new(
+                AiErrorCode::NoModel,
+                "Must provide a model name. Example: EmbeddingModel::new(ai, \"text-embedding-004\", None)",
+                None,
+            ));
+        }
+        let model = normalize_model_name(model_name, service.backend_type());
+        Ok(Self {
+            service,
+            model,
+            default_request_options: request_options,
+        })
+    }
+
+    /// Returns the fully qualified model resource identifier.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Embeds a single piece of content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/unsupported` for non-text content on Vertex AI, `AI/parse-failed` for
+    /// unexpected responses and `AI/fetch-error` for HTTP failures.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use firebase_rs_sdk::ai::{AiService, EmbedContentRequest, EmbeddingModel, EmbeddingTaskType};
+    /// # async fn example(ai: Arc<AiService>) -> firebase_rs_sdk::ai::AiResult<()> {
+    /// let model = EmbeddingModel::new(ai, "text-embedding-004", None)?;
+    /// let request = EmbedContentRequest::from("What is Rust?").with_task_type(EmbeddingTaskType::RetrievalQuery);
+    /// let response = model.embed_content(request).await?;
+    /// println!("{} dimensions", response.embedding.values.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn embed_content(&self, request: impl Into<EmbedContentRequest>) -> AiResult<EmbedContentResponse> {
+        let request = request.into();
+        match self.service.backend_type() {
+            BackendType::GoogleAi => self.send(Task::EmbedContent, encode(&request)?).await,
+            BackendType::VertexAi => {
+                let mut embeddings = self.predict(std::slice::from_ref(&request)).await?;
+                Ok(EmbedContentResponse {
+                    embedding: embeddings.pop().unwrap_or_default(),
+                })
+            }
+        }
+    }
+
+    /// Embeds several pieces of content in one call; embeddings are returned in request order.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`embed_content`](Self::embed_content). On Vertex AI all requests must share the
+    /// same `output_dimensionality`.
+    pub async fn batch_embed_contents(
+        &self,
+        requests: Vec<EmbedContentRequest>,
+    ) -> AiResult<BatchEmbedContentsResponse> {
+        if requests.is_empty() {
+            return Ok(BatchEmbedContentsResponse::default());
+        }
+        match self.service.backend_type() {
+            BackendType::GoogleAi => {
+                let mut entries = Vec::with_capacity(requests.len());
+                for request in &requests {
+                    let mut entry = encode(request)?;
+                    if let Value::Object(map) = &mut entry {
+                        map.insert("model".into(), Value::String(self.model.clone()));
+                    }
+                    entries.push(entry);
+                }
+                let response: BatchEmbedContentsResponse = self
+                    .send(Task::BatchEmbedContents, json!({ "requests": entries }))
+                    .await?;
+                check_count(response.embeddings.len(), requests.len())?;
+                Ok(response)
+            }
+            BackendType::VertexAi => Ok(BatchEmbedContentsResponse {
+                embeddings: self.predict(&requests).await?,
+            }),
+        }
+    }
+
+    /// Maps embedding requests onto the Vertex AI text embedding `predict` API.
+    async fn predict(&self, requests: &[EmbedContentRequest]) -> AiResult<Vec<ContentEmbedding>> {
+        let dimensionality = requests[0].output_dimensionality;
+        if requests
+            .iter()
+            .any(|request| request.output_dimensionality != dimensionality)
+        {
+            return Err(AiError::new(
+                AiErrorCode::InvalidArgument,
+                "All batched embedding requests must use the same output_dimensionality on Vertex AI",
+                None,
+            ));
+        }
+
+        let mut instances = Vec::with_capacity(requests.len());
+        for request in requests {
+            let mut instance = Map::new();
+            instance.insert("content".into(), Value::String(text_content(request)?));
+            if let Some(task_type) = request.task_type {
+                instance.insert("task_type".into(), encode(&task_type)?);
+            }
+            if let Some(title) = &request.title {
+                instance.insert("title".into(), Value::String(title.clone()));
+            }
+            instances.push(Value::Object(instance));
+        }
+        let mut body = json!({ "instances": instances });
+        if let Some(dimensionality) = dimensionality {
+            body["parameters"] = json!({ "outputDimensionality": dimensionality });
+        }
+
+        let response: Value = self.send(Task::Predict, body).await?;
+        let predictions = response
+            .get("predictions")
+            .and_then(Value::as_array)
+            .ok_or_else(|| parse_error("predict response is missing predictions"))?;
+        let embeddings = predictions
+            .iter()
+            .map(|prediction| {
+                let values = prediction
+                    .pointer("/embeddings/values")
+                    .cloned()
+                    .ok_or_else(|| parse_error("prediction is missing embeddings.values"))?;
+                serde_json::from_value(values)
+                    .map(|values| ContentEmbedding { values })
+                    .map_err(|err| parse_error(format!("invalid embedding values: {err}")))
+            })
+            .collect::<AiResult<Vec<_>>>()?;
+        check_count(embeddings.len(), requests.len())?;
+        Ok(embeddings)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, task: Task, body: Value) -> AiResult<T> {
+        let factory = self.service.request_factory().await?;
+        let prepared =
+            factory.construct_request(&self.model, task, false, body, self.default_request_options.clone())?;
+        let value = self.service.execute_prepared_request(prepared).await?;
+        serde_json::from_value(value)
+            .map_err(|err| parse_error(format!("failed to parse {} response: {err}", task.as_operation())))
+    }
+}
+
+fn text_content(request: &EmbedContentRequest) -> AiResult<String> {
+    let mut texts = Vec::with_capacity(request.content.parts.len());
+    for part in &request.content.parts {
+        match &part.data {
+            PartData::Text(text) => texts.push(text.as_str()),
+            _ => {
+                return Err(AiError::new(
+                    AiErrorCode::Unsupported,
+                    "Vertex AI embeddings only support text parts",
+                    None,
+                ))
+            }
+        }
+    }
+    Ok(texts.join("\n"))
+}
+
+fn check_count(received: usize, expected: usize) -> AiResult<()> {
+    if received == expected {
+        Ok(())
+    } else {
+        Err(parse_error(format!(
+            "expected {expected} embeddings but the response contained {received}"
+        )))
+    }
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> AiResult<Value> {
+    serde_json::to_value(value)
+        .map_err(|err| AiError::new(AiErrorCode::InvalidContent, format!("failed to encode request: {err}"), None))
+}
+
+fn parse_error(message: impl Into<String>) -> AiError {
+    AiError::new(AiErrorCode::ParseFailed, message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::backend::Backend;
+    use crate::ai::public_types::AiOptions;
+    use crate::ai::types::{EmbeddingTaskType, Part};
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use std::time::Duration;
+
+    fn unique_settings() -> FirebaseAppSettings {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("embedding-model-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn model_for(server: &MockServer, backend: Backend) -> EmbeddingModel {
+        let options = FirebaseOptions {
+            api_key: Some("api".into()),
+            project_id: Some("project".into()),
+            app_id: Some("app".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let service = crate::ai::get_ai(
+            Some(app),
+            Some(AiOptions {
+                backend: Some(backend),
+                use_limited_use_app_check_tokens: None,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let request_options = RequestOptions {
+            timeout: Some(Duration::from_secs(5)),
+            base_url: Some(server.base_url()),
+        };
+        EmbeddingModel::new(service, "text-embedding-004", Some(request_options)).unwrap()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn google_ai_embeds_single_and_batched_content() {
+        let server = start_mock_server();
+        let single = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/models/text-embedding-004:embedContent")
+                .header("x-goog-api-key", "api")
+                .json_body(json!({
+                    "content": { "role": "user", "parts": [{ "text": "hello" }] },
+                    "taskType": "RETRIEVAL_QUERY",
+                    "outputDimensionality": 3
+                }));
+            then.status(200)
+                .json_body(json!({ "embedding": { "values": [0.1, 0.2, 0.3] } }));
+        });
+        let batch = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/models/text-embedding-004:batchEmbedContents")
+                .json_body(json!({ "requests": [
+                    { "model": "models/text-embedding-004", "content": { "role": "user", "parts": [{ "text": "a" }] } },
+                    {
+                        "model": "models/text-embedding-004",
+                        "content": { "role": "user", "parts": [{ "text": "b" }] },
+                        "taskType": "RETRIEVAL_DOCUMENT",
+                        "title": "B"
+                    }
+                ] }));
+            then.status(200)
+                .json_body(json!({ "embeddings": [{ "values": [1.0] }, { "values": [2.0] }] }));
+        });
+
+        let model = model_for(&server, Backend::google_ai()).await;
+        let request = EmbedContentRequest::from("hello")
+            .with_task_type(EmbeddingTaskType::RetrievalQuery)
+            .with_output_dimensionality(3);
+        let response = model.embed_content(request).await.unwrap();
+        single.assert();
+        assert_eq!(response.embedding.values, vec![0.1, 0.2, 0.3]);
+
+        let response = model
+            .batch_embed_contents(vec![
+                EmbedContentRequest::from("a"),
+                EmbedContentRequest::from("b")
+                    .with_task_type(EmbeddingTaskType::RetrievalDocument)
+                    .with_title("B"),
+            ])
+            .await
+            .unwrap();
+        batch.assert();
+        let values: Vec<Vec<f32>> = response.embeddings.into_iter().map(|e| e.values).collect();
+        assert_eq!(values, vec![vec![1.0], vec![2.0]]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn vertex_ai_maps_embeddings_to_predict() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path(
+                    "/v1beta/projects/project/locations/us-central1/publishers/google/models/text-embedding-004:predict",
+                )
+                .json_body(json!({
+                    "instances": [
+                        { "content": "first" },
+                        { "content": "second", "task_type": "CLUSTERING" }
+                    ],
+                    "parameters": { "outputDimensionality": 2 }
+                }));
+            then.status(200).json_body(json!({ "predictions": [
+                { "embeddings": { "values": [0.5, 0.5], "statistics": { "token_count": 1 } } },
+                { "embeddings": { "values": [0.25, 0.75] } }
+            ] }));
+        });
+
+        let model = model_for(&server, Backend::vertex_ai("us-central1")).await;
+        let response = model
+            .batch_embed_contents(vec![
+                EmbedContentRequest::from("first").with_output_dimensionality(2),
+                EmbedContentRequest::from("second")
+                    .with_task_type(EmbeddingTaskType::Clustering)
+                    .with_output_dimensionality(2),
+            ])
+            .await
+            .unwrap();
+        mock.assert();
+        assert_eq!(response.embeddings[1].values, vec![0.25, 0.75]);
+
+        let image =
+            EmbedContentRequest::new(crate::ai::types::Content::user(vec![Part::inline_data("image/png", b"png")]));
+        let err = model.embed_content(image).await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::Unsupported);
+    }
+}
@@ -1,20 +1,53 @@
+use std::fmt;
 use std::sync::Arc;
 
-use serde_json::Value;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
 
 use crate::ai::api::AiService;
 use crate::ai::backend::BackendType;
-use crate::ai::error::AiResult;
+use crate::ai::error::{AiError, AiErrorCode, AiResult, CustomErrorData};
+use crate::ai::methods::chat_session::ChatSession;
+use crate::ai::methods::local_model_adapter::{call_local_or_cloud, LocalModelAdapter};
 use crate::ai::requests::{PreparedRequest, RequestOptions, Task};
+use crate::ai::stream::GenerateContentStream;
+use crate::ai::types::{
+    Content, CountTokensResponse, GenerateContentRequest, GenerateContentResponse, GenerationConfig, InferenceMode,
+    ModelParams, SafetySetting, StartChatParams, Tool, ToolConfig,
+};
 
 /// Port of the Firebase JS SDK `GenerativeModel` class.
 ///
-/// Reference: `packages/ai/src/models/generative-model.ts`.
-#[derive(Clone, Debug)]
+/// Reference: `packages/ai/src/models/generative-model.ts`. A model with a
+/// [`LocalModelAdapter`] runs hybrid inference according to its [`InferenceMode`].
+#[derive(Clone)]
 pub struct GenerativeModel {
     service: Arc<AiService>,
     model: String,
+    generation_config: Option<GenerationConfig>,
+    safety_settings: Vec<SafetySetting>,
+    system_instruction: Option<Content>,
+    tools: Vec<Tool>,
+    tool_config: Option<ToolConfig>,
     default_request_options: Option<RequestOptions>,
+    inference_mode: InferenceMode,
+    local_model: Option<Arc<dyn LocalModelAdapter>>,
+}
+
+impl fmt::Debug for GenerativeModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GenerativeModel")
+            .field("model", &self.model)
+            .field("generation_config", &self.generation_config)
+            .field("safety_settings", &self.safety_settings)
+            .field("system_instruction", &self.system_instruction)
+            .field("tools", &self.tools)
+            .field("tool_config", &self.tool_config)
+            .field("default_request_options", &self.default_request_options)
+            .field("inference_mode", &self.inference_mode)
+            .field("local_model", &self.local_model.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl GenerativeModel {
@@ -27,20 +60,61 @@ impl GenerativeModel {
         model_name: impl Into<String>,
         request_options: Option<RequestOptions>,
     ) -> AiResult<Self> {
+        Self::from_params(service, ModelParams::new(model_name), request_options)
+    }
+
+    /// Creates a generative model whose generation config, safety settings, system instruction and
+    /// tools are applied to every request that does not set its own.
+    pub fn from_params(
+        service: Arc<AiService>,
+        params: ModelParams,
+        request_options: Option<RequestOptions>,
+    ) -> AiResult<Self> {
+        if params.model.trim().is_empty() {
+            return Err(AiError::new(
+                AiErrorCode::NoModel,
+                "Must provide a model name. Example: GenerativeModel::new(ai, \"gemini-2.0-flash\", None)",
+                None,
+            ));
+        }
         let backend_type = service.backend_type();
-        let model = normalize_model_name(model_name.into(), backend_type);
+        let model = normalize_model_name(params.model, backend_type);
         Ok(Self {
             service,
             model,
+            generation_config: params.generation_config,
+            safety_settings: params.safety_settings,
+            system_instruction: params.system_instruction,
+            tools: params.tools,
+            tool_config: params.tool_config,
             default_request_options: request_options,
+            inference_mode: InferenceMode::default(),
+            local_model: None,
         })
     }
 
+    /// Installs an on-device model used according to the [`InferenceMode`].
+    pub fn with_local_model(mut self, adapter: Arc<dyn LocalModelAdapter>) -> Self {
+        self.local_model = Some(adapter);
+        self
+    }
+
+    /// Chooses between the local model and the cloud. Defaults to
+    /// [`InferenceMode::PreferOnDevice`], which uses the cloud when no local model is installed.
+    pub fn with_inference_mode(mut self, mode: InferenceMode) -> Self {
+        self.inference_mode = mode;
+        self
+    }
+
     /// Returns the fully qualified model resource identifier.
     pub fn model(&self) -> &str {
         &self.model
     }
 
+    pub fn inference_mode(&self) -> InferenceMode {
+        self.inference_mode
+    }
+
     /// Prepares a `generateContent` request using the stored API settings.
     pub async fn prepare_generate_content_request(
         &self,
@@ -51,9 +125,302 @@ impl GenerativeModel {
         let effective_options = request_options.or_else(|| self.default_request_options.clone());
         factory.construct_request(&self.model, Task::GenerateContent, false, body, effective_options)
     }
+
+    /// Generates a response for `request`, which may be a prompt string, a list of parts or a full
+    /// [`GenerateContentRequest`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/response-error` when the backend blocks the prompt, `AI/parse-failed` when the
+    /// response cannot be decoded, and `AI/fetch-error` for HTTP failures. With
+    /// [`InferenceMode::OnlyOnDevice`], returns `AI/unsupported` when the local model is
+    /// unavailable and propagates its errors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use firebase_rs_sdk::ai::{AiService, GenerativeModel};
+    /// # async fn example(ai: Arc<AiService>) -> firebase_rs_sdk::ai::AiResult<()> {
+    /// let model = GenerativeModel::new(ai, "gemini-2.0-flash", None)?;
+    /// let response = model.generate_content("Write a haiku about Rust").await?;
+    /// println!("{}", response.text()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate_content(
+        &self,
+        request: impl Into<GenerateContentRequest>,
+    ) -> AiResult<GenerateContentResponse> {
+        let request = self.build_request(request.into())?;
+        let local_request = request.clone();
+        let response = call_local_or_cloud(
+            self.inference_mode,
+            self.local_model.as_ref(),
+            &request,
+            |adapter| async move { adapter.generate_content(local_request).await },
+            || self.generate_content_in_cloud(&request),
+        )
+        .await?;
+        match response.prompt_block_error() {
+            Some(err) => Err(err),
+            None => Ok(response),
+        }
+    }
+
+    /// Generates JSON output and deserialises it into `T`.
+    ///
+    /// Forces `response_mime_type` to `application/json` on the request (or model) generation config;
+    /// set [`GenerationConfig::response_schema`] to constrain the output to the shape of `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/parse-failed` when the model output is not valid JSON for `T`; the raw output is
+    /// attached as `{"text": ...}` in the error's custom data. Other errors are those of
+    /// [`generate_content`](Self::generate_content).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use firebase_rs_sdk::ai::{AiResult, GenerateContentRequest, GenerationConfig, GenerativeModel, Schema};
+    /// # async fn example(model: GenerativeModel) -> AiResult<()> {
+    /// #[derive(serde::Deserialize)]
+    /// struct City {
+    ///     name: String,
+    ///     population: u64,
+    /// }
+    ///
+    /// let schema = Schema::array(
+    ///     Schema::object()
+    ///         .property("name", Schema::string())
+    ///         .property("population", Schema::integer()),
+    /// );
+    /// let request = GenerateContentRequest::from("List the three largest cities in Italy.").with_generation_config(
+    ///     GenerationConfig {
+    ///         response_schema: Some(schema),
+    ///         ..Default::default()
+    ///     },
+    /// );
+    /// let cities: Vec<City> = model.generate_object(request).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate_object<T: DeserializeOwned>(
+        &self,
+        request: impl Into<GenerateContentRequest>,
+    ) -> AiResult<T> {
+        let mut request = request.into();
+        let mut config = request
+            .generation_config
+            .take()
+            .or_else(|| self.generation_config.clone())
+            .unwrap_or_default();
+        config.response_mime_type = Some("application/json".into());
+        request.generation_config = Some(config);
+
+        let text = self.generate_content(request).await?.text()?;
+        serde_json::from_str(&text).map_err(|err| {
+            AiError::new(
+                AiErrorCode::ParseFailed,
+                format!("Model output could not be parsed as {}: {err}", std::any::type_name::<T>()),
+                Some(CustomErrorData::default().with_response(json!({ "text": text }))),
+            )
+        })
+    }
+
+    /// Streams the response to `request` as it is generated.
+    ///
+    /// Calls `streamGenerateContent?alt=sse`. Each item of the returned stream is a partial
+    /// response; [`GenerateContentStream::response`] drains the rest and returns all chunks merged
+    /// into a single response. Dropping the stream cancels the underlying HTTP request.
+    ///
+    /// # Errors
+    ///
+    /// Request validation and HTTP failures are returned directly. Errors reported mid-stream
+    /// (error frames, malformed chunks, dropped connections) are yielded as the final stream item.
+    /// With [`InferenceMode::PreferOnDevice`] only a failure to start the local stream falls back
+    /// to the cloud.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use firebase_rs_sdk::ai::{AiService, GenerativeModel};
+    /// # async fn example(ai: Arc<AiService>) -> firebase_rs_sdk::ai::AiResult<()> {
+    /// use futures::StreamExt;
+    ///
+    /// let model = GenerativeModel::new(ai, "gemini-2.0-flash", None)?;
+    /// let mut stream = model.generate_content_stream("Tell me a story").await?;
+    /// while let Some(chunk) = stream.next().await {
+    ///     print!("{}", chunk?.text()?);
+    /// }
+    /// let full = stream.aggregated();
+    /// println!("\n{:?}", full.usage_metadata);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate_content_stream(
+        &self,
+        request: impl Into<GenerateContentRequest>,
+    ) -> AiResult<GenerateContentStream> {
+        let request = self.build_request(request.into())?;
+        let local_request = request.clone();
+        call_local_or_cloud(
+            self.inference_mode,
+            self.local_model.as_ref(),
+            &request,
+            |adapter| async move { adapter.generate_content_stream(local_request).await },
+            || self.generate_content_stream_in_cloud(&request),
+        )
+        .await
+    }
+
+    async fn generate_content_in_cloud(&self, request: &GenerateContentRequest) -> AiResult<GenerateContentResponse> {
+        let prepared = self.prepare_generate_content_request(encode(request)?, None).await?;
+        let value = self.service.execute_prepared_request(prepared).await?;
+        serde_json::from_value(value).map_err(|err| {
+            AiError::new(
+                AiErrorCode::ParseFailed,
+                format!("failed to parse generateContent response: {err}"),
+                None,
+            )
+        })
+    }
+
+    async fn generate_content_stream_in_cloud(
+        &self,
+        request: &GenerateContentRequest,
+    ) -> AiResult<GenerateContentStream> {
+        let body = encode(request)?;
+        let factory = self.service.request_factory().await?;
+        let prepared = factory.construct_request(
+            &self.model,
+            Task::StreamGenerateContent,
+            true,
+            body,
+            self.default_request_options.clone(),
+        )?;
+        let bytes = self.service.execute_streaming_request(prepared).await?;
+        Ok(GenerateContentStream::new(bytes))
+    }
+
+    /// Counts the tokens `request` would use, including the model's default system instruction,
+    /// tools and generation config.
+    ///
+    /// The Gemini Developer API expects the request wrapped in a `generateContentRequest` envelope
+    /// naming the model (`mapCountTokensRequest` in `packages/ai/src/googleai-mappers.ts`), while
+    /// Vertex AI takes the contents directly and rejects safety settings and tool config.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`generate_content`](Self::generate_content), minus the prompt-blocking checks.
+    /// Tokens are always counted in the cloud, so [`InferenceMode::OnlyOnDevice`] models return
+    /// `AI/unsupported`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use firebase_rs_sdk::ai::{AiResult, GenerativeModel};
+    /// # async fn example(model: GenerativeModel) -> AiResult<()> {
+    /// let count = model.count_tokens("How long is this prompt?").await?;
+    /// println!("{} tokens", count.total_tokens);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn count_tokens(&self, request: impl Into<GenerateContentRequest>) -> AiResult<CountTokensResponse> {
+        if self.inference_mode == InferenceMode::OnlyOnDevice {
+            return Err(AiError::new(
+                AiErrorCode::Unsupported,
+                "count_tokens() is not supported for on-device models.",
+                None,
+            ));
+        }
+        let mut request = self.build_request(request.into())?;
+        let body = match self.service.backend_type() {
+            BackendType::GoogleAi => {
+                let mut inner = encode(&request)?;
+                if let Value::Object(map) = &mut inner {
+                    map.insert("model".into(), Value::String(self.model.clone()));
+                }
+                json!({ "generateContentRequest": inner })
+            }
+            BackendType::VertexAi => {
+                request.safety_settings.clear();
+                request.tool_config = None;
+                encode(&request)?
+            }
+        };
+        let factory = self.service.request_factory().await?;
+        let prepared = factory.construct_request(
+            &self.model,
+            Task::CountTokens,
+            false,
+            body,
+            self.default_request_options.clone(),
+        )?;
+        let value = self.service.execute_prepared_request(prepared).await?;
+        serde_json::from_value(value).map_err(|err| {
+            AiError::new(
+                AiErrorCode::ParseFailed,
+                format!("failed to parse countTokens response: {err}"),
+                None,
+            )
+        })
+    }
+
+    /// Starts a multi-turn [`ChatSession`] that reuses this model and its defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/invalid-content` when `params.history` does not start with a user turn,
+    /// alternates roles incorrectly or contains parts that are not valid for their role.
+    pub fn start_chat(&self, params: StartChatParams) -> AiResult<ChatSession> {
+        ChatSession::new(self.clone(), params)
+    }
+
+    /// Fills unset request fields with the model defaults and validates backend support.
+    fn build_request(&self, mut request: GenerateContentRequest) -> AiResult<GenerateContentRequest> {
+        if request.contents.is_empty() {
+            return Err(AiError::new(
+                AiErrorCode::InvalidContent,
+                "generateContent requests must include at least one content entry",
+                None,
+            ));
+        }
+        if request.generation_config.is_none() {
+            request.generation_config = self.generation_config.clone();
+        }
+        if request.safety_settings.is_empty() {
+            request.safety_settings = self.safety_settings.clone();
+        }
+        if request.system_instruction.is_none() {
+            request.system_instruction = self.system_instruction.clone();
+        }
+        if request.tools.is_empty() {
+            request.tools = self.tools.clone();
+        }
+        if request.tool_config.is_none() {
+            request.tool_config = self.tool_config.clone();
+        }
+        if self.service.backend_type() == BackendType::GoogleAi
+            && request.safety_settings.iter().any(|setting| setting.method.is_some())
+        {
+            return Err(AiError::new(
+                AiErrorCode::Unsupported,
+                "SafetySetting.method is not supported in the Gemini Developer API. Please remove this property.",
+                None,
+            ));
+        }
+        Ok(request)
+    }
 }
 
-fn normalize_model_name(model: String, backend_type: BackendType) -> String {
+fn encode(request: &GenerateContentRequest) -> AiResult<Value> {
+    serde_json::to_value(request)
+        .map_err(|err| AiError::new(AiErrorCode::InvalidContent, format!("failed to encode request: {err}"), None))
+}
+
+pub(crate) fn normalize_model_name(model: String, backend_type: BackendType) -> String {
     match backend_type {
         BackendType::GoogleAi => normalize_google_ai_model(model),
         BackendType::VertexAi => normalize_vertex_ai_model(model),
@@ -84,9 +451,14 @@ fn normalize_vertex_ai_model(model: String) -> String {
 mod tests {
     use super::*;
     use crate::ai::backend::Backend;
+    use crate::ai::methods::local_model_adapter::TestLocalModelAdapter;
     use crate::ai::public_types::AiOptions;
+    use crate::ai::types::{HarmBlockMethod, HarmBlockThreshold, HarmCategory, Schema};
     use crate::app::initialize_app;
     use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::test_support::start_mock_server;
+    use futures::StreamExt;
+    use httpmock::prelude::*;
     use serde_json::json;
     use std::time::Duration;
 
@@ -107,6 +479,7 @@ mod tests {
                 Some(AiOptions {
                     backend: Some(backend),
                     use_limited_use_app_check_tokens: None,
+                    ..Default::default()
                 }),
             )
             .await
@@ -168,4 +541,404 @@ mod tests {
         );
         assert_eq!(prepared.timeout, Duration::from_secs(5));
     }
+
+    fn app_options() -> FirebaseOptions {
+        FirebaseOptions {
+            api_key: Some("api".into()),
+            project_id: Some("project".into()),
+            app_id: Some("app".into()),
+            ..Default::default()
+        }
+    }
+
+    fn mock_options(server: &MockServer) -> Option<RequestOptions> {
+        Some(RequestOptions {
+            timeout: Some(Duration::from_secs(5)),
+            base_url: Some(server.base_url()),
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_content_sends_typed_request_and_parses_response() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/models/gemini-pro:generateContent")
+                .json_body(json!({
+                    "contents": [{ "role": "user", "parts": [{ "text": "Hi" }] }],
+                    "systemInstruction": { "role": "system", "parts": [{ "text": "Be brief" }] },
+                    "generationConfig": { "temperature": 0.5 }
+                }));
+            then.status(200).json_body(json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [{ "text": "Hello!" }] },
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": { "promptTokenCount": 2, "candidatesTokenCount": 1, "totalTokenCount": 3 }
+            }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let params = ModelParams::new("gemini-pro")
+            .with_system_instruction(Content::system("Be brief"))
+            .with_generation_config(GenerationConfig {
+                temperature: Some(0.5),
+                ..Default::default()
+            });
+        let model = GenerativeModel::from_params(service, params, mock_options(&server)).unwrap();
+        let response = model.generate_content("Hi").await.unwrap();
+
+        mock.assert();
+        assert_eq!(response.text().unwrap(), "Hello!");
+        assert_eq!(response.usage_metadata.unwrap().total_token_count, 3);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_content_maps_blocked_prompt_to_response_error() {
+        let server = start_mock_server();
+        let _mock = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent");
+            then.status(200)
+                .json_body(json!({ "promptFeedback": { "blockReason": "PROHIBITED_CONTENT" } }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let model = GenerativeModel::new(service, "gemini-pro", mock_options(&server)).unwrap();
+        let err = model.generate_content("something bad").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ResponseError);
+        assert!(err.message().contains("PROHIBITED_CONTENT"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn google_ai_rejects_safety_setting_method() {
+        let service = init_service(app_options(), None).await;
+        let mut setting = SafetySetting::new(HarmCategory::Harassment, HarmBlockThreshold::BlockOnlyHigh);
+        setting.method = Some(HarmBlockMethod::Severity);
+        let params = ModelParams::new("gemini-pro").with_safety_settings(vec![setting]);
+        let model = GenerativeModel::from_params(service.clone(), params, None).unwrap();
+        let err = model.generate_content("Hi").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::Unsupported);
+
+        let err = GenerativeModel::new(service, " ", None).unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::NoModel);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_content_stream_parses_sse_chunks() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/models/gemini-pro:streamGenerateContent")
+                .query_param("alt", "sse");
+            then.status(200)
+                .header("content-type", "text/event-stream")
+                .body(concat!(
+                "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Once \"}]}}]}\r\n\r\n",
+                "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"upon\"}]},",
+                "\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"totalTokenCount\":5}}\r\n\r\n"
+            ));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let model = GenerativeModel::new(service, "gemini-pro", mock_options(&server)).unwrap();
+        let mut stream = model.generate_content_stream("Tell a story").await.unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.text().unwrap(), "Once ");
+
+        let response = stream.response().await.unwrap();
+        mock.assert();
+        assert_eq!(response.text().unwrap(), "Once upon");
+        assert_eq!(response.usage_metadata.unwrap().total_token_count, 5);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_content_stream_maps_http_errors() {
+        let server = start_mock_server();
+        let _mock = server.mock(|when, then| {
+            when.method(POST).path_contains(":streamGenerateContent");
+            then.status(400)
+                .json_body(json!({ "error": { "code": 400, "message": "API key not valid" } }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let model = GenerativeModel::new(service, "gemini-pro", mock_options(&server)).unwrap();
+        let err = model.generate_content_stream("Hi").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::FetchError);
+        assert_eq!(err.message(), "API key not valid");
+        assert_eq!(err.custom_error_data.unwrap().status, Some(400));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn dropping_stream_closes_connection() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            // Read the headers and the JSON body before answering.
+            loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                assert!(read > 0, "client closed before sending the request");
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |value| value.trim().parse::<usize>().unwrap());
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            let event = "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"partial\"}]}}]}\n\n";
+            let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n";
+            let chunk = format!("{head}{:x}\r\n{event}\r\n", event.len());
+            socket.write_all(chunk.as_bytes()).await.unwrap();
+            // The connection stays open until the client goes away.
+            socket.read(&mut buffer).await.unwrap()
+        });
+
+        let service = init_service(app_options(), None).await;
+        let options = Some(RequestOptions {
+            timeout: Some(Duration::from_secs(30)),
+            base_url: Some(base_url),
+        });
+        let model = GenerativeModel::new(service, "gemini-pro", options).unwrap();
+        let mut stream = model.generate_content_stream("Hi").await.unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.text().unwrap(), "partial");
+        drop(stream);
+
+        let read = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server should observe the closed connection")
+            .unwrap();
+        assert_eq!(read, 0);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_object_requests_json_and_deserializes() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct City {
+            name: String,
+            population: u64,
+        }
+
+        let server = start_mock_server();
+        let schema = Schema::object()
+            .property("name", Schema::string())
+            .property("population", Schema::integer());
+        let mock = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent").json_body(json!({
+                "contents": [{ "role": "user", "parts": [{ "text": "Largest city?" }] }],
+                "generationConfig": {
+                    "temperature": 0.0,
+                    "responseMimeType": "application/json",
+                    "responseSchema": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" }, "population": { "type": "integer" } },
+                        "required": ["name", "population"]
+                    }
+                }
+            }));
+            then.status(200).json_body(json!({ "candidates": [{ "content": {
+                "role": "model",
+                "parts": [{ "text": "{\"name\": \"Rome\"," }, { "text": " \"population\": 2800000}" }]
+            } }] }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let params = ModelParams::new("gemini-pro").with_generation_config(GenerationConfig {
+            temperature: Some(0.0),
+            response_schema: Some(schema),
+            ..Default::default()
+        });
+        let model = GenerativeModel::from_params(service, params, mock_options(&server)).unwrap();
+        let city: City = model.generate_object("Largest city?").await.unwrap();
+        mock.assert();
+        assert_eq!(
+            city,
+            City {
+                name: "Rome".into(),
+                population: 2_800_000
+            }
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_object_reports_unparseable_output() {
+        let server = start_mock_server();
+        let _mock = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent");
+            then.status(200).json_body(json!({ "candidates": [{ "content": {
+                "role": "model", "parts": [{ "text": "{\"count\": \"many\"}" }]
+            } }] }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let model = GenerativeModel::new(service, "gemini-pro", mock_options(&server)).unwrap();
+        let err = model
+            .generate_object::<std::collections::HashMap<String, u32>>("Count")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ParseFailed);
+        assert!(err.message().contains("could not be parsed as"));
+        assert_eq!(
+            err.custom_error_data.unwrap().response,
+            Some(json!({ "text": "{\"count\": \"many\"}" }))
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn count_tokens_wraps_google_ai_request() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/models/gemini-pro:countTokens")
+                .header("x-goog-api-key", "api")
+                .json_body(json!({ "generateContentRequest": {
+                    "model": "models/gemini-pro",
+                    "contents": [{ "role": "user", "parts": [{ "text": "Hi" }] }],
+                    "systemInstruction": { "role": "system", "parts": [{ "text": "Be brief" }] }
+                } }));
+            then.status(200).json_body(json!({
+                "totalTokens": 7,
+                "promptTokensDetails": [{ "modality": "TEXT", "tokenCount": 7 }]
+            }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let params = ModelParams::new("gemini-pro").with_system_instruction(Content::system("Be brief"));
+        let model = GenerativeModel::from_params(service, params, mock_options(&server)).unwrap();
+        let response = model.count_tokens("Hi").await.unwrap();
+        mock.assert();
+        assert_eq!(response.total_tokens, 7);
+        assert_eq!(response.prompt_tokens_details[0].token_count, 7);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn count_tokens_sends_vertex_ai_request_directly() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/locations/us-central1/publishers/google/models/gemini-pro:countTokens")
+                .json_body(json!({
+                    "contents": [{ "role": "user", "parts": [{ "text": "Hi" }] }],
+                    "generationConfig": { "temperature": 0.5 }
+                }));
+            then.status(200)
+                .json_body(json!({ "totalTokens": 2, "totalBillableCharacters": 2 }));
+        });
+
+        let service = init_service(app_options(), Some(Backend::vertex_ai("us-central1"))).await;
+        let params = ModelParams::new("gemini-pro")
+            .with_generation_config(GenerationConfig {
+                temperature: Some(0.5),
+                ..Default::default()
+            })
+            .with_safety_settings(vec![SafetySetting::new(
+                HarmCategory::Harassment,
+                HarmBlockThreshold::BlockOnlyHigh,
+            )])
+            .with_tool_config(ToolConfig::none());
+        let model = GenerativeModel::from_params(service, params, mock_options(&server)).unwrap();
+        let response = model.count_tokens("Hi").await.unwrap();
+        mock.assert();
+        assert_eq!(response.total_tokens, 2);
+        assert_eq!(response.total_billable_characters, Some(2));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn only_on_device_uses_local_model_with_model_defaults() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST);
+            then.status(500);
+        });
+
+        let local = TestLocalModelAdapter::replying("Local answer here");
+        let service = init_service(app_options(), None).await;
+        let params = ModelParams::new("gemini-pro").with_system_instruction(Content::system("Be brief"));
+        let model = GenerativeModel::from_params(service, params, mock_options(&server))
+            .unwrap()
+            .with_local_model(Arc::new(local.clone()))
+            .with_inference_mode(InferenceMode::OnlyOnDevice);
+
+        let response = model.generate_content("Hi").await.unwrap();
+        assert_eq!(response.text().unwrap(), "Local answer here");
+
+        let mut stream = model.generate_content_stream("Hi again").await.unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunks.push(chunk.unwrap().text().unwrap());
+        }
+        assert_eq!(chunks, vec!["Local ", "answer ", "here"]);
+        assert_eq!(stream.aggregated().text().unwrap(), "Local answer here");
+
+        let requests = local.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].system_instruction, Some(Content::system("Be brief")));
+
+        let err = model.count_tokens("Hi").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::Unsupported);
+        mock.assert_hits(0);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn only_on_device_fails_when_local_model_is_unavailable() {
+        let service = init_service(app_options(), None).await;
+        let model = GenerativeModel::new(service.clone(), "gemini-pro", None)
+            .unwrap()
+            .with_local_model(Arc::new(TestLocalModelAdapter::unavailable()))
+            .with_inference_mode(InferenceMode::OnlyOnDevice);
+        let err = model.generate_content("Hi").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::Unsupported);
+
+        let without_adapter = GenerativeModel::new(service, "gemini-pro", None)
+            .unwrap()
+            .with_inference_mode(InferenceMode::OnlyOnDevice);
+        let err = without_adapter.generate_content_stream("Hi").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::Unsupported);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn prefer_on_device_falls_back_to_cloud() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path_contains(":generateContent");
+            then.status(200).json_body(json!({
+                "candidates": [{ "content": { "role": "model", "parts": [{ "text": "From the cloud" }] } }]
+            }));
+        });
+
+        let service = init_service(app_options(), None).await;
+        let failing = TestLocalModelAdapter::failing("out of memory");
+        let model = GenerativeModel::new(service.clone(), "gemini-pro", mock_options(&server))
+            .unwrap()
+            .with_local_model(Arc::new(failing.clone()));
+        assert_eq!(model.inference_mode(), InferenceMode::PreferOnDevice);
+        let response = model.generate_content("Hi").await.unwrap();
+        assert_eq!(response.text().unwrap(), "From the cloud");
+        assert_eq!(failing.requests().len(), 1);
+
+        let unavailable = GenerativeModel::new(service.clone(), "gemini-pro", mock_options(&server))
+            .unwrap()
+            .with_local_model(Arc::new(TestLocalModelAdapter::unavailable()));
+        let response = unavailable.generate_content("Hi").await.unwrap();
+        assert_eq!(response.text().unwrap(), "From the cloud");
+
+        let local = TestLocalModelAdapter::replying("On device");
+        let cloud_only = GenerativeModel::new(service, "gemini-pro", mock_options(&server))
+            .unwrap()
+            .with_local_model(Arc::new(local.clone()))
+            .with_inference_mode(InferenceMode::OnlyInCloud);
+        let response = cloud_only.generate_content("Hi").await.unwrap();
+        assert_eq!(response.text().unwrap(), "From the cloud");
+        assert!(local.requests().is_empty());
+        mock.assert_hits(3);
+    }
 }
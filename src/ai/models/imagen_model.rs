@@ -0,0 +1,370 @@
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde_json::{json, Map, Value};
+
+use crate::ai::api::AiService;
+use crate::ai::backend::BackendType;
+use crate::ai::error::{AiError, AiErrorCode, AiResult, CustomErrorData};
+use crate::ai::models::generative_model::normalize_model_name;
+use crate::ai::requests::{RequestOptions, Task};
+use crate::ai::types::{
+    ImagenGcsImage, ImagenGenerationConfig, ImagenGenerationResponse, ImagenInlineImage, ImagenModelParams,
+    ImagenSafetySettings,
+};
+
+/// Port of the Firebase JS SDK `ImagenModel` class.
+///
+/// Reference: `packages/ai/src/models/imagen-model.ts`.
+#[derive(Clone, Debug)]
+pub struct ImagenModel {
+    service: Arc<AiService>,
+    model: String,
+    generation_config: ImagenGenerationConfig,
+    safety_settings: ImagenSafetySettings,
+    default_request_options: Option<RequestOptions>,
+}
+
+impl ImagenModel {
+    /// Creates an Imagen model, e.g. `imagen-3.0-generate-002`.
+    pub fn new(
+        service: Arc<AiService>,
+        params: ImagenModelParams,
+        request_options: Option<RequestOptions>,
+    ) -> AiResult<Self> {
+        if params.model.trim().is_empty() {
+            return Err(AiError::new(
+                AiErrorCode::NoModel,
+                "Must provide a model name. Example: ImagenModelParams::new(\"imagen-3.0-generate-002\")",
+                None,
+            ));
+        }
+        let model = normalize_model_name(params.model, service.backend_type());
+        Ok(Self {
+            service,
+            model,
+            generation_config: params.generation_config.unwrap_or_default(),
+            safety_settings: params.safety_settings.unwrap_or_default(),
+            default_request_options: request_options,
+        })
+    }
+
+    /// Returns the fully qualified model resource identifier.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Generates images for `prompt` and returns their decoded bytes.
+    ///
+    /// Images blocked by the safety filters are left out; `filtered_reason` then explains why.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/response-error` when the backend returns neither images nor a filtered
+    /// reason, or an unexpected prediction, and `AI/fetch-error` for HTTP failures.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use firebase_rs_sdk::ai::{AiService, ImagenAspectRatio, ImagenGenerationConfig, ImagenModel, ImagenModelParams};
+    /// # async fn example(ai: Arc<AiService>) -> firebase_rs_sdk::ai::AiResult<()> {
+    /// let params = ImagenModelParams::new("imagen-3.0-generate-002").with_generation_config(ImagenGenerationConfig {
+    ///     number_of_images: Some(2),
+    ///     aspect_ratio: Some(ImagenAspectRatio::Square),
+    ///     ..Default::default()
+    /// });
+    /// let model = ImagenModel::new(ai, params, None)?;
+    /// let response = model.generate_images("A ceramic mug on a wooden table").await?;
+    /// for image in &response.images {
+    ///     println!("{} bytes of {}", image.bytes.len(), image.mime_type);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn generate_images(&self, prompt: &str) -> AiResult<ImagenGenerationResponse<ImagenInlineImage>> {
+        let predictions = self.predict(prompt, None).await?;
+        let (images, filtered_reason) = collect_images(predictions, |prediction| {
+            let mime_type = prediction.get("mimeType").and_then(Value::as_str)?;
+            let encoded = prediction.get("bytesBase64Encoded").and_then(Value::as_str)?;
+            Some(
+                STANDARD
+                    .decode(encoded)
+                    .map(|bytes| ImagenInlineImage {
+                        mime_type: mime_type.to_string(),
+                        bytes,
+                    })
+                    .map_err(|err| {
+                        AiError::new(
+                            AiErrorCode::ParseFailed,
+                            format!("generated image is not valid base64: {err}"),
+                            None,
+                        )
+                    }),
+            )
+        })?;
+        Ok(ImagenGenerationResponse {
+            images,
+            filtered_reason,
+        })
+    }
+
+    /// Generates images for `prompt` and writes them under `gcs_uri` (a `gs://` prefix).
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/unsupported` on the Gemini Developer API, which cannot write to Cloud Storage;
+    /// otherwise the same errors as [`generate_images`](Self::generate_images).
+    pub async fn generate_images_gcs(
+        &self,
+        prompt: &str,
+        gcs_uri: &str,
+    ) -> AiResult<ImagenGenerationResponse<ImagenGcsImage>> {
+        if self.service.backend_type() == BackendType::GoogleAi {
+            return Err(AiError::new(
+                AiErrorCode::Unsupported,
+                "Writing generated images to Cloud Storage is only supported by the Vertex AI backend.",
+                None,
+            ));
+        }
+        let predictions = self.predict(prompt, Some(gcs_uri)).await?;
+        let (images, filtered_reason) = collect_images(predictions, |prediction| {
+            let mime_type = prediction.get("mimeType").and_then(Value::as_str)?;
+            let gcs_uri = prediction.get("gcsUri").and_then(Value::as_str)?;
+            Some(Ok(ImagenGcsImage {
+                mime_type: mime_type.to_string(),
+                gcs_uri: gcs_uri.to_string(),
+            }))
+        })?;
+        Ok(ImagenGenerationResponse {
+            images,
+            filtered_reason,
+        })
+    }
+
+    async fn predict(&self, prompt: &str, gcs_uri: Option<&str>) -> AiResult<Vec<Value>> {
+        let body = self.request_body(prompt, gcs_uri);
+        let factory = self.service.request_factory().await?;
+        let prepared =
+            factory.construct_request(&self.model, Task::Predict, false, body, self.default_request_options.clone())?;
+        let response = self.service.execute_prepared_request(prepared).await?;
+        match response.get("predictions").and_then(Value::as_array) {
+            Some(predictions) if !predictions.is_empty() => Ok(predictions.clone()),
+            _ => Err(AiError::new(
+                AiErrorCode::ResponseError,
+                "No predictions or filtered reason received from the backend.",
+                Some(CustomErrorData::default().with_response(response)),
+            )),
+        }
+    }
+
+    /// Mirrors `createPredictRequestBody` in `packages/ai/src/requests/request-helpers.ts`.
+    fn request_body(&self, prompt: &str, gcs_uri: Option<&str>) -> Value {
+        let config = &self.generation_config;
+        let safety = &self.safety_settings;
+        let mut parameters = Map::new();
+        let mut insert = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                parameters.insert(key.to_string(), value);
+            }
+        };
+        insert("storageUri", gcs_uri.map(|uri| json!(uri)));
+        insert("negativePrompt", config.negative_prompt.as_ref().map(|prompt| json!(prompt)));
+        insert("sampleCount", Some(json!(config.number_of_images.unwrap_or(1))));
+        insert("aspectRatio", config.aspect_ratio.map(|ratio| json!(ratio)));
+        insert("outputOptions", config.image_format.as_ref().map(|format| json!(format)));
+        insert("addWatermark", config.add_watermark.map(Value::Bool));
+        insert("safetyFilterLevel", safety.safety_filter_level.map(|level| json!(level)));
+        insert("personGeneration", safety.person_filter_level.map(|level| json!(level)));
+        insert("includeRaiReason", Some(Value::Bool(true)));
+        insert("includeSafetyAttributes", Some(Value::Bool(true)));
+        json!({
+            "instances": [{ "prompt": prompt }],
+            "parameters": parameters,
+        })
+    }
+}
+
+/// Splits predictions into images and the filtered reason.
+///
+/// Mirrors `handlePredictResponse` in `packages/ai/src/requests/response-helpers.ts`: `extract`
+/// returns `None` for predictions that are not images of the requested kind.
+fn collect_images<T>(
+    predictions: Vec<Value>,
+    extract: impl Fn(&Value) -> Option<AiResult<T>>,
+) -> AiResult<(Vec<T>, Option<String>)> {
+    let mut images = Vec::new();
+    let mut filtered_reason = None;
+    for prediction in predictions {
+        if let Some(reason) = prediction.get("raiFilteredReason").and_then(Value::as_str) {
+            filtered_reason = Some(reason.to_string());
+        } else if let Some(image) = extract(&prediction) {
+            images.push(image?);
+        } else if prediction.get("safetyAttributes").is_none() {
+            return Err(AiError::new(
+                AiErrorCode::ResponseError,
+                format!("Unexpected element in 'predictions' array in response: '{prediction}'"),
+                None,
+            ));
+        }
+    }
+    Ok((images, filtered_reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::backend::Backend;
+    use crate::ai::public_types::AiOptions;
+    use crate::ai::types::{ImagenAspectRatio, ImagenImageFormat, ImagenPersonFilterLevel, ImagenSafetyFilterLevel};
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use std::time::Duration;
+
+    fn unique_settings() -> FirebaseAppSettings {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("imagen-model-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn model_for(server: &MockServer, backend: Backend, params: ImagenModelParams) -> ImagenModel {
+        let options = FirebaseOptions {
+            api_key: Some("api".into()),
+            project_id: Some("project".into()),
+            app_id: Some("app".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let service = crate::ai::get_ai(
+            Some(app),
+            Some(AiOptions {
+                backend: Some(backend),
+                use_limited_use_app_check_tokens: None,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let request_options = RequestOptions {
+            timeout: Some(Duration::from_secs(5)),
+            base_url: Some(server.base_url()),
+        };
+        ImagenModel::new(service, params, Some(request_options)).unwrap()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn generate_images_sends_options_and_decodes_images() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/models/imagen-3.0-generate-002:predict")
+                .json_body(json!({
+                    "instances": [{ "prompt": "a mug" }],
+                    "parameters": {
+                        "negativePrompt": "text",
+                        "sampleCount": 2,
+                        "aspectRatio": "16:9",
+                        "outputOptions": { "mimeType": "image/jpeg", "compressionQuality": 100 },
+                        "safetyFilterLevel": "block_only_high",
+                        "personGeneration": "dont_allow",
+                        "includeRaiReason": true,
+                        "includeSafetyAttributes": true
+                    }
+                }));
+            then.status(200).json_body(json!({ "predictions": [
+                { "mimeType": "image/jpeg", "bytesBase64Encoded": STANDARD.encode(b"jpeg-bytes") },
+                { "raiFilteredReason": "One image was filtered." },
+                { "safetyAttributes": { "categories": [], "scores": [] }, "contentType": "Positive Prompt" }
+            ] }));
+        });
+
+        let params = ImagenModelParams::new("imagen-3.0-generate-002")
+            .with_generation_config(ImagenGenerationConfig {
+                negative_prompt: Some("text".into()),
+                number_of_images: Some(2),
+                aspect_ratio: Some(ImagenAspectRatio::Landscape16x9),
+                image_format: Some(ImagenImageFormat::jpeg(Some(120))),
+                add_watermark: None,
+            })
+            .with_safety_settings(ImagenSafetySettings {
+                safety_filter_level: Some(ImagenSafetyFilterLevel::BlockOnlyHigh),
+                person_filter_level: Some(ImagenPersonFilterLevel::BlockAll),
+            });
+        let model = model_for(&server, Backend::google_ai(), params).await;
+        let response = model.generate_images("a mug").await.unwrap();
+        mock.assert();
+        assert_eq!(
+            response.images,
+            vec![ImagenInlineImage {
+                mime_type: "image/jpeg".into(),
+                bytes: b"jpeg-bytes".to_vec()
+            }]
+        );
+        assert_eq!(response.filtered_reason.as_deref(), Some("One image was filtered."));
+
+        let err = model.generate_images_gcs("a mug", "gs://bucket/out").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::Unsupported);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn vertex_ai_writes_images_to_cloud_storage() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1beta/projects/project/locations/us-central1/publishers/google/models/imagen-3.0-generate-002:predict")
+                .json_body(json!({
+                    "instances": [{ "prompt": "a mug" }],
+                    "parameters": {
+                        "storageUri": "gs://bucket/out",
+                        "sampleCount": 1,
+                        "addWatermark": false,
+                        "includeRaiReason": true,
+                        "includeSafetyAttributes": true
+                    }
+                }));
+            then.status(200).json_body(json!({ "predictions": [
+                { "mimeType": "image/png", "gcsUri": "gs://bucket/out/sample_0.png" }
+            ] }));
+        });
+
+        let params = ImagenModelParams::new("imagen-3.0-generate-002").with_generation_config(ImagenGenerationConfig {
+            add_watermark: Some(false),
+            ..Default::default()
+        });
+        let model = model_for(&server, Backend::vertex_ai("us-central1"), params).await;
+        let response = model.generate_images_gcs("a mug", "gs://bucket/out").await.unwrap();
+        mock.assert();
+        assert_eq!(response.images[0].gcs_uri, "gs://bucket/out/sample_0.png");
+        assert_eq!(response.filtered_reason, None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rejects_empty_and_unexpected_predictions() {
+        let server = start_mock_server();
+        let mut empty = server.mock(|when, then| {
+            when.method(POST).path_contains(":predict");
+            then.status(200).json_body(json!({}));
+        });
+
+        let model = model_for(&server, Backend::google_ai(), ImagenModelParams::new("imagen-3.0-generate-002")).await;
+        let err = model.generate_images("a mug").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ResponseError);
+        assert!(err.message().contains("No predictions"));
+
+        empty.delete();
+        let _unexpected = server.mock(|when, then| {
+            when.method(POST).path_contains(":predict");
+            then.status(200)
+                .json_body(json!({ "predictions": [{ "mimeType": "image/png", "gcsUri": "gs://x" }] }));
+        });
+        let err = model.generate_images("a mug").await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ResponseError);
+        assert!(err.message().contains("Unexpected element"));
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use serde_json::{json, Map, Value};
+
+use crate::ai::api::AiService;
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::methods::live_session::LiveSession;
+use crate::ai::models::generative_model::normalize_model_name;
+use crate::ai::requests::{RequestFactory, RequestOptions};
+use crate::ai::types::{Content, LiveGenerationConfig, LiveModelParams, Tool, ToolConfig};
+use crate::ai::websocket::WebSocketConnection;
+
+/// Close code sent when the server does not complete the setup handshake.
+const CLOSE_HANDSHAKE_FAILURE: u16 = 1011;
+
+/// Port of the Firebase JS SDK `LiveGenerativeModel` class.
+///
+/// Reference: `packages/ai/src/models/live-generative-model.ts`. Each call to
+/// [`connect`](Self::connect) opens a new `BidiGenerateContent` WebSocket.
+#[derive(Clone, Debug)]
+pub struct LiveGenerativeModel {
+    service: Arc<AiService>,
+    model: String,
+    generation_config: Option<LiveGenerationConfig>,
+    system_instruction: Option<Content>,
+    tools: Vec<Tool>,
+    tool_config: Option<ToolConfig>,
+    default_request_options: Option<RequestOptions>,
+}
+
+impl LiveGenerativeModel {
+    /// Creates a Live model, e.g. `gemini-2.0-flash-live-preview-04-09`.
+    ///
+    /// Only [`RequestOptions::base_url`] is used; it is rewritten to `ws://`/`wss://`.
+    pub fn new(
+        service: Arc<AiService>,
+        params: LiveModelParams,
+        request_options: Option<RequestOptions>,
+    ) -> AiResult<Self> {
+        if params.model.trim().is_empty() {
+            return Err(AiError::new(
+                AiErrorCode::NoModel,
+                "Must provide a model name. Example: LiveModelParams::new(\"gemini-2.0-flash-live-preview-04-09\")",
+                None,
+            ));
+        }
+        let model = normalize_model_name(params.model, service.backend_type());
+        Ok(Self {
+            service,
+            model,
+            generation_config: params.generation_config,
+            system_instruction: params.system_instruction,
+            tools: params.tools,
+            tool_config: params.tool_config,
+            default_request_options: request_options,
+        })
+    }
+
+    /// Returns the fully qualified model resource identifier.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Opens a Live session and performs the setup handshake.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/fetch-error` when the connection cannot be opened and `AI/response-error` when
+    /// the server does not answer the setup message with `setupComplete`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use futures::StreamExt;
+    /// # use firebase_rs_sdk::ai::{AiService, LiveGenerativeModel, LiveModelParams, LiveServerMessage};
+    /// # async fn example(ai: Arc<AiService>) -> firebase_rs_sdk::ai::AiResult<()> {
+    /// let model = LiveGenerativeModel::new(ai, LiveModelParams::new("gemini-2.0-flash-live-preview-04-09"), None)?;
+    /// let session = model.connect().await?;
+    /// session.send_text("Hello!").await?;
+    /// let mut messages = Box::pin(session.receive());
+    /// while let Some(message) = messages.next().await {
+    ///     if let LiveServerMessage::ServerContent(content) = message? {
+    ///         if content.turn_complete {
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// drop(messages);
+    /// session.close().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect(&self) -> AiResult<LiveSession> {
+        let factory = self.service.request_factory().await?;
+        let url = factory.websocket_url(self.default_request_options.as_ref())?;
+        let setup = self.setup_message(&factory)?;
+
+        let (connection, mut messages) = WebSocketConnection::connect(&url).await?;
+        if let Err(err) = connection.send(setup.to_string()).await {
+            let _ = connection.close(CLOSE_HANDSHAKE_FAILURE, "Handshake failure").await;
+            return Err(err);
+        }
+        match messages.next().await {
+            Some(Ok(message)) if message.get("setupComplete").is_some() => Ok(LiveSession::new(connection, messages)),
+            Some(Err(err)) => {
+                let _ = connection.close(CLOSE_HANDSHAKE_FAILURE, "Handshake failure").await;
+                Err(err)
+            }
+            _ => {
+                let _ = connection.close(CLOSE_HANDSHAKE_FAILURE, "Handshake failure").await;
+                Err(AiError::new(
+                    AiErrorCode::ResponseError,
+                    "Server connection handshake failed. The server did not respond with a setupComplete message.",
+                    None,
+                ))
+            }
+        }
+    }
+
+    fn setup_message(&self, factory: &RequestFactory) -> AiResult<Value> {
+        let mut setup = Map::new();
+        setup.insert("model".into(), Value::String(factory.model_resource(&self.model)));
+        if let Some(config) = &self.generation_config {
+            setup.insert("generationConfig".into(), to_json(config)?);
+            if config.input_audio_transcription {
+                setup.insert("inputAudioTranscription".into(), json!({}));
+            }
+            if config.output_audio_transcription {
+                setup.insert("outputAudioTranscription".into(), json!({}));
+            }
+        }
+        if let Some(instruction) = &self.system_instruction {
+            setup.insert("systemInstruction".into(), to_json(instruction)?);
+        }
+        if !self.tools.is_empty() {
+            setup.insert("tools".into(), to_json(&self.tools)?);
+        }
+        if let Some(tool_config) = &self.tool_config {
+            setup.insert("toolConfig".into(), to_json(tool_config)?);
+        }
+        Ok(json!({ "setup": setup }))
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> AiResult<Value> {
+    serde_json::to_value(value).map_err(|err| {
+        AiError::new(
+            AiErrorCode::InvalidContent,
+            format!("Failed to serialize Live setup message: {err}"),
+            None,
+        )
+    })
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::ai::backend::Backend;
+    use crate::ai::public_types::AiOptions;
+    use crate::ai::types::{ResponseModality, SpeechConfig};
+    use crate::ai::websocket::testing::{recv_json, send_json, serve_once};
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+
+    fn unique_settings() -> FirebaseAppSettings {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("live-model-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn model_for(base_url: String, backend: Backend, params: LiveModelParams) -> LiveGenerativeModel {
+        let options = FirebaseOptions {
+            api_key: Some("api".into()),
+            project_id: Some("project".into()),
+            app_id: Some("app".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let service = crate::ai::get_ai(
+            Some(app),
+            Some(AiOptions {
+                backend: Some(backend),
+                use_limited_use_app_check_tokens: None,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let request_options = RequestOptions {
+            timeout: None,
+            base_url: Some(base_url),
+        };
+        LiveGenerativeModel::new(service, params, Some(request_options)).unwrap()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn connect_sends_setup_and_waits_for_setup_complete() {
+        let (base_url, server) = serve_once(|path, mut socket| async move {
+            assert_eq!(
+                path,
+                "/ws/google.firebase.vertexai.v1beta.LlmBidiService/BidiGenerateContent/locations/us-central1?key=api"
+            );
+            assert_eq!(
+                recv_json(&mut socket).await,
+                json!({
+                    "setup": {
+                        "model": "projects/project/locations/us-central1/publishers/google/models/gemini-live",
+                        "generationConfig": {
+                            "speechConfig": { "voiceConfig": { "prebuiltVoiceConfig": { "voiceName": "Puck" } } },
+                            "responseModalities": ["AUDIO"]
+                        },
+                        "outputAudioTranscription": {},
+                        "systemInstruction": { "role": "system", "parts": [{ "text": "Be brief." }] }
+                    }
+                })
+            );
+            send_json(&mut socket, json!({ "setupComplete": {} })).await;
+            recv_json(&mut socket).await;
+        })
+        .await;
+
+        let params = LiveModelParams::new("gemini-live")
+            .with_generation_config(LiveGenerationConfig {
+                speech_config: Some(SpeechConfig::prebuilt_voice("Puck")),
+                response_modalities: vec![ResponseModality::Audio],
+                output_audio_transcription: true,
+                ..Default::default()
+            })
+            .with_system_instruction(Content::system("Be brief."));
+        let model = model_for(base_url, Backend::vertex_ai("us-central1"), params).await;
+        let session = model.connect().await.unwrap();
+        session.send_text("hi").await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn connect_fails_without_setup_complete() {
+        let (base_url, server) = serve_once(|path, mut socket| async move {
+            assert!(path.starts_with("/ws/google.firebase.vertexai.v1beta.GenerativeService/BidiGenerateContent"));
+            let setup = recv_json(&mut socket).await;
+            assert_eq!(setup["setup"]["model"], "projects/project/models/gemini-live");
+            send_json(&mut socket, json!({ "serverContent": {} })).await;
+        })
+        .await;
+
+        let model = model_for(base_url, Backend::google_ai(), LiveModelParams::new("gemini-live")).await;
+        let err = model.connect().await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ResponseError);
+        assert!(err.message().contains("setupComplete"));
+        server.await.unwrap();
+    }
+}
@@ -1 +1,4 @@
+pub mod embedding_model;
 pub mod generative_model;
+pub mod imagen_model;
+pub mod live_generative_model;
@@ -1,16 +1,61 @@
+use std::fmt;
+use std::sync::Arc;
+
 use crate::ai::backend::Backend;
+use crate::ai::transport::AiHttpTransport;
 
 /// Options for configuring the AI service at initialization time.
 ///
-/// Mirrors `AIOptions` from `packages/ai/src/public-types.ts`.
+/// Mirrors `AIOptions` from `packages/ai/src/public-types.ts`. A custom HTTP transport is set with
+/// [`with_transport`](Self::with_transport) and installed by [`get_ai`](crate::ai::get_ai).
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct AiOptions {
     /// Backend to use for inference. Defaults to `GoogleAIBackend` when omitted.
     pub backend: Option<Backend>,
     /// Whether to use limited-use App Check tokens for authenticated requests.
     pub use_limited_use_app_check_tokens: Option<bool>,
+    /// HTTP transport installed on the service by [`get_ai`](crate::ai::get_ai). Empty by default,
+    /// which keeps the service's current transport.
+    pub transport: AiTransportOption,
+}
+
+/// Optional [`AiHttpTransport`] carried by [`AiOptions`].
+///
+/// Transports compare by identity, so [`AiOptions`] keeps its `Debug`/`PartialEq`/`Eq` derives.
+#[derive(Clone, Default)]
+pub struct AiTransportOption(Option<Arc<dyn AiHttpTransport>>);
+
+impl AiTransportOption {
+    /// Returns the configured transport, if any.
+    pub fn get(&self) -> Option<Arc<dyn AiHttpTransport>> {
+        self.0.clone()
+    }
+}
+
+impl From<Arc<dyn AiHttpTransport>> for AiTransportOption {
+    fn from(transport: Arc<dyn AiHttpTransport>) -> Self {
+        Self(Some(transport))
+    }
 }
 
+impl fmt::Debug for AiTransportOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(<transport>)" } else { "None" })
+    }
+}
+
+impl PartialEq for AiTransportOption {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AiTransportOption {}
+
 impl AiOptions {
     /// Returns the configured backend or the default Google AI backend when unset.
     pub fn backend_or_default(&self) -> Backend {
@@ -21,6 +66,12 @@ impl AiOptions {
     pub fn limited_use_app_check(&self) -> bool {
         self.use_limited_use_app_check_tokens.unwrap_or(false)
     }
+
+    /// Sets the HTTP transport [`get_ai`](crate::ai::get_ai) installs on the returned service.
+    pub fn with_transport(mut self, transport: Arc<dyn AiHttpTransport>) -> Self {
+        self.transport = transport.into();
+        self
+    }
 }
 
 /// Runtime options stored on the {@link AiService} once the backend has been resolved.
@@ -5,7 +5,7 @@ use url::Url;
 
 use crate::ai::backend::Backend;
 use crate::ai::constants::{
-    DEFAULT_API_VERSION, DEFAULT_DOMAIN, DEFAULT_FETCH_TIMEOUT_MS, LANGUAGE_TAG, PACKAGE_VERSION,
+    DEFAULT_API_VERSION, DEFAULT_DOMAIN, DEFAULT_FETCH_TIMEOUT_MS, LANGUAGE_TAG, LIVE_SERVICE_NAME, PACKAGE_VERSION,
 };
 use crate::ai::error::{AiError, AiErrorCode, AiResult};
 
@@ -139,16 +139,7 @@ impl RequestFactory {
     ) -> AiResult<PreparedRequest> {
         let options = request_options.unwrap_or_default();
         let mut url = self.compose_base_url(&options)?;
-        let trimmed_model = model.trim_start_matches('/');
-        let model_path = match &self.settings.backend {
-            Backend::GoogleAi(_) => format!("projects/{}/{}", self.settings.project, trimmed_model),
-            Backend::VertexAi(inner) => format!(
-                "projects/{}/locations/{}/{}",
-                self.settings.project,
-                inner.location(),
-                trimmed_model
-            ),
-        };
+        let model_path = self.model_resource(model);
         let path = format!("/{}/{model_path}:{}", DEFAULT_API_VERSION, task.as_operation());
         url.set_path(&path);
         if stream {
@@ -171,6 +162,50 @@ impl RequestFactory {
         })
     }
 
+    /// Returns the full resource name of `model`, scoped to the project (and location on Vertex AI).
+    pub fn model_resource(&self, model: &str) -> String {
+        let trimmed_model = model.trim_start_matches('/');
+        match &self.settings.backend {
+            Backend::GoogleAi(_) => format!("projects/{}/{}", self.settings.project, trimmed_model),
+            Backend::VertexAi(inner) => format!(
+                "projects/{}/locations/{}/{}",
+                self.settings.project,
+                inner.location(),
+                trimmed_model
+            ),
+        }
+    }
+
+    /// Builds the `BidiGenerateContent` WebSocket URL used by Live sessions.
+    ///
+    /// Mirrors `WebSocketUrl` in `packages/ai/src/requests/request.ts`; browsers cannot attach headers
+    /// to a WebSocket handshake, so the API key travels as the `key` query parameter.
+    pub fn websocket_url(&self, request_options: Option<&RequestOptions>) -> AiResult<Url> {
+        let default_options = RequestOptions::default();
+        let mut url = self.compose_base_url(request_options.unwrap_or(&default_options))?;
+        let scheme = if url.scheme() == "http" { "ws" } else { "wss" };
+        url.set_scheme(scheme).map_err(|_| {
+            AiError::new(
+                AiErrorCode::InvalidArgument,
+                format!("Invalid WebSocket base URL '{url}'"),
+                None,
+            )
+        })?;
+        let path = match &self.settings.backend {
+            Backend::GoogleAi(_) => {
+                format!("/ws/{LIVE_SERVICE_NAME}.{DEFAULT_API_VERSION}.GenerativeService/BidiGenerateContent")
+            }
+            Backend::VertexAi(inner) => format!(
+                "/ws/{LIVE_SERVICE_NAME}.{DEFAULT_API_VERSION}.LlmBidiService/BidiGenerateContent/locations/{}",
+                inner.location()
+            ),
+        };
+        url.set_path(&path);
+        url.set_query(None);
+        url.query_pairs_mut().append_pair("key", &self.settings.api_key);
+        Ok(url)
+    }
+
     fn compose_base_url(&self, options: &RequestOptions) -> AiResult<Url> {
         let base = options
             .base_url
@@ -219,9 +254,10 @@ impl RequestFactory {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Task {
     GenerateContent,
-    #[allow(dead_code)]
+    StreamGenerateContent,
     CountTokens,
-    #[allow(dead_code)]
+    EmbedContent,
+    BatchEmbedContents,
     Predict,
 }
 
@@ -229,7 +265,10 @@ impl Task {
     pub fn as_operation(&self) -> &'static str {
         match self {
             Task::GenerateContent => "generateContent",
+            Task::StreamGenerateContent => "streamGenerateContent",
             Task::CountTokens => "countTokens",
+            Task::EmbedContent => "embedContent",
+            Task::BatchEmbedContents => "batchEmbedContents",
             Task::Predict => "predict",
         }
     }
@@ -295,6 +334,29 @@ mod tests {
         assert_eq!(req.timeout, Duration::from_secs(10));
     }
 
+    #[test]
+    fn builds_live_websocket_urls() {
+        let factory = RequestFactory::new(settings_with_backend(Backend::google_ai()));
+        assert_eq!(
+            factory.websocket_url(None).unwrap().as_str(),
+            "wss://firebasevertexai.googleapis.com/ws/google.firebase.vertexai.v1beta.GenerativeService/BidiGenerateContent?key=test-key"
+        );
+
+        let factory = RequestFactory::new(settings_with_backend(Backend::vertex_ai("europe-west4")));
+        let options = RequestOptions {
+            timeout: None,
+            base_url: Some("http://127.0.0.1:8080".into()),
+        };
+        assert_eq!(
+            factory.websocket_url(Some(&options)).unwrap().as_str(),
+            "ws://127.0.0.1:8080/ws/google.firebase.vertexai.v1beta.LlmBidiService/BidiGenerateContent/locations/europe-west4?key=test-key"
+        );
+        assert_eq!(
+            factory.model_resource("publishers/google/models/gemini-live"),
+            "projects/test-project/locations/europe-west4/publishers/google/models/gemini-live"
+        );
+    }
+
     #[test]
     fn invalid_base_url_returns_error() {
         let factory = RequestFactory::new(settings_with_backend(Backend::google_ai()));
@@ -0,0 +1,421 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures::stream::BoxStream;
+#[cfg(target_arch = "wasm32")]
+use futures::stream::LocalBoxStream;
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::Value;
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult, CustomErrorData};
+use crate::ai::transport::AiByteStream;
+use crate::ai::types::{GenerateContentCandidate, GenerateContentResponse, Part, PartData};
+
+#[cfg(target_arch = "wasm32")]
+type ResponseChunkStream = LocalBoxStream<'static, AiResult<GenerateContentResponse>>;
+#[cfg(not(target_arch = "wasm32"))]
+type ResponseChunkStream = BoxStream<'static, AiResult<GenerateContentResponse>>;
+
+#[cfg(target_arch = "wasm32")]
+fn box_stream<S, T>(stream: S) -> LocalBoxStream<'static, T>
+where
+    S: Stream<Item = T> + 'static,
+{
+    stream.boxed_local()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn box_stream<S, T>(stream: S) -> BoxStream<'static, T>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    stream.boxed()
+}
+
+/// Partial responses of a `streamGenerateContent` call.
+///
+/// Each item is one server-sent chunk; the chunks are also folded into an aggregated response
+/// (the equivalent of the JS `GenerateContentStreamResult.response` promise) available through
+/// [`aggregated`](Self::aggregated) or, once the stream is drained, [`response`](Self::response).
+/// Dropping the stream closes the underlying connection.
+pub struct GenerateContentStream {
+    inner: ResponseChunkStream,
+    aggregate: GenerateContentResponse,
+}
+
+impl GenerateContentStream {
+    pub(crate) fn new(bytes: AiByteStream) -> Self {
+        Self {
+            inner: response_chunks(bytes),
+            aggregate: GenerateContentResponse::default(),
+        }
+    }
+
+    /// Wraps partial responses produced without HTTP, e.g. by a
+    /// [`LocalModelAdapter`](crate::ai::LocalModelAdapter).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_chunks<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = AiResult<GenerateContentResponse>> + Send + 'static,
+    {
+        Self {
+            inner: box_stream(chunks),
+            aggregate: GenerateContentResponse::default(),
+        }
+    }
+
+    /// Wraps partial responses produced without HTTP, e.g. by a
+    /// [`LocalModelAdapter`](crate::ai::LocalModelAdapter).
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_chunks<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = AiResult<GenerateContentResponse>> + 'static,
+    {
+        Self {
+            inner: box_stream(chunks),
+            aggregate: GenerateContentResponse::default(),
+        }
+    }
+
+    /// Returns the chunks received so far merged into a single response.
+    pub fn aggregated(&self) -> &GenerateContentResponse {
+        &self.aggregate
+    }
+
+    /// Drains the remaining chunks and returns the aggregated response.
+    ///
+    /// # Errors
+    ///
+    /// Propagates the first stream error and returns `AI/response-error` when the prompt was
+    /// blocked.
+    pub async fn response(mut self) -> AiResult<GenerateContentResponse> {
+        while let Some(chunk) = self.next().await {
+            chunk?;
+        }
+        match self.aggregate.prompt_block_error() {
+            Some(err) => Err(err),
+            None => Ok(self.aggregate),
+        }
+    }
+}
+
+impl Stream for GenerateContentStream {
+    type Item = AiResult<GenerateContentResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match this.inner.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                merge_chunk(&mut this.aggregate, &chunk);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Debug for GenerateContentStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GenerateContentStream")
+            .field("aggregate", &self.aggregate)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Incremental parser for `text/event-stream` bodies.
+///
+/// Bytes are buffered until a full line is available, so events may be split at any byte
+/// (including inside a UTF-8 sequence). Only `data` fields are collected; comments and other
+/// fields are ignored.
+#[derive(Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feeds a chunk and returns the data payloads of the events it completed.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            self.process_line(&line, &mut events);
+        }
+        events
+    }
+
+    /// Flushes an event that was not terminated by a blank line before the body ended.
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        let mut events = Vec::new();
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.process_line(&line, &mut events);
+        }
+        self.process_line(&[], &mut events);
+        events.pop()
+    }
+
+    fn process_line(&mut self, line: &[u8], events: &mut Vec<String>) {
+        if line.is_empty() {
+            if !self.data.is_empty() {
+                events.push(self.data.join("\n"));
+                self.data.clear();
+            }
+            return;
+        }
+        let line = String::from_utf8_lossy(line);
+        if let Some(value) = line.strip_prefix("data:") {
+            self.data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+    }
+}
+
+struct ChunkState {
+    bytes: AiByteStream,
+    parser: SseParser,
+    pending: VecDeque<String>,
+    done: bool,
+}
+
+/// Turns an SSE byte stream into parsed response chunks, ending after the first error.
+fn response_chunks(bytes: AiByteStream) -> ResponseChunkStream {
+    let state = ChunkState {
+        bytes,
+        parser: SseParser::default(),
+        pending: VecDeque::new(),
+        done: false,
+    };
+    box_stream(stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(data) = state.pending.pop_front() {
+                let parsed = parse_event(&data);
+                if parsed.is_err() {
+                    state.pending.clear();
+                    state.done = true;
+                }
+                return Some((parsed, state));
+            }
+            if state.done {
+                return None;
+            }
+            match state.bytes.next().await {
+                Some(Ok(chunk)) => {
+                    let events = state.parser.push(&chunk);
+                    state.pending.extend(events);
+                }
+                Some(Err(err)) => {
+                    state.done = true;
+                    return Some((Err(err), state));
+                }
+                None => {
+                    state.done = true;
+                    state.pending.extend(state.parser.finish());
+                }
+            }
+        }
+    }))
+}
+
+/// Parses one event payload; `{"error": …}` frames become `AI/fetch-error`.
+fn parse_event(data: &str) -> AiResult<GenerateContentResponse> {
+    let value: Value = serde_json::from_str(data).map_err(|_| {
+        AiError::new(
+            AiErrorCode::ParseFailed,
+            format!("Error parsing JSON response: \"{data}\""),
+            None,
+        )
+    })?;
+    if let Some(error) = value.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("stream returned an error frame")
+            .to_string();
+        let mut custom = CustomErrorData::default();
+        if let Some(status) = error.get("code").and_then(Value::as_u64) {
+            custom = custom.with_status(status as u16);
+        }
+        return Err(AiError::new(
+            AiErrorCode::FetchError,
+            message,
+            Some(custom.with_response(value)),
+        ));
+    }
+    serde_json::from_value(value).map_err(|err| {
+        AiError::new(
+            AiErrorCode::ParseFailed,
+            format!("failed to parse streamed response chunk: {err}"),
+            None,
+        )
+    })
+}
+
+/// Folds a streamed chunk into the aggregated response.
+///
+/// Mirrors `aggregateResponses` in `packages/ai/src/requests/stream-reader.ts`, additionally
+/// joining consecutive text parts so the aggregate reads like a non-streamed response.
+pub(crate) fn merge_chunk(aggregate: &mut GenerateContentResponse, chunk: &GenerateContentResponse) {
+    for candidate in &chunk.candidates {
+        let position = match aggregate
+            .candidates
+            .iter()
+            .position(|existing| existing.index == candidate.index)
+        {
+            Some(position) => position,
+            None => {
+                aggregate.candidates.push(GenerateContentCandidate {
+                    index: candidate.index,
+                    ..Default::default()
+                });
+                aggregate.candidates.len() - 1
+            }
+        };
+        let target = &mut aggregate.candidates[position];
+        if candidate.citation_metadata.is_some() {
+            target.citation_metadata = candidate.citation_metadata.clone();
+        }
+        if candidate.finish_reason.is_some() {
+            target.finish_reason = candidate.finish_reason;
+        }
+        if candidate.finish_message.is_some() {
+            target.finish_message = candidate.finish_message.clone();
+        }
+        if !candidate.safety_ratings.is_empty() {
+            target.safety_ratings = candidate.safety_ratings.clone();
+        }
+        if !candidate.content.parts.is_empty() {
+            target.content.role = candidate.content.role;
+        }
+        for part in &candidate.content.parts {
+            match (&part.data, target.content.parts.last_mut()) {
+                (PartData::Text(text), _) if text.is_empty() && part.thought_signature.is_none() => {}
+                (PartData::Text(text), Some(existing)) if can_extend_text(existing, part) => {
+                    if let PartData::Text(existing_text) = &mut existing.data {
+                        existing_text.push_str(text);
+                    }
+                    if part.thought_signature.is_some() {
+                        existing.thought_signature = part.thought_signature.clone();
+                    }
+                }
+                _ => target.content.parts.push(part.clone()),
+            }
+        }
+    }
+    if chunk.prompt_feedback.is_some() {
+        aggregate.prompt_feedback = chunk.prompt_feedback.clone();
+    }
+    if chunk.usage_metadata.is_some() {
+        aggregate.usage_metadata = chunk.usage_metadata.clone();
+    }
+}
+
+/// Streamed text chunks are merged into the previous text part unless that would mix thoughts with
+/// the answer or overwrite a thought signature.
+fn can_extend_text(existing: &Part, part: &Part) -> bool {
+    matches!(existing.data, PartData::Text(_))
+        && existing.is_thought() == part.is_thought()
+        && existing.thought_signature.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::FinishReason;
+
+    const BODY: &str = concat!(
+        "data: {\"candidates\":[{\"index\":0,\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hel\"}]}}]}\r\n\r\n",
+        ": keep-alive\n\n",
+        "data: {\"candidates\":[{\"index\":0,\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"lo ✓\"}]},",
+        "\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"totalTokenCount\":4}}\n\n"
+    );
+
+    fn byte_stream(chunks: Vec<Vec<u8>>) -> AiByteStream {
+        box_stream(stream::iter(chunks.into_iter().map(Ok)))
+    }
+
+    #[test]
+    fn parser_handles_every_chunk_boundary() {
+        let bytes = BODY.as_bytes();
+        for split in 0..bytes.len() {
+            let mut parser = SseParser::default();
+            let mut events = parser.push(&bytes[..split]);
+            events.extend(parser.push(&bytes[split..]));
+            events.extend(parser.finish());
+            assert_eq!(events.len(), 2, "split at {split}");
+            assert!(events[1].contains("lo ✓"));
+        }
+    }
+
+    #[test]
+    fn parser_flushes_unterminated_event() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"data: {\"a\":").is_empty());
+        assert!(parser.push(b"\ndata: 1}").is_empty());
+        assert_eq!(parser.finish().as_deref(), Some("{\"a\":\n1}"));
+        assert_eq!(parser.finish(), None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn stream_yields_chunks_and_aggregates() {
+        let chunks = BODY.as_bytes().chunks(7).map(<[u8]>::to_vec).collect();
+        let mut stream = GenerateContentStream::new(byte_stream(chunks));
+        let mut texts = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            texts.push(chunk.unwrap().text().unwrap());
+        }
+        assert_eq!(texts, vec!["Hel".to_string(), "lo ✓".to_string()]);
+
+        let aggregated = stream.aggregated();
+        assert_eq!(aggregated.candidates.len(), 1);
+        assert_eq!(aggregated.candidates[0].content.parts.len(), 1);
+        assert_eq!(aggregated.text().unwrap(), "Hello ✓");
+        assert_eq!(aggregated.candidates[0].finish_reason, Some(FinishReason::Stop));
+        assert_eq!(aggregated.usage_metadata.as_ref().unwrap().total_token_count, 4);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn aggregation_keeps_thoughts_and_signatures_apart() {
+        let body = concat!(
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Let me \",\"thought\":true}]}}]}\n\n",
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"think\",\"thought\":true}]}}]}\n\n",
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hi\"}]}}]}\n\n",
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"\",\"thoughtSignature\":\"c2ln\"}]}}]}\n\n"
+        );
+        let stream = GenerateContentStream::new(byte_stream(vec![body.as_bytes().to_vec()]));
+        let aggregated = stream.response().await.unwrap();
+        let parts = &aggregated.candidates[0].content.parts;
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].is_thought());
+        assert_eq!(parts[0].as_text(), Some("Let me think"));
+        assert_eq!(parts[1].as_text(), Some("Hi"));
+        assert_eq!(parts[1].thought_signature.as_deref(), Some("c2ln"));
+        assert_eq!(aggregated.text().unwrap(), "Hi");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn error_frames_end_the_stream() {
+        let body = concat!(
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"partial\"}]}}]}\n\n",
+            "data: {\"error\":{\"code\":503,\"message\":\"overloaded\"}}\n\n",
+            "data: {\"candidates\":[]}\n\n"
+        );
+        let mut stream = GenerateContentStream::new(byte_stream(vec![body.as_bytes().to_vec()]));
+        assert!(stream.next().await.unwrap().is_ok());
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::FetchError);
+        assert_eq!(err.message(), "overloaded");
+        assert_eq!(err.custom_error_data.unwrap().status, Some(503));
+        assert!(stream.next().await.is_none());
+
+        let stream = GenerateContentStream::new(byte_stream(vec![b"data: not json\n\n".to_vec()]));
+        let err = stream.response().await.unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ParseFailed);
+    }
+}
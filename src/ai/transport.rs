@@ -0,0 +1,889 @@
+//! HTTP transport used to execute prepared AI requests.
+//!
+//! [`AiHttpTransport`] is the extension point: install a custom implementation through
+//! [`AiOptions::with_transport`](crate::ai::AiOptions::with_transport) to route requests through
+//! another HTTP stack or to replay canned responses. [`ReqwestTransport`] is the default. It retries
+//! rate-limited requests, applies [`PreparedRequest::timeout`] to every attempt and reports
+//! traffic to an optional [`TransportObserver`].
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use async_trait::async_trait;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::stream::BoxStream;
+#[cfg(target_arch = "wasm32")]
+use futures::stream::LocalBoxStream;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::ai::error::{internal_error, AiError, AiErrorCode, AiResult, CustomErrorData};
+use crate::ai::requests::PreparedRequest;
+use crate::platform::runtime;
+
+/// Raw response body chunks produced by [`AiHttpTransport::send_stream`].
+#[cfg(target_arch = "wasm32")]
+pub type AiByteStream = LocalBoxStream<'static, AiResult<Vec<u8>>>;
+/// Raw response body chunks produced by [`AiHttpTransport::send_stream`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type AiByteStream = BoxStream<'static, AiResult<Vec<u8>>>;
+
+/// Executes prepared AI requests.
+///
+/// Implementations map non-success responses to `AI/fetch-error` with the HTTP status in
+/// [`CustomErrorData::status`].
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait AiHttpTransport: Send + Sync {
+    /// Sends `request` and returns the decoded JSON response body.
+    async fn send(&self, request: PreparedRequest) -> AiResult<Value>;
+
+    /// Sends a request whose response body is consumed incrementally (server-sent events).
+    async fn send_stream(&self, _request: PreparedRequest) -> AiResult<AiByteStream> {
+        Err(AiError::new(
+            AiErrorCode::Unsupported,
+            "this transport does not support streaming responses",
+            None,
+        ))
+    }
+}
+
+/// Retry policy for rate-limited (`429`) and unavailable (`503`) responses.
+///
+/// Delays grow exponentially from `initial_delay`. A `Retry-After` header replaces the computed
+/// delay; when it asks for longer than `max_delay` the error is returned instead of waiting.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetrySettings {
+    /// Total attempts including the first one; `1` disables retries.
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+    pub max_delay: Duration,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetrySettings {
+    /// Sends each request exactly once.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay before retrying after failed attempt number `attempt` (starting at 1),
+    /// or `None` when the request should not be retried.
+    fn retry_delay(&self, attempt: u32, status: StatusCode, retry_after: Option<Duration>) -> Option<Duration> {
+        let retryable = matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE);
+        if !retryable || attempt >= self.max_attempts {
+            return None;
+        }
+        match retry_after {
+            Some(delay) if delay > self.max_delay => None,
+            Some(delay) => Some(delay),
+            None => {
+                let delay = self.initial_delay.mul_f64(self.multiplier.powi(attempt as i32 - 1));
+                Some(delay.min(self.max_delay))
+            }
+        }
+    }
+}
+
+/// Traffic reported to a [`TransportObserver`].
+///
+/// Credentials are redacted: the API key, App Check and `Authorization` headers and the `key`
+/// query parameter are replaced by `REDACTED`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransportEvent {
+    Request {
+        attempt: u32,
+        url: String,
+        headers: Vec<(String, String)>,
+        body: Value,
+    },
+    /// A response was received. `body` is `None` for successful streaming responses, which are
+    /// not buffered; bodies that are not JSON are reported as strings.
+    Response {
+        attempt: u32,
+        url: String,
+        status: u16,
+        body: Option<Value>,
+    },
+    /// The attempt failed before a response was received, e.g. on a timeout.
+    Failure { attempt: u32, url: String, message: String },
+}
+
+/// Receives the [`TransportEvent`]s of a [`ReqwestTransport`], e.g. for debugging or to record
+/// golden files. Implemented for closures taking `&TransportEvent`.
+pub trait TransportObserver: Send + Sync {
+    fn on_event(&self, event: &TransportEvent);
+}
+
+impl<F> TransportObserver for F
+where
+    F: Fn(&TransportEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &TransportEvent) {
+        self(event)
+    }
+}
+
+/// [`TransportObserver`] that keeps every event in memory.
+#[derive(Clone, Debug, Default)]
+pub struct TransportRecorder {
+    events: Arc<Mutex<Vec<TransportEvent>>>,
+}
+
+impl TransportRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the events recorded so far.
+    pub fn events(&self) -> Vec<TransportEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Returns and clears the events recorded so far.
+    pub fn take(&self) -> Vec<TransportEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+impl TransportObserver for TransportRecorder {
+    fn on_event(&self, event: &TransportEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+/// Default [`AiHttpTransport`] built on `reqwest`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use firebase_rs_sdk::ai::{ReqwestTransport, RetrySettings, TransportRecorder};
+///
+/// let recorder = TransportRecorder::new();
+/// let transport = ReqwestTransport::new()
+///     .with_retry_settings(RetrySettings {
+///         max_attempts: 3,
+///         initial_delay: Duration::from_millis(200),
+///         ..Default::default()
+///     })
+///     .with_observer(Arc::new(recorder.clone()));
+/// # let _ = transport;
+/// ```
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    retry: RetrySettings,
+    observer: Option<Arc<dyn TransportObserver>>,
+}
+
+impl fmt::Debug for ReqwestTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReqwestTransport")
+            .field("retry", &self.retry)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            retry: RetrySettings::default(),
+            observer: None,
+        }
+    }
+}
+
+/// Outcome of a single HTTP attempt.
+enum Attempt {
+    Success {
+        status: StatusCode,
+        body: SuccessBody,
+    },
+    Failed {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        body: Vec<u8>,
+    },
+}
+
+enum SuccessBody {
+    Buffered(Vec<u8>),
+    Stream(AiByteStream),
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `client` instead of a default `reqwest::Client`.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_retry_settings(mut self, retry: RetrySettings) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Reports every request and response to `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn TransportObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    async fn execute(&self, request: PreparedRequest, stream: bool) -> AiResult<SuccessBody> {
+        let mut attempt = 1;
+        loop {
+            match self.attempt(&request, attempt, stream).await? {
+                Attempt::Success { body, .. } => return Ok(body),
+                Attempt::Failed {
+                    status,
+                    retry_after,
+                    body,
+                } => match self.retry.retry_delay(attempt, status, retry_after) {
+                    Some(delay) => {
+                        log::debug!("AI request returned HTTP {status}; retrying in {delay:?} (attempt {attempt})");
+                        runtime::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(http_error(status, &body)),
+                },
+            }
+        }
+    }
+
+    /// Runs one attempt, bounded by the request timeout up to the point where the body is
+    /// buffered (or, for streams, handed back to the caller).
+    async fn attempt(&self, request: &PreparedRequest, attempt: u32, stream: bool) -> AiResult<Attempt> {
+        let url = redact_url(request);
+        self.notify(|| TransportEvent::Request {
+            attempt,
+            url: url.clone(),
+            headers: redact_headers(&request.headers),
+            body: request.body.clone(),
+        });
+
+        let result = match runtime::with_timeout(self.fetch(request.clone(), stream), request.timeout).await {
+            Ok(result) => result,
+            Err(_) => Err(AiError::new(
+                AiErrorCode::FetchError,
+                format!("AI request timed out after {} ms", request.timeout.as_millis()),
+                None,
+            )),
+        };
+
+        match &result {
+            Ok(Attempt::Success { status, body }) => self.notify(|| TransportEvent::Response {
+                attempt,
+                url: url.clone(),
+                status: status.as_u16(),
+                body: match body {
+                    SuccessBody::Buffered(bytes) => body_value(bytes),
+                    SuccessBody::Stream(_) => None,
+                },
+            }),
+            Ok(Attempt::Failed { status, body, .. }) => self.notify(|| TransportEvent::Response {
+                attempt,
+                url: url.clone(),
+                status: status.as_u16(),
+                body: body_value(body),
+            }),
+            Err(err) => self.notify(|| TransportEvent::Failure {
+                attempt,
+                url: url.clone(),
+                message: err.message().to_string(),
+            }),
+        }
+        result
+    }
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-web"))]
+    async fn fetch(&self, request: PreparedRequest, stream: bool) -> AiResult<Attempt> {
+        if stream {
+            return wasm::fetch_stream(request).await;
+        }
+        self.fetch_with_reqwest(request, false).await
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm-web")))]
+    async fn fetch(&self, request: PreparedRequest, stream: bool) -> AiResult<Attempt> {
+        self.fetch_with_reqwest(request, stream).await
+    }
+
+    async fn fetch_with_reqwest(&self, request: PreparedRequest, stream: bool) -> AiResult<Attempt> {
+        let builder = request
+            .into_reqwest(&self.client)
+            .map_err(|err| internal_error(format!("failed to encode AI request: {err}")))?;
+        let response = builder
+            .send()
+            .await
+            .map_err(|err| AiError::new(AiErrorCode::FetchError, format!("failed to send AI request: {err}"), None))?;
+
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        if status.is_success() && stream {
+            return Ok(Attempt::Success {
+                status,
+                body: SuccessBody::Stream(body_stream(response).await?),
+            });
+        }
+
+        let bytes = response.bytes().await.map_err(|err| {
+            AiError::new(AiErrorCode::FetchError, format!("failed to read AI response body: {err}"), None)
+        })?;
+        if status.is_success() {
+            Ok(Attempt::Success {
+                status,
+                body: SuccessBody::Buffered(bytes.to_vec()),
+            })
+        } else {
+            Ok(Attempt::Failed {
+                status,
+                retry_after,
+                body: bytes.to_vec(),
+            })
+        }
+    }
+
+    fn notify(&self, event: impl FnOnce() -> TransportEvent) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(&event());
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn body_stream(response: reqwest::Response) -> AiResult<AiByteStream> {
+    use futures::StreamExt;
+
+    // Dropping the returned stream drops the response, which closes the connection.
+    Ok(response
+        .bytes_stream()
+        .map(|chunk| {
+            chunk.map(|bytes| bytes.to_vec()).map_err(|err| {
+                AiError::new(
+                    AiErrorCode::FetchError,
+                    format!("failed to read AI response stream: {err}"),
+                    None,
+                )
+            })
+        })
+        .boxed())
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn body_stream(response: reqwest::Response) -> AiResult<AiByteStream> {
+    use futures::StreamExt;
+
+    // Without `web-sys` the body cannot be read incrementally; buffer it and replay it as a
+    // single chunk so the SSE parser still sees a well-formed stream.
+    let bytes = response.bytes().await.map_err(|err| {
+        AiError::new(AiErrorCode::FetchError, format!("failed to read AI response body: {err}"), None)
+    })?;
+    Ok(futures::stream::once(async move { Ok(bytes.to_vec()) }).boxed_local())
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AiHttpTransport for ReqwestTransport {
+    async fn send(&self, request: PreparedRequest) -> AiResult<Value> {
+        let bytes = match self.execute(request, false).await? {
+            SuccessBody::Buffered(bytes) => bytes,
+            SuccessBody::Stream(_) => return Err(internal_error("unexpected streaming AI response")),
+        };
+        serde_json::from_slice::<Value>(&bytes).map_err(|err| {
+            AiError::new(
+                AiErrorCode::ParseFailed,
+                format!("failed to parse AI response JSON: {err}"),
+                None,
+            )
+        })
+    }
+
+    async fn send_stream(&self, request: PreparedRequest) -> AiResult<AiByteStream> {
+        match self.execute(request, true).await? {
+            SuccessBody::Stream(stream) => Ok(stream),
+            SuccessBody::Buffered(_) => Err(internal_error("unexpected buffered AI response")),
+        }
+    }
+}
+
+/// Maps a non-success HTTP response to an `AI/fetch-error` carrying the status and body.
+pub(crate) fn http_error(status: StatusCode, bytes: &[u8]) -> AiError {
+    let mut data = CustomErrorData::default().with_status(status.as_u16());
+    if let Some(reason) = status.canonical_reason() {
+        data = data.with_status_text(reason);
+    }
+
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(json) => {
+            let message = extract_error_message(&json).unwrap_or_else(|| format!("AI endpoint returned HTTP {status}"));
+            AiError::new(AiErrorCode::FetchError, message, Some(data.with_response(json)))
+        }
+        Err(_) => {
+            let raw = String::from_utf8_lossy(bytes).to_string();
+            AiError::new(
+                AiErrorCode::FetchError,
+                format!("AI endpoint returned HTTP {status}"),
+                Some(data.with_response(json!({ "raw": raw }))),
+            )
+        }
+    }
+}
+
+pub(crate) fn extract_error_message(value: &Value) -> Option<String> {
+    if let Some(error) = value.get("error") {
+        if let Some(message) = error.get("message").and_then(|v| v.as_str()) {
+            return Some(message.to_string());
+        }
+    }
+
+    value
+        .get("message")
+        .and_then(|v| v.as_str())
+        .map(|message| message.to_string())
+}
+
+/// Parses a `Retry-After` header given either as delay-seconds or as an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let now = runtime::now().duration_since(UNIX_EPOCH).ok()?;
+    let at = Duration::from_secs(u64::try_from(at.timestamp()).ok()?);
+    Some(at.saturating_sub(now))
+}
+
+const REDACTED: &str = "REDACTED";
+const CREDENTIAL_HEADERS: [&str; 3] = ["x-goog-api-key", "x-firebase-appcheck", "authorization"];
+
+fn redact_headers(headers: &[(String, String)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if CREDENTIAL_HEADERS
+                .iter()
+                .any(|header| name.eq_ignore_ascii_case(header))
+            {
+                REDACTED.to_string()
+            } else {
+                value.clone()
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+fn redact_url(request: &PreparedRequest) -> String {
+    let mut url = request.url.clone();
+    if url.query_pairs().any(|(name, _)| name == "key") {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if name == "key" {
+                    REDACTED.into()
+                } else {
+                    value.into_owned()
+                };
+                (name.into_owned(), value)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+fn body_value(bytes: &[u8]) -> Option<Value> {
+    if bytes.is_empty() {
+        return None;
+    }
+    Some(serde_json::from_slice(bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned())))
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-web"))]
+mod wasm {
+    use futures::stream::{self, StreamExt};
+    use js_sys::{Reflect, Uint8Array};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{AbortController, ReadableStreamDefaultReader, Request, RequestInit, RequestMode, Response};
+
+    use super::{parse_retry_after, Attempt, SuccessBody};
+    use crate::ai::error::{internal_error, AiError, AiErrorCode, AiResult};
+    use crate::ai::requests::PreparedRequest;
+
+    /// Aborts the fetch when the body stream is dropped so the browser releases the connection.
+    struct AbortOnDrop {
+        controller: AbortController,
+        reader: ReadableStreamDefaultReader,
+        done: bool,
+    }
+
+    impl Drop for AbortOnDrop {
+        fn drop(&mut self) {
+            if !self.done {
+                self.controller.abort();
+            }
+        }
+    }
+
+    pub(super) async fn fetch_stream(request: PreparedRequest) -> AiResult<Attempt> {
+        let window = web_sys::window().ok_or_else(|| internal_error("window is not available in this environment"))?;
+        let controller =
+            AbortController::new().map_err(|err| internal_error(format_js_error("create AbortController", err)))?;
+
+        let init = RequestInit::new();
+        init.set_method("POST");
+        init.set_mode(RequestMode::Cors);
+        init.set_signal(Some(&controller.signal()));
+        init.set_body(&JsValue::from_str(&request.body.to_string()));
+
+        let fetch_request = Request::new_with_str_and_init(request.url.as_str(), &init)
+            .map_err(|err| internal_error(format_js_error("build AI request", err)))?;
+        let headers = fetch_request.headers();
+        for (name, value) in &request.headers {
+            headers
+                .set(name, value)
+                .map_err(|err| internal_error(format_js_error("set AI request header", err)))?;
+        }
+
+        let response: Response = JsFuture::from(window.fetch_with_request(&fetch_request))
+            .await
+            .map_err(|err| AiError::new(AiErrorCode::FetchError, format_js_error("AI fetch", err), None))?
+            .dyn_into()
+            .map_err(|_| internal_error("AI fetch did not return a Response"))?;
+
+        let status = reqwest::StatusCode::from_u16(response.status())
+            .map_err(|err| internal_error(format!("invalid AI response status: {err}")))?;
+        if !status.is_success() {
+            // Only visible when the server exposes the header through CORS.
+            let retry_after = response
+                .headers()
+                .get("Retry-After")
+                .ok()
+                .flatten()
+                .and_then(|value| parse_retry_after(&value));
+            let text = match response.text() {
+                Ok(promise) => JsFuture::from(promise)
+                    .await
+                    .ok()
+                    .and_then(|value| value.as_string())
+                    .unwrap_or_default(),
+                Err(_) => String::new(),
+            };
+            return Ok(Attempt::Failed {
+                status,
+                retry_after,
+                body: text.into_bytes(),
+            });
+        }
+
+        let body = response
+            .body()
+            .ok_or_else(|| internal_error("AI streaming response has no body"))?;
+        let reader = body
+            .get_reader()
+            .dyn_into::<ReadableStreamDefaultReader>()
+            .map_err(|err| internal_error(format_js_error("ReadableStreamDefaultReader", err.into())))?;
+
+        let guard = AbortOnDrop {
+            controller,
+            reader,
+            done: false,
+        };
+        let stream = stream::unfold(guard, |mut guard| async move {
+            if guard.done {
+                return None;
+            }
+            match read_chunk(&guard.reader).await {
+                Ok(Some(chunk)) => Some((Ok(chunk), guard)),
+                Ok(None) => {
+                    guard.done = true;
+                    None
+                }
+                Err(err) => {
+                    guard.done = true;
+                    Some((Err(err), guard))
+                }
+            }
+        })
+        .boxed_local();
+        Ok(Attempt::Success {
+            status,
+            body: SuccessBody::Stream(stream),
+        })
+    }
+
+    async fn read_chunk(reader: &ReadableStreamDefaultReader) -> AiResult<Option<Vec<u8>>> {
+        let result = JsFuture::from(reader.read()).await.map_err(|err| {
+            AiError::new(AiErrorCode::FetchError, format_js_error("read AI response stream", err), None)
+        })?;
+        let done = Reflect::get(&result, &JsValue::from_str("done"))
+            .ok()
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        if done {
+            return Ok(None);
+        }
+        let value = Reflect::get(&result, &JsValue::from_str("value"))
+            .map_err(|err| internal_error(format_js_error("read AI response chunk", err)))?;
+        Ok(Some(Uint8Array::new(&value).to_vec()))
+    }
+
+    fn format_js_error(context: &str, err: JsValue) -> String {
+        let description = err.as_string().unwrap_or_else(|| format!("{:?}", err));
+        format!("{context}: {description}")
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::ai::requests::HttpMethod;
+    use httpmock::prelude::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use url::Url;
+
+    fn request(url: &str, timeout: Duration) -> PreparedRequest {
+        PreparedRequest {
+            method: HttpMethod::Post,
+            url: Url::parse(url).unwrap(),
+            headers: vec![
+                ("Content-Type".into(), "application/json".into()),
+                ("x-goog-api-key".into(), "secret".into()),
+                ("X-Firebase-AppCheck".into(), "app-check-token".into()),
+            ],
+            body: json!({ "contents": [] }),
+            timeout,
+        }
+    }
+
+    fn fast_retries(max_attempts: u32) -> RetrySettings {
+        RetrySettings {
+            max_attempts,
+            initial_delay: Duration::from_millis(5),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(2),
+        }
+    }
+
+    /// Serves one canned `(status line, extra headers, body)` response per connection, in order.
+    async fn serve_sequence(responses: Vec<(&'static str, &'static str, &'static str)>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for (status, headers, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut received = Vec::new();
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    received.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&received).to_string();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                line.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(str::to_string)
+                            })
+                            .and_then(|value| value.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if received.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                    if read == 0 {
+                        break;
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {status}\r\n{headers}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        format!("http://{address}/v1beta/models/gemini:generateContent?key=secret&alt=sse")
+    }
+
+    #[test]
+    fn computes_backoff_and_honours_retry_after() {
+        let retry = RetrySettings::default();
+        let unavailable = StatusCode::SERVICE_UNAVAILABLE;
+        assert_eq!(retry.retry_delay(1, unavailable, None), Some(Duration::from_secs(1)));
+        assert_eq!(retry.retry_delay(3, unavailable, None), Some(Duration::from_secs(4)));
+        assert_eq!(retry.retry_delay(4, unavailable, None), None);
+        assert_eq!(
+            retry.retry_delay(1, StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(7))),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(retry.retry_delay(1, unavailable, Some(Duration::from_secs(60))), None);
+        assert_eq!(retry.retry_delay(1, StatusCode::INTERNAL_SERVER_ERROR, None), None);
+        assert_eq!(RetrySettings::disabled().retry_delay(1, unavailable, None), None);
+
+        assert_eq!(parse_retry_after(" 12 "), Some(Duration::from_secs(12)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn retries_rate_limited_request_and_records_redacted_traffic() {
+        let url = serve_sequence(vec![
+            (
+                "429 Too Many Requests",
+                "Retry-After: 0\r\n",
+                r#"{"error":{"message":"slow down"}}"#,
+            ),
+            ("200 OK", "", r#"{"ok":true}"#),
+        ])
+        .await;
+        let recorder = TransportRecorder::new();
+        let transport = ReqwestTransport::new()
+            .with_retry_settings(fast_retries(3))
+            .with_observer(Arc::new(recorder.clone()));
+
+        let response = transport.send(request(&url, Duration::from_secs(5))).await.unwrap();
+        assert_eq!(response, json!({ "ok": true }));
+
+        let events = recorder.take();
+        assert_eq!(events.len(), 4);
+        match &events[0] {
+            TransportEvent::Request {
+                attempt, url, headers, ..
+            } => {
+                assert_eq!(*attempt, 1);
+                assert!(url.contains("key=REDACTED&alt=sse"));
+                assert!(!url.contains("secret"));
+                assert!(headers.contains(&("x-goog-api-key".into(), "REDACTED".into())));
+                assert!(headers.contains(&("X-Firebase-AppCheck".into(), "REDACTED".into())));
+                assert!(headers.contains(&("Content-Type".into(), "application/json".into())));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(matches!(
+            &events[1],
+            TransportEvent::Response { attempt: 1, status: 429, body: Some(body), .. }
+                if body["error"]["message"] == "slow down"
+        ));
+        assert!(matches!(&events[2], TransportEvent::Request { attempt: 2, .. }));
+        assert_eq!(
+            events[3],
+            TransportEvent::Response {
+                attempt: 2,
+                url: url.replace("key=secret", "key=REDACTED"),
+                status: 200,
+                body: Some(json!({ "ok": true })),
+            }
+        );
+        assert!(recorder.events().is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reports_actual_success_status() {
+        let url = serve_sequence(vec![("202 Accepted", "", r#"{"ok":true}"#)]).await;
+        let recorder = TransportRecorder::new();
+        let transport = ReqwestTransport::new().with_observer(Arc::new(recorder.clone()));
+
+        transport.send(request(&url, Duration::from_secs(5))).await.unwrap();
+        let events = recorder.take();
+        assert!(matches!(&events[1], TransportEvent::Response { status: 202, .. }));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn gives_up_after_max_attempts() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1beta/models/gemini:generateContent");
+                then.status(503)
+                    .json_body(json!({ "error": { "message": "overloaded" } }));
+            })
+            .await;
+
+        let transport = ReqwestTransport::new().with_retry_settings(fast_retries(3));
+        let err = transport
+            .send(request(
+                &server.url("/v1beta/models/gemini:generateContent"),
+                Duration::from_secs(5),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::FetchError);
+        assert_eq!(err.message(), "overloaded");
+        assert_eq!(err.custom_error_data.as_ref().and_then(|data| data.status), Some(503));
+        mock.assert_hits_async(3).await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn does_not_wait_longer_than_max_delay() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST);
+                then.status(429).header("Retry-After", "3600").body("quota exhausted");
+            })
+            .await;
+
+        let transport = ReqwestTransport::new().with_retry_settings(fast_retries(5));
+        let err = transport
+            .send(request(&server.url("/generate"), Duration::from_secs(5)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.custom_error_data.as_ref().and_then(|data| data.status), Some(429));
+        mock.assert_hits_async(1).await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn applies_request_timeout_without_retrying() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST);
+                then.status(200).delay(Duration::from_millis(500)).json_body(json!({}));
+            })
+            .await;
+
+        let recorder = TransportRecorder::new();
+        let transport = ReqwestTransport::new()
+            .with_retry_settings(fast_retries(3))
+            .with_observer(Arc::new(recorder.clone()));
+        let err = transport
+            .send(request(&server.url("/generate"), Duration::from_millis(50)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::FetchError);
+        assert!(matches!(
+            recorder.events().last(),
+            Some(TransportEvent::Failure { attempt: 1, .. })
+        ));
+        mock.assert_hits_async(1).await;
+    }
+}
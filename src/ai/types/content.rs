@@ -0,0 +1,369 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde::ser::SerializeMap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+use crate::ai::types::enums::Role;
+
+/// A turn of a conversation: the producing role plus an ordered list of parts.
+///
+/// Ported from `packages/ai/src/types/content.ts` (`Content`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Content {
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+impl Content {
+    pub fn new(role: Role, parts: Vec<Part>) -> Self {
+        Self { role, parts }
+    }
+
+    /// Content authored by the user.
+    pub fn user(parts: Vec<Part>) -> Self {
+        Self::new(Role::User, parts)
+    }
+
+    /// Content authored by the model.
+    pub fn model(parts: Vec<Part>) -> Self {
+        Self::new(Role::Model, parts)
+    }
+
+    /// A system instruction made of a single text part.
+    pub fn system(text: impl Into<String>) -> Self {
+        Self::new(Role::System, vec![Part::text(text)])
+    }
+
+    /// Concatenates the text parts, skipping thoughts, or returns `None` when there are none.
+    pub fn text(&self) -> Option<String> {
+        let texts: Vec<&str> = self
+            .parts
+            .iter()
+            .filter(|part| !part.is_thought())
+            .filter_map(Part::as_text)
+            .collect();
+        (!texts.is_empty()).then(|| texts.concat())
+    }
+}
+
+/// A single piece of [`Content`].
+///
+/// The JS SDK models parts as a union of objects that each carry exactly one payload field
+/// (`text`, `inlineData`, `fileData`, `functionCall` or `functionResponse`) plus the optional
+/// `thought` and `thoughtSignature` fields; the Rust port keeps the payload in [`PartData`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Part {
+    pub data: PartData,
+    /// Whether the part is a summary of the model's reasoning rather than part of the answer.
+    pub thought: Option<bool>,
+    /// Opaque signature of the model's reasoning; it must be sent back unchanged with the chat
+    /// history for thinking models to keep their context across turns.
+    pub thought_signature: Option<String>,
+}
+
+/// The payload of a [`Part`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum PartData {
+    Text(String),
+    InlineData(GenerativeContentBlob),
+    FileData(FileData),
+    FunctionCall(FunctionCall),
+    FunctionResponse(FunctionResponse),
+    /// A part kind the port does not model (e.g. `executableCode`, `codeExecutionResult` or a
+    /// signature-only part), kept as raw JSON so it is sent back unchanged with the chat history.
+    Unknown(Value),
+}
+
+impl Part {
+    pub fn new(data: PartData) -> Self {
+        Self {
+            data,
+            thought: None,
+            thought_signature: None,
+        }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(PartData::Text(text.into()))
+    }
+
+    /// Inline data, base64-encoding `data`.
+    pub fn inline_data(mime_type: impl Into<String>, data: &[u8]) -> Self {
+        Self::new(PartData::InlineData(GenerativeContentBlob::from_bytes(mime_type, data)))
+    }
+
+    /// A reference to a file stored in Cloud Storage (`gs://` URI) or uploaded through the Files API.
+    pub fn file_data(mime_type: impl Into<String>, file_uri: impl Into<String>) -> Self {
+        Self::new(PartData::FileData(FileData {
+            mime_type: mime_type.into(),
+            file_uri: file_uri.into(),
+        }))
+    }
+
+    pub fn function_call(call: FunctionCall) -> Self {
+        Self::new(PartData::FunctionCall(call))
+    }
+
+    pub fn function_response(response: FunctionResponse) -> Self {
+        Self::new(PartData::FunctionResponse(response))
+    }
+
+    /// Returns `true` for parts carrying the model's reasoning (`thought: true`).
+    pub fn is_thought(&self) -> bool {
+        self.thought == Some(true)
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match &self.data {
+            PartData::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl From<PartData> for Part {
+    fn from(data: PartData) -> Self {
+        Part::new(data)
+    }
+}
+
+impl From<&str> for Part {
+    fn from(text: &str) -> Self {
+        Part::text(text)
+    }
+}
+
+impl From<String> for Part {
+    fn from(text: String) -> Self {
+        Part::text(text)
+    }
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let PartData::Unknown(value) = &self.data {
+            return value.serialize(serializer);
+        }
+        let mut map = serializer.serialize_map(None)?;
+        match &self.data {
+            PartData::Text(text) => map.serialize_entry("text", text)?,
+            PartData::InlineData(blob) => map.serialize_entry("inlineData", blob)?,
+            PartData::FileData(file) => map.serialize_entry("fileData", file)?,
+            PartData::FunctionCall(call) => map.serialize_entry("functionCall", call)?,
+            PartData::FunctionResponse(response) => map.serialize_entry("functionResponse", response)?,
+            PartData::Unknown(_) => unreachable!("handled above"),
+        }
+        if let Some(thought) = self.thought {
+            map.serialize_entry("thought", &thought)?;
+        }
+        if let Some(signature) = &self.thought_signature {
+            map.serialize_entry("thoughtSignature", signature)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Part {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let repr = PartRepr::deserialize(&value).map_err(de::Error::custom)?;
+        let thought = repr.thought;
+        let thought_signature = repr.thought_signature.clone();
+        // Unknown parts keep the thought fields inside the raw JSON they are serialized from.
+        let data = repr.into_data().unwrap_or(PartData::Unknown(value));
+        Ok(Part {
+            data,
+            thought,
+            thought_signature,
+        })
+    }
+}
+
+/// Known fields of a wire part; other fields are only kept by [`PartData::Unknown`].
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartRepr {
+    text: Option<String>,
+    inline_data: Option<GenerativeContentBlob>,
+    file_data: Option<FileData>,
+    function_call: Option<FunctionCall>,
+    function_response: Option<FunctionResponse>,
+    thought: Option<bool>,
+    thought_signature: Option<String>,
+}
+
+impl PartRepr {
+    fn into_data(self) -> Option<PartData> {
+        if let Some(call) = self.function_call {
+            Some(PartData::FunctionCall(call))
+        } else if let Some(response) = self.function_response {
+            Some(PartData::FunctionResponse(response))
+        } else if let Some(blob) = self.inline_data {
+            Some(PartData::InlineData(blob))
+        } else if let Some(file) = self.file_data {
+            Some(PartData::FileData(file))
+        } else {
+            self.text.map(PartData::Text)
+        }
+    }
+}
+
+/// Base64-encoded bytes with their MIME type.
+///
+/// Ported from `packages/ai/src/types/content.ts` (`GenerativeContentBlob`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerativeContentBlob {
+    pub mime_type: String,
+    /// Base64-encoded payload.
+    pub data: String,
+}
+
+impl GenerativeContentBlob {
+    pub fn from_bytes(mime_type: impl Into<String>, data: &[u8]) -> Self {
+        Self {
+            mime_type: mime_type.into(),
+            data: STANDARD.encode(data),
+        }
+    }
+
+    /// Decodes the base64 payload.
+    pub fn decode(&self) -> AiResult<Vec<u8>> {
+        STANDARD.decode(&self.data).map_err(|err| {
+            AiError::new(
+                AiErrorCode::ParseFailed,
+                format!("inline data is not valid base64: {err}"),
+                None,
+            )
+        })
+    }
+}
+
+/// Reference to file data stored outside the request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileData {
+    pub mime_type: String,
+    pub file_uri: String,
+}
+
+/// A function invocation requested by the model.
+///
+/// Ported from `packages/ai/src/types/content.ts` (`FunctionCall`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: Map<String, Value>,
+}
+
+/// The result of a [`FunctionCall`], sent back to the model.
+///
+/// Ported from `packages/ai/src/types/content.ts` (`FunctionResponse`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub response: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parts_round_trip_through_wire_format() {
+        let content = Content::user(vec![
+            Part::text("Describe this"),
+            Part::inline_data("image/png", b"png"),
+            Part::file_data("application/pdf", "gs://bucket/doc.pdf"),
+        ]);
+        let value = serde_json::to_value(&content).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "role": "user",
+                "parts": [
+                    { "text": "Describe this" },
+                    { "inlineData": { "mimeType": "image/png", "data": "cG5n" } },
+                    { "fileData": { "mimeType": "application/pdf", "fileUri": "gs://bucket/doc.pdf" } }
+                ]
+            })
+        );
+        let parsed: Content = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, content);
+        match &parsed.parts[1].data {
+            PartData::InlineData(blob) => assert_eq!(blob.decode().unwrap(), b"png".to_vec()),
+            other => panic!("unexpected part: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn function_parts_and_unknown_fields() {
+        let parsed: Content = serde_json::from_value(json!({
+            "role": "model",
+            "parts": [
+                { "text": "thinking", "thought": true },
+                { "functionCall": { "name": "lookup", "args": { "city": "Rome" } } },
+                { "text": "answer", "futureField": 1 }
+            ]
+        }))
+        .unwrap();
+        assert!(parsed.parts[0].is_thought());
+        assert_eq!(parsed.text().as_deref(), Some("answer"));
+        match &parsed.parts[1].data {
+            PartData::FunctionCall(call) => {
+                assert_eq!(call.name, "lookup");
+                assert_eq!(call.args["city"], "Rome");
+            }
+            other => panic!("unexpected part: {other:?}"),
+        }
+
+        assert!(serde_json::from_value::<Part>(json!("text")).is_err());
+    }
+
+    #[test]
+    fn thought_fields_round_trip() {
+        let raw = json!({
+            "role": "model",
+            "parts": [
+                { "text": "plan", "thought": true },
+                { "functionCall": { "name": "lookup", "args": {} }, "thoughtSignature": "c2ln" }
+            ]
+        });
+        let parsed: Content = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(parsed.text(), None);
+        assert_eq!(parsed.parts[1].thought_signature.as_deref(), Some("c2ln"));
+        assert!(matches!(parsed.parts[1].data, PartData::FunctionCall(_)));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), raw);
+    }
+
+    #[test]
+    fn unknown_part_kinds_are_kept_verbatim() {
+        let raw = json!({
+            "role": "model",
+            "parts": [
+                { "executableCode": { "language": "PYTHON", "code": "print(1)" } },
+                { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "1\n" } },
+                { "thought": true, "thoughtSignature": "c2ln" },
+                { "text": "1" }
+            ]
+        });
+        let parsed: Content = serde_json::from_value(raw.clone()).unwrap();
+        assert!(
+            matches!(&parsed.parts[0].data, PartData::Unknown(value) if value["executableCode"]["code"] == "print(1)")
+        );
+        assert!(matches!(&parsed.parts[2].data, PartData::Unknown(_)));
+        assert_eq!(parsed.parts[2].thought_signature.as_deref(), Some("c2ln"));
+        assert_eq!(parsed.text().as_deref(), Some("1"));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), raw);
+    }
+}
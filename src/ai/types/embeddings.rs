@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ai::types::content::{Content, Part};
+
+/// Intended use of an embedding, which lets the model optimise the vector for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EmbeddingTaskType {
+    RetrievalQuery,
+    RetrievalDocument,
+    SemanticSimilarity,
+    Classification,
+    Clustering,
+    QuestionAnswering,
+    FactVerification,
+    CodeRetrievalQuery,
+}
+
+/// Request body of an `embedContent` call.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedContentRequest {
+    pub content: Content,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_type: Option<EmbeddingTaskType>,
+    /// Document title; only used with [`EmbeddingTaskType::RetrievalDocument`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Truncates the embedding to this many dimensions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dimensionality: Option<u32>,
+}
+
+impl EmbedContentRequest {
+    pub fn new(content: Content) -> Self {
+        Self {
+            content,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_task_type(mut self, task_type: EmbeddingTaskType) -> Self {
+        self.task_type = Some(task_type);
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_output_dimensionality(mut self, dimensions: u32) -> Self {
+        self.output_dimensionality = Some(dimensions);
+        self
+    }
+}
+
+impl From<&str> for EmbedContentRequest {
+    fn from(text: &str) -> Self {
+        Self::new(Content::user(vec![Part::text(text)]))
+    }
+}
+
+impl From<String> for EmbedContentRequest {
+    fn from(text: String) -> Self {
+        Self::new(Content::user(vec![Part::text(text)]))
+    }
+}
+
+impl From<Content> for EmbedContentRequest {
+    fn from(content: Content) -> Self {
+        Self::new(content)
+    }
+}
+
+/// An embedding vector.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContentEmbedding {
+    #[serde(default)]
+    pub values: Vec<f32>,
+}
+
+/// Response of an `embedContent` call.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbedContentResponse {
+    pub embedding: ContentEmbedding,
+}
+
+/// Response of a `batchEmbedContents` call, with one embedding per request in order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchEmbedContentsResponse {
+    #[serde(default)]
+    pub embeddings: Vec<ContentEmbedding>,
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+/// Role that produced a piece of [`Content`](crate::ai::Content).
+///
+/// Ported from `packages/ai/src/types/content.ts` (`Role`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    Model,
+    Function,
+    System,
+}
+
+/// Harm categories that can be configured or reported by the safety filters.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`HarmCategory`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmCategory {
+    #[serde(rename = "HARM_CATEGORY_HATE_SPEECH")]
+    HateSpeech,
+    #[serde(rename = "HARM_CATEGORY_SEXUALLY_EXPLICIT")]
+    SexuallyExplicit,
+    #[serde(rename = "HARM_CATEGORY_HARASSMENT")]
+    Harassment,
+    #[serde(rename = "HARM_CATEGORY_DANGEROUS_CONTENT")]
+    DangerousContent,
+    /// Category not known to this version of the SDK.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Threshold above which content is blocked.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`HarmBlockThreshold`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HarmBlockThreshold {
+    BlockLowAndAbove,
+    BlockMediumAndAbove,
+    BlockOnlyHigh,
+    BlockNone,
+    Off,
+}
+
+/// Whether the block threshold applies to the probability or the severity score.
+///
+/// Only supported by the Vertex AI backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HarmBlockMethod {
+    Severity,
+    Probability,
+}
+
+/// Probability that a piece of content is harmful.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HarmProbability {
+    Negligible,
+    Low,
+    Medium,
+    High,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Severity of a harmful piece of content (Vertex AI only).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmSeverity {
+    #[serde(rename = "HARM_SEVERITY_NEGLIGIBLE")]
+    Negligible,
+    #[serde(rename = "HARM_SEVERITY_LOW")]
+    Low,
+    #[serde(rename = "HARM_SEVERITY_MEDIUM")]
+    Medium,
+    #[serde(rename = "HARM_SEVERITY_HIGH")]
+    High,
+    #[serde(rename = "HARM_SEVERITY_UNSUPPORTED")]
+    Unsupported,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Reason a prompt was blocked before any candidate was generated.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`BlockReason`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BlockReason {
+    Safety,
+    Other,
+    Blocklist,
+    ProhibitedContent,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Reason a candidate stopped generating tokens.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`FinishReason`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FinishReason {
+    Stop,
+    MaxTokens,
+    Safety,
+    Recitation,
+    Other,
+    Blocklist,
+    ProhibitedContent,
+    Spii,
+    MalformedFunctionCall,
+    #[serde(other)]
+    Unknown,
+}
+
+impl FinishReason {
+    /// Whether the candidate was cut off by a filter rather than finishing normally.
+    ///
+    /// Mirrors `badFinishReasons` in `packages/ai/src/requests/response-helpers.ts`.
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, FinishReason::Safety | FinishReason::Recitation)
+    }
+}
+
+/// How the model may use the declared functions.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`FunctionCallingMode`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FunctionCallingMode {
+    /// The model chooses between calling a function and answering in text.
+    Auto,
+    /// The model always calls a function.
+    Any,
+    /// Function calling is disabled.
+    None,
+}
+
+/// Output modality requested from the model.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`ResponseModality`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResponseModality {
+    Text,
+    Image,
+    Audio,
+}
+
+/// Content modality reported in token usage breakdowns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Modality {
+    ModalityUnspecified,
+    Text,
+    Image,
+    Video,
+    Audio,
+    Document,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Where a hybrid [`GenerativeModel`](crate::ai::GenerativeModel) runs inference.
+///
+/// Ported from `packages/ai/src/types/enums.ts` (`InferenceMode`). On-device inference goes
+/// through the [`LocalModelAdapter`](crate::ai::LocalModelAdapter) installed on the model.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InferenceMode {
+    /// Use the local model when it is available and succeeds, otherwise the cloud.
+    #[default]
+    PreferOnDevice,
+    /// Use the local model only; fail when it is unavailable.
+    OnlyOnDevice,
+    /// Always use the cloud, ignoring any local model.
+    OnlyInCloud,
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+/// Aspect ratio of generated images.
+///
+/// Ported from `packages/ai/src/types/imagen/requests.ts` (`ImagenAspectRatio`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ImagenAspectRatio {
+    #[serde(rename = "1:1")]
+    Square,
+    #[serde(rename = "3:4")]
+    Landscape3x4,
+    #[serde(rename = "4:3")]
+    Portrait4x3,
+    #[serde(rename = "16:9")]
+    Landscape16x9,
+    #[serde(rename = "9:16")]
+    Portrait9x16,
+}
+
+/// How aggressively generated images are filtered for unsafe content.
+///
+/// Ported from `packages/ai/src/types/imagen/requests.ts` (`ImagenSafetyFilterLevel`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImagenSafetyFilterLevel {
+    BlockLowAndAbove,
+    BlockMediumAndAbove,
+    BlockOnlyHigh,
+    BlockNone,
+}
+
+/// Whether generated images may depict people.
+///
+/// Ported from `packages/ai/src/types/imagen/requests.ts` (`ImagenPersonFilterLevel`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ImagenPersonFilterLevel {
+    #[serde(rename = "dont_allow")]
+    BlockAll,
+    #[serde(rename = "allow_adult")]
+    AllowAdult,
+    #[serde(rename = "allow_all")]
+    AllowAll,
+}
+
+/// Output encoding of generated images.
+///
+/// Ported from `packages/ai/src/requests/imagen-image-format.ts` (`ImagenImageFormat`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagenImageFormat {
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_quality: Option<u8>,
+}
+
+impl ImagenImageFormat {
+    /// JPEG output; `compression_quality` is clamped to `0..=100`.
+    pub fn jpeg(compression_quality: Option<u8>) -> Self {
+        Self {
+            mime_type: "image/jpeg".into(),
+            compression_quality: compression_quality.map(|quality| quality.min(100)),
+        }
+    }
+
+    pub fn png() -> Self {
+        Self {
+            mime_type: "image/png".into(),
+            compression_quality: None,
+        }
+    }
+}
+
+/// Options controlling image generation.
+///
+/// Ported from `packages/ai/src/types/imagen/requests.ts` (`ImagenGenerationConfig`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImagenGenerationConfig {
+    pub negative_prompt: Option<String>,
+    /// Between 1 and 4; the backend generates one image when unset.
+    pub number_of_images: Option<u32>,
+    pub aspect_ratio: Option<ImagenAspectRatio>,
+    pub image_format: Option<ImagenImageFormat>,
+    /// Only supported by Vertex AI.
+    pub add_watermark: Option<bool>,
+}
+
+/// Safety filters applied to generated images.
+///
+/// Ported from `packages/ai/src/types/imagen/requests.ts` (`ImagenSafetySettings`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImagenSafetySettings {
+    pub safety_filter_level: Option<ImagenSafetyFilterLevel>,
+    pub person_filter_level: Option<ImagenPersonFilterLevel>,
+}
+
+/// Parameters used to configure an [`ImagenModel`](crate::ai::ImagenModel).
+///
+/// Ported from `packages/ai/src/types/imagen/requests.ts` (`ImagenModelParams`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImagenModelParams {
+    pub model: String,
+    pub generation_config: Option<ImagenGenerationConfig>,
+    pub safety_settings: Option<ImagenSafetySettings>,
+}
+
+impl ImagenModelParams {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_generation_config(mut self, config: ImagenGenerationConfig) -> Self {
+        self.generation_config = Some(config);
+        self
+    }
+
+    pub fn with_safety_settings(mut self, settings: ImagenSafetySettings) -> Self {
+        self.safety_settings = Some(settings);
+        self
+    }
+}
+
+/// A generated image returned inline, already decoded from base64.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImagenInlineImage {
+    pub mime_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// A generated image written to Cloud Storage (Vertex AI only).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImagenGcsImage {
+    pub mime_type: String,
+    /// `gs://` URI of the image.
+    pub gcs_uri: String,
+}
+
+/// Images returned by an Imagen model.
+///
+/// Ported from `packages/ai/src/types/imagen/responses.ts` (`ImagenGenerationResponse`). When some
+/// or all images were blocked by the safety filters, `filtered_reason` explains why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImagenGenerationResponse<T> {
+    pub images: Vec<T>,
+    pub filtered_reason: Option<String>,
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ai::types::content::{Content, FunctionCall};
+use crate::ai::types::enums::ResponseModality;
+use crate::ai::types::requests::{Tool, ToolConfig};
+
+/// Voice used for audio responses.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`SpeechConfig`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice_config: Option<VoiceConfig>,
+}
+
+impl SpeechConfig {
+    /// Speaks with the prebuilt voice `voice_name`, e.g. `Puck`.
+    pub fn prebuilt_voice(voice_name: impl Into<String>) -> Self {
+        Self {
+            voice_config: Some(VoiceConfig {
+                prebuilt_voice_config: Some(PrebuiltVoiceConfig {
+                    voice_name: Some(voice_name.into()),
+                }),
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prebuilt_voice_config: Option<PrebuiltVoiceConfig>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrebuiltVoiceConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice_name: Option<String>,
+}
+
+/// Generation options for a Live session.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`LiveGenerationConfig`). The transcription
+/// flags are sent next to the generation config in the setup message.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveGenerationConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speech_config: Option<SpeechConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_modalities: Vec<ResponseModality>,
+    /// Transcribe the audio sent by the client.
+    #[serde(skip)]
+    pub input_audio_transcription: bool,
+    /// Transcribe the audio produced by the model.
+    #[serde(skip)]
+    pub output_audio_transcription: bool,
+}
+
+/// Parameters used to configure a [`LiveGenerativeModel`](crate::ai::LiveGenerativeModel).
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`LiveModelParams`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LiveModelParams {
+    pub model: String,
+    pub generation_config: Option<LiveGenerationConfig>,
+    pub system_instruction: Option<Content>,
+    pub tools: Vec<Tool>,
+    pub tool_config: Option<ToolConfig>,
+}
+
+impl LiveModelParams {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_generation_config(mut self, config: LiveGenerationConfig) -> Self {
+        self.generation_config = Some(config);
+        self
+    }
+
+    pub fn with_system_instruction(mut self, instruction: Content) -> Self {
+        self.system_instruction = Some(instruction);
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_tool_config(mut self, config: ToolConfig) -> Self {
+        self.tool_config = Some(config);
+        self
+    }
+}
+
+/// Transcription of input or output audio.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Incremental model output.
+///
+/// Ported from `packages/ai/src/types/live-responses.ts` (`LiveServerContent`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveServerContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_turn: Option<Content>,
+    /// The model finished its turn and waits for more input.
+    #[serde(default)]
+    pub turn_complete: bool,
+    /// The client interrupted the model, which stopped generating.
+    #[serde(default)]
+    pub interrupted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_transcription: Option<Transcription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_transcription: Option<Transcription>,
+}
+
+/// Functions the model wants the client to call.
+///
+/// Ported from `packages/ai/src/types/live-responses.ts` (`LiveServerToolCall`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveServerToolCall {
+    #[serde(default)]
+    pub function_calls: Vec<FunctionCall>,
+}
+
+/// Earlier function calls the client should no longer answer.
+///
+/// Ported from `packages/ai/src/types/live-responses.ts` (`LiveServerToolCallCancellation`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveServerToolCallCancellation {
+    #[serde(default)]
+    pub function_ids: Vec<String>,
+}
+
+/// A message received from the server during a Live session.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LiveServerMessage {
+    ServerContent(LiveServerContent),
+    ToolCall(LiveServerToolCall),
+    ToolCallCancellation(LiveServerToolCallCancellation),
+}
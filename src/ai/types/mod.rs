@@ -0,0 +1,36 @@
+//! Request and response data model, ported from `packages/ai/src/types`.
+
+pub mod content;
+pub mod embeddings;
+pub mod enums;
+pub mod imagen;
+pub mod live;
+pub mod requests;
+pub mod responses;
+pub mod schema;
+
+pub use content::{Content, FileData, FunctionCall, FunctionResponse, GenerativeContentBlob, Part, PartData};
+pub use embeddings::{
+    BatchEmbedContentsResponse, ContentEmbedding, EmbedContentRequest, EmbedContentResponse, EmbeddingTaskType,
+};
+pub use enums::{
+    BlockReason, FinishReason, FunctionCallingMode, HarmBlockMethod, HarmBlockThreshold, HarmCategory, HarmProbability,
+    HarmSeverity, InferenceMode, Modality, ResponseModality, Role,
+};
+pub use imagen::{
+    ImagenAspectRatio, ImagenGcsImage, ImagenGenerationConfig, ImagenGenerationResponse, ImagenImageFormat,
+    ImagenInlineImage, ImagenModelParams, ImagenPersonFilterLevel, ImagenSafetyFilterLevel, ImagenSafetySettings,
+};
+pub use live::{
+    LiveGenerationConfig, LiveModelParams, LiveServerContent, LiveServerMessage, LiveServerToolCall,
+    LiveServerToolCallCancellation, PrebuiltVoiceConfig, SpeechConfig, Transcription, VoiceConfig,
+};
+pub use requests::{
+    FunctionCallingConfig, FunctionDeclaration, GenerateContentRequest, GenerationConfig, ModelParams, SafetySetting,
+    StartChatParams, Tool, ToolConfig,
+};
+pub use responses::{
+    Citation, CitationDate, CitationMetadata, CountTokensResponse, GenerateContentCandidate, GenerateContentResponse,
+    ModalityTokenCount, PromptFeedback, SafetyRating, UsageMetadata,
+};
+pub use schema::{Schema, SchemaType};
@@ -0,0 +1,371 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ai::types::content::{Content, Part};
+use crate::ai::types::enums::{FunctionCallingMode, HarmBlockMethod, HarmBlockThreshold, HarmCategory};
+use crate::ai::types::schema::Schema;
+
+/// Parameters used to configure a [`GenerativeModel`](crate::ai::GenerativeModel).
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`ModelParams`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelParams {
+    pub model: String,
+    pub generation_config: Option<GenerationConfig>,
+    pub safety_settings: Vec<SafetySetting>,
+    pub system_instruction: Option<Content>,
+    pub tools: Vec<Tool>,
+    pub tool_config: Option<ToolConfig>,
+}
+
+impl ModelParams {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_generation_config(mut self, config: GenerationConfig) -> Self {
+        self.generation_config = Some(config);
+        self
+    }
+
+    pub fn with_safety_settings(mut self, settings: Vec<SafetySetting>) -> Self {
+        self.safety_settings = settings;
+        self
+    }
+
+    pub fn with_system_instruction(mut self, instruction: Content) -> Self {
+        self.system_instruction = Some(instruction);
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_tool_config(mut self, config: ToolConfig) -> Self {
+        self.tool_config = Some(config);
+        self
+    }
+}
+
+/// Parameters used to start a [`ChatSession`](crate::ai::ChatSession).
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`StartChatParams`). Unset generation config,
+/// safety settings, system instruction and tools fall back to the model defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StartChatParams {
+    /// Earlier turns, e.g. restored from [`ChatSession::history`](crate::ai::ChatSession::history).
+    pub history: Vec<Content>,
+    pub generation_config: Option<GenerationConfig>,
+    pub safety_settings: Vec<SafetySetting>,
+    pub system_instruction: Option<Content>,
+    pub tools: Vec<Tool>,
+    pub tool_config: Option<ToolConfig>,
+}
+
+impl StartChatParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_history(mut self, history: Vec<Content>) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn with_generation_config(mut self, config: GenerationConfig) -> Self {
+        self.generation_config = Some(config);
+        self
+    }
+
+    pub fn with_safety_settings(mut self, settings: Vec<SafetySetting>) -> Self {
+        self.safety_settings = settings;
+        self
+    }
+
+    pub fn with_system_instruction(mut self, instruction: Content) -> Self {
+        self.system_instruction = Some(instruction);
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_tool_config(mut self, config: ToolConfig) -> Self {
+        self.tool_config = Some(config);
+        self
+    }
+}
+
+/// Request body of a `generateContent` call.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`GenerateContentRequest`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentRequest {
+    pub contents: Vec<Content>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<GenerationConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub safety_settings: Vec<SafetySetting>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Tool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_config: Option<ToolConfig>,
+}
+
+impl GenerateContentRequest {
+    pub fn new(contents: Vec<Content>) -> Self {
+        Self {
+            contents,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_system_instruction(mut self, instruction: Content) -> Self {
+        self.system_instruction = Some(instruction);
+        self
+    }
+
+    pub fn with_generation_config(mut self, config: GenerationConfig) -> Self {
+        self.generation_config = Some(config);
+        self
+    }
+
+    pub fn with_safety_settings(mut self, settings: Vec<SafetySetting>) -> Self {
+        self.safety_settings = settings;
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_tool_config(mut self, config: ToolConfig) -> Self {
+        self.tool_config = Some(config);
+        self
+    }
+}
+
+impl From<&str> for GenerateContentRequest {
+    fn from(prompt: &str) -> Self {
+        Self::new(vec![Content::user(vec![Part::text(prompt)])])
+    }
+}
+
+impl From<String> for GenerateContentRequest {
+    fn from(prompt: String) -> Self {
+        Self::new(vec![Content::user(vec![Part::text(prompt)])])
+    }
+}
+
+impl From<Vec<Part>> for GenerateContentRequest {
+    fn from(parts: Vec<Part>) -> Self {
+        Self::new(vec![Content::user(parts)])
+    }
+}
+
+impl From<Content> for GenerateContentRequest {
+    fn from(content: Content) -> Self {
+        Self::new(vec![content])
+    }
+}
+
+/// Sampling and output options for content generation.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`GenerationConfig`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    /// Output MIME type, e.g. `text/plain` or `application/json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_mime_type: Option<String>,
+    /// Structure of the JSON output; requires `response_mime_type` to be `application/json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<Schema>,
+}
+
+/// Per-category safety threshold sent with a request.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`SafetySetting`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetySetting {
+    pub category: HarmCategory,
+    pub threshold: HarmBlockThreshold,
+    /// Only supported by the Vertex AI backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<HarmBlockMethod>,
+}
+
+impl SafetySetting {
+    pub fn new(category: HarmCategory, threshold: HarmBlockThreshold) -> Self {
+        Self {
+            category,
+            threshold,
+            method: None,
+        }
+    }
+}
+
+/// A set of capabilities the model may use while generating a response.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`FunctionDeclarationsTool`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub function_declarations: Vec<FunctionDeclaration>,
+}
+
+impl Tool {
+    /// A tool exposing the given functions to the model.
+    pub fn functions(declarations: Vec<FunctionDeclaration>) -> Self {
+        Self {
+            function_declarations: declarations,
+        }
+    }
+}
+
+/// Describes a function the model may call.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`FunctionDeclaration`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionDeclaration {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema (OpenAPI subset) of the arguments object, usually built with [`Schema`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+}
+
+impl FunctionDeclaration {
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: Some(description.into()),
+            parameters: None,
+        }
+    }
+
+    pub fn with_parameters(mut self, parameters: impl Into<Value>) -> Self {
+        self.parameters = Some(parameters.into());
+        self
+    }
+}
+
+/// Tool configuration shared by all tools of a request.
+///
+/// Ported from `packages/ai/src/types/requests.ts` (`ToolConfig`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_calling_config: Option<FunctionCallingConfig>,
+}
+
+impl ToolConfig {
+    /// The model decides whether to call a function or answer directly.
+    pub fn auto() -> Self {
+        Self::with_mode(FunctionCallingMode::Auto, Vec::new())
+    }
+
+    /// The model must call one of `allowed_function_names`, or any declared function when empty.
+    pub fn any(allowed_function_names: Vec<String>) -> Self {
+        Self::with_mode(FunctionCallingMode::Any, allowed_function_names)
+    }
+
+    /// The model never calls functions.
+    pub fn none() -> Self {
+        Self::with_mode(FunctionCallingMode::None, Vec::new())
+    }
+
+    fn with_mode(mode: FunctionCallingMode, allowed_function_names: Vec<String>) -> Self {
+        Self {
+            function_calling_config: Some(FunctionCallingConfig {
+                mode: Some(mode),
+                allowed_function_names,
+            }),
+        }
+    }
+}
+
+/// Ported from `packages/ai/src/types/requests.ts` (`FunctionCallingConfig`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCallingConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<FunctionCallingMode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_function_names: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tools_serialize_to_wire_format() {
+        let request = GenerateContentRequest::from("weather?")
+            .with_tools(vec![Tool::functions(vec![FunctionDeclaration::new(
+                "get_weather",
+                "Current weather for a city",
+            )
+            .with_parameters(json!({
+                "type": "OBJECT",
+                "properties": { "city": { "type": "STRING" } },
+                "required": ["city"]
+            }))])])
+            .with_tool_config(ToolConfig::any(vec!["get_weather".into()]));
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value["tools"],
+            json!([{ "functionDeclarations": [{
+                "name": "get_weather",
+                "description": "Current weather for a city",
+                "parameters": {
+                    "type": "OBJECT",
+                    "properties": { "city": { "type": "STRING" } },
+                    "required": ["city"]
+                }
+            }] }])
+        );
+        assert_eq!(
+            value["toolConfig"],
+            json!({ "functionCallingConfig": { "mode": "ANY", "allowedFunctionNames": ["get_weather"] } })
+        );
+        assert_eq!(
+            serde_json::to_value(ToolConfig::none()).unwrap(),
+            json!({ "functionCallingConfig": { "mode": "NONE" } })
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult, CustomErrorData};
+use crate::ai::types::content::{Content, FunctionCall, GenerativeContentBlob, PartData};
+use crate::ai::types::enums::{BlockReason, FinishReason, HarmCategory, HarmProbability, HarmSeverity, Modality};
+
+/// Response of a `generateContent` call.
+///
+/// Ported from `packages/ai/src/types/responses.ts` (`GenerateContentResponse`), with the
+/// `EnhancedGenerateContentResponse` helpers (`text`, `function_calls`, `inline_data_parts`)
+/// implemented as methods.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<GenerateContentCandidate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_feedback: Option<PromptFeedback>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_metadata: Option<UsageMetadata>,
+}
+
+impl GenerateContentResponse {
+    /// Returns the concatenated text of the first candidate.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AI/response-error` when the prompt or the first candidate was blocked.
+    pub fn text(&self) -> AiResult<String> {
+        Ok(self
+            .first_candidate("Text not available.")?
+            .and_then(|candidate| candidate.content.text())
+            .unwrap_or_default())
+    }
+
+    /// Returns the function calls requested by the first candidate.
+    pub fn function_calls(&self) -> AiResult<Vec<FunctionCall>> {
+        Ok(self
+            .first_candidate("Function call not available.")?
+            .map(|candidate| {
+                candidate
+                    .content
+                    .parts
+                    .iter()
+                    .filter_map(|part| match &part.data {
+                        PartData::FunctionCall(call) => Some(call.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the inline data parts (e.g. generated images) of the first candidate.
+    pub fn inline_data_parts(&self) -> AiResult<Vec<GenerativeContentBlob>> {
+        Ok(self
+            .first_candidate("Data not available.")?
+            .map(|candidate| {
+                candidate
+                    .content
+                    .parts
+                    .iter()
+                    .filter_map(|part| match &part.data {
+                        PartData::InlineData(blob) => Some(blob.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the error describing why the prompt was blocked, if it was.
+    pub(crate) fn prompt_block_error(&self) -> Option<AiError> {
+        let feedback = self.prompt_feedback.as_ref()?;
+        if !self.candidates.is_empty() || feedback.block_reason.is_none() {
+            return None;
+        }
+        Some(self.response_error(format!("Prompt blocked. {}", self.block_message())))
+    }
+
+    fn first_candidate(&self, unavailable: &str) -> AiResult<Option<&GenerateContentCandidate>> {
+        match self.candidates.first() {
+            Some(candidate) if candidate.finish_reason.is_some_and(|reason| reason.is_blocked()) => Err(self
+                .response_error(format!(
+                    "Response error: {}. Response body stored in error.response",
+                    self.block_message()
+                ))),
+            Some(candidate) => Ok(Some(candidate)),
+            None if self.prompt_feedback.is_some() => {
+                Err(self.response_error(format!("{unavailable} {}", self.block_message())))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Mirrors `formatBlockErrorMessage` in `packages/ai/src/requests/response-helpers.ts`.
+    fn block_message(&self) -> String {
+        let mut message = String::new();
+        match (self.candidates.first(), &self.prompt_feedback) {
+            (None, Some(feedback)) => {
+                message.push_str("Response was blocked");
+                if let Some(reason) = feedback.block_reason {
+                    message.push_str(&format!(" due to {}", wire_name(&reason)));
+                }
+                if let Some(detail) = &feedback.block_reason_message {
+                    message.push_str(&format!(": {detail}"));
+                }
+            }
+            (Some(candidate), _) => {
+                if let Some(reason) = candidate.finish_reason.filter(FinishReason::is_blocked) {
+                    message.push_str(&format!("Candidate was blocked due to {}", wire_name(&reason)));
+                    if let Some(detail) = &candidate.finish_message {
+                        message.push_str(&format!(": {detail}"));
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+        message
+    }
+
+    fn response_error(&self, message: String) -> AiError {
+        let data = serde_json::to_value(self)
+            .ok()
+            .map(|response| CustomErrorData::default().with_response(response));
+        AiError::new(AiErrorCode::ResponseError, message, data)
+    }
+}
+
+fn wire_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// A single response candidate.
+///
+/// Ported from `packages/ai/src/types/responses.ts` (`GenerateContentCandidate`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentCandidate {
+    #[serde(default)]
+    pub index: u32,
+    #[serde(default)]
+    pub content: Content,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_message: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub safety_ratings: Vec<SafetyRating>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_metadata: Option<CitationMetadata>,
+}
+
+/// Feedback on the prompt, present when the prompt itself was blocked.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFeedback {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_reason: Option<BlockReason>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub safety_ratings: Vec<SafetyRating>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_reason_message: Option<String>,
+}
+
+/// Safety rating of a candidate or prompt for one harm category.
+///
+/// The severity fields are only reported by the Vertex AI backend.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetyRating {
+    pub category: HarmCategory,
+    pub probability: HarmProbability,
+    #[serde(default)]
+    pub blocked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability_score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<HarmSeverity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity_score: Option<f32>,
+}
+
+/// Sources cited by a candidate.
+///
+/// Google AI reports them as `citationSources`, Vertex AI as `citations`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationMetadata {
+    #[serde(default, alias = "citationSources")]
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Citation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publication_date: Option<CitationDate>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CitationDate {
+    #[serde(default)]
+    pub year: u32,
+    #[serde(default)]
+    pub month: u32,
+    #[serde(default)]
+    pub day: u32,
+}
+
+/// Token accounting for a request.
+///
+/// Ported from `packages/ai/src/types/responses.ts` (`UsageMetadata`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    pub prompt_token_count: u32,
+    #[serde(default)]
+    pub candidates_token_count: u32,
+    #[serde(default)]
+    pub total_token_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_content_token_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thoughts_token_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompt_tokens_details: Vec<ModalityTokenCount>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates_tokens_details: Vec<ModalityTokenCount>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModalityTokenCount {
+    pub modality: Modality,
+    #[serde(default)]
+    pub token_count: u32,
+}
+
+/// Result of a `countTokens` call.
+///
+/// Ported from `packages/ai/src/types/responses.ts` (`CountTokensResponse`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensResponse {
+    #[serde(default)]
+    pub total_tokens: u32,
+    /// Only reported by the Vertex AI backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_billable_characters: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompt_tokens_details: Vec<ModalityTokenCount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_candidates_usage_and_citations() {
+        let response: GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "index": 0,
+                "content": { "role": "model", "parts": [{ "text": "Hello" }, { "text": " world" }] },
+                "finishReason": "STOP",
+                "safetyRatings": [{ "category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE" }],
+                "citationMetadata": { "citationSources": [{ "startIndex": 1, "endIndex": 5, "uri": "https://a.b" }] }
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 3,
+                "candidatesTokenCount": 2,
+                "totalTokenCount": 5,
+                "promptTokensDetails": [{ "modality": "TEXT", "tokenCount": 3 }]
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(response.text().unwrap(), "Hello world");
+        let candidate = &response.candidates[0];
+        assert_eq!(candidate.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(candidate.safety_ratings[0].category, HarmCategory::Harassment);
+        let citations = &candidate.citation_metadata.as_ref().unwrap().citations;
+        assert_eq!(citations[0].uri.as_deref(), Some("https://a.b"));
+        let usage = response.usage_metadata.unwrap();
+        assert_eq!(usage.total_token_count, 5);
+        assert_eq!(usage.prompt_tokens_details[0].modality, Modality::Text);
+    }
+
+    #[test]
+    fn blocked_prompt_and_candidate_surface_response_errors() {
+        let blocked: GenerateContentResponse = serde_json::from_value(json!({
+            "promptFeedback": { "blockReason": "SAFETY", "blockReasonMessage": "unsafe" }
+        }))
+        .unwrap();
+        let err = blocked.prompt_block_error().unwrap();
+        assert_eq!(err.code(), AiErrorCode::ResponseError);
+        assert_eq!(err.message(), "Prompt blocked. Response was blocked due to SAFETY: unsafe");
+        assert!(blocked.text().is_err());
+
+        let stopped: GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{ "finishReason": "RECITATION", "finishMessage": "copied" }]
+        }))
+        .unwrap();
+        assert!(stopped.prompt_block_error().is_none());
+        let err = stopped.function_calls().unwrap_err();
+        assert!(err
+            .message()
+            .contains("Candidate was blocked due to RECITATION: copied"));
+        assert!(err.custom_error_data.unwrap().response.is_some());
+
+        let unknown: GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{ "finishReason": "SOMETHING_NEW", "content": { "parts": [] } }]
+        }))
+        .unwrap();
+        assert_eq!(unknown.candidates[0].finish_reason, Some(FinishReason::Unknown));
+        assert_eq!(unknown.text().unwrap(), "");
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Data types supported by [`Schema`].
+///
+/// Ported from `packages/ai/src/types/schema.ts` (`SchemaType`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Array,
+    Object,
+}
+
+/// Description of structured data, serialised to the OpenAPI 3.0 subset understood by the backends.
+///
+/// Port of the JS SDK `Schema` builder (`packages/ai/src/requests/schema-builder.ts`). Used for
+/// [`GenerationConfig::response_schema`](crate::ai::GenerationConfig::response_schema) and
+/// function parameters. Object properties are required unless added through
+/// [`optional_property`](Self::optional_property).
+///
+/// ```
+/// use firebase_rs_sdk::ai::Schema;
+///
+/// let recipe = Schema::object()
+///     .property("name", Schema::string())
+///     .property("servings", Schema::integer().with_minimum(1.0))
+///     .property("difficulty", Schema::enum_string(["easy", "hard"]))
+///     .optional_property("notes", Schema::string().nullable());
+/// let json = serde_json::to_value(&recipe).unwrap();
+/// assert_eq!(json["required"], serde_json::json!(["difficulty", "name", "servings"]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<SchemaType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<Value>,
+    #[serde(rename = "enum", default, skip_serializing_if = "Vec::is_empty")]
+    pub enum_values: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Schema>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, Schema>,
+    /// Names of the required properties, kept in sync by the property builders.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any_of: Vec<Schema>,
+}
+
+impl Schema {
+    fn of_type(schema_type: SchemaType) -> Self {
+        Self {
+            schema_type: Some(schema_type),
+            ..Default::default()
+        }
+    }
+
+    pub fn string() -> Self {
+        Self::of_type(SchemaType::String)
+    }
+
+    /// A string restricted to `values`.
+    pub fn enum_string<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            format: Some("enum".into()),
+            enum_values: values.into_iter().map(Into::into).collect(),
+            ..Self::string()
+        }
+    }
+
+    pub fn number() -> Self {
+        Self::of_type(SchemaType::Number)
+    }
+
+    pub fn integer() -> Self {
+        Self::of_type(SchemaType::Integer)
+    }
+
+    pub fn boolean() -> Self {
+        Self::of_type(SchemaType::Boolean)
+    }
+
+    /// An array whose elements match `items`.
+    pub fn array(items: Schema) -> Self {
+        Self {
+            items: Some(Box::new(items)),
+            ..Self::of_type(SchemaType::Array)
+        }
+    }
+
+    /// An object without properties; add them with [`property`](Self::property) and
+    /// [`optional_property`](Self::optional_property).
+    pub fn object() -> Self {
+        Self::of_type(SchemaType::Object)
+    }
+
+    /// A value matching any of `schemas`.
+    pub fn any_of(schemas: Vec<Schema>) -> Self {
+        Self {
+            any_of: schemas,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a required property.
+    pub fn property(mut self, name: impl Into<String>, schema: Schema) -> Self {
+        let name = name.into();
+        if !self.required.contains(&name) {
+            self.required.push(name.clone());
+            self.required.sort();
+        }
+        self.properties.insert(name, schema);
+        self
+    }
+
+    /// Adds a property the model may omit.
+    pub fn optional_property(mut self, name: impl Into<String>, schema: Schema) -> Self {
+        let name = name.into();
+        self.required.retain(|required| required != &name);
+        self.properties.insert(name, schema);
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Format hint, e.g. `date-time` for strings or `int32`/`double` for numbers.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    pub fn with_example(mut self, example: Value) -> Self {
+        self.example = Some(example);
+        self
+    }
+
+    /// Marks the value as allowed to be `null`.
+    pub fn nullable(mut self) -> Self {
+        self.nullable = Some(true);
+        self
+    }
+
+    pub fn with_minimum(mut self, minimum: f64) -> Self {
+        self.minimum = Some(minimum);
+        self
+    }
+
+    pub fn with_maximum(mut self, maximum: f64) -> Self {
+        self.maximum = Some(maximum);
+        self
+    }
+
+    /// Restricts numbers to the inclusive range `minimum..=maximum`.
+    pub fn with_range(self, minimum: f64, maximum: f64) -> Self {
+        self.with_minimum(minimum).with_maximum(maximum)
+    }
+
+    pub fn with_min_items(mut self, min_items: u32) -> Self {
+        self.min_items = Some(min_items);
+        self
+    }
+
+    pub fn with_max_items(mut self, max_items: u32) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+}
+
+impl From<Schema> for Value {
+    fn from(schema: Schema) -> Self {
+        serde_json::to_value(schema).expect("schema serialization should never fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_nested_schema() {
+        let schema = Schema::object()
+            .with_description("A recipe")
+            .property(
+                "ingredients",
+                Schema::array(Schema::string()).with_min_items(1).with_max_items(20),
+            )
+            .property("rating", Schema::number().with_range(0.0, 5.0))
+            .optional_property("course", Schema::enum_string(["starter", "main"]).nullable())
+            .property("vegan", Schema::boolean());
+
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "type": "object",
+                "description": "A recipe",
+                "properties": {
+                    "course": { "type": "string", "format": "enum", "nullable": true, "enum": ["starter", "main"] },
+                    "ingredients": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": 20 },
+                    "rating": { "type": "number", "minimum": 0.0, "maximum": 5.0 },
+                    "vegan": { "type": "boolean" }
+                },
+                "required": ["ingredients", "rating", "vegan"]
+            })
+        );
+    }
+
+    #[test]
+    fn optional_property_overrides_required() {
+        let schema = Schema::object()
+            .property("id", Schema::integer())
+            .optional_property("id", Schema::integer().with_format("int64"));
+        let value = Value::from(schema.clone());
+        assert!(value.get("required").is_none());
+        assert_eq!(value["properties"]["id"]["format"], "int64");
+
+        let roundtrip: Schema = serde_json::from_value(value).unwrap();
+        assert_eq!(roundtrip, schema);
+        assert_eq!(
+            Value::from(Schema::any_of(vec![Schema::string(), Schema::integer()])),
+            json!({ "anyOf": [{ "type": "string" }, { "type": "integer" }] })
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! WebSocket connection used by Live sessions.
+//!
+//! Ported from `packages/ai/src/websocket.ts`. Native builds use `tokio-tungstenite`; wasm builds
+//! wrap `web_sys::WebSocket`. Incoming frames are decoded as JSON and exposed as a stream that ends
+//! when the socket closes, after yielding an error if the close was not clean.
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures::stream::BoxStream;
+#[cfg(target_arch = "wasm32")]
+use futures::stream::LocalBoxStream;
+use serde_json::Value;
+use url::Url;
+
+use crate::ai::error::{AiError, AiErrorCode, AiResult};
+
+/// JSON messages received from the server.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type ServerMessages = BoxStream<'static, AiResult<Value>>;
+#[cfg(target_arch = "wasm32")]
+pub(crate) type ServerMessages = LocalBoxStream<'static, AiResult<Value>>;
+
+/// Close code for a normal closure.
+pub(crate) const CLOSE_NORMAL: u16 = 1000;
+
+#[cfg_attr(all(target_arch = "wasm32", not(feature = "wasm-web")), allow(dead_code))]
+fn connection_error(message: impl Into<String>) -> AiError {
+    AiError::new(AiErrorCode::FetchError, message, None)
+}
+
+#[cfg_attr(all(target_arch = "wasm32", not(feature = "wasm-web")), allow(dead_code))]
+fn decode_message(bytes: &[u8]) -> AiResult<Value> {
+    serde_json::from_slice(bytes).map_err(|err| {
+        AiError::new(
+            AiErrorCode::ParseFailed,
+            format!("Failed to parse Live API message: {err}"),
+            None,
+        )
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", not(feature = "wasm-web")), allow(dead_code))]
+fn unexpected_close(code: u16, reason: &str) -> AiError {
+    connection_error(format!(
+        "WebSocket connection closed unexpectedly. Code: {code}, Reason: {reason}"
+    ))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use native::WebSocketConnection;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::*;
+
+    use async_lock::Mutex as AsyncMutex;
+    use futures::stream::{self, SplitSink, SplitStream, StreamExt};
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::error::{Error, ProtocolError};
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+    type NativeWebSocket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+    pub(crate) struct WebSocketConnection {
+        sink: AsyncMutex<SplitSink<NativeWebSocket, Message>>,
+    }
+
+    impl WebSocketConnection {
+        pub(crate) async fn connect(url: &Url) -> AiResult<(Self, ServerMessages)> {
+            let (socket, _) = connect_async(url.as_str())
+                .await
+                .map_err(|err| connection_error(format!("Failed to open WebSocket connection: {err}")))?;
+            let (sink, source) = socket.split();
+            let connection = Self {
+                sink: AsyncMutex::new(sink),
+            };
+            Ok((connection, server_messages(source)))
+        }
+
+        pub(crate) async fn send(&self, text: String) -> AiResult<()> {
+            self.sink
+                .lock()
+                .await
+                .send(Message::Text(text))
+                .await
+                .map_err(|err| connection_error(format!("Failed to send WebSocket message: {err}")))
+        }
+
+        pub(crate) async fn close(&self, code: u16, reason: &str) -> AiResult<()> {
+            let frame = CloseFrame {
+                code: CloseCode::from(code),
+                reason: reason.to_string().into(),
+            };
+            match self.sink.lock().await.send(Message::Close(Some(frame))).await {
+                Ok(()) => Ok(()),
+                // The connection is already closing or closed; nothing left to do.
+                Err(Error::ConnectionClosed)
+                | Err(Error::AlreadyClosed)
+                | Err(Error::Protocol(ProtocolError::SendAfterClosing)) => Ok(()),
+                Err(err) => Err(connection_error(format!("Failed to close WebSocket connection: {err}"))),
+            }
+        }
+    }
+
+    fn server_messages(source: SplitStream<NativeWebSocket>) -> ServerMessages {
+        stream::unfold(Some(source), |state| async move {
+            let mut source = state?;
+            loop {
+                let item = match source.next().await {
+                    None => return None,
+                    Some(Ok(Message::Text(text))) => decode_message(text.as_bytes()),
+                    Some(Ok(Message::Binary(bytes))) => decode_message(&bytes),
+                    Some(Ok(Message::Close(frame))) => match frame {
+                        Some(frame) if frame.code != CloseCode::Normal => {
+                            return Some((Err(unexpected_close(frame.code.into(), &frame.reason)), None));
+                        }
+                        _ => return None,
+                    },
+                    // Pings are answered by tungstenite itself.
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => {
+                        return Some((Err(connection_error(format!("WebSocket error: {err}"))), None));
+                    }
+                };
+                return Some((item, Some(source)));
+            }
+        })
+        .boxed()
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-web"))]
+pub(crate) use wasm::WebSocketConnection;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-web"))]
+mod wasm {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use futures::channel::{mpsc, oneshot};
+    use futures::StreamExt;
+    use js_sys::{ArrayBuffer, Uint8Array};
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
+    use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+    type Sender = Rc<RefCell<Option<mpsc::UnboundedSender<AiResult<Value>>>>>;
+
+    pub(crate) struct WebSocketConnection {
+        socket: WebSocket,
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+        _on_error: Closure<dyn FnMut(Event)>,
+        _on_close: Closure<dyn FnMut(CloseEvent)>,
+    }
+
+    impl WebSocketConnection {
+        pub(crate) async fn connect(url: &Url) -> AiResult<(Self, ServerMessages)> {
+            let socket = WebSocket::new(url.as_str())
+                .map_err(|err| connection_error(format!("Failed to open WebSocket connection: {err:?}")))?;
+            socket.set_binary_type(BinaryType::Arraybuffer);
+
+            let (tx, rx) = mpsc::unbounded();
+            let sender: Sender = Rc::new(RefCell::new(Some(tx)));
+            let (open_tx, open_rx) = oneshot::channel::<AiResult<()>>();
+            let open_tx = Rc::new(RefCell::new(Some(open_tx)));
+
+            let on_open_tx = open_tx.clone();
+            let on_open = Closure::once(move |_event: Event| {
+                if let Some(tx) = on_open_tx.borrow_mut().take() {
+                    let _ = tx.send(Ok(()));
+                }
+            });
+
+            let message_sender = sender.clone();
+            let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+                let data = event.data();
+                let item = if let Some(text) = data.as_string() {
+                    decode_message(text.as_bytes())
+                } else if let Ok(buffer) = data.dyn_into::<ArrayBuffer>() {
+                    decode_message(&Uint8Array::new(&buffer).to_vec())
+                } else {
+                    return;
+                };
+                if let Some(tx) = message_sender.borrow().as_ref() {
+                    let _ = tx.unbounded_send(item);
+                }
+            }) as Box<dyn FnMut(MessageEvent)>);
+
+            let error_open_tx = open_tx.clone();
+            let on_error = Closure::wrap(Box::new(move |_event: Event| {
+                if let Some(tx) = error_open_tx.borrow_mut().take() {
+                    let _ = tx.send(Err(connection_error("Failed to open WebSocket connection")));
+                }
+            }) as Box<dyn FnMut(Event)>);
+
+            let close_sender = sender.clone();
+            let close_open_tx = open_tx;
+            let on_close = Closure::wrap(Box::new(move |event: CloseEvent| {
+                if let Some(tx) = close_open_tx.borrow_mut().take() {
+                    let _ = tx.send(Err(unexpected_close(event.code(), &event.reason())));
+                }
+                // Dropping the sender ends the message stream.
+                if let Some(tx) = close_sender.borrow_mut().take() {
+                    if event.code() != CLOSE_NORMAL {
+                        let _ = tx.unbounded_send(Err(unexpected_close(event.code(), &event.reason())));
+                    }
+                }
+            }) as Box<dyn FnMut(CloseEvent)>);
+
+            socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+            let opened = open_rx
+                .await
+                .unwrap_or_else(|_| Err(connection_error("WebSocket connection was dropped")));
+            socket.set_onopen(None);
+            drop(on_open);
+            if let Err(err) = opened {
+                let _ = socket.close();
+                return Err(err);
+            }
+
+            let connection = Self {
+                socket,
+                _on_message: on_message,
+                _on_error: on_error,
+                _on_close: on_close,
+            };
+            Ok((connection, rx.boxed_local()))
+        }
+
+        pub(crate) async fn send(&self, text: String) -> AiResult<()> {
+            self.socket
+                .send_with_str(&text)
+                .map_err(|err| connection_error(format!("Failed to send WebSocket message: {err:?}")))
+        }
+
+        pub(crate) async fn close(&self, code: u16, reason: &str) -> AiResult<()> {
+            self.socket
+                .close_with_code_and_reason(code, reason)
+                .map_err(|err| connection_error(format!("Failed to close WebSocket connection: {err:?}")))
+        }
+    }
+
+    impl Drop for WebSocketConnection {
+        fn drop(&mut self) {
+            self.socket.set_onmessage(None);
+            self.socket.set_onerror(None);
+            self.socket.set_onclose(None);
+            let _ = self.socket.close();
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm-web")))]
+pub(crate) use unsupported::WebSocketConnection;
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm-web")))]
+mod unsupported {
+    use super::*;
+
+    pub(crate) struct WebSocketConnection;
+
+    impl WebSocketConnection {
+        pub(crate) async fn connect(_url: &Url) -> AiResult<(Self, ServerMessages)> {
+            Err(AiError::new(
+                AiErrorCode::Unsupported,
+                "Live sessions on wasm require the `wasm-web` feature.",
+                None,
+            ))
+        }
+
+        pub(crate) async fn send(&self, _text: String) -> AiResult<()> {
+            Ok(())
+        }
+
+        pub(crate) async fn close(&self, _code: u16, _reason: &str) -> AiResult<()> {
+            Ok(())
+        }
+    }
+}
+
+/// Scripted local WebSocket server for Live API tests.
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub(crate) mod testing {
+    use std::future::Future;
+
+    use futures::{SinkExt, StreamExt};
+    use serde_json::Value;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinHandle;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::WebSocketStream;
+
+    pub(crate) type ServerSocket = WebSocketStream<TcpStream>;
+
+    /// Accepts one connection and runs `script` with the request path and the socket.
+    ///
+    /// Returns the `http://` base URL to pass as [`RequestOptions::base_url`](crate::ai::RequestOptions)
+    /// and the server task, which should be awaited so script assertions surface.
+    // The handshake callback's error type is fixed by tungstenite.
+    #[allow(clippy::result_large_err)]
+    pub(crate) async fn serve_once<F, Fut>(script: F) -> (String, JoinHandle<()>)
+    where
+        F: FnOnce(String, ServerSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut path = String::new();
+            let socket = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+                path = request.uri().to_string();
+                Ok(response)
+            })
+            .await
+            .unwrap();
+            script(path, socket).await;
+        });
+        (base_url, handle)
+    }
+
+    /// Reads the next JSON message sent by the client.
+    pub(crate) async fn recv_json(socket: &mut ServerSocket) -> Value {
+        loop {
+            match socket.next().await.expect("client closed the socket").unwrap() {
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                Message::Binary(bytes) => return serde_json::from_slice(&bytes).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Sends `value` as a binary frame, as the Live API does.
+    pub(crate) async fn send_json(socket: &mut ServerSocket, value: Value) {
+        socket
+            .send(Message::Binary(value.to_string().into_bytes()))
+            .await
+            .unwrap();
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::testing::{send_json, serve_once};
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use serde_json::json;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn ws_url(base_url: &str) -> Url {
+        Url::parse(&base_url.replacen("http://", "ws://", 1)).unwrap()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn decodes_messages_and_reports_abnormal_close() {
+        let (base_url, server) = serve_once(|_, mut socket| async move {
+            send_json(&mut socket, json!({ "setupComplete": {} })).await;
+            socket.send(Message::Text("not json".into())).await.unwrap();
+            socket
+                .send(Message::Close(Some(CloseFrame {
+                    code: CloseCode::Error,
+                    reason: "internal".into(),
+                })))
+                .await
+                .unwrap();
+        })
+        .await;
+
+        let (_connection, mut messages) = WebSocketConnection::connect(&ws_url(&base_url)).await.unwrap();
+        assert_eq!(messages.next().await.unwrap().unwrap(), json!({ "setupComplete": {} }));
+        let err = messages.next().await.unwrap().unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::ParseFailed);
+        let err = messages.next().await.unwrap().unwrap_err();
+        assert_eq!(err.code(), AiErrorCode::FetchError);
+        assert!(err.message().contains("Code: 1011, Reason: internal"));
+        assert!(messages.next().await.is_none());
+        server.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn normal_close_ends_stream() {
+        let (base_url, server) = serve_once(|_, mut socket| async move {
+            socket.close(None).await.unwrap();
+        })
+        .await;
+
+        let (connection, mut messages) = WebSocketConnection::connect(&ws_url(&base_url)).await.unwrap();
+        assert!(messages.next().await.is_none());
+        connection.close(CLOSE_NORMAL, "done").await.unwrap();
+        server.await.unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use std::fmt;
 use std::time::{Duration, SystemTimeError};
 
 use crate::app_check::util::format_duration;
+use crate::util::jwt::TokenVerificationError;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppCheckError {
@@ -17,6 +18,7 @@ pub enum AppCheckError {
     InitialThrottle { http_status: u16, retry_after: Duration },
     Throttled { http_status: u16, retry_after: Duration },
     TokenExpired,
+    InvalidToken(TokenVerificationError),
     Internal(String),
 }
 
@@ -77,6 +79,9 @@ impl fmt::Display for AppCheckError {
             AppCheckError::TokenExpired => {
                 write!(f, "App Check token has expired")
             }
+            AppCheckError::InvalidToken(error) => {
+                write!(f, "App Check token verification failed: {error}")
+            }
             AppCheckError::Internal(message) => {
                 write!(f, "Internal App Check error: {message}")
             }
@@ -12,6 +12,7 @@ mod refresher;
 mod state;
 //#[cfg(feature = "firestore")]
 mod token_provider;
+mod token_verifier;
 mod types;
 mod util;
 
@@ -48,6 +49,9 @@ pub use providers::{CustomProvider, CustomProviderOptions, ReCaptchaEnterprisePr
 #[doc(inline)]
 pub use token_provider::{app_check_token_provider_arc, AppCheckTokenProvider};
 
+#[doc(inline)]
+pub use token_verifier::{AppCheckTokenClaims, AppCheckTokenVerifier, APP_CHECK_JWKS_URL};
+
 #[allow(unused_imports)]
 pub(crate) use types::{box_app_check_future, AppCheckState, TokenListenerEntry};
 
@@ -85,7 +85,7 @@ fn map_app_check_error(error: AppCheckError) -> FirestoreError {
         AppCheckError::AlreadyInitialized { .. }
         | AppCheckError::UseBeforeActivation { .. }
         | AppCheckError::InvalidConfiguration { .. } => invalid_argument(error.to_string()),
-        AppCheckError::TokenExpired => unauthenticated(error.to_string()),
+        AppCheckError::TokenExpired | AppCheckError::InvalidToken(_) => unauthenticated(error.to_string()),
         AppCheckError::Internal(message) => internal_error(message),
         AppCheckError::TokenFetchFailed { .. }
         | AppCheckError::ProviderError { .. }
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::app::FirebaseApp;
+use crate::app_check::errors::{AppCheckError, AppCheckResult};
+use crate::util::jwks::{HttpPublicKeySource, PublicKeySource};
+use crate::util::jwt::{check_time_claims, now_seconds, string_claim, verify_rs256, TokenVerificationError};
+
+/// JWKS endpoint publishing the keys that sign App Check tokens.
+pub const APP_CHECK_JWKS_URL: &str = "https://firebaseappcheck.googleapis.com/v1/jwks";
+
+const APP_CHECK_ISSUER_PREFIX: &str = "https://firebaseappcheck.googleapis.com/";
+
+/// Verified claims of an App Check token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppCheckTokenClaims {
+    /// The Firebase app ID the token was minted for (`sub` claim).
+    #[serde(rename = "sub")]
+    pub app_id: String,
+    pub iss: String,
+    pub aud: Vec<String>,
+    pub iat: i64,
+    pub exp: i64,
+    /// Any other claims not modelled above.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Verifies App Check tokens offline against the App Check JWKS.
+///
+/// Mirrors the Admin SDK's `appCheck().verifyToken`: an RS256 signature from a known key, a `JWT`
+/// header type, an App Check issuer, `projects/{project_number_or_id}` within `aud`, a non-empty
+/// `sub` and `exp`/`iat` consistent with the current time.
+#[derive(Clone)]
+pub struct AppCheckTokenVerifier {
+    project_id: String,
+    key_source: Arc<dyn PublicKeySource>,
+    clock_skew: Duration,
+}
+
+impl AppCheckTokenVerifier {
+    /// Creates a verifier for `project_id` (or project number) that downloads the App Check JWKS.
+    pub fn new(project_id: impl Into<String>) -> Self {
+        Self {
+            project_id: project_id.into(),
+            key_source: Arc::new(HttpPublicKeySource::new(APP_CHECK_JWKS_URL)),
+            clock_skew: Duration::ZERO,
+        }
+    }
+
+    /// Creates a verifier for the project configured on `app`.
+    pub fn for_app(app: &FirebaseApp) -> AppCheckResult<Self> {
+        let project_id = app
+            .options()
+            .project_id
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| AppCheckError::InvalidConfiguration {
+                message: "FirebaseOptions.project_id is required".into(),
+            })?;
+        Ok(Self::new(project_id))
+    }
+
+    /// Replaces the source of signing keys, e.g. with a fixed `PublicKeySet` in tests.
+    pub fn with_key_source(mut self, key_source: Arc<dyn PublicKeySource>) -> Self {
+        self.key_source = key_source;
+        self
+    }
+
+    /// Tolerates the given clock difference when checking time-based claims.
+    pub fn with_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Returns the project whose tokens this verifier accepts.
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    /// Verifies `token` and returns its typed claims.
+    pub async fn verify(&self, token: &str) -> AppCheckResult<AppCheckTokenClaims> {
+        self.verify_token(token).await.map_err(AppCheckError::InvalidToken)
+    }
+
+    async fn verify_token(&self, token: &str) -> Result<AppCheckTokenClaims, TokenVerificationError> {
+        let keys = self.key_source.public_keys().await?;
+        let verified = verify_rs256(token, &keys)?;
+
+        match verified.header.get("typ").and_then(Value::as_str) {
+            Some("JWT") => {}
+            other => {
+                return Err(TokenVerificationError::Malformed(format!(
+                    "expected header typ \"JWT\" but got {other:?}"
+                )))
+            }
+        }
+
+        let claims = verified.claims;
+        let iss = string_claim(&claims, "iss")?;
+        if !iss.starts_with(APP_CHECK_ISSUER_PREFIX) {
+            return Err(TokenVerificationError::invalid_claim(
+                "iss",
+                format!("expected an issuer starting with \"{APP_CHECK_ISSUER_PREFIX}\" but got \"{iss}\""),
+            ));
+        }
+
+        let expected_audience = format!("projects/{}", self.project_id);
+        let audience_matches = claims
+            .get("aud")
+            .and_then(Value::as_array)
+            .is_some_and(|values| values.iter().any(|value| value.as_str() == Some(&expected_audience)));
+        if !audience_matches {
+            return Err(TokenVerificationError::invalid_claim(
+                "aud",
+                format!("expected an array containing \"{expected_audience}\""),
+            ));
+        }
+
+        if string_claim(&claims, "sub")?.is_empty() {
+            return Err(TokenVerificationError::invalid_claim("sub", "must be a non-empty string"));
+        }
+
+        let skew = self.clock_skew.as_secs() as i64;
+        check_time_claims(&claims, now_seconds(), skew, &["iat"])?;
+
+        serde_json::from_value(Value::Object(claims)).map_err(|err| TokenVerificationError::Malformed(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::jwks::test_keys::{sign_rs256, test_key_pair};
+    use crate::util::jwks::PublicKeySet;
+    use serde_json::json;
+
+    const PROJECT_NUMBER: &str = "123456789";
+    const APP_ID: &str = "1:123456789:web:abcdef";
+
+    fn verifier_with_key() -> (AppCheckTokenVerifier, rsa::RsaPrivateKey) {
+        let (private_key, public_key) = test_key_pair();
+        let mut keys = PublicKeySet::new();
+        keys.insert("kid-1", public_key);
+        let verifier = AppCheckTokenVerifier::new(PROJECT_NUMBER).with_key_source(Arc::new(keys));
+        (verifier, private_key)
+    }
+
+    fn valid_claims() -> Value {
+        let now = now_seconds();
+        json!({
+            "iss": format!("{APP_CHECK_ISSUER_PREFIX}{PROJECT_NUMBER}"),
+            "aud": [format!("projects/{PROJECT_NUMBER}"), "projects/demo-project"],
+            "sub": APP_ID,
+            "iat": now - 10,
+            "exp": now + 3600
+        })
+    }
+
+    fn expect_error(result: AppCheckResult<AppCheckTokenClaims>) -> TokenVerificationError {
+        match result {
+            Err(AppCheckError::InvalidToken(err)) => err,
+            other => panic!("expected invalid token error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_returns_typed_claims() {
+        let (verifier, private_key) = verifier_with_key();
+        let token = sign_rs256(&private_key, "kid-1", &valid_claims());
+
+        let claims = verifier.verify(&token).await.expect("token should verify");
+        assert_eq!(claims.app_id, APP_ID);
+        assert_eq!(claims.aud.len(), 2);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_rejects_wrong_audience_and_issuer() {
+        let (verifier, private_key) = verifier_with_key();
+
+        let mut claims = valid_claims();
+        claims["aud"] = json!(["projects/other"]);
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "aud", .. }));
+
+        let mut claims = valid_claims();
+        claims["iss"] = json!("https://securetoken.google.com/demo-project");
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "iss", .. }));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_rejects_expired_tokens_and_empty_subject() {
+        let (verifier, private_key) = verifier_with_key();
+
+        let mut claims = valid_claims();
+        claims["exp"] = json!(now_seconds() - 1);
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::Expired { .. }));
+
+        let mut claims = valid_claims();
+        claims["sub"] = json!("");
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "sub", .. }));
+    }
+}
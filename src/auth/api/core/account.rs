@@ -1,4 +1,4 @@
-use crate::auth::error::{map_mfa_error_code, AuthError, AuthResult};
+use crate::auth::error::{map_rest_error_body, AuthError, AuthResult};
 use crate::auth::model::{
     GetAccountInfoResponse, MfaEnrollmentInfo, ProviderUserInfo, SignInWithPasswordRequest, SignInWithPasswordResponse,
 };
@@ -167,16 +167,6 @@ pub struct UpdateAccountResponse {
     pub mfa_info: Option<Vec<MfaEnrollmentInfo>>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Option<ErrorBody>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorBody {
-    message: Option<String>,
-}
-
 pub async fn send_password_reset_email(client: &Client, endpoint: &str, api_key: &str, email: &str) -> AuthResult<()> {
     let mut request = SendOobCodeRequest::new(ActionCodeOperation::PasswordReset);
     request.email = Some(email.to_owned());
@@ -189,6 +179,23 @@ pub async fn send_email_verification(client: &Client, endpoint: &str, api_key: &
     send_oob_code_async(client.clone(), endpoint.to_owned(), api_key.to_owned(), request).await
 }
 
+pub async fn verify_and_change_email(
+    client: &Client,
+    endpoint: &str,
+    api_key: &str,
+    id_token: &str,
+    new_email: &str,
+    settings: Option<&ActionCodeSettings>,
+) -> AuthResult<()> {
+    let mut request = SendOobCodeRequest::new(ActionCodeOperation::VerifyAndChangeEmail);
+    request.id_token = Some(id_token.to_owned());
+    request.new_email = Some(new_email.to_owned());
+    if let Some(settings) = settings {
+        apply_action_code_settings(&mut request, settings)?;
+    }
+    send_oob_code_async(client.clone(), endpoint.to_owned(), api_key.to_owned(), request).await
+}
+
 async fn send_oob_code_async(
     client: Client,
     endpoint: String,
@@ -570,19 +577,99 @@ async fn get_account_info_async(
     }
 }
 
-fn map_error(status: StatusCode, body: String) -> AuthError {
-    if let Ok(parsed) = serde_json::from_str::<ErrorResponse>(&body) {
-        if let Some(error) = parsed.error {
-            if let Some(message) = error.message {
-                if let Some(mapped) = map_mfa_error_code(&message) {
-                    return mapped;
-                }
-                return AuthError::InvalidCredential(message);
-            }
-        }
+#[derive(Debug, Serialize)]
+struct CreateAuthUriRequest {
+    identifier: String,
+    #[serde(rename = "continueUri")]
+    continue_uri: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateAuthUriResponse {
+    #[serde(rename = "signinMethods", default)]
+    pub signin_methods: Vec<String>,
+    #[serde(default)]
+    pub registered: Option<bool>,
+}
+
+pub async fn create_auth_uri(
+    client: &Client,
+    endpoint: &str,
+    api_key: &str,
+    identifier: &str,
+    continue_uri: &str,
+) -> AuthResult<CreateAuthUriResponse> {
+    let url = identity_toolkit_url(endpoint, "accounts:createAuthUri", api_key);
+    let request = CreateAuthUriRequest {
+        identifier: identifier.to_owned(),
+        continue_uri: continue_uri.to_owned(),
+    };
+
+    let response = client
+        .post(url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|err| AuthError::Network(err.to_string()))?;
+
+    if response.status().is_success() {
+        response
+            .json::<CreateAuthUriResponse>()
+            .await
+            .map_err(|err| AuthError::InvalidCredential(err.to_string()))
+    } else {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_else(|_| String::new());
+        Err(map_error(status, body))
     }
+}
 
-    AuthError::InvalidCredential(format!("Request failed with status {status}: {body}"))
+#[derive(Debug, Serialize)]
+struct RevokeTokenRequest {
+    #[serde(rename = "providerId")]
+    provider_id: String,
+    #[serde(rename = "tokenType")]
+    token_type: &'static str,
+    token: String,
+    #[serde(rename = "idToken")]
+    id_token: String,
+}
+
+pub async fn revoke_token(
+    client: &Client,
+    endpoint: &str,
+    api_key: &str,
+    id_token: &str,
+    provider_id: &str,
+    token: &str,
+) -> AuthResult<()> {
+    let url = identity_toolkit_url(endpoint, "accounts:revokeToken", api_key);
+    let request = RevokeTokenRequest {
+        provider_id: provider_id.to_owned(),
+        token_type: "ACCESS_TOKEN",
+        token: token.to_owned(),
+        id_token: id_token.to_owned(),
+    };
+
+    let response = client
+        .post(url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|err| AuthError::Network(err.to_string()))?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_else(|_| String::new());
+        Err(map_error(status, body))
+    }
+}
+
+fn map_error(status: StatusCode, body: String) -> AuthError {
+    map_rest_error_body(&body)
+        .unwrap_or_else(|| AuthError::InvalidCredential(format!("Request failed with status {status}: {body}")))
 }
 
 #[cfg(test)]
@@ -0,0 +1,296 @@
+use std::env;
+
+use reqwest::Url;
+use serde::Deserialize;
+
+use super::Auth;
+use crate::app::LOGGER as APP_LOGGER;
+use crate::auth::error::{AuthError, AuthResult};
+use crate::platform::environment::default_emulator_host;
+
+/// Environment variable used by the Admin SDKs and the Firebase CLI to point at the Auth Emulator.
+pub const AUTH_EMULATOR_HOST_ENV: &str = "FIREBASE_AUTH_EMULATOR_HOST";
+
+/// Options accepted by [`connect_auth_emulator`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EmulatorOptions {
+    /// Suppresses the warning logged when the emulator is connected.
+    pub disable_warnings: bool,
+}
+
+/// Emulator settings applied to an [`Auth`] instance, mirroring the JS `auth.emulatorConfig`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmulatorConfig {
+    pub protocol: String,
+    pub host: String,
+    pub port: Option<u16>,
+    pub options: EmulatorOptions,
+}
+
+impl EmulatorConfig {
+    fn origin(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}://{}:{port}", self.protocol, self.host),
+            None => format!("{}://{}", self.protocol, self.host),
+        }
+    }
+}
+
+/// An out-of-band code (email verification, password reset, email sign-in) captured by the emulator.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EmulatorOobCode {
+    pub email: String,
+    pub oob_code: String,
+    pub oob_link: String,
+    pub request_type: String,
+}
+
+/// An SMS verification code captured by the emulator.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EmulatorSmsCode {
+    pub phone_number: String,
+    pub session_info: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OobCodesResponse {
+    #[serde(default)]
+    oob_codes: Vec<EmulatorOobCode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationCodesResponse {
+    #[serde(default)]
+    verification_codes: Vec<EmulatorSmsCode>,
+}
+
+/// Routes all requests made by `auth` to the Auth Emulator running at `url`.
+///
+/// Mirrors the JS SDK `connectAuthEmulator`; `url` must include the scheme, e.g.
+/// `http://127.0.0.1:9099`.
+pub fn connect_auth_emulator(auth: &Auth, url: &str, options: Option<EmulatorOptions>) -> AuthResult<()> {
+    auth.connect_emulator(url, options.unwrap_or_default())
+}
+
+impl Auth {
+    /// Routes the Identity Toolkit, Secure Token and IdP requests to the Auth Emulator at `url`.
+    ///
+    /// Connecting again with the same settings is a no-op; switching to a different emulator after
+    /// the first connection is rejected, matching the JS SDK.
+    pub fn connect_emulator(&self, url: &str, options: EmulatorOptions) -> AuthResult<()> {
+        let config = parse_emulator_url(url, options)?;
+        {
+            let mut current = self.emulator_config.lock().unwrap();
+            if let Some(existing) = current.as_ref() {
+                if existing == &config {
+                    return Ok(());
+                }
+                return Err(AuthError::InvalidCredential(
+                    "Auth is already connected to a different emulator".into(),
+                ));
+            }
+            *current = Some(config.clone());
+        }
+
+        let origin = config.origin();
+        self.set_identity_toolkit_endpoint(format!("{origin}/identitytoolkit.googleapis.com/v1"));
+        self.set_secure_token_endpoint(format!("{origin}/securetoken.googleapis.com/v1/token"));
+
+        if !config.options.disable_warnings {
+            APP_LOGGER.warn(
+                "WARNING: You are using the Auth Emulator, which is intended for local testing only. Do not use \
+                 with production credentials.",
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the emulator settings when [`connect_emulator`](Self::connect_emulator) has been applied.
+    pub fn emulator_config(&self) -> Option<EmulatorConfig> {
+        self.emulator_config.lock().unwrap().clone()
+    }
+
+    /// Lists the out-of-band codes the emulator has issued for this project.
+    pub async fn emulator_oob_codes(&self) -> AuthResult<Vec<EmulatorOobCode>> {
+        let response: OobCodesResponse = self.emulator_get("oobCodes").await?;
+        Ok(response.oob_codes)
+    }
+
+    /// Lists the SMS verification codes the emulator has issued for this project.
+    pub async fn emulator_sms_codes(&self) -> AuthResult<Vec<EmulatorSmsCode>> {
+        let response: VerificationCodesResponse = self.emulator_get("verificationCodes").await?;
+        Ok(response.verification_codes)
+    }
+
+    /// Connects to the emulator advertised by `FIREBASE_AUTH_EMULATOR_HOST` or
+    /// `__FIREBASE_DEFAULTS__.emulatorHosts.auth`, if any.
+    pub(super) fn connect_default_emulator(&self) -> AuthResult<()> {
+        let host = env::var(AUTH_EMULATOR_HOST_ENV)
+            .ok()
+            .filter(|host| !host.is_empty())
+            .or_else(|| default_emulator_host("auth"));
+        match host {
+            Some(host) => self.connect_emulator(&format!("http://{host}"), EmulatorOptions::default()),
+            None => Ok(()),
+        }
+    }
+
+    async fn emulator_get<T: serde::de::DeserializeOwned>(&self, resource: &str) -> AuthResult<T> {
+        let config = self.emulator_config().ok_or_else(|| {
+            AuthError::InvalidCredential("Emulator helpers require connect_auth_emulator to be called first".into())
+        })?;
+        let project_id = self
+            .app
+            .options()
+            .project_id
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| AuthError::InvalidCredential("FirebaseOptions.project_id is required".into()))?;
+        let url = format!("{}/emulator/v1/projects/{project_id}/{resource}", config.origin());
+
+        let response = self
+            .rest_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|err| AuthError::Network(err.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AuthError::Network(format!("Emulator request failed ({status}): {body}")));
+        }
+        response
+            .json::<T>()
+            .await
+            .map_err(|err| AuthError::Network(err.to_string()))
+    }
+}
+
+/// Returns `true` when the process is configured to talk to the Auth Emulator.
+pub(crate) fn emulator_host_configured() -> bool {
+    env::var(AUTH_EMULATOR_HOST_ENV).is_ok_and(|host| !host.is_empty()) || default_emulator_host("auth").is_some()
+}
+
+fn parse_emulator_url(url: &str, options: EmulatorOptions) -> AuthResult<EmulatorConfig> {
+    let parsed = Url::parse(url)
+        .map_err(|err| AuthError::InvalidCredential(format!("Invalid emulator URL \"{url}\": {err}")))?;
+    let protocol = parsed.scheme();
+    if protocol != "http" && protocol != "https" {
+        return Err(AuthError::InvalidCredential(format!(
+            "Emulator URL must start with a valid scheme (http:// or https://), got \"{url}\""
+        )));
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| AuthError::InvalidCredential(format!("Emulator URL \"{url}\" is missing a host")))?;
+    Ok(EmulatorConfig {
+        protocol: protocol.to_string(),
+        host: host.to_string(),
+        port: parsed.port(),
+        options,
+    })
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::app::{FirebaseApp, FirebaseAppConfig, FirebaseOptions};
+    use crate::component::ComponentContainer;
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn build_auth() -> Arc<Auth> {
+        let options = FirebaseOptions {
+            api_key: Some("test-api-key".into()),
+            project_id: Some("project".into()),
+            ..Default::default()
+        };
+        let app = FirebaseApp::new(options, FirebaseAppConfig::new("test", false), ComponentContainer::new("test"));
+        Auth::builder(app)
+            .defer_initialization()
+            .build()
+            .expect("failed to build auth")
+    }
+
+    #[test]
+    fn connect_emulator_rewrites_endpoints() {
+        let auth = build_auth();
+        connect_auth_emulator(&auth, "http://127.0.0.1:9099", Some(EmulatorOptions { disable_warnings: true }))
+            .unwrap();
+
+        assert_eq!(
+            auth.identity_toolkit_endpoint(),
+            "http://127.0.0.1:9099/identitytoolkit.googleapis.com/v1"
+        );
+        assert_eq!(
+            auth.secure_token_endpoint(),
+            "http://127.0.0.1:9099/securetoken.googleapis.com/v1/token"
+        );
+        let config = auth.emulator_config().unwrap();
+        assert_eq!(config.host, "127.0.0.1");
+        assert_eq!(config.port, Some(9099));
+    }
+
+    #[test]
+    fn connect_emulator_validates_url_and_rejects_reconnect() {
+        let auth = build_auth();
+        assert!(auth
+            .connect_emulator("127.0.0.1:9099", EmulatorOptions::default())
+            .is_err());
+
+        let options = EmulatorOptions { disable_warnings: true };
+        auth.connect_emulator("http://localhost:9099", options.clone()).unwrap();
+        auth.connect_emulator("http://localhost:9099", options.clone()).unwrap();
+        assert!(auth.connect_emulator("http://localhost:9199", options).is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn emulator_helpers_list_codes() {
+        let server = start_mock_server();
+        let oob_mock = server.mock(|when, then| {
+            when.method(GET).path("/emulator/v1/projects/project/oobCodes");
+            then.status(200).json_body(json!({
+                "oobCodes": [{
+                    "email": "user@example.com",
+                    "oobCode": "code-123",
+                    "oobLink": "http://localhost/emulator/action?oobCode=code-123",
+                    "requestType": "EMAIL_SIGNIN"
+                }]
+            }));
+        });
+        let sms_mock = server.mock(|when, then| {
+            when.method(GET).path("/emulator/v1/projects/project/verificationCodes");
+            then.status(200).json_body(json!({
+                "verificationCodes": [{
+                    "phoneNumber": "+15555550100",
+                    "sessionInfo": "session",
+                    "code": "123456"
+                }]
+            }));
+        });
+
+        let auth = build_auth();
+        auth.connect_emulator(&server.base_url(), EmulatorOptions { disable_warnings: true })
+            .unwrap();
+
+        let oob_codes = auth.emulator_oob_codes().await.unwrap();
+        assert_eq!(oob_codes[0].oob_code, "code-123");
+        assert_eq!(oob_codes[0].request_type, "EMAIL_SIGNIN");
+        let sms_codes = auth.emulator_sms_codes().await.unwrap();
+        assert_eq!(sms_codes[0].code, "123456");
+        oob_mock.assert();
+        sms_mock.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn emulator_helpers_require_connection() {
+        let auth = build_auth();
+        assert!(auth.emulator_oob_codes().await.is_err());
+    }
+}
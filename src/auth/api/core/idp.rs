@@ -1,7 +1,9 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::auth::error::{AuthError, AuthResult};
+use crate::auth::error::{
+    account_conflict, map_account_conflict, map_rest_error_body, AccountConflictCode, AuthError, AuthResult,
+};
 use crate::auth::model::MfaEnrollmentInfo;
 
 /// Fields returned by the `signInWithIdp` Firebase Auth REST endpoint.
@@ -44,23 +46,27 @@ pub struct SignInWithIdpRequest {
     pub return_secure_token: bool,
     #[serde(rename = "idToken", skip_serializing_if = "Option::is_none")]
     pub id_token: Option<String>,
+    #[serde(rename = "pendingToken", skip_serializing_if = "Option::is_none")]
+    pub pending_token: Option<String>,
 }
 
 /// Signs a user in with an identity provider using the `signInWithIdp` REST endpoint.
 pub async fn sign_in_with_idp(
     client: &Client,
+    endpoint: &str,
     api_key: &str,
     request: &SignInWithIdpRequest,
 ) -> AuthResult<SignInWithIdpResponse> {
-    sign_in_with_idp_async(client.clone(), api_key.to_owned(), request.clone()).await
+    sign_in_with_idp_async(client.clone(), endpoint.to_owned(), api_key.to_owned(), request.clone()).await
 }
 
 async fn sign_in_with_idp_async(
     client: Client,
+    endpoint: String,
     api_key: String,
     request: SignInWithIdpRequest,
 ) -> AuthResult<SignInWithIdpResponse> {
-    let url = format!("https://identitytoolkit.googleapis.com/v1/accounts:signInWithIdp?key={api_key}");
+    let url = format!("{}/accounts:signInWithIdp?key={api_key}", endpoint.trim_end_matches('/'));
 
     let response = client
         .post(&url)
@@ -70,13 +76,27 @@ async fn sign_in_with_idp_async(
         .map_err(|err| AuthError::Network(err.to_string()))?;
 
     let status = response.status();
+    let body = response.text().await.unwrap_or_default();
     if !status.is_success() {
-        let body = response.text().await.unwrap_or_default();
-        return Err(AuthError::InvalidCredential(format!("signInWithIdp failed ({status}): {body}")));
+        return Err(map_rest_error_body(&body)
+            .unwrap_or_else(|| AuthError::InvalidCredential(format!("signInWithIdp failed ({status}): {body}"))));
     }
 
-    response
-        .json::<SignInWithIdpResponse>()
-        .await
-        .map_err(|err| AuthError::InvalidCredential(err.to_string()))
+    let value: serde_json::Value =
+        serde_json::from_str(&body).map_err(|err| AuthError::InvalidCredential(err.to_string()))?;
+    // With `returnIdpCredential`, linking conflicts arrive as a 200 response carrying the
+    // conflicting provider tokens so the caller can recover the credential.
+    if value.get("needConfirmation").and_then(serde_json::Value::as_bool) == Some(true) {
+        return Err(account_conflict(
+            AccountConflictCode::AccountExistsWithDifferentCredential,
+            "NEED_CONFIRMATION",
+            &value,
+        ));
+    }
+    if let Some(message) = value.get("errorMessage").and_then(serde_json::Value::as_str) {
+        return Err(map_account_conflict(message, &value)
+            .unwrap_or_else(|| AuthError::InvalidCredential(format!("signInWithIdp failed: {message}"))));
+    }
+
+    serde_json::from_value::<SignInWithIdpResponse>(value).map_err(|err| AuthError::InvalidCredential(err.to_string()))
 }
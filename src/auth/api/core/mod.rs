@@ -8,17 +8,24 @@ use reqwest::Url;
 use serde::Serialize;
 
 mod account;
+mod emulator;
 mod idp;
 mod mfa;
 mod phone;
+mod session;
 mod token;
 
 // Re-export for public use
+pub(crate) use emulator::emulator_host_configured;
+pub use emulator::{
+    connect_auth_emulator, EmulatorConfig, EmulatorOobCode, EmulatorOptions, EmulatorSmsCode, AUTH_EMULATOR_HOST_ENV,
+};
+pub use session::UserSession;
 pub(crate) use token::DEFAULT_SECURE_TOKEN_ENDPOINT;
 pub use token::{refresh_id_token, refresh_id_token_with_endpoint, RefreshTokenResponse};
 
 use crate::app::{register_component, AppError, FirebaseApp, LOGGER as APP_LOGGER};
-use crate::auth::error::{AuthError, AuthResult};
+use crate::auth::error::{map_rest_error_body, AuthError, AuthResult};
 use crate::auth::model::MfaEnrollmentInfo;
 use crate::auth::model::{
     AuthConfig, AuthCredential, AuthStateListeners, EmailAuthProvider, GetAccountInfoResponse,
@@ -31,6 +38,7 @@ use crate::auth::persistence::indexed_db::IndexedDbPersistence;
 use crate::auth::persistence::{
     AuthPersistence, InMemoryPersistence, PersistedAuthState, PersistenceListener, PersistenceSubscription,
 };
+use crate::auth::phone::PhoneCredentialParts;
 use crate::auth::types::{
     ActionCodeInfo, ActionCodeInfoData, ActionCodeOperation, ActionCodeSettings, ActionCodeUrl, ApplicationVerifier,
     ConfirmationResult, MultiFactorError, MultiFactorInfo, MultiFactorOperation, MultiFactorSession,
@@ -50,9 +58,9 @@ use crate::platform::runtime::{sleep as runtime_sleep, spawn_detached};
 use crate::platform::token::{AsyncTokenProvider, TokenError};
 use crate::util::PartialObserver;
 use account::{
-    apply_action_code, confirm_password_reset, delete_account, get_account_info, reset_password_info,
-    send_email_verification, send_password_reset_email, send_sign_in_link_to_email, update_account, verify_password,
-    UpdateAccountRequest, UpdateAccountResponse, UpdateString,
+    apply_action_code, confirm_password_reset, create_auth_uri, delete_account, get_account_info, reset_password_info,
+    revoke_token, send_email_verification, send_password_reset_email, send_sign_in_link_to_email, update_account,
+    verify_and_change_email, verify_password, UpdateAccountRequest, UpdateAccountResponse, UpdateString,
 };
 use idp::{sign_in_with_idp, SignInWithIdpRequest, SignInWithIdpResponse};
 use mfa::{
@@ -119,6 +127,7 @@ pub struct Auth {
     oauth_request_uri: Mutex<String>,
     identity_toolkit_endpoint: Mutex<String>,
     secure_token_endpoint: Mutex<String>,
+    emulator_config: Mutex<Option<EmulatorConfig>>,
     refresh_cancel: Mutex<Option<Arc<AtomicBool>>>,
     self_ref: Mutex<Weak<Auth>>,
 }
@@ -192,6 +201,7 @@ impl Auth {
             oauth_request_uri: Mutex::new(DEFAULT_OAUTH_REQUEST_URI.to_string()),
             identity_toolkit_endpoint: Mutex::new(DEFAULT_IDENTITY_TOOLKIT_ENDPOINT.to_string()),
             secure_token_endpoint: Mutex::new(token::DEFAULT_SECURE_TOKEN_ENDPOINT.to_string()),
+            emulator_config: Mutex::new(None),
             refresh_cancel: Mutex::new(None),
             self_ref: Mutex::new(Weak::new()),
         })
@@ -604,6 +614,11 @@ impl Auth {
 
         if !response.status().is_success() {
             let message = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            if let Some(conflict) =
+                map_rest_error_body(&message).filter(|err| matches!(err, AuthError::AccountConflict(_)))
+            {
+                return Err(conflict);
+            }
             return Err(AuthError::Network(message));
         }
 
@@ -1335,11 +1350,19 @@ impl Auth {
         verification_code: String,
         flow: PhoneFinalization,
     ) -> AuthResult<UserCredential> {
-        let api_key = self.api_key()?;
-        let endpoint = self.identity_toolkit_endpoint();
         let mut request = SignInWithPhoneNumberRequest::default();
         request.session_info = Some(session_info);
         request.code = Some(verification_code);
+        self.finalize_phone_request(request, flow).await
+    }
+
+    async fn finalize_phone_request(
+        self: Arc<Self>,
+        mut request: SignInWithPhoneNumberRequest,
+        flow: PhoneFinalization,
+    ) -> AuthResult<UserCredential> {
+        let api_key = self.api_key()?;
+        let endpoint = self.identity_toolkit_endpoint();
 
         let response = match &flow {
             PhoneFinalization::SignIn => {
@@ -1363,10 +1386,22 @@ impl Auth {
         credential: PhoneAuthCredential,
         flow: PhoneFinalization,
     ) -> AuthResult<UserCredential> {
-        let (verification_id, verification_code) = credential.into_parts();
-        self.clone()
-            .finalize_phone_confirmation(verification_id, verification_code, flow)
-            .await
+        match credential.into_parts() {
+            PhoneCredentialParts::Verification { session_info, code } => {
+                self.clone().finalize_phone_confirmation(session_info, code, flow).await
+            }
+            PhoneCredentialParts::TemporaryProof {
+                phone_number,
+                temporary_proof,
+            } => {
+                let request = SignInWithPhoneNumberRequest {
+                    phone_number: Some(phone_number),
+                    temporary_proof: Some(temporary_proof),
+                    ..Default::default()
+                };
+                self.clone().finalize_phone_request(request, flow).await
+            }
+        }
     }
 
     async fn handle_phone_response(
@@ -1665,6 +1700,39 @@ impl Auth {
     /// # Ok(()) }
     /// ```
     pub async fn sign_in_with_email_link(&self, email: &str, email_link: &str) -> AuthResult<UserCredential> {
+        self.complete_email_link(email, email_link, MultiFactorOperation::SignIn, None)
+            .await
+    }
+
+    /// Links an email link credential to the current user, mirroring `linkWithCredential` with
+    /// `EmailAuthProvider.credentialWithLink`.
+    pub async fn link_with_email_link(&self, email: &str, email_link: &str) -> AuthResult<UserCredential> {
+        let user = self.require_current_user()?;
+        self.complete_email_link(email, email_link, MultiFactorOperation::Link, Some(user.get_id_token(false)?))
+            .await
+    }
+
+    /// Reauthenticates the current user with an email sign-in link.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::UserMismatch`] without replacing the current user when the link signs
+    /// in a different account.
+    pub async fn reauthenticate_with_email_link(&self, email: &str, email_link: &str) -> AuthResult<Arc<User>> {
+        self.require_current_user()?;
+        let result = self
+            .complete_email_link(email, email_link, MultiFactorOperation::Reauthenticate, None)
+            .await?;
+        Ok(result.user)
+    }
+
+    async fn complete_email_link(
+        &self,
+        email: &str,
+        email_link: &str,
+        operation: MultiFactorOperation,
+        id_token: Option<String>,
+    ) -> AuthResult<UserCredential> {
         let api_key = self.api_key()?;
         let action_url = ActionCodeUrl::parse(email_link)
             .ok_or_else(|| AuthError::InvalidCredential("Invalid email action link".into()))?;
@@ -1680,7 +1748,7 @@ impl Auth {
             oob_code: action_url.code.clone(),
             return_secure_token: true,
             tenant_id: action_url.tenant_id.clone(),
-            id_token: None,
+            id_token,
         };
 
         let response: SignInWithEmailLinkResponse = self
@@ -1694,19 +1762,22 @@ impl Auth {
             context.provider_id = Some(EmailAuthProvider::PROVIDER_ID.to_string());
             context.anonymous = false;
 
-            return Err(self.build_multi_factor_error(
-                MultiFactorOperation::SignIn,
-                pending,
-                response.mfa_info.clone(),
-                context,
-                None,
-            ));
+            let user = match operation {
+                MultiFactorOperation::SignIn => None,
+                _ => self.current_user(),
+            };
+            return Err(self.build_multi_factor_error(operation, pending, response.mfa_info.clone(), context, user));
         }
 
         let local_id = response
             .local_id
             .as_deref()
             .ok_or_else(|| AuthError::InvalidCredential("Missing localId".into()))?;
+        if operation == MultiFactorOperation::Reauthenticate
+            && self.current_user().as_deref().map(User::uid) != Some(local_id)
+        {
+            return Err(AuthError::UserMismatch);
+        }
         let id_token = response
             .id_token
             .as_deref()
@@ -1717,10 +1788,11 @@ impl Auth {
             .ok_or_else(|| AuthError::InvalidCredential("Missing refreshToken".into()))?;
         let expires_in = response.expires_in.as_deref();
         let response_email = response.email.as_deref().unwrap_or(email);
-        let operation = if response.is_new_user.unwrap_or(false) {
-            "signUp"
-        } else {
-            "signIn"
+        let operation = match operation {
+            MultiFactorOperation::Link => "link",
+            MultiFactorOperation::Reauthenticate => "reauthenticate",
+            MultiFactorOperation::SignIn if response.is_new_user.unwrap_or(false) => "signUp",
+            MultiFactorOperation::SignIn => "signIn",
         };
 
         let payload = SignInResponsePayload {
@@ -1854,6 +1926,40 @@ impl Auth {
         self.perform_account_update(user, request).await
     }
 
+    /// Sends a verification email to `new_email`; the address is only updated once the link is
+    /// followed.
+    pub async fn verify_before_update_email(
+        &self,
+        new_email: &str,
+        settings: Option<&ActionCodeSettings>,
+    ) -> AuthResult<()> {
+        let user = self.require_current_user()?;
+        let id_token = user.get_id_token(false)?;
+        let api_key = self.api_key()?;
+        let endpoint = self.identity_toolkit_endpoint();
+        verify_and_change_email(&self.rest_client, &endpoint, &api_key, &id_token, new_email, settings).await
+    }
+
+    /// Returns the sign-in methods registered for the given email address.
+    ///
+    /// Projects with email enumeration protection enabled always receive an empty list.
+    pub async fn fetch_sign_in_methods_for_email(&self, email: &str) -> AuthResult<Vec<String>> {
+        let api_key = self.api_key()?;
+        let endpoint = self.identity_toolkit_endpoint();
+        let response =
+            create_auth_uri(&self.rest_client, &endpoint, &api_key, email, &self.oauth_request_uri()).await?;
+        Ok(response.signin_methods)
+    }
+
+    /// Revokes an Apple OAuth access token for the current user.
+    pub async fn revoke_access_token(&self, token: &str) -> AuthResult<()> {
+        let user = self.require_current_user()?;
+        let id_token = user.get_id_token(false)?;
+        let api_key = self.api_key()?;
+        let endpoint = self.identity_toolkit_endpoint();
+        revoke_token(&self.rest_client, &endpoint, &api_key, &id_token, "apple.com", token).await
+    }
+
     /// Updates the current user's password.
     pub async fn update_password(&self, password: &str) -> AuthResult<Arc<User>> {
         let user = self.require_current_user()?;
@@ -1934,17 +2040,24 @@ impl Auth {
         id_token: Option<String>,
     ) -> AuthResult<UserCredential> {
         let oauth_credential = OAuthCredential::try_from(credential)?;
-        let post_body = oauth_credential.build_post_body()?;
+        // Credentials recovered from conflict errors may only carry a pending token.
+        let pending_token = oauth_credential.pending_token().map(str::to_string);
+        let post_body = match pending_token {
+            Some(_) => String::new(),
+            None => oauth_credential.build_post_body()?,
+        };
         let request = SignInWithIdpRequest {
             post_body,
             request_uri: self.oauth_request_uri(),
             return_idp_credential: true,
             return_secure_token: true,
             id_token,
+            pending_token,
         };
 
         let api_key = self.api_key()?;
-        let response = sign_in_with_idp(&self.rest_client, &api_key, &request).await?;
+        let endpoint = self.identity_toolkit_endpoint();
+        let response = sign_in_with_idp(&self.rest_client, &endpoint, &api_key, &request).await?;
         if let Some(pending) = response.mfa_pending_credential.clone() {
             let mut context = MultiFactorSignInContext::default();
             context.local_id = response.local_id.clone();
@@ -2499,6 +2612,7 @@ impl AuthBuilder {
         if let Some(persistence) = self.redirect_persistence {
             auth.set_redirect_persistence(persistence);
         }
+        auth.connect_default_emulator()?;
         if let Some(endpoint) = self.identity_toolkit_endpoint {
             auth.set_identity_toolkit_endpoint(endpoint);
         }
@@ -2537,6 +2651,11 @@ fn auth_factory(
         name: "auth".to_string(),
         reason: err.to_string(),
     })?;
+    auth.connect_default_emulator()
+        .map_err(|err| ComponentError::InitializationFailed {
+            name: "auth".to_string(),
+            reason: err.to_string(),
+        })?;
     let auth = Arc::new(auth);
     auth.initialize().map_err(|err| ComponentError::InitializationFailed {
         name: "auth".to_string(),
@@ -2559,7 +2678,7 @@ pub fn auth_for_app(app: FirebaseApp) -> AuthResult<Arc<Auth>> {
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use super::*;
-    use crate::auth::error::MultiFactorAuthErrorCode;
+    use crate::auth::error::{AccountConflictCode, MultiFactorAuthErrorCode};
     use crate::auth::types::{ActionCodeSettings, AndroidSettings, ApplicationVerifier, IosSettings};
     use crate::auth::{
         get_multi_factor_resolver, FirebaseAuth, PhoneAuthProvider, PhoneMultiFactorGenerator,
@@ -4695,4 +4814,270 @@ mod tests {
             Err(AuthError::InvalidCredential(message)) if message == "INVALID_ID_TOKEN"
         ));
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn link_conflict_returns_recoverable_credential() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        sign_in_user(&auth, &server).await;
+
+        let link_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:signInWithIdp")
+                .query_param("key", TEST_API_KEY)
+                .json_body_partial(json!({ "idToken": TEST_ID_TOKEN }).to_string());
+            then.status(200).json_body(json!({
+                "errorMessage": "FEDERATED_USER_ID_ALREADY_LINKED",
+                "providerId": GOOGLE_PROVIDER_ID,
+                "email": "other@example.com",
+                "oauthIdToken": "google-id-token",
+                "pendingToken": "pending-123"
+            }));
+        });
+
+        let credential = AuthCredential {
+            provider_id: GOOGLE_PROVIDER_ID.into(),
+            sign_in_method: GOOGLE_PROVIDER_ID.into(),
+            token_response: json!({ "idToken": "google-id-token" }),
+        };
+        let err = auth
+            .link_with_oauth_credential(credential)
+            .await
+            .expect_err("link should conflict");
+        link_mock.assert();
+
+        let conflict = match err {
+            AuthError::AccountConflict(conflict) => conflict,
+            other => panic!("unexpected error: {other:?}"),
+        };
+        assert_eq!(conflict.code(), AccountConflictCode::CredentialAlreadyInUse);
+        assert_eq!(conflict.email(), Some("other@example.com"));
+        let recovered = conflict.credential().cloned().expect("credential attached");
+        assert_eq!(recovered.provider_id, GOOGLE_PROVIDER_ID);
+
+        let sign_in_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:signInWithIdp")
+                .query_param("key", TEST_API_KEY)
+                .json_body_partial(json!({ "pendingToken": "pending-123", "postBody": "" }).to_string());
+            then.status(200).json_body(json!({
+                "localId": "other-uid",
+                "email": "other@example.com",
+                "idToken": "other-id-token",
+                "refreshToken": "other-refresh-token",
+                "expiresIn": "3600",
+                "providerId": GOOGLE_PROVIDER_ID
+            }));
+        });
+
+        let result = auth
+            .sign_in_with_oauth_credential(recovered)
+            .await
+            .expect("recovered credential should sign in");
+        sign_in_mock.assert();
+        assert_eq!(result.user.uid(), "other-uid");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sign_up_conflict_maps_email_exists() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:signUp")
+                .query_param("key", TEST_API_KEY);
+            then.status(400).body("{\"error\":{\"message\":\"EMAIL_EXISTS\"}}");
+        });
+
+        let err = auth
+            .create_user_with_email_and_password(TEST_EMAIL, TEST_PASSWORD)
+            .await
+            .expect_err("sign-up should conflict");
+        mock.assert();
+        assert!(matches!(
+            err,
+            AuthError::AccountConflict(conflict) if conflict.code() == AccountConflictCode::EmailAlreadyInUse
+        ));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fetch_sign_in_methods_for_email_returns_methods() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:createAuthUri")
+                .query_param("key", TEST_API_KEY)
+                .json_body(json!({
+                    "identifier": TEST_EMAIL,
+                    "continueUri": auth.oauth_request_uri()
+                }));
+            then.status(200).json_body(json!({
+                "registered": true,
+                "signinMethods": ["password", "emailLink"]
+            }));
+        });
+
+        let methods = auth
+            .fetch_sign_in_methods_for_email(TEST_EMAIL)
+            .await
+            .expect("fetch should succeed");
+        mock.assert();
+        assert_eq!(methods, vec!["password".to_string(), "emailLink".to_string()]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_before_update_email_sends_request() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        sign_in_user(&auth, &server).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:sendOobCode")
+                .query_param("key", TEST_API_KEY)
+                .json_body(json!({
+                    "requestType": "VERIFY_AND_CHANGE_EMAIL",
+                    "idToken": TEST_ID_TOKEN,
+                    "newEmail": "new@example.com",
+                    "continueUrl": "https://example.com/done",
+                    "canHandleCodeInApp": false
+                }));
+            then.status(200).json_body(json!({ "email": TEST_EMAIL }));
+        });
+
+        let settings = ActionCodeSettings {
+            url: "https://example.com/done".into(),
+            ..Default::default()
+        };
+        auth.verify_before_update_email("new@example.com", Some(&settings))
+            .await
+            .expect("verify before update should succeed");
+        mock.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn link_with_email_link_uses_current_id_token() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        sign_in_user(&auth, &server).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:signInWithEmailLink")
+                .query_param("key", TEST_API_KEY)
+                .json_body(json!({
+                    "email": TEST_EMAIL,
+                    "oobCode": "OOB",
+                    "returnSecureToken": true,
+                    "idToken": TEST_ID_TOKEN
+                }));
+            then.status(200).json_body(json!({
+                "localId": TEST_UID,
+                "email": TEST_EMAIL,
+                "idToken": UPDATED_ID_TOKEN,
+                "refreshToken": UPDATED_REFRESH_TOKEN,
+                "expiresIn": "3600"
+            }));
+        });
+
+        let link = "https://example.com/__/auth/action?mode=signIn&oobCode=OOB&apiKey=test-api-key";
+        let result = auth
+            .link_with_email_link(TEST_EMAIL, link)
+            .await
+            .expect("email link linking should succeed");
+        mock.assert();
+        assert_eq!(result.operation_type.as_deref(), Some("link"));
+        assert_eq!(result.user.token_manager().access_token(), Some(UPDATED_ID_TOKEN.to_string()));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reauthenticate_with_email_link_signs_in_without_id_token() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        sign_in_user(&auth, &server).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:signInWithEmailLink")
+                .query_param("key", TEST_API_KEY)
+                .json_body(json!({
+                    "email": TEST_EMAIL,
+                    "oobCode": "OOB",
+                    "returnSecureToken": true
+                }));
+            then.status(200).json_body(json!({
+                "localId": TEST_UID,
+                "email": TEST_EMAIL,
+                "idToken": UPDATED_ID_TOKEN,
+                "refreshToken": UPDATED_REFRESH_TOKEN,
+                "expiresIn": "3600"
+            }));
+        });
+
+        let link = "https://example.com/__/auth/action?mode=signIn&oobCode=OOB&apiKey=test-api-key";
+        let user = auth
+            .reauthenticate_with_email_link(TEST_EMAIL, link)
+            .await
+            .expect("email link reauthentication should succeed");
+        mock.assert();
+        assert_eq!(user.uid(), TEST_UID);
+        assert_eq!(user.token_manager().access_token(), Some(UPDATED_ID_TOKEN.to_string()));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reauthenticate_with_email_link_rejects_other_user() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        sign_in_user(&auth, &server).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v1/accounts:signInWithEmailLink");
+            then.status(200).json_body(json!({
+                "localId": "other-uid",
+                "email": "other@example.com",
+                "idToken": UPDATED_ID_TOKEN,
+                "refreshToken": UPDATED_REFRESH_TOKEN,
+                "expiresIn": "3600"
+            }));
+        });
+
+        let link = "https://example.com/__/auth/action?mode=signIn&oobCode=OOB&apiKey=test-api-key";
+        let err = auth
+            .reauthenticate_with_email_link("other@example.com", link)
+            .await
+            .unwrap_err();
+        mock.assert();
+        assert!(matches!(err, AuthError::UserMismatch));
+        let current = auth.current_user().expect("current user should be kept");
+        assert_eq!(current.uid(), TEST_UID);
+        assert_eq!(current.token_manager().access_token(), Some(TEST_ID_TOKEN.to_string()));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn revoke_access_token_posts_apple_token() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        sign_in_user(&auth, &server).await;
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/accounts:revokeToken")
+                .query_param("key", TEST_API_KEY)
+                .json_body(json!({
+                    "providerId": "apple.com",
+                    "tokenType": "ACCESS_TOKEN",
+                    "token": "apple-access-token",
+                    "idToken": TEST_ID_TOKEN
+                }));
+            then.status(200).json_body(json!({}));
+        });
+
+        auth.revoke_access_token("apple-access-token")
+            .await
+            .expect("revoke should succeed");
+        mock.assert();
+    }
 }
@@ -1,23 +1,13 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::auth::error::{map_mfa_error_code, AuthError, AuthResult};
+use crate::auth::error::{account_conflict, map_rest_error_body, AccountConflictCode, AuthError, AuthResult};
 use crate::auth::model::MfaEnrollmentInfo;
 
 fn endpoint_url(base: &str, path: &str, api_key: &str) -> String {
     format!("{}/{}?key={}", base.trim_end_matches('/'), path, api_key)
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: Option<ErrorBody>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorBody {
-    message: Option<String>,
-}
-
 #[derive(Debug, Serialize, Clone, Default)]
 pub struct SendPhoneVerificationCodeRequest {
     #[serde(rename = "phoneNumber")]
@@ -62,19 +52,8 @@ pub async fn send_phone_verification_code(
     } else {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
-        if let Ok(parsed) = serde_json::from_str::<ErrorResponse>(&body) {
-            if let Some(error) = parsed.error {
-                if let Some(message) = error.message {
-                    if let Some(mapped) = map_mfa_error_code(&message) {
-                        return Err(mapped);
-                    }
-                    return Err(AuthError::InvalidCredential(message));
-                }
-            }
-        }
-        Err(AuthError::InvalidCredential(format!(
-            "sendVerificationCode failed ({status}): {body}"
-        )))
+        Err(map_rest_error_body(&body)
+            .unwrap_or_else(|| AuthError::InvalidCredential(format!("sendVerificationCode failed ({status}): {body}"))))
     }
 }
 
@@ -134,9 +113,15 @@ pub async fn link_with_phone_number(
     request: &SignInWithPhoneNumberRequest,
 ) -> AuthResult<PhoneSignInResponse> {
     let response = execute_phone_request(client, endpoint, api_key, request).await?;
-    if response.temporary_proof.is_some() {
-        return Err(AuthError::InvalidCredential(
-            "Phone number linking requires confirmation".into(),
+    if let Some(temporary_proof) = response.temporary_proof.as_deref() {
+        let data = serde_json::json!({
+            "phoneNumber": response.phone_number,
+            "temporaryProof": temporary_proof,
+        });
+        return Err(account_conflict(
+            AccountConflictCode::CredentialAlreadyInUse,
+            "PHONE_NUMBER_EXISTS",
+            &data,
         ));
     }
     Ok(response)
@@ -175,18 +160,8 @@ async fn execute_phone_request(
     } else {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
-        if let Ok(parsed) = serde_json::from_str::<ErrorResponse>(&body) {
-            if let Some(error) = parsed.error {
-                if let Some(message) = error.message {
-                    if let Some(mapped) = map_mfa_error_code(&message) {
-                        return Err(mapped);
-                    }
-                    return Err(AuthError::InvalidCredential(message));
-                }
-            }
-        }
-        Err(AuthError::InvalidCredential(format!(
-            "signInWithPhoneNumber failed ({status}): {body}"
-        )))
+        Err(map_rest_error_body(&body).unwrap_or_else(|| {
+            AuthError::InvalidCredential(format!("signInWithPhoneNumber failed ({status}): {body}"))
+        }))
     }
 }
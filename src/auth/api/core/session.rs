@@ -0,0 +1,312 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use async_lock::Mutex as AsyncMutex;
+
+use super::{token, Auth};
+use crate::auth::error::{AuthError, AuthResult};
+use crate::auth::model::{User, UserInfo};
+use crate::auth::persistence::PersistedAuthState;
+use crate::auth::token_provider::map_auth_error;
+use crate::firestore::{FirestoreResult, TokenProvider, TokenProviderArc};
+use crate::platform::token::{AsyncTokenProvider, TokenError};
+
+/// A signed-in user that lives alongside, but independently of, [`Auth::current_user`].
+///
+/// Server workloads such as API gateways act on behalf of many users at once. Each
+/// `UserSession` owns its own tokens and refreshes them on demand against the Secure Token
+/// endpoint configured on the parent [`Auth`], without touching the instance's current user,
+/// persistence or auth state listeners.
+pub struct UserSession {
+    auth: Arc<Auth>,
+    user: Arc<User>,
+    refresh_lock: AsyncMutex<()>,
+}
+
+impl std::fmt::Debug for UserSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserSession").field("uid", &self.uid()).finish()
+    }
+}
+
+impl Auth {
+    /// Restores an independent [`UserSession`] from previously persisted auth state.
+    ///
+    /// The session does not replace the current user; it is intended for processes that act on
+    /// behalf of several users at once.
+    pub fn restore_session(self: &Arc<Self>, state: &PersistedAuthState) -> AuthResult<Arc<UserSession>> {
+        if !Self::has_refresh_token(state) {
+            return Err(AuthError::InvalidCredential(
+                "Persisted auth state is missing a refresh token".into(),
+            ));
+        }
+        let user = self.build_user_from_persisted_state(state);
+        Ok(Arc::new(UserSession::new(self.clone(), user)))
+    }
+
+    /// Exchanges a stored refresh token for a fresh ID token and wraps it in a [`UserSession`].
+    ///
+    /// The Secure Token endpoint only returns the UID, so the session user's profile is not
+    /// populated: the email is unset and the provider id is the neutral `"firebase"`.
+    pub async fn session_from_refresh_token(self: &Arc<Self>, refresh_token: &str) -> AuthResult<Arc<UserSession>> {
+        if refresh_token.is_empty() {
+            return Err(AuthError::InvalidCredential("Missing refresh token".into()));
+        }
+        let response = self.exchange_refresh_token(refresh_token).await?;
+        let info = UserInfo {
+            uid: response.user_id.clone(),
+            provider_id: "firebase".to_string(),
+            ..Default::default()
+        };
+        let user = User::new(self.app.clone(), info);
+        let expires_in = self.parse_expires_in(&response.expires_in)?;
+        user.update_tokens(Some(response.id_token), Some(response.refresh_token), Some(expires_in));
+        Ok(Arc::new(UserSession::new(self.clone(), Arc::new(user))))
+    }
+
+    async fn exchange_refresh_token(&self, refresh_token: &str) -> AuthResult<token::RefreshTokenResponse> {
+        let api_key = self.api_key()?;
+        let secure_endpoint = self.secure_token_endpoint();
+        token::refresh_id_token_with_endpoint(&self.rest_client, &secure_endpoint, &api_key, refresh_token).await
+    }
+}
+
+impl UserSession {
+    fn new(auth: Arc<Auth>, user: Arc<User>) -> Self {
+        Self {
+            auth,
+            user,
+            refresh_lock: AsyncMutex::new(()),
+        }
+    }
+
+    /// Returns the `Auth` instance whose endpoints and API key this session uses.
+    pub fn auth(&self) -> &Arc<Auth> {
+        &self.auth
+    }
+
+    /// Returns the user held by this session.
+    pub fn user(&self) -> Arc<User> {
+        self.user.clone()
+    }
+
+    /// Returns the Firebase UID of the session user.
+    pub fn uid(&self) -> String {
+        self.user().uid().to_string()
+    }
+
+    /// Returns the session's ID token, refreshing it when close to expiry or when requested.
+    ///
+    /// Concurrent callers share a single refresh round-trip.
+    pub async fn get_token(&self, force_refresh: bool) -> AuthResult<String> {
+        if !force_refresh {
+            if let Some(token) = self.fresh_token() {
+                return Ok(token);
+            }
+        }
+
+        let _guard = self.refresh_lock.lock().await;
+        if !force_refresh {
+            if let Some(token) = self.fresh_token() {
+                return Ok(token);
+            }
+        }
+        self.refresh().await
+    }
+
+    /// Serializes the session so it can be stored and later passed to [`Auth::restore_session`].
+    ///
+    /// Call this after token refreshes to capture rotated refresh tokens.
+    pub fn persisted_state(&self) -> PersistedAuthState {
+        let user = self.user();
+        let expires_at = user
+            .token_manager()
+            .expiration_time()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs() as i64);
+        PersistedAuthState {
+            user_id: user.uid().to_string(),
+            email: user.info().email.clone(),
+            refresh_token: user.refresh_token(),
+            access_token: user.token_manager().access_token(),
+            expires_at,
+        }
+    }
+
+    /// Exposes this session as a Firestore-compatible token provider.
+    pub fn token_provider(self: &Arc<Self>) -> TokenProviderArc {
+        Arc::new(UserSessionTokenProvider {
+            session: self.clone(),
+            force_refresh: AtomicBool::new(false),
+        })
+    }
+
+    fn fresh_token(&self) -> Option<String> {
+        let user = self.user();
+        let manager = user.token_manager();
+        if manager.should_refresh(self.auth.token_refresh_tolerance) {
+            None
+        } else {
+            manager.access_token()
+        }
+    }
+
+    async fn refresh(&self) -> AuthResult<String> {
+        let user = self.user();
+        let refresh_token = user
+            .refresh_token()
+            .ok_or_else(|| AuthError::InvalidCredential("Missing refresh token".into()))?;
+        let response = self.auth.exchange_refresh_token(&refresh_token).await?;
+        if response.user_id != user.uid() {
+            return Err(AuthError::InvalidCredential(
+                "Refreshed token belongs to a different user".into(),
+            ));
+        }
+        let expires_in = self.auth.parse_expires_in(&response.expires_in)?;
+        user.update_tokens(Some(response.id_token.clone()), Some(response.refresh_token), Some(expires_in));
+        Ok(response.id_token)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl AsyncTokenProvider for UserSession {
+    async fn get_token(&self, force_refresh: bool) -> Result<Option<String>, TokenError> {
+        UserSession::get_token(self, force_refresh)
+            .await
+            .map(Some)
+            .map_err(TokenError::from_error)
+    }
+}
+
+struct UserSessionTokenProvider {
+    session: Arc<UserSession>,
+    force_refresh: AtomicBool,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+impl TokenProvider for UserSessionTokenProvider {
+    async fn get_token(&self) -> FirestoreResult<Option<String>> {
+        let force_refresh = self.force_refresh.swap(false, Ordering::SeqCst);
+        self.session
+            .get_token(force_refresh)
+            .await
+            .map(Some)
+            .map_err(map_auth_error)
+    }
+
+    fn invalidate_token(&self) {
+        self.force_refresh.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_support::{start_mock_server, test_firebase_app_with_api_key};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    const TEST_API_KEY: &str = "test-api-key";
+
+    fn build_auth(server: &MockServer) -> Arc<Auth> {
+        Auth::builder(test_firebase_app_with_api_key(TEST_API_KEY))
+            .with_identity_toolkit_endpoint(server.url("/v1"))
+            .with_secure_token_endpoint(server.url("/token"))
+            .defer_initialization()
+            .build()
+            .expect("failed to build auth")
+    }
+
+    fn mock_refresh<'a>(server: &'a MockServer, refresh_token: &str, uid: &str, id_token: &str) -> httpmock::Mock<'a> {
+        let refresh_body = format!("refresh_token={refresh_token}");
+        let uid = uid.to_string();
+        let id_token = id_token.to_string();
+        server.mock(move |when, then| {
+            when.method(POST)
+                .path("/token")
+                .query_param("key", TEST_API_KEY)
+                .body_contains(refresh_body.as_str());
+            then.status(200).json_body(json!({
+                "access_token": id_token,
+                "refresh_token": format!("{uid}-rotated"),
+                "id_token": id_token,
+                "expires_in": "3600",
+                "user_id": uid
+            }));
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sessions_refresh_independently_of_current_user() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        let alice_mock = mock_refresh(&server, "alice-refresh", "alice", "alice-id");
+        let bob_mock = mock_refresh(&server, "bob-refresh", "bob", "bob-id");
+
+        let alice = auth.session_from_refresh_token("alice-refresh").await.unwrap();
+        let bob = auth.session_from_refresh_token("bob-refresh").await.unwrap();
+
+        alice_mock.assert();
+        bob_mock.assert();
+        assert_eq!(alice.uid(), "alice");
+        assert_eq!(bob.uid(), "bob");
+        assert_eq!(alice.user().info().provider_id, "firebase");
+        assert_eq!(alice.user().info().email, None);
+        assert_eq!(alice.get_token(false).await.unwrap(), "alice-id");
+        assert_eq!(bob.get_token(false).await.unwrap(), "bob-id");
+        assert!(auth.current_user().is_none());
+        assert_eq!(alice.persisted_state().refresh_token.as_deref(), Some("alice-rotated"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn restored_session_refreshes_expired_token() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        let mock = mock_refresh(&server, "stored-refresh", "carol", "carol-fresh");
+
+        let state = PersistedAuthState {
+            user_id: "carol".into(),
+            email: Some("carol@example.com".into()),
+            refresh_token: Some("stored-refresh".into()),
+            access_token: Some("carol-stale".into()),
+            expires_at: Some(1),
+        };
+        let session = auth.restore_session(&state).unwrap();
+
+        let provider = session.token_provider();
+        assert_eq!(provider.get_token().await.unwrap().as_deref(), Some("carol-fresh"));
+        mock.assert();
+        assert_eq!(session.user().info().email.as_deref(), Some("carol@example.com"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn refresh_rejects_mismatched_user() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        let _mock = mock_refresh(&server, "stored-refresh", "mallory", "mallory-id");
+
+        let state = PersistedAuthState {
+            user_id: "carol".into(),
+            refresh_token: Some("stored-refresh".into()),
+            ..Default::default()
+        };
+        let session = auth.restore_session(&state).unwrap();
+
+        let result = session.get_token(true).await;
+        assert!(matches!(result, Err(AuthError::InvalidCredential(_))));
+    }
+
+    #[test]
+    fn restore_session_requires_refresh_token() {
+        let server = start_mock_server();
+        let auth = build_auth(&server);
+        let state = PersistedAuthState {
+            user_id: "dave".into(),
+            ..Default::default()
+        };
+        assert!(auth.restore_session(&state).is_err());
+    }
+}
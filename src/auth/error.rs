@@ -1,6 +1,10 @@
 use crate::app::AppError;
+use crate::auth::model::AuthCredential;
+use crate::auth::phone::PHONE_PROVIDER_ID;
 use crate::auth::types::MultiFactorError;
+use crate::util::jwt::TokenVerificationError;
 use crate::util::FirebaseError;
+use serde_json::{Map, Value};
 use std::borrow::Cow;
 use std::fmt;
 
@@ -15,6 +19,14 @@ pub enum AuthError {
     NotImplemented(&'static str),
     MultiFactorRequired(MultiFactorError),
     MultiFactor(MultiFactorAuthError),
+    TokenVerification(TokenVerificationError),
+    PersistenceTampered(String),
+    /// Auth state could not be written to persistence, e.g. because sealing it failed.
+    Persistence(String),
+    AccountConflict(Box<AccountConflictError>),
+    /// `auth/user-mismatch`: a reauthentication credential belongs to a different user than the
+    /// one currently signed in.
+    UserMismatch,
 }
 
 impl fmt::Display for AuthError {
@@ -27,6 +39,15 @@ impl fmt::Display for AuthError {
             AuthError::NotImplemented(feature) => write!(f, "{feature} is not implemented"),
             AuthError::MultiFactorRequired(err) => write!(f, "{err}"),
             AuthError::MultiFactor(err) => write!(f, "{err}"),
+            AuthError::TokenVerification(err) => write!(f, "Token verification failed: {err}"),
+            AuthError::AccountConflict(err) => write!(f, "{err}"),
+            AuthError::PersistenceTampered(message) => {
+                write!(f, "Persisted auth state was rejected and cleared: {message}")
+            }
+            AuthError::Persistence(message) => write!(f, "Auth persistence error: {message}"),
+            AuthError::UserMismatch => {
+                write!(f, "The supplied credentials do not correspond to the previously signed in user")
+            }
         }
     }
 }
@@ -111,6 +132,190 @@ impl fmt::Display for MultiFactorAuthError {
     }
 }
 
+/// Enumerates the account-linking conflicts Firebase Auth reports when a credential or
+/// identifier already belongs to another account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountConflictCode {
+    /// `auth/credential-already-in-use`: the credential is linked to a different user.
+    CredentialAlreadyInUse,
+    /// `auth/email-already-in-use`: the email address is used by a different user.
+    EmailAlreadyInUse,
+    /// `auth/account-exists-with-different-credential`: the email is registered with another provider.
+    AccountExistsWithDifferentCredential,
+}
+
+impl AccountConflictCode {
+    /// Returns the JS SDK error code string, e.g. `auth/credential-already-in-use`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountConflictCode::CredentialAlreadyInUse => "auth/credential-already-in-use",
+            AccountConflictCode::EmailAlreadyInUse => "auth/email-already-in-use",
+            AccountConflictCode::AccountExistsWithDifferentCredential => {
+                "auth/account-exists-with-different-credential"
+            }
+        }
+    }
+
+    fn default_message(self) -> &'static str {
+        match self {
+            AccountConflictCode::CredentialAlreadyInUse => {
+                "This credential is already associated with a different user account"
+            }
+            AccountConflictCode::EmailAlreadyInUse => "The email address is already in use by another account",
+            AccountConflictCode::AccountExistsWithDifferentCredential => {
+                "An account already exists with the same email address but different sign-in credentials"
+            }
+        }
+    }
+}
+
+/// Account-linking conflict carrying the data needed to recover from it.
+///
+/// Mirrors the JS SDK's `credentialFromError` and `customData`: when the backend returns the
+/// conflicting provider credential, it is exposed via [`credential`](Self::credential) so the app
+/// can sign in with it and merge the accounts.
+#[derive(Debug, Clone)]
+pub struct AccountConflictError {
+    code: AccountConflictCode,
+    credential: Option<AuthCredential>,
+    email: Option<String>,
+    phone_number: Option<String>,
+    server_message: Option<String>,
+}
+
+impl AccountConflictError {
+    /// Creates a conflict error without recovery data.
+    pub fn new(code: AccountConflictCode, server_message: Option<String>) -> Self {
+        Self {
+            code,
+            credential: None,
+            email: None,
+            phone_number: None,
+            server_message,
+        }
+    }
+
+    /// Returns the conflict category.
+    pub fn code(&self) -> AccountConflictCode {
+        self.code
+    }
+
+    /// Returns the credential that is already in use, when the backend supplied one.
+    pub fn credential(&self) -> Option<&AuthCredential> {
+        self.credential.as_ref()
+    }
+
+    /// Returns the email address involved in the conflict.
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    /// Returns the phone number involved in the conflict.
+    pub fn phone_number(&self) -> Option<&str> {
+        self.phone_number.as_deref()
+    }
+
+    /// Returns the raw server message if Firebase sent one.
+    pub fn server_message(&self) -> Option<&str> {
+        self.server_message.as_deref()
+    }
+
+    /// Fills in credential, email and phone number from an Identity Toolkit response body.
+    pub(crate) fn with_response_data(mut self, response: &Value) -> Self {
+        self.email = response.get("email").and_then(Value::as_str).map(str::to_string);
+        self.phone_number = response.get("phoneNumber").and_then(Value::as_str).map(str::to_string);
+        self.credential = credential_from_response(response);
+        self
+    }
+}
+
+impl fmt::Display for AccountConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let base = self.code.default_message();
+        match self.server_message() {
+            Some(detail) if !detail.is_empty() => write!(f, "{base} ({detail})"),
+            _ => write!(f, "{base}"),
+        }
+    }
+}
+
+fn credential_from_response(response: &Value) -> Option<AuthCredential> {
+    if let (Some(phone_number), Some(temporary_proof)) = (
+        response.get("phoneNumber").and_then(Value::as_str),
+        response.get("temporaryProof").and_then(Value::as_str),
+    ) {
+        return Some(AuthCredential {
+            provider_id: PHONE_PROVIDER_ID.to_string(),
+            sign_in_method: PHONE_PROVIDER_ID.to_string(),
+            token_response: serde_json::json!({
+                "phoneNumber": phone_number,
+                "temporaryProof": temporary_proof,
+            }),
+        });
+    }
+
+    let provider_id = response.get("providerId").and_then(Value::as_str)?;
+    let mut token_response = Map::new();
+    for field in [
+        "oauthIdToken",
+        "oauthAccessToken",
+        "oauthTokenSecret",
+        "pendingToken",
+        "nonce",
+    ] {
+        if let Some(value) = response.get(field).and_then(Value::as_str) {
+            token_response.insert(field.to_string(), Value::String(value.to_string()));
+        }
+    }
+    let has_token = ["oauthIdToken", "oauthAccessToken", "pendingToken"]
+        .iter()
+        .any(|field| token_response.contains_key(*field));
+    if !has_token {
+        return None;
+    }
+    Some(AuthCredential {
+        provider_id: provider_id.to_string(),
+        sign_in_method: provider_id.to_string(),
+        token_response: Value::Object(token_response),
+    })
+}
+
+/// Converts account-linking conflict codes into [`AuthError::AccountConflict`], attaching the
+/// recovery data found in `response`.
+pub(crate) fn map_account_conflict(message: &str, response: &Value) -> Option<AuthError> {
+    let (raw_code, detail) = split_error_message(message);
+    let code = match normalize_error_code(raw_code).as_ref() {
+        "FEDERATED_USER_ID_ALREADY_LINKED" | "CREDENTIAL_ALREADY_IN_USE" | "PHONE_NUMBER_EXISTS" => {
+            AccountConflictCode::CredentialAlreadyInUse
+        }
+        "EMAIL_EXISTS" | "EMAIL_ALREADY_IN_USE" => AccountConflictCode::EmailAlreadyInUse,
+        "NEED_CONFIRMATION" | "ACCOUNT_EXISTS_WITH_DIFFERENT_CREDENTIAL" => {
+            AccountConflictCode::AccountExistsWithDifferentCredential
+        }
+        _ => return None,
+    };
+    let server_message = detail.unwrap_or(raw_code);
+    Some(account_conflict(code, server_message, response))
+}
+
+/// Builds an [`AuthError::AccountConflict`] carrying the recovery data found in `response`.
+pub(crate) fn account_conflict(code: AccountConflictCode, server_message: &str, response: &Value) -> AuthError {
+    AuthError::AccountConflict(Box::new(
+        AccountConflictError::new(code, Some(server_message.to_string())).with_response_data(response),
+    ))
+}
+
+/// Maps an Identity Toolkit error body to the most specific [`AuthError`] available.
+pub(crate) fn map_rest_error_body(body: &str) -> Option<AuthError> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let message = value.get("error")?.get("message")?.as_str()?;
+    Some(
+        map_mfa_error_code(message)
+            .or_else(|| map_account_conflict(message, &value))
+            .unwrap_or_else(|| AuthError::InvalidCredential(message.to_string())),
+    )
+}
+
 /// Attempts to convert a REST error message into a typed multi-factor [`AuthError`].
 pub(crate) fn map_mfa_error_code(message: &str) -> Option<AuthError> {
     let (raw_code, detail) = split_error_message(message);
@@ -207,6 +412,44 @@ mod tests {
         assert!(map_mfa_error_code("SOME_OTHER_ERROR").is_none());
     }
 
+    #[test]
+    fn map_account_conflict_attaches_recovery_data() {
+        let response = serde_json::json!({
+            "errorMessage": "FEDERATED_USER_ID_ALREADY_LINKED",
+            "providerId": "google.com",
+            "oauthIdToken": "google-id-token",
+            "email": "user@example.com"
+        });
+        match map_account_conflict("FEDERATED_USER_ID_ALREADY_LINKED", &response) {
+            Some(AuthError::AccountConflict(err)) => {
+                assert_eq!(err.code(), AccountConflictCode::CredentialAlreadyInUse);
+                assert_eq!(err.email(), Some("user@example.com"));
+                let credential = err.credential().expect("credential");
+                assert_eq!(credential.provider_id, "google.com");
+                assert_eq!(credential.token_response["oauthIdToken"], "google-id-token");
+            }
+            other => panic!("unexpected mapping result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn map_rest_error_body_maps_email_exists_and_phone_conflicts() {
+        let error = map_rest_error_body(r#"{"error":{"message":"EMAIL_EXISTS"}}"#);
+        assert!(matches!(
+            error,
+            Some(AuthError::AccountConflict(ref err)) if err.code() == AccountConflictCode::EmailAlreadyInUse
+        ));
+
+        let response = serde_json::json!({"phoneNumber": "+15555550100", "temporaryProof": "proof"});
+        match map_account_conflict("PHONE_NUMBER_EXISTS", &response) {
+            Some(AuthError::AccountConflict(err)) => {
+                assert_eq!(err.phone_number(), Some("+15555550100"));
+                assert_eq!(err.credential().unwrap().provider_id, PHONE_PROVIDER_ID);
+            }
+            other => panic!("unexpected mapping result: {other:?}"),
+        }
+    }
+
     #[test]
     fn map_mfa_error_code_handles_challenge_required() {
         let error = map_mfa_error_code("auth/multi-factor-auth-required");
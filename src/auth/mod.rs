@@ -9,19 +9,23 @@ mod token_manager;
 
 //#![cfg(feature = "firestore")]
 mod token_provider;
+mod token_verifier;
 mod types;
 
 #[doc(inline)]
 pub use api::{
-    auth_for_app, refresh_id_token, refresh_id_token_with_endpoint, register_auth_component, Auth, AuthBuilder,
-    RefreshTokenResponse,
+    auth_for_app, connect_auth_emulator, refresh_id_token, refresh_id_token_with_endpoint, register_auth_component,
+    Auth, AuthBuilder, EmulatorConfig, EmulatorOobCode, EmulatorOptions, EmulatorSmsCode, RefreshTokenResponse,
+    UserSession, AUTH_EMULATOR_HOST_ENV,
 };
 
 #[allow(unused_imports)]
 pub(crate) use api::DEFAULT_SECURE_TOKEN_ENDPOINT;
 
 #[doc(inline)]
-pub use error::{AuthError, AuthResult, MultiFactorAuthError, MultiFactorAuthErrorCode};
+pub use error::{
+    AccountConflictCode, AccountConflictError, AuthError, AuthResult, MultiFactorAuthError, MultiFactorAuthErrorCode,
+};
 
 #[allow(unused_imports)]
 pub(crate) use error::map_mfa_error_code;
@@ -49,10 +53,13 @@ pub use oauth::{
 
 #[doc(inline)]
 pub use persistence::{
-    AuthPersistence, ClosurePersistence, InMemoryPersistence, PersistedAuthState, PersistenceListener,
-    PersistenceSubscription,
+    AuthPayloadStore, AuthPersistence, ClosurePersistence, InMemoryPersistence, PayloadListener, PersistedAuthState,
+    PersistenceListener, PersistenceSubscription,
 };
 
+#[doc(inline)]
+pub use persistence::encrypted::{EncryptedPersistence, PersistenceKey};
+
 // persistence::indexed_db::IndexedDbPersistence;
 #[cfg(all(feature = "wasm-web", target_arch = "wasm32", feature = "experimental-indexed-db"))]
 #[doc(inline)]
@@ -75,6 +82,9 @@ pub use phone::{PhoneAuthCredential, PhoneAuthProvider, PhoneMultiFactorGenerato
 #[doc(inline)]
 pub use token_provider::{auth_token_provider_arc, AuthTokenProvider};
 
+#[doc(inline)]
+pub use token_verifier::{FirebaseIdTokenInfo, IdTokenClaims, IdTokenVerifier, ID_TOKEN_CERTIFICATES_URL};
+
 #[doc(inline)]
 pub use types::{
     get_multi_factor_resolver, ActionCodeInfo, ActionCodeInfoData, ActionCodeOperation, ActionCodeSettings,
@@ -51,6 +51,11 @@ impl OAuthCredential {
         self.raw_nonce.as_ref()
     }
 
+    /// Returns the pending token attached to credentials recovered from account conflicts.
+    pub fn pending_token(&self) -> Option<&str> {
+        self.token_response.get("pendingToken").and_then(Value::as_str)
+    }
+
     /// Serializes the credential to a JSON value.
     pub fn to_json(&self) -> Value {
         let mut value = json!({
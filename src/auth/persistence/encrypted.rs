@@ -0,0 +1,398 @@
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::auth::error::{AuthError, AuthResult};
+use crate::auth::persistence::{
+    AuthPayloadStore, AuthPersistence, PersistedAuthState, PersistenceListener, PersistenceSubscription,
+};
+use crate::util::base64::{base64_decode_bytes, base64_url_encode_bytes};
+
+const ENVELOPE_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const HKDF_INFO: &[u8] = b"firebase-auth-persistence-v1";
+
+/// A 256-bit AES-GCM key used to seal persisted auth state.
+///
+/// Each key carries an identifier that is stored next to the ciphertext so that
+/// [`EncryptedPersistence`] can pick the right key after a rotation.
+#[derive(Clone)]
+pub struct PersistenceKey {
+    id: String,
+    key: [u8; 32],
+}
+
+impl std::fmt::Debug for PersistenceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PersistenceKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PersistenceKey {
+    /// Wraps raw key material, e.g. a key loaded from the OS keychain.
+    pub fn new(id: impl Into<String>, key: [u8; 32]) -> Self {
+        Self { id: id.into(), key }
+    }
+
+    /// Derives a key from a high-entropy secret using HKDF-SHA256.
+    ///
+    /// `salt` should be unique per installation when available; it does not need to be secret.
+    pub fn derive(id: impl Into<String>, secret: &[u8], salt: &[u8]) -> Self {
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(salt), secret)
+            .expand(HKDF_INFO, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self::new(id, key)
+    }
+
+    /// Returns the identifier recorded alongside sealed state.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct KeyRing {
+    current: PersistenceKey,
+    previous: Vec<PersistenceKey>,
+    allow_plaintext: bool,
+}
+
+/// What the inner store holds: a sealed envelope, or plaintext state left by an unwrapped backend.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    Sealed { encrypted: String },
+    Plain(PersistedAuthState),
+}
+
+enum Opened {
+    Current(PersistedAuthState),
+    NeedsReseal(PersistedAuthState),
+}
+
+impl KeyRing {
+    fn seal(&self, state: &PersistedAuthState) -> AuthResult<String> {
+        let plaintext = serde_json::to_vec(state)
+            .map_err(|err| AuthError::Persistence(format!("Failed to serialize auth state for persistence: {err}")))?;
+        let aad = format!("{ENVELOPE_PREFIX}{}", self.current.id);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .current
+            .cipher()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| AuthError::Persistence("Failed to encrypt auth state".into()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        let stored = Stored::Sealed {
+            encrypted: format!("{aad}:{}", base64_url_encode_bytes(&sealed)),
+        };
+        serde_json::to_string(&stored)
+            .map_err(|err| AuthError::Persistence(format!("Failed to serialize sealed auth state: {err}")))
+    }
+
+    fn open(&self, payload: &str) -> Result<Opened, String> {
+        let stored: Stored =
+            serde_json::from_str(payload).map_err(|_| "persisted auth state is not readable".to_string())?;
+        let sealed = match stored {
+            Stored::Sealed { encrypted } => encrypted,
+            Stored::Plain(state) if self.allow_plaintext => return Ok(Opened::NeedsReseal(state)),
+            Stored::Plain(_) => return Err("persisted auth state is not encrypted".into()),
+        };
+        let envelope = sealed
+            .strip_prefix(ENVELOPE_PREFIX)
+            .ok_or_else(|| "unsupported encrypted auth state format".to_string())?;
+        let (key_id, payload) = envelope
+            .rsplit_once(':')
+            .ok_or_else(|| "malformed encrypted auth state".to_string())?;
+        let key = std::iter::once(&self.current)
+            .chain(self.previous.iter())
+            .find(|key| key.id == key_id)
+            .ok_or_else(|| format!("no key registered with id \"{key_id}\""))?;
+
+        let sealed =
+            base64_decode_bytes(payload).map_err(|_| "encrypted auth state is not valid base64".to_string())?;
+        if sealed.len() <= NONCE_LEN {
+            return Err("encrypted auth state is truncated".into());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let aad = format!("{ENVELOPE_PREFIX}{key_id}");
+        let plaintext = key
+            .cipher()
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| "encrypted auth state failed authentication".to_string())?;
+        let opened: PersistedAuthState =
+            serde_json::from_slice(&plaintext).map_err(|err| format!("decrypted auth state is invalid: {err}"))?;
+
+        if key.id == self.current.id {
+            Ok(Opened::Current(opened))
+        } else {
+            Ok(Opened::NeedsReseal(opened))
+        }
+    }
+}
+
+/// Wraps the storage slot of another backend and seals the stored state with AES-256-GCM.
+///
+/// The inner [`AuthPayloadStore`] only ever sees an opaque envelope, so refresh tokens and profile data are
+/// unreadable at rest. State sealed under a key passed to [`with_previous_key`](Self::with_previous_key)
+/// is transparently re-sealed under the current key on the next read. Envelopes that fail
+/// authentication are cleared from the inner backend and reported as
+/// [`AuthError::PersistenceTampered`].
+pub struct EncryptedPersistence {
+    inner: Arc<dyn AuthPayloadStore>,
+    keys: Arc<KeyRing>,
+}
+
+impl std::fmt::Debug for EncryptedPersistence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedPersistence")
+            .field("current_key", &self.keys.current.id)
+            .finish()
+    }
+}
+
+impl EncryptedPersistence {
+    /// Creates a wrapper that seals state written to `inner` with `key`.
+    pub fn new(inner: Arc<dyn AuthPayloadStore>, key: PersistenceKey) -> Self {
+        Self {
+            inner,
+            keys: Arc::new(KeyRing {
+                current: key,
+                previous: Vec::new(),
+                allow_plaintext: false,
+            }),
+        }
+    }
+
+    /// Accepts state sealed under a retired key and re-seals it with the current key when read.
+    pub fn with_previous_key(mut self, key: PersistenceKey) -> Self {
+        self.keys_mut().previous.push(key);
+        self
+    }
+
+    /// Accepts unencrypted state left by the wrapped backend and encrypts it on first read.
+    ///
+    /// Use this once when migrating existing installs; otherwise plaintext is treated as tampering.
+    pub fn with_plaintext_migration(mut self, allow: bool) -> Self {
+        self.keys_mut().allow_plaintext = allow;
+        self
+    }
+
+    fn keys_mut(&mut self) -> &mut KeyRing {
+        Arc::make_mut(&mut self.keys)
+    }
+}
+
+impl AuthPersistence for EncryptedPersistence {
+    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+        let sealed = state.as_ref().map(|state| self.keys.seal(state)).transpose()?;
+        self.inner.set_payload(sealed)
+    }
+
+    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+        let Some(stored) = self.inner.get_payload()? else {
+            return Ok(None);
+        };
+        match self.keys.open(&stored) {
+            Ok(Opened::Current(state)) => Ok(Some(state)),
+            Ok(Opened::NeedsReseal(state)) => {
+                self.set(Some(state.clone()))?;
+                Ok(Some(state))
+            }
+            Err(message) => {
+                let _ = self.inner.set_payload(None);
+                Err(AuthError::PersistenceTampered(message))
+            }
+        }
+    }
+
+    /// Delivers decrypted state to `listener`. An envelope that fails to open is cleared from the
+    /// inner store like in [`get`](AuthPersistence::get), logged as
+    /// [`AuthError::PersistenceTampered`] and reported to the listener as signed out.
+    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+        let keys = self.keys.clone();
+        let inner = Arc::downgrade(&self.inner);
+        self.inner.subscribe_payload(Arc::new(move |payload: Option<String>| {
+            let Some(payload) = payload else {
+                listener(None);
+                return;
+            };
+            match keys.open(&payload) {
+                Ok(Opened::Current(state)) | Ok(Opened::NeedsReseal(state)) => listener(Some(state)),
+                Err(message) => {
+                    log::warn!("{}", AuthError::PersistenceTampered(message));
+                    listener(None);
+                    if let Some(inner) = inner.upgrade() {
+                        let _ = inner.set_payload(None);
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::persistence::InMemoryPersistence;
+    use std::sync::Mutex;
+
+    fn sample_state() -> PersistedAuthState {
+        PersistedAuthState {
+            user_id: "uid-1".into(),
+            email: Some("user@example.com".into()),
+            refresh_token: Some("refresh-secret".into()),
+            access_token: Some("access".into()),
+            expires_at: Some(1_700_000_000),
+        }
+    }
+
+    fn envelope(inner: &InMemoryPersistence) -> String {
+        let payload = inner.get_payload().unwrap().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        value["encrypted"].as_str().unwrap().to_string()
+    }
+
+    /// A sealed payload whose envelope has its last character flipped.
+    fn tampered(envelope: &str) -> String {
+        let mut envelope = envelope.to_string();
+        let flipped = if envelope.ends_with('A') { 'B' } else { 'A' };
+        envelope.pop();
+        envelope.push(flipped);
+        serde_json::json!({ "encrypted": envelope }).to_string()
+    }
+
+    fn key(id: &str) -> PersistenceKey {
+        PersistenceKey::derive(id, format!("secret-{id}").as_bytes(), b"install-salt")
+    }
+
+    #[test]
+    fn roundtrip_hides_state_from_inner_backend() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        let persistence = EncryptedPersistence::new(inner.clone(), key("k1"));
+
+        persistence.set(Some(sample_state())).unwrap();
+
+        let payload = inner.get_payload().unwrap().unwrap();
+        assert!(!payload.contains("refresh-secret"));
+        assert!(!payload.contains("uid-1"));
+        assert!(envelope(&inner).starts_with("enc:v1:k1:"));
+        assert_eq!(persistence.get().unwrap(), Some(sample_state()));
+
+        persistence.set(None).unwrap();
+        assert_eq!(inner.get_payload().unwrap(), None);
+    }
+
+    #[test]
+    fn previous_key_is_accepted_and_state_is_resealed() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        EncryptedPersistence::new(inner.clone(), key("old"))
+            .set(Some(sample_state()))
+            .unwrap();
+
+        let rotated = EncryptedPersistence::new(inner.clone(), key("new")).with_previous_key(key("old"));
+        assert_eq!(rotated.get().unwrap(), Some(sample_state()));
+        assert!(envelope(&inner).starts_with("enc:v1:new:"));
+
+        let without_old = EncryptedPersistence::new(inner, key("new"));
+        assert_eq!(without_old.get().unwrap(), Some(sample_state()));
+    }
+
+    #[test]
+    fn tampered_state_is_cleared_and_reported() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        let persistence = EncryptedPersistence::new(inner.clone(), key("k1"));
+        persistence.set(Some(sample_state())).unwrap();
+
+        inner.set_payload(Some(tampered(&envelope(&inner)))).unwrap();
+
+        assert!(matches!(persistence.get(), Err(AuthError::PersistenceTampered(_))));
+        assert_eq!(inner.get_payload().unwrap(), None);
+    }
+
+    #[test]
+    fn wrong_key_and_plaintext_are_rejected() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        EncryptedPersistence::new(inner.clone(), key("k1"))
+            .set(Some(sample_state()))
+            .unwrap();
+        let impostor = EncryptedPersistence::new(inner.clone(), PersistenceKey::new("k1", [7u8; 32]));
+        assert!(matches!(impostor.get(), Err(AuthError::PersistenceTampered(_))));
+
+        inner.set(Some(sample_state())).unwrap();
+        let strict = EncryptedPersistence::new(inner.clone(), key("k1"));
+        assert!(matches!(strict.get(), Err(AuthError::PersistenceTampered(_))));
+        assert_eq!(inner.get_payload().unwrap(), None);
+    }
+
+    #[test]
+    fn plaintext_migration_encrypts_existing_state() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        inner.set(Some(sample_state())).unwrap();
+
+        let persistence = EncryptedPersistence::new(inner.clone(), key("k1")).with_plaintext_migration(true);
+        assert_eq!(persistence.get().unwrap(), Some(sample_state()));
+        assert!(envelope(&inner).starts_with("enc:v1:k1:"));
+    }
+
+    #[test]
+    fn tampered_state_seen_by_subscribers_is_cleared() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        let persistence = EncryptedPersistence::new(inner.clone(), key("k1"));
+        persistence.set(Some(sample_state())).unwrap();
+        let sealed = envelope(&inner);
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let _subscription = persistence
+            .subscribe(Arc::new(move |state| sink.lock().unwrap().push(state)))
+            .unwrap();
+
+        inner.set_payload(Some(tampered(&sealed))).unwrap();
+
+        assert_eq!(inner.get_payload().unwrap(), None);
+        let received = received.lock().unwrap();
+        assert!(!received.is_empty());
+        assert!(received.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn subscribers_receive_decrypted_state() {
+        let inner = Arc::new(InMemoryPersistence::default());
+        let persistence = EncryptedPersistence::new(inner, key("k1"));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let _subscription = persistence
+            .subscribe(Arc::new(move |state| sink.lock().unwrap().push(state)))
+            .unwrap();
+
+        persistence.set(Some(sample_state())).unwrap();
+        persistence.set(None).unwrap();
+
+        assert_eq!(*received.lock().unwrap(), vec![Some(sample_state()), None]);
+    }
+}
@@ -4,13 +4,15 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use crate::auth::error::{AuthError, AuthResult};
-use crate::auth::persistence::{AuthPersistence, PersistedAuthState, PersistenceListener, PersistenceSubscription};
-use serde_json::{from_str as deserialize_state, to_string as serialize_state};
+use crate::auth::persistence::{
+    deserialize_state, serialize_state, state_listener, AuthPayloadStore, AuthPersistence, PayloadListener,
+    PersistedAuthState, PersistenceListener, PersistenceSubscription,
+};
 
 #[derive(Clone)]
 pub struct FilePersistence {
     path: Arc<PathBuf>,
-    listeners: Arc<Mutex<Vec<PersistenceListener>>>,
+    listeners: Arc<Mutex<Vec<PayloadListener>>>,
 }
 
 impl std::fmt::Debug for FilePersistence {
@@ -27,21 +29,18 @@ impl FilePersistence {
         }
     }
 
-    fn notify_listeners(&self, state: Option<PersistedAuthState>) {
+    fn notify_listeners(&self, payload: Option<String>) {
         let listeners = self.listeners.lock().unwrap().clone();
         for listener in listeners {
-            listener(state.clone());
+            listener(payload.clone());
         }
     }
 }
 
-impl AuthPersistence for FilePersistence {
-    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
-        match &state {
-            Some(state) => {
-                let serialized = serialize_state(state).map_err(|err| {
-                    AuthError::InvalidCredential(format!("Failed to serialize auth state for persistence: {err}"))
-                })?;
+impl AuthPayloadStore for FilePersistence {
+    fn set_payload(&self, payload: Option<String>) -> AuthResult<()> {
+        match &payload {
+            Some(payload) => {
                 if let Some(parent) = self.path.parent() {
                     std::fs::create_dir_all(parent).map_err(|err| {
                         AuthError::InvalidCredential(format!("Failed to create persistence directory: {err}"))
@@ -50,7 +49,7 @@ impl AuthPersistence for FilePersistence {
                 let mut file = File::create(&*self.path).map_err(|err| {
                     AuthError::InvalidCredential(format!("Failed to create auth persistence file: {err}"))
                 })?;
-                file.write_all(serialized.as_bytes()).map_err(|err| {
+                file.write_all(payload.as_bytes()).map_err(|err| {
                     AuthError::InvalidCredential(format!("Failed to write auth persistence file: {err}"))
                 })?;
             }
@@ -63,11 +62,11 @@ impl AuthPersistence for FilePersistence {
             }
         }
 
-        self.notify_listeners(state.clone());
+        self.notify_listeners(payload);
         Ok(())
     }
 
-    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+    fn get_payload(&self) -> AuthResult<Option<String>> {
         if !self.path.exists() {
             return Ok(None);
         }
@@ -78,16 +77,10 @@ impl AuthPersistence for FilePersistence {
         file.read_to_string(&mut buffer)
             .map_err(|err| AuthError::InvalidCredential(format!("Failed to read auth persistence file: {err}")))?;
 
-        if buffer.is_empty() {
-            return Ok(None);
-        }
-
-        let state = deserialize_state(&buffer)
-            .map_err(|err| AuthError::InvalidCredential(format!("Failed to parse auth persistence payload: {err}")))?;
-        Ok(Some(state))
+        Ok((!buffer.is_empty()).then_some(buffer))
     }
 
-    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+    fn subscribe_payload(&self, listener: PayloadListener) -> AuthResult<PersistenceSubscription> {
         let listener_arc = listener.clone();
         let mut listeners = self.listeners.lock().unwrap();
         listeners.push(listener_arc.clone());
@@ -103,6 +96,23 @@ impl AuthPersistence for FilePersistence {
     }
 }
 
+impl AuthPersistence for FilePersistence {
+    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+        let payload = state.as_ref().map(serialize_state).transpose()?;
+        self.set_payload(payload)
+    }
+
+    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+        self.get_payload()?
+            .map(|payload| deserialize_state(&payload))
+            .transpose()
+    }
+
+    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+        self.subscribe_payload(state_listener(listener))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,11 +1,13 @@
 use std::sync::{Arc, Mutex};
 
 use crate::auth::error::{AuthError, AuthResult};
-use crate::auth::persistence::{AuthPersistence, PersistedAuthState, PersistenceListener, PersistenceSubscription};
+use crate::auth::persistence::{
+    deserialize_state, serialize_state, AuthPayloadStore, AuthPersistence, PayloadListener, PersistedAuthState,
+    PersistenceListener, PersistenceSubscription,
+};
 #[allow(unused_imports)]
 use crate::platform::browser::indexed_db::get_string;
 use crate::platform::browser::indexed_db::{delete_key, open_database_with_store, put_string, IndexedDbError};
-use serde_json::{from_str as deserialize_state, to_string as serialize_state};
 use wasm_bindgen_futures::spawn_local;
 
 const DB_NAME: &str = "firebase-auth";
@@ -17,7 +19,7 @@ const AUTH_STATE_KEY: &str = "firebase-auth-state";
 pub struct IndexedDbPersistence {
     db_name: Arc<String>,
     store_name: Arc<String>,
-    cache: Arc<Mutex<Option<PersistedAuthState>>>,
+    cache: Arc<Mutex<Option<String>>>,
 }
 
 impl IndexedDbPersistence {
@@ -44,13 +46,13 @@ impl IndexedDbPersistence {
     }
 }
 
-impl AuthPersistence for IndexedDbPersistence {
-    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+impl AuthPayloadStore for IndexedDbPersistence {
+    fn set_payload(&self, payload: Option<String>) -> AuthResult<()> {
         {
             let mut cache = self.cache.lock().unwrap();
-            *cache = state.clone();
+            *cache = payload.clone();
         }
-        write_to_local_storage(&self.db_name, &state);
+        write_to_local_storage(&self.db_name, &payload);
 
         let db_name = self.db_name.clone();
         let store_name = self.store_name.clone();
@@ -60,11 +62,8 @@ impl AuthPersistence for IndexedDbPersistence {
                 Err(_) => return,
             };
 
-            let result = if let Some(state) = state {
-                match serialize_state(&state) {
-                    Ok(serialized) => put_string(&db, &store_name, AUTH_STATE_KEY, &serialized).await,
-                    Err(_) => return,
-                }
+            let result = if let Some(payload) = payload {
+                put_string(&db, &store_name, AUTH_STATE_KEY, &payload).await
             } else {
                 delete_key(&db, &store_name, AUTH_STATE_KEY).await
             };
@@ -76,7 +75,7 @@ impl AuthPersistence for IndexedDbPersistence {
         Ok(())
     }
 
-    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+    fn get_payload(&self) -> AuthResult<Option<String>> {
         // Refresh cache from local storage on each read in case another tab updated it.
         {
             let mut cache = self.cache.lock().unwrap();
@@ -85,13 +84,29 @@ impl AuthPersistence for IndexedDbPersistence {
         }
     }
 
-    fn subscribe(&self, _listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+    fn subscribe_payload(&self, _listener: PayloadListener) -> AuthResult<PersistenceSubscription> {
         // IndexedDB does not expose a simple cross-tab notification mechanism without
         // additional BroadcastChannel wiring. Defer to higher-level coordination for now.
         Ok(PersistenceSubscription::noop())
     }
 }
 
+impl AuthPersistence for IndexedDbPersistence {
+    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+        let payload = state.as_ref().map(serialize_state).transpose()?;
+        self.set_payload(payload)
+    }
+
+    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+        // Unreadable payloads are treated as signed out, as before.
+        Ok(self.get_payload()?.and_then(|payload| deserialize_state(&payload).ok()))
+    }
+
+    fn subscribe(&self, _listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+        Ok(PersistenceSubscription::noop())
+    }
+}
+
 fn map_error(error: IndexedDbError) -> AuthError {
     AuthError::InvalidCredential(format!("IndexedDB auth persistence error: {error}"))
 }
@@ -100,28 +115,22 @@ fn storage_key(db_name: &str) -> String {
     format!("{db_name}::{AUTH_STATE_KEY}")
 }
 
-fn write_to_local_storage(db_name: &str, state: &Option<PersistedAuthState>) {
+fn write_to_local_storage(db_name: &str, payload: &Option<String>) {
     if let Some(window) = web_sys::window() {
         if let Ok(Some(storage)) = window.local_storage() {
             let key = storage_key(db_name);
-            let _ = match state {
-                Some(state) => serialize_state(state)
-                    .map(|json| storage.set_item(&key, &json))
-                    .unwrap_or_else(|_| Ok(())),
+            let _ = match payload {
+                Some(payload) => storage.set_item(&key, payload),
                 None => storage.remove_item(&key),
             };
         }
     }
 }
 
-fn load_from_local_storage(db_name: &str) -> Option<PersistedAuthState> {
+fn load_from_local_storage(db_name: &str) -> Option<String> {
     let window = web_sys::window()?;
     let storage = window.local_storage().ok().flatten()?;
     let key = storage_key(db_name);
     let value = storage.get_item(&key).ok().flatten()?;
-    if value.is_empty() {
-        return None;
-    }
-    let parsed = deserialize_state(&value).ok()?;
-    Some(parsed)
+    (!value.is_empty()).then_some(value)
 }
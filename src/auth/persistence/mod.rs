@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 
 use serde::{Deserialize, Serialize};
 
-use crate::auth::error::AuthResult;
+use crate::auth::error::{AuthError, AuthResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct PersistedAuthState {
@@ -16,11 +16,12 @@ pub struct PersistedAuthState {
 }
 
 pub type PersistenceListener = Arc<dyn Fn(Option<PersistedAuthState>) + Send + Sync>;
+pub type PayloadListener = Arc<dyn Fn(Option<String>) + Send + Sync>;
 
 #[derive(Default)]
 struct InMemoryState {
-    value: Option<PersistedAuthState>,
-    listeners: Vec<(usize, PersistenceListener)>,
+    value: Option<String>,
+    listeners: Vec<(usize, PayloadListener)>,
 }
 
 pub struct PersistenceSubscription {
@@ -73,6 +74,38 @@ pub trait AuthPersistence: Send + Sync {
     }
 }
 
+/// Raw storage slot holding the serialized auth state.
+///
+/// Wrappers that persist their own format, such as
+/// [`EncryptedPersistence`](crate::auth::EncryptedPersistence), write through this trait instead of
+/// [`AuthPersistence`]. The built-in backends share one slot between both traits, so state written
+/// with [`AuthPersistence::set`] reads back here as its JSON form.
+pub trait AuthPayloadStore: Send + Sync {
+    fn set_payload(&self, payload: Option<String>) -> AuthResult<()>;
+    fn get_payload(&self) -> AuthResult<Option<String>>;
+
+    fn subscribe_payload(&self, _listener: PayloadListener) -> AuthResult<PersistenceSubscription> {
+        Ok(PersistenceSubscription::noop())
+    }
+}
+
+pub(crate) fn serialize_state(state: &PersistedAuthState) -> AuthResult<String> {
+    serde_json::to_string(state)
+        .map_err(|err| AuthError::InvalidCredential(format!("Failed to serialize auth state for persistence: {err}")))
+}
+
+pub(crate) fn deserialize_state(payload: &str) -> AuthResult<PersistedAuthState> {
+    serde_json::from_str(payload)
+        .map_err(|err| AuthError::InvalidCredential(format!("Failed to parse auth persistence payload: {err}")))
+}
+
+/// Adapts a state listener to payload notifications, treating unreadable payloads as signed out.
+pub(crate) fn state_listener(listener: PersistenceListener) -> PayloadListener {
+    Arc::new(move |payload: Option<String>| {
+        listener(payload.and_then(|payload| deserialize_state(&payload).ok()));
+    })
+}
+
 pub struct InMemoryPersistence {
     state: Arc<Mutex<InMemoryState>>,
     next_id: AtomicUsize,
@@ -87,11 +120,11 @@ impl Default for InMemoryPersistence {
     }
 }
 
-impl AuthPersistence for InMemoryPersistence {
-    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+impl AuthPayloadStore for InMemoryPersistence {
+    fn set_payload(&self, payload: Option<String>) -> AuthResult<()> {
         let listeners = {
             let mut guard = self.state.lock().unwrap();
-            guard.value = state.clone();
+            guard.value = payload.clone();
             guard
                 .listeners
                 .iter()
@@ -100,17 +133,17 @@ impl AuthPersistence for InMemoryPersistence {
         };
 
         for listener in listeners {
-            listener(state.clone());
+            listener(payload.clone());
         }
 
         Ok(())
     }
 
-    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+    fn get_payload(&self) -> AuthResult<Option<String>> {
         Ok(self.state.lock().unwrap().value.clone())
     }
 
-    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+    fn subscribe_payload(&self, listener: PayloadListener) -> AuthResult<PersistenceSubscription> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         {
             let mut guard = self.state.lock().unwrap();
@@ -128,6 +161,23 @@ impl AuthPersistence for InMemoryPersistence {
     }
 }
 
+impl AuthPersistence for InMemoryPersistence {
+    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+        let payload = state.as_ref().map(serialize_state).transpose()?;
+        self.set_payload(payload)
+    }
+
+    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+        self.get_payload()?
+            .map(|payload| deserialize_state(&payload))
+            .transpose()
+    }
+
+    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+        self.subscribe_payload(state_listener(listener))
+    }
+}
+
 type DynSetFn = dyn Fn(Option<PersistedAuthState>) -> AuthResult<()> + Send + Sync;
 type DynGetFn = dyn Fn() -> AuthResult<Option<PersistedAuthState>> + Send + Sync;
 type DynSubscribeFn = dyn Fn(PersistenceListener) -> AuthResult<PersistenceSubscription> + Send + Sync;
@@ -163,6 +213,8 @@ impl ClosurePersistence {
     }
 }
 
+pub mod encrypted;
+
 #[cfg(all(feature = "wasm-web", target_arch = "wasm32", feature = "experimental-indexed-db"))]
 pub mod indexed_db;
 
@@ -7,7 +7,10 @@ use web_sys::{self, BroadcastChannel, EventTarget, MessageEvent, Storage, Storag
 
 use crate::auth::error::{AuthError, AuthResult};
 
-use super::{AuthPersistence, PersistedAuthState, PersistenceListener, PersistenceSubscription};
+use super::{
+    deserialize_state, serialize_state, state_listener, AuthPayloadStore, AuthPersistence, PayloadListener,
+    PersistedAuthState, PersistenceListener, PersistenceSubscription,
+};
 
 const DEFAULT_STORAGE_KEY: &str = "firebase:authUser";
 const DEFAULT_CHANNEL_NAME: &str = "firebase-auth-uplink";
@@ -59,17 +62,8 @@ impl WebStoragePersistence {
             .ok_or_else(|| AuthError::InvalidCredential("window object is not available in this environment".into()))
     }
 
-    fn deserialize(value: &str) -> Option<PersistedAuthState> {
-        if value.is_empty() {
-            return None;
-        }
-
-        serde_json::from_str(value).ok()
-    }
-
-    fn serialize(state: &PersistedAuthState) -> Result<String, AuthError> {
-        serde_json::to_string(state)
-            .map_err(|err| AuthError::InvalidCredential(format!("Failed to serialize auth persistence payload: {err}")))
+    fn payload(value: String) -> Option<String> {
+        (!value.is_empty()).then_some(value)
     }
 
     fn notify_via_broadcast(&self, payload: Option<&str>) {
@@ -84,34 +78,33 @@ impl WebStoragePersistence {
         }
     }
 
-    fn parse_broadcast_message(event: &MessageEvent) -> Option<Option<PersistedAuthState>> {
+    fn parse_broadcast_message(event: &MessageEvent) -> Option<Option<String>> {
         let data = event.data();
         if data.is_null() || data.is_undefined() {
             return Some(None);
         }
 
         if let Some(text) = data.as_string() {
-            return Some(Self::deserialize(&text));
+            return Some(Self::payload(text));
         }
 
         // Attempt to stringify non-string payloads.
         match js_sys::JSON::stringify(&data) {
-            Ok(value) => value.as_string().map(|text| Self::deserialize(&text)),
+            Ok(value) => value.as_string().map(Self::payload),
             Err(_) => None,
         }
     }
 }
 
-impl AuthPersistence for WebStoragePersistence {
-    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+impl AuthPayloadStore for WebStoragePersistence {
+    fn set_payload(&self, payload: Option<String>) -> AuthResult<()> {
         let window = Self::window()?;
         let storage = self.storage(&window)?;
 
-        match state {
-            Some(ref state) => {
-                let serialized = Self::serialize(state)?;
-                storage.set_item(self.key.as_ref(), &serialized).map_err(map_js_error)?;
-                self.notify_via_broadcast(Some(&serialized));
+        match payload {
+            Some(ref serialized) => {
+                storage.set_item(self.key.as_ref(), serialized).map_err(map_js_error)?;
+                self.notify_via_broadcast(Some(serialized.as_str()));
             }
             None => {
                 storage.remove_item(self.key.as_ref()).map_err(map_js_error)?;
@@ -122,15 +115,15 @@ impl AuthPersistence for WebStoragePersistence {
         Ok(())
     }
 
-    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+    fn get_payload(&self) -> AuthResult<Option<String>> {
         let window = Self::window()?;
         let storage = self.storage(&window)?;
         let value = storage.get_item(self.key.as_ref()).map_err(map_js_error)?;
 
-        Ok(value.and_then(|string| Self::deserialize(&string)))
+        Ok(value.and_then(Self::payload))
     }
 
-    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+    fn subscribe_payload(&self, listener: PayloadListener) -> AuthResult<PersistenceSubscription> {
         let window = Self::window()?;
         let key = self.key.clone();
         let storage_listener = listener.clone();
@@ -144,10 +137,7 @@ impl AuthPersistence for WebStoragePersistence {
                 return;
             }
 
-            let new_state = event
-                .new_value()
-                .and_then(|value| WebStoragePersistence::deserialize(&value));
-            storage_listener(new_state);
+            storage_listener(event.new_value().and_then(WebStoragePersistence::payload));
         }) as Box<dyn FnMut(StorageEvent)>);
 
         let storage_handle = StorageListenerHandle::attach(window.clone().into(), "storage", storage_closure)?;
@@ -179,6 +169,22 @@ impl AuthPersistence for WebStoragePersistence {
     }
 }
 
+impl AuthPersistence for WebStoragePersistence {
+    fn set(&self, state: Option<PersistedAuthState>) -> AuthResult<()> {
+        let payload = state.as_ref().map(serialize_state).transpose()?;
+        self.set_payload(payload)
+    }
+
+    fn get(&self) -> AuthResult<Option<PersistedAuthState>> {
+        // Unreadable payloads are treated as signed out, as before.
+        Ok(self.get_payload()?.and_then(|payload| deserialize_state(&payload).ok()))
+    }
+
+    fn subscribe(&self, listener: PersistenceListener) -> AuthResult<PersistenceSubscription> {
+        self.subscribe_payload(state_listener(listener))
+    }
+}
+
 fn map_js_error(err: JsValue) -> AuthError {
     AuthError::InvalidCredential(format!("Web storage error: {}", stringify_js_error(err)))
 }
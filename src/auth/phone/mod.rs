@@ -1,17 +1,34 @@
 use std::sync::Arc;
 
 use crate::auth::api::Auth;
-use crate::auth::error::AuthResult;
+use crate::auth::error::{AuthError, AuthResult};
+use crate::auth::model::AuthCredential;
 use crate::auth::types::{ApplicationVerifier, ConfirmationResult, MultiFactorAssertion};
 
 /// Provider ID for phone authentication.
 pub const PHONE_PROVIDER_ID: &str = "phone";
 
 /// Represents a credential produced during a phone verification flow.
+///
+/// Credentials recovered from a `credential-already-in-use` error carry a phone number and a
+/// temporary proof instead of a verification ID/code pair.
 #[derive(Clone, Debug)]
 pub struct PhoneAuthCredential {
     verification_id: String,
     verification_code: String,
+    phone_number: Option<String>,
+    temporary_proof: Option<String>,
+}
+
+pub(crate) enum PhoneCredentialParts {
+    Verification {
+        session_info: String,
+        code: String,
+    },
+    TemporaryProof {
+        phone_number: String,
+        temporary_proof: String,
+    },
 }
 
 impl PhoneAuthCredential {
@@ -20,6 +37,18 @@ impl PhoneAuthCredential {
         Self {
             verification_id: verification_id.into(),
             verification_code: verification_code.into(),
+            phone_number: None,
+            temporary_proof: None,
+        }
+    }
+
+    /// Creates a credential from the phone number and temporary proof returned by the backend.
+    pub fn from_temporary_proof(phone_number: impl Into<String>, temporary_proof: impl Into<String>) -> Self {
+        Self {
+            verification_id: String::new(),
+            verification_code: String::new(),
+            phone_number: Some(phone_number.into()),
+            temporary_proof: Some(temporary_proof.into()),
         }
     }
 
@@ -33,8 +62,55 @@ impl PhoneAuthCredential {
         &self.verification_code
     }
 
-    pub(crate) fn into_parts(self) -> (String, String) {
-        (self.verification_id, self.verification_code)
+    /// Returns the phone number attached to a temporary-proof credential.
+    pub fn phone_number(&self) -> Option<&str> {
+        self.phone_number.as_deref()
+    }
+
+    /// Returns the temporary proof attached to credentials recovered from conflict errors.
+    pub fn temporary_proof(&self) -> Option<&str> {
+        self.temporary_proof.as_deref()
+    }
+
+    pub(crate) fn into_parts(self) -> PhoneCredentialParts {
+        match (self.phone_number, self.temporary_proof) {
+            (Some(phone_number), Some(temporary_proof)) => PhoneCredentialParts::TemporaryProof {
+                phone_number,
+                temporary_proof,
+            },
+            _ => PhoneCredentialParts::Verification {
+                session_info: self.verification_id,
+                code: self.verification_code,
+            },
+        }
+    }
+}
+
+impl TryFrom<AuthCredential> for PhoneAuthCredential {
+    type Error = AuthError;
+
+    fn try_from(credential: AuthCredential) -> Result<Self, Self::Error> {
+        if credential.provider_id != PHONE_PROVIDER_ID {
+            return Err(AuthError::InvalidCredential(format!(
+                "Expected a {PHONE_PROVIDER_ID} credential, got {}",
+                credential.provider_id
+            )));
+        }
+        let field = |name: &str| {
+            credential
+                .token_response
+                .get(name)
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        };
+        match (field("phoneNumber"), field("temporaryProof")) {
+            (Some(phone_number), Some(temporary_proof)) => {
+                Ok(Self::from_temporary_proof(phone_number, temporary_proof))
+            }
+            _ => Err(AuthError::InvalidCredential(
+                "Phone credential is missing phoneNumber or temporaryProof".into(),
+            )),
+        }
     }
 }
 
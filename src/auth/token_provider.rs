@@ -49,7 +49,7 @@ impl TokenProvider for AuthTokenProvider {
     }
 }
 
-fn map_auth_error(error: AuthError) -> FirestoreError {
+pub(crate) fn map_auth_error(error: AuthError) -> FirestoreError {
     match error {
         AuthError::InvalidCredential(message) => unauthenticated(message),
         AuthError::Network(message) => unavailable(message),
@@ -58,6 +58,11 @@ fn map_auth_error(error: AuthError) -> FirestoreError {
         AuthError::NotImplemented(feature) => internal_error(format!("{feature} is not implemented")),
         AuthError::MultiFactorRequired(err) => unauthenticated(err.to_string()),
         AuthError::MultiFactor(err) => unauthenticated(err.to_string()),
+        AuthError::TokenVerification(err) => unauthenticated(err.to_string()),
+        AuthError::PersistenceTampered(message) => unauthenticated(message),
+        AuthError::Persistence(message) => internal_error(message),
+        AuthError::AccountConflict(err) => unauthenticated(err.to_string()),
+        AuthError::UserMismatch => unauthenticated(AuthError::UserMismatch.to_string()),
     }
 }
 
@@ -0,0 +1,298 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::app::FirebaseApp;
+use crate::auth::api::emulator_host_configured;
+use crate::auth::error::{AuthError, AuthResult};
+use crate::util::jwks::{HttpPublicKeySource, PublicKeySource};
+use crate::util::jwt::{
+    check_time_claims, decode_unsigned, now_seconds, string_claim, verify_rs256, TokenVerificationError,
+};
+
+/// Google endpoint publishing the x509 certificates that sign Firebase ID tokens.
+pub const ID_TOKEN_CERTIFICATES_URL: &str =
+    "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
+
+const ID_TOKEN_ISSUER_PREFIX: &str = "https://securetoken.google.com/";
+
+/// Verified claims of a Firebase ID token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    /// The Firebase UID (`sub` claim).
+    #[serde(rename = "sub")]
+    pub uid: String,
+    pub aud: String,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub auth_time: i64,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub phone_number: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    pub firebase: Option<FirebaseIdTokenInfo>,
+    /// Custom claims and any other claims not modelled above.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The `firebase` claim describing how the user signed in.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FirebaseIdTokenInfo {
+    pub sign_in_provider: Option<String>,
+    pub sign_in_second_factor: Option<String>,
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub identities: Map<String, Value>,
+}
+
+/// Verifies Firebase ID tokens offline against Google's published signing keys.
+///
+/// Mirrors the checks performed by the Admin SDK's `verifyIdToken`: an RS256 signature from a
+/// known key, `aud` equal to the project ID, `iss` matching the project, `exp`/`iat`/`auth_time`
+/// consistent with the current time, and a non-empty `sub` of at most 128 characters.
+#[derive(Clone)]
+pub struct IdTokenVerifier {
+    project_id: String,
+    key_source: Arc<dyn PublicKeySource>,
+    clock_skew: Duration,
+    accept_unsigned: bool,
+}
+
+impl IdTokenVerifier {
+    /// Creates a verifier for `project_id` that downloads Google's ID token certificates.
+    pub fn new(project_id: impl Into<String>) -> Self {
+        Self {
+            project_id: project_id.into(),
+            key_source: Arc::new(HttpPublicKeySource::new(ID_TOKEN_CERTIFICATES_URL)),
+            clock_skew: Duration::ZERO,
+            accept_unsigned: false,
+        }
+    }
+
+    /// Creates a verifier for the project configured on `app`.
+    ///
+    /// When `FIREBASE_AUTH_EMULATOR_HOST` or `__FIREBASE_DEFAULTS__.emulatorHosts.auth` is set, the
+    /// verifier accepts the unsigned tokens issued by the Auth Emulator.
+    pub fn for_app(app: &FirebaseApp) -> AuthResult<Self> {
+        let project_id = app
+            .options()
+            .project_id
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| AuthError::InvalidCredential("FirebaseOptions.project_id is required".into()))?;
+        Ok(Self::new(project_id).with_unsigned_tokens(emulator_host_configured()))
+    }
+
+    /// Replaces the source of signing keys, e.g. with a fixed `PublicKeySet` in tests.
+    pub fn with_key_source(mut self, key_source: Arc<dyn PublicKeySource>) -> Self {
+        self.key_source = key_source;
+        self
+    }
+
+    /// Tolerates the given clock difference when checking time-based claims.
+    pub fn with_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Skips signature checks so Auth Emulator tokens are accepted; claims are still validated.
+    pub fn with_unsigned_tokens(mut self, accept: bool) -> Self {
+        self.accept_unsigned = accept;
+        self
+    }
+
+    /// Returns the project whose tokens this verifier accepts.
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    /// Verifies `token` and returns its typed claims.
+    pub async fn verify(&self, token: &str) -> AuthResult<IdTokenClaims> {
+        self.verify_token(token).await.map_err(AuthError::TokenVerification)
+    }
+
+    async fn verify_token(&self, token: &str) -> Result<IdTokenClaims, TokenVerificationError> {
+        let verified = if self.accept_unsigned {
+            decode_unsigned(token)?
+        } else {
+            let keys = self.key_source.public_keys().await?;
+            verify_rs256(token, &keys)?
+        };
+        let claims = verified.claims;
+
+        let aud = string_claim(&claims, "aud")?;
+        if aud != self.project_id {
+            return Err(TokenVerificationError::invalid_claim(
+                "aud",
+                format!("expected \"{}\" but got \"{aud}\"", self.project_id),
+            ));
+        }
+
+        let expected_issuer = format!("{ID_TOKEN_ISSUER_PREFIX}{}", self.project_id);
+        let iss = string_claim(&claims, "iss")?;
+        if iss != expected_issuer {
+            return Err(TokenVerificationError::invalid_claim(
+                "iss",
+                format!("expected \"{expected_issuer}\" but got \"{iss}\""),
+            ));
+        }
+
+        let sub = string_claim(&claims, "sub")?;
+        if sub.is_empty() {
+            return Err(TokenVerificationError::invalid_claim("sub", "must be a non-empty string"));
+        }
+        if sub.chars().count() > 128 {
+            return Err(TokenVerificationError::invalid_claim("sub", "must not exceed 128 characters"));
+        }
+
+        let now = now_seconds();
+        let skew = self.clock_skew.as_secs() as i64;
+        check_time_claims(&claims, now, skew, &["iat", "auth_time"])?;
+
+        serde_json::from_value(Value::Object(claims)).map_err(|err| TokenVerificationError::Malformed(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::jwks::test_keys::{sign_rs256, test_key_pair};
+    use crate::util::jwks::PublicKeySet;
+    use serde_json::json;
+
+    const PROJECT_ID: &str = "demo-project";
+
+    fn verifier_with_key() -> (IdTokenVerifier, rsa::RsaPrivateKey) {
+        let (private_key, public_key) = test_key_pair();
+        let mut keys = PublicKeySet::new();
+        keys.insert("kid-1", public_key);
+        let verifier = IdTokenVerifier::new(PROJECT_ID).with_key_source(Arc::new(keys));
+        (verifier, private_key)
+    }
+
+    fn valid_claims() -> Value {
+        let now = now_seconds();
+        json!({
+            "iss": format!("{ID_TOKEN_ISSUER_PREFIX}{PROJECT_ID}"),
+            "aud": PROJECT_ID,
+            "sub": "uid-123",
+            "iat": now - 10,
+            "exp": now + 3600,
+            "auth_time": now - 20,
+            "email": "user@example.com",
+            "email_verified": true,
+            "admin": true,
+            "firebase": {"sign_in_provider": "password", "identities": {"email": ["user@example.com"]}}
+        })
+    }
+
+    fn expect_error(result: AuthResult<IdTokenClaims>) -> TokenVerificationError {
+        match result {
+            Err(AuthError::TokenVerification(err)) => err,
+            other => panic!("expected token verification error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_returns_typed_claims() {
+        let (verifier, private_key) = verifier_with_key();
+        let token = sign_rs256(&private_key, "kid-1", &valid_claims());
+
+        let claims = verifier.verify(&token).await.expect("token should verify");
+        assert_eq!(claims.uid, "uid-123");
+        assert_eq!(claims.email.as_deref(), Some("user@example.com"));
+        assert_eq!(claims.email_verified, Some(true));
+        assert_eq!(
+            claims.firebase.and_then(|info| info.sign_in_provider).as_deref(),
+            Some("password")
+        );
+        assert_eq!(claims.other.get("admin"), Some(&json!(true)));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_rejects_wrong_audience_and_issuer() {
+        let (verifier, private_key) = verifier_with_key();
+
+        let mut claims = valid_claims();
+        claims["aud"] = json!("other-project");
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "aud", .. }));
+
+        let mut claims = valid_claims();
+        claims["iss"] = json!("https://securetoken.google.com/other-project");
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "iss", .. }));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_rejects_invalid_time_claims() {
+        let (verifier, private_key) = verifier_with_key();
+        let now = now_seconds();
+
+        let mut claims = valid_claims();
+        claims["exp"] = json!(now - 1);
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::Expired { .. }));
+
+        let mut claims = valid_claims();
+        claims["auth_time"] = json!(now + 600);
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(
+            error,
+            TokenVerificationError::IssuedInFuture { claim: "auth_time", .. }
+        ));
+
+        let lenient = verifier.clone().with_clock_skew(Duration::from_secs(900));
+        assert!(lenient
+            .verify(&sign_rs256(&private_key, "kid-1", &claims))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_rejects_invalid_subject() {
+        let (verifier, private_key) = verifier_with_key();
+
+        let mut claims = valid_claims();
+        claims["sub"] = json!("");
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "sub", .. }));
+
+        claims["sub"] = json!("x".repeat(129));
+        let error = expect_error(verifier.verify(&sign_rs256(&private_key, "kid-1", &claims)).await);
+        assert!(matches!(error, TokenVerificationError::InvalidClaim { claim: "sub", .. }));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_rejects_tokens_signed_by_unknown_keys() {
+        let (verifier, _) = verifier_with_key();
+        let (other_key, _) = test_key_pair();
+        let token = sign_rs256(&other_key, "kid-1", &valid_claims());
+
+        let error = expect_error(verifier.verify(&token).await);
+        assert_eq!(error, TokenVerificationError::InvalidSignature);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn verify_accepts_unsigned_emulator_tokens_when_enabled() {
+        use crate::util::base64::base64_url_encode_trimmed;
+
+        let header = json!({"alg": "none", "typ": "JWT"});
+        let token = format!(
+            "{}.{}.",
+            base64_url_encode_trimmed(&header.to_string()),
+            base64_url_encode_trimmed(&valid_claims().to_string())
+        );
+
+        let (verifier, _) = verifier_with_key();
+        let error = expect_error(verifier.clone().verify(&token).await);
+        assert!(matches!(error, TokenVerificationError::UnsupportedAlgorithm(_)));
+
+        let claims = verifier.with_unsigned_tokens(true).verify(&token).await.unwrap();
+        assert_eq!(claims.uid, "uid-123");
+    }
+}
@@ -9,6 +9,7 @@ use crate::functions::constants::FUNCTIONS_COMPONENT_NAME;
 use crate::functions::context::ContextProvider;
 use crate::functions::error::{internal_error, invalid_argument, FunctionsResult};
 use crate::functions::transport::{invoke_callable_async, CallableRequest};
+use crate::platform::token::AsyncTokenProvider;
 use serde_json::{json, Value as JsonValue};
 use url::Url;
 
@@ -47,6 +48,22 @@ impl Functions {
         &self.inner.app
     }
 
+    /// Returns a `Functions` handle whose callables authenticate with `provider`.
+    ///
+    /// The app's own `Auth` instance is bypassed, which lets servers invoke callables on behalf
+    /// of a specific user, e.g. through an `Arc<UserSession>`.
+    pub fn with_auth_token_provider(&self, provider: Arc<dyn AsyncTokenProvider>) -> Self {
+        let app = self.inner.app.clone();
+        let context = ContextProvider::new(app.clone()).with_auth_token_provider(provider);
+        Self {
+            inner: Arc::new(FunctionsInner {
+                app,
+                endpoint: self.inner.endpoint.clone(),
+                context,
+            }),
+        }
+    }
+
     pub fn region(&self) -> &str {
         self.inner.endpoint.region()
     }
@@ -435,4 +452,45 @@ mod tests {
         assert_eq!(response, json!({ "ok": true }));
         mock.assert();
     }
+
+    struct StaticAuthTokenProvider;
+
+    #[async_trait::async_trait]
+    impl AsyncTokenProvider for StaticAuthTokenProvider {
+        async fn get_token(&self, _force_refresh: bool) -> Result<Option<String>, crate::platform::token::TokenError> {
+            Ok(Some("session-token".into()))
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn https_callable_uses_custom_auth_token_provider() {
+        let server = match panic::catch_unwind(MockServer::start) {
+            Ok(server) => server,
+            Err(_) => {
+                eprintln!("Skipping https_callable_uses_custom_auth_token_provider: unable to bind mock server");
+                return;
+            }
+        };
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/callable/asUser")
+                .header("authorization", "Bearer session-token");
+            then.status(200).json_body(json!({ "data": null }));
+        });
+
+        let options = FirebaseOptions {
+            project_id: Some("demo-project".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let functions = get_functions(Some(app), Some(&server.url("/callable"))).await.unwrap();
+        let scoped = functions.with_auth_token_provider(Arc::new(StaticAuthTokenProvider));
+
+        let callable = scoped
+            .https_callable::<serde_json::Value, serde_json::Value>("asUser")
+            .unwrap();
+        callable.call_async(&json!({})).await.unwrap();
+        mock.assert();
+    }
 }
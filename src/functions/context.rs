@@ -10,6 +10,7 @@ use crate::auth::Auth;
 use crate::component::provider::Provider;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::messaging::Messaging;
+use crate::platform::token::AsyncTokenProvider;
 
 /// Metadata that may be attached to callable Function requests.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -26,6 +27,7 @@ pub struct ContextProvider {
     #[cfg(not(target_arch = "wasm32"))]
     messaging_provider: Provider,
     app_check_provider: Provider,
+    auth_token_provider: Option<Arc<dyn AsyncTokenProvider>>,
     cached_auth: Mutex<Option<Arc<Auth>>>,
     #[cfg(not(target_arch = "wasm32"))]
     cached_messaging: Mutex<Option<Arc<Messaging>>>,
@@ -67,6 +69,7 @@ impl ContextProvider {
             auth_provider,
             auth_internal_provider,
             app_check_provider,
+            auth_token_provider: None,
             cached_auth: Mutex::new(None),
             cached_app_check: Mutex::new(None),
             overrides: Mutex::new(None),
@@ -77,6 +80,12 @@ impl ContextProvider {
         }
     }
 
+    /// Sources auth tokens from `provider` instead of the app's `Auth` instance.
+    pub fn with_auth_token_provider(mut self, provider: Arc<dyn AsyncTokenProvider>) -> Self {
+        self.auth_token_provider = Some(provider);
+        self
+    }
+
     pub async fn get_context_async(&self, limited_use_app_check_tokens: bool) -> CallContext {
         if let Some(overrides) = self.overrides.lock().unwrap().clone() {
             return overrides;
@@ -94,6 +103,13 @@ impl ContextProvider {
     }
 
     async fn fetch_auth_token(&self) -> Option<String> {
+        if let Some(provider) = self.auth_token_provider.as_ref() {
+            return match provider.get_token(false).await {
+                Ok(Some(token)) if !token.is_empty() => Some(token),
+                _ => None,
+            };
+        }
+
         let auth = self.ensure_auth()?;
         match auth.get_token(false).await {
             Ok(Some(token)) if !token.is_empty() => Some(token),
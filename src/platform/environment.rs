@@ -137,6 +137,16 @@ pub fn default_app_config_json() -> Option<Map<String, Value>> {
     firebase_config_from_env()?.as_object().cloned()
 }
 
+/// Returns the `host:port` configured for `product_name` in `__FIREBASE_DEFAULTS__.emulatorHosts`.
+pub fn default_emulator_host(product_name: &str) -> Option<String> {
+    firebase_defaults()?
+        .get("emulatorHosts")?
+        .get(product_name)?
+        .as_str()
+        .filter(|host| !host.is_empty())
+        .map(str::to_string)
+}
+
 fn force_environment() -> Option<String> {
     firebase_defaults()
         .and_then(|defaults| defaults.get("forceEnvironment").cloned())
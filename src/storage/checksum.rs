@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use md5::{Digest, Md5};
+
+use crate::storage::error::{checksum_mismatch, StorageResult};
+use crate::storage::metadata::serde::ObjectMetadata;
+use crate::storage::UploadMetadata;
+
+/// Controls which checksums are computed while transferring objects and how they are used.
+///
+/// CRC32C verification is enabled by default because it is cheap and the server always reports
+/// it. MD5 is opt-in: it costs more CPU and is absent for composite objects. When
+/// `send_client_hashes` is set, uploads also send the client-side hashes so the server rejects
+/// corrupted payloads instead of storing them.
+///
+/// `get_bytes` and `get_stream` verify against the `x-goog-hash` header of the response. When
+/// `metadata_fallback` is set, a response without that header triggers an extra metadata request
+/// for the checksums; a failed metadata request then skips verification instead of failing the
+/// download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntegrityOptions {
+    pub verify_crc32c: bool,
+    pub verify_md5: bool,
+    pub send_client_hashes: bool,
+    pub metadata_fallback: bool,
+}
+
+impl Default for IntegrityOptions {
+    fn default() -> Self {
+        Self {
+            verify_crc32c: true,
+            verify_md5: false,
+            send_client_hashes: false,
+            metadata_fallback: false,
+        }
+    }
+}
+
+impl IntegrityOptions {
+    /// Options that skip all checksum work.
+    pub fn disabled() -> Self {
+        Self {
+            verify_crc32c: false,
+            verify_md5: false,
+            send_client_hashes: false,
+            metadata_fallback: false,
+        }
+    }
+
+    pub fn with_md5(mut self, enabled: bool) -> Self {
+        self.verify_md5 = enabled;
+        self
+    }
+
+    pub fn with_client_hashes(mut self, enabled: bool) -> Self {
+        self.send_client_hashes = enabled;
+        self
+    }
+
+    pub fn with_metadata_fallback(mut self, enabled: bool) -> Self {
+        self.metadata_fallback = enabled;
+        self
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.verify_crc32c || self.verify_md5 || self.send_client_hashes
+    }
+}
+
+/// Base64-encoded checksums in the format used by `ObjectMetadata`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectChecksums {
+    pub crc32c: String,
+    pub md5_hash: Option<String>,
+}
+
+impl ObjectChecksums {
+    /// Computes the checksums of an in-memory payload.
+    pub fn compute(data: &[u8], include_md5: bool) -> Self {
+        let mut hasher = ChecksumHasher::new(include_md5);
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    /// Formats the checksums as an `X-Goog-Hash` header value.
+    pub(crate) fn header_value(&self) -> String {
+        match &self.md5_hash {
+            Some(md5) => format!("crc32c={},md5={md5}", self.crc32c),
+            None => format!("crc32c={}", self.crc32c),
+        }
+    }
+
+    /// Fills the hash fields of upload metadata that the caller left unset.
+    pub(crate) fn apply_to(&self, metadata: Option<UploadMetadata>) -> UploadMetadata {
+        let mut metadata = metadata.unwrap_or_default();
+        if metadata.crc32c.is_none() {
+            metadata.crc32c = Some(self.crc32c.clone());
+        }
+        if metadata.md5_hash.is_none() {
+            metadata.md5_hash = self.md5_hash.clone();
+        }
+        metadata
+    }
+}
+
+/// Incremental CRC32C/MD5 hasher fed while bytes stream through a transfer.
+pub(crate) struct ChecksumHasher {
+    crc32c: u32,
+    md5: Option<Md5>,
+}
+
+impl ChecksumHasher {
+    pub(crate) fn new(include_md5: bool) -> Self {
+        Self {
+            crc32c: 0,
+            md5: include_md5.then(Md5::new),
+        }
+    }
+
+    pub(crate) fn for_options(options: &IntegrityOptions) -> Option<Self> {
+        options.is_enabled().then(|| Self::new(options.verify_md5))
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.crc32c = crc32c::crc32c_append(self.crc32c, data);
+        if let Some(md5) = self.md5.as_mut() {
+            md5.update(data);
+        }
+    }
+
+    pub(crate) fn finish(self) -> ObjectChecksums {
+        ObjectChecksums {
+            crc32c: STANDARD.encode(self.crc32c.to_be_bytes()),
+            md5_hash: self.md5.map(|md5| STANDARD.encode(md5.finalize())),
+        }
+    }
+}
+
+/// Compares client-side checksums with the values reported by the server.
+///
+/// Hashes the server did not report (emulators, composite objects) are skipped rather than treated
+/// as failures.
+pub(crate) fn verify_checksums(
+    options: &IntegrityOptions,
+    computed: &ObjectChecksums,
+    metadata: &ObjectMetadata,
+) -> StorageResult<()> {
+    let path = metadata
+        .full_path
+        .as_deref()
+        .or(metadata.name.as_deref())
+        .unwrap_or_default();
+    if options.verify_crc32c {
+        if let Some(expected) = metadata.crc32c.as_deref() {
+            if expected != computed.crc32c {
+                return Err(checksum_mismatch(path, "CRC32C", expected, &computed.crc32c));
+            }
+        }
+    }
+    if options.verify_md5 {
+        if let (Some(expected), Some(actual)) = (metadata.md5_hash.as_deref(), computed.md5_hash.as_deref()) {
+            if expected != actual {
+                return Err(checksum_mismatch(path, "MD5", expected, actual));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the checksums a media response reports in `x-goog-hash` (`crc32c=...,md5=...`) into the
+/// shape [`verify_checksums`] expects. Returns `None` when the response carries no hashes.
+pub(crate) fn reported_checksums(headers: &HashMap<String, String>, full_path: &str) -> Option<ObjectMetadata> {
+    let value = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("x-goog-hash"))
+        .map(|(_, value)| value)?;
+    let mut reported = ObjectMetadata {
+        full_path: Some(full_path.to_string()),
+        ..Default::default()
+    };
+    for entry in value.split(',') {
+        match entry.trim().split_once('=') {
+            Some(("crc32c", hash)) => reported.crc32c = Some(hash.to_string()),
+            Some(("md5", hash)) => reported.md5_hash = Some(hash.to_string()),
+            _ => {}
+        }
+    }
+    (reported.crc32c.is_some() || reported.md5_hash.is_some()).then_some(reported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::error::StorageErrorCode;
+
+    #[test]
+    fn computes_known_vectors() {
+        let checksums = ObjectChecksums::compute(b"123456789", true);
+        assert_eq!(checksums.crc32c, "4waSgw==");
+        assert_eq!(checksums.md5_hash.as_deref(), Some("JfnnlDI7RTiF9RgfG2JNCw=="));
+        assert_eq!(checksums.header_value(), "crc32c=4waSgw==,md5=JfnnlDI7RTiF9RgfG2JNCw==");
+    }
+
+    #[test]
+    fn incremental_updates_match_single_pass() {
+        let mut hasher = ChecksumHasher::new(true);
+        hasher.update(b"1234");
+        hasher.update(b"56789");
+        assert_eq!(hasher.finish(), ObjectChecksums::compute(b"123456789", true));
+    }
+
+    #[test]
+    fn verify_reports_mismatch_and_skips_missing_hashes() {
+        let computed = ObjectChecksums::compute(b"123456789", true);
+        let options = IntegrityOptions::default().with_md5(true);
+
+        let mut metadata = ObjectMetadata::default();
+        assert!(verify_checksums(&options, &computed, &metadata).is_ok());
+
+        metadata.crc32c = Some("AAAAAA==".into());
+        let err = verify_checksums(&options, &computed, &metadata).unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::ChecksumMismatch);
+
+        metadata.crc32c = Some(computed.crc32c.clone());
+        metadata.md5_hash = Some("bogus".into());
+        assert!(verify_checksums(&options, &computed, &metadata).is_err());
+        assert!(verify_checksums(&IntegrityOptions::default(), &computed, &metadata).is_ok());
+    }
+
+    #[test]
+    fn reads_reported_checksums_from_hash_header() {
+        let headers = HashMap::from([(
+            "x-goog-hash".to_string(),
+            "crc32c=4waSgw==,md5=JfnnlDI7RTiF9RgfG2JNCw==".to_string(),
+        )]);
+        let reported = reported_checksums(&headers, "a/b").unwrap();
+        assert_eq!(reported.crc32c.as_deref(), Some("4waSgw=="));
+        assert_eq!(reported.md5_hash.as_deref(), Some("JfnnlDI7RTiF9RgfG2JNCw=="));
+        assert_eq!(reported.full_path.as_deref(), Some("a/b"));
+        assert!(reported_checksums(&HashMap::new(), "a/b").is_none());
+    }
+}
@@ -0,0 +1,605 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{Error as IoError, ErrorKind};
+#[cfg(not(target_arch = "wasm32"))]
+use std::pin::Pin;
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use bytes::Bytes;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::{ready, Stream, StreamExt};
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::platform::runtime;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::checksum::{verify_checksums, ChecksumHasher, IntegrityOptions};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::error::{internal_error, object_changed, StorageError, StorageErrorCode, StorageResult};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::metadata::serde::ObjectMetadata;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::reference::StorageReference;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::request::{download_range_request, BackoffConfig, BackoffState, StreamingResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::util::is_retry_status_code;
+
+#[cfg(not(target_arch = "wasm32"))]
+const DOWNLOAD_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Progress information emitted while downloading objects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+}
+
+impl DownloadProgress {
+    pub fn new(bytes_transferred: u64, total_bytes: u64) -> Self {
+        Self {
+            bytes_transferred,
+            total_bytes,
+        }
+    }
+}
+
+/// Streams the object behind `reference` into `writer`, resuming with a ranged request whenever
+/// the connection drops before the full object has been received. Transient failures of the
+/// initial request or of a reconnect are retried with the same backoff, which starts over
+/// whenever a reconnect delivers more bytes.
+///
+/// The object generation is captured up front; a different generation on resume (or in the
+/// `x-goog-generation` response header) aborts with `storage/object-changed` so that bytes from
+/// two versions of the object are never stitched together. Once every byte has been written the
+/// client-side checksums are compared with the metadata according to the storage's
+/// [`IntegrityOptions`](crate::storage::IntegrityOptions).
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn download_to_writer<W, F>(
+    reference: &StorageReference,
+    writer: &mut W,
+    progress: &mut F,
+) -> StorageResult<ObjectMetadata>
+where
+    W: AsyncWrite + Unpin,
+    F: FnMut(DownloadProgress),
+{
+    let metadata = reference.get_metadata().await?;
+    let total = metadata
+        .size_bytes()
+        .ok_or_else(|| internal_error("object metadata is missing its size"))?;
+    let generation = metadata.generation.clone();
+
+    let storage = reference.storage();
+    let options = storage.integrity_options();
+    let mut hasher = ChecksumHasher::for_options(&options);
+    let timeout = Duration::from_millis(storage.max_operation_retry_time());
+    let mut backoff = BackoffState::new(BackoffConfig::standard_operation().with_total_timeout(timeout));
+    let mut buffer = vec![0u8; DOWNLOAD_BUFFER_SIZE];
+    let mut offset = 0u64;
+    progress(DownloadProgress::new(0, total));
+
+    while offset < total {
+        let attempt_offset = offset;
+        let request = download_range_request(&storage, reference.location(), offset, None);
+        let response = match storage.run_streaming_request(request).await {
+            Ok(response) => Some(response),
+            Err(err) if is_transient(&err) => {
+                log::debug!("download request failed at byte {offset}, retrying: {err}");
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        let interrupted = match response {
+            Some(response) => {
+                let served_generation = response
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("x-goog-generation"))
+                    .map(|(_, value)| value.as_str());
+                check_generation(reference, generation.as_deref(), served_generation)?;
+                // A server that ignores `Range` replays the object from the start.
+                let mut skip = if response.status.as_u16() == 200 { offset } else { 0 };
+                let mut reader = response.reader;
+                loop {
+                    match reader.read(&mut buffer).await {
+                        Ok(0) => break offset < total,
+                        Ok(read) => {
+                            let skipped = skip.min(read as u64) as usize;
+                            skip -= skipped as u64;
+                            let remaining = (total - offset) as usize;
+                            let chunk = &buffer[skipped..read.min(skipped + remaining)];
+                            if chunk.is_empty() {
+                                continue;
+                            }
+                            writer
+                                .write_all(chunk)
+                                .await
+                                .map_err(|err| internal_error(format!("failed to write download output: {err}")))?;
+                            if let Some(hasher) = hasher.as_mut() {
+                                hasher.update(chunk);
+                            }
+                            offset += chunk.len() as u64;
+                            progress(DownloadProgress::new(offset, total));
+                        }
+                        Err(_) => break true,
+                    }
+                }
+            }
+            None => true,
+        };
+
+        if !interrupted {
+            break;
+        }
+        if offset > attempt_offset {
+            backoff.reset();
+        }
+        if !backoff.can_retry() {
+            return Err(internal_error(format!(
+                "download interrupted at byte {offset} of {total} after retries"
+            )));
+        }
+        let delay = backoff.next_delay();
+        if delay > Duration::from_millis(0) {
+            runtime::sleep(delay).await;
+        }
+        let current = reference.get_metadata().await?;
+        check_generation(reference, generation.as_deref(), current.generation.as_deref())?;
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|err| internal_error(format!("failed to flush download output: {err}")))?;
+    if let Some(hasher) = hasher {
+        verify_checksums(&options, &hasher.finish(), &metadata)?;
+    }
+    Ok(metadata)
+}
+
+/// Wraps a full-object response so that the reader fails with [`ErrorKind::InvalidData`] at the
+/// end of the body when the received bytes do not match `expected`. The inner error is the
+/// `storage/checksum-mismatch` [`StorageError`].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn verify_stream(
+    response: StreamingResponse,
+    options: IntegrityOptions,
+    expected: ObjectMetadata,
+) -> StreamingResponse {
+    let StreamingResponse {
+        status,
+        headers,
+        reader,
+    } = response;
+    let stream = VerifyingStream {
+        inner: reader.into_inner(),
+        hasher: Some(ChecksumHasher::new(options.verify_md5)),
+        options,
+        expected,
+    };
+    StreamingResponse {
+        status,
+        headers,
+        reader: tokio_util::io::StreamReader::new(Box::pin(stream)),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct VerifyingStream<S> {
+    inner: S,
+    // Dropped after a read error, when the body is incomplete anyway.
+    hasher: Option<ChecksumHasher>,
+    options: IntegrityOptions,
+    expected: ObjectMetadata,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S> Stream for VerifyingStream<S>
+where
+    S: Stream<Item = Result<Bytes, IoError>> + Unpin,
+{
+    type Item = Result<Bytes, IoError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match ready!(this.inner.poll_next_unpin(cx)) {
+            Some(Ok(chunk)) => {
+                if let Some(hasher) = this.hasher.as_mut() {
+                    hasher.update(&chunk);
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Some(Err(err)) => {
+                this.hasher = None;
+                Poll::Ready(Some(Err(err)))
+            }
+            None => match this.hasher.take() {
+                Some(hasher) => match verify_checksums(&this.options, &hasher.finish(), &this.expected) {
+                    Ok(()) => Poll::Ready(None),
+                    Err(err) => Poll::Ready(Some(Err(IoError::new(ErrorKind::InvalidData, err)))),
+                },
+                None => Poll::Ready(None),
+            },
+        }
+    }
+}
+
+/// Network failures, timeouts and retryable statuses (5xx, 408, 429) are worth another attempt.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_transient(err: &StorageError) -> bool {
+    match err.status {
+        Some(status) => is_retry_status_code(status, &[]),
+        None => err.code == StorageErrorCode::InternalError,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn check_generation(reference: &StorageReference, expected: Option<&str>, actual: Option<&str>) -> StorageResult<()> {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) if expected != actual => {
+            Err(object_changed(reference.full_path(), expected, actual))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::storage::error::StorageErrorCode;
+    use crate::storage::service::FirebaseStorageImpl;
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn unique_settings() -> FirebaseAppSettings {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("storage-download-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn build_reference(server: &MockServer) -> StorageReference {
+        let options = FirebaseOptions {
+            storage_bucket: Some("bucket".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let container = app.container();
+        let auth_provider = container.get_provider("auth-internal");
+        let app_check_provider = container.get_provider("app-check-internal");
+        let storage = FirebaseStorageImpl::new(app, auth_provider, app_check_provider, None, None).unwrap();
+        storage.connect_emulator("127.0.0.1", server.port(), None).unwrap();
+        storage.root_reference().unwrap().child("report")
+    }
+
+    fn mock_metadata<'a>(server: &'a MockServer, generation: &str) -> httpmock::Mock<'a> {
+        let generation = generation.to_string();
+        server.mock(move |when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "json");
+            then.status(200)
+                .json_body(json!({ "name": "report", "bucket": "bucket", "size": "10", "generation": generation }));
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_range_requests_byte_range() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media")
+                .header("Range", "bytes=2-5");
+            then.status(206).body("2345");
+        });
+
+        let reference = build_reference(&server).await;
+        let bytes = reference.get_range(2..6).await.unwrap();
+        mock.assert();
+        assert_eq!(bytes, b"2345".to_vec());
+        assert!(reference.get_range(4..4).await.is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_resumes_after_truncated_stream() {
+        let server = start_mock_server();
+        let metadata_mock = mock_metadata(&server, "7");
+        let first = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media")
+                .header("Range", "bytes=0-");
+            then.status(206).header("x-goog-generation", "7").body("01234");
+        });
+        let second = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media")
+                .header("Range", "bytes=5-");
+            then.status(206).header("x-goog-generation", "7").body("56789");
+        });
+
+        let reference = build_reference(&server).await;
+        let mut output = Vec::new();
+        let mut reported = Vec::new();
+        let metadata = reference
+            .download_to_writer_with_progress(&mut output, |progress| reported.push(progress.bytes_transferred))
+            .await
+            .unwrap();
+
+        assert_eq!(output, b"0123456789".to_vec());
+        assert_eq!(reported, vec![0, 5, 10]);
+        assert_eq!(metadata.generation.as_deref(), Some("7"));
+        metadata_mock.assert_hits(2);
+        first.assert();
+        second.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_resets_backoff_after_progress() {
+        let server = start_mock_server();
+        let _metadata_mock = mock_metadata(&server, "7");
+        let segments = [("0", "012", 0), ("3", "345", 250), ("6", "67", 250), ("8", "89", 0)];
+        let mocks: Vec<_> = segments
+            .iter()
+            .map(|(start, body, delay)| {
+                server.mock(|when, then| {
+                    when.method(GET)
+                        .path("/v0/b/bucket/o/report")
+                        .query_param("alt", "media")
+                        .header("Range", format!("bytes={start}-"));
+                    then.status(206)
+                        .delay(std::time::Duration::from_millis(*delay))
+                        .body(*body);
+                })
+            })
+            .collect();
+
+        let reference = build_reference(&server).await;
+        // Three interruptions spread over more than the retry budget, each after new bytes arrived.
+        reference.storage().set_max_operation_retry_time(400);
+        let mut output = Vec::new();
+        reference.download_to_writer(&mut output).await.unwrap();
+        assert_eq!(output, b"0123456789".to_vec());
+        for mock in &mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_retries_failed_initial_request() {
+        let server = start_mock_server();
+        let _metadata_mock = mock_metadata(&server, "7");
+        let mut unavailable = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(503);
+        });
+
+        let reference = build_reference(&server).await;
+        let download = tokio::spawn(async move {
+            let mut output = Vec::new();
+            reference.download_to_writer(&mut output).await.map(|_| output)
+        });
+        for _ in 0..200 {
+            if unavailable.hits() >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        unavailable.assert_hits(2);
+        unavailable.delete();
+        let media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(206).header("x-goog-generation", "7").body("0123456789");
+        });
+
+        let output = download.await.unwrap().unwrap();
+        assert_eq!(output, b"0123456789".to_vec());
+        media.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_detects_generation_change_on_resume() {
+        let server = start_mock_server();
+        let _metadata_mock = mock_metadata(&server, "7");
+        let _first = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media")
+                .header("Range", "bytes=0-");
+            then.status(206).body("01234");
+        });
+        let _second = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media")
+                .header("Range", "bytes=5-");
+            then.status(206).header("x-goog-generation", "8").body("abcde");
+        });
+
+        let reference = build_reference(&server).await;
+        let mut output = Vec::new();
+        let err = reference.download_to_writer(&mut output).await.unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::ObjectChanged);
+        assert_eq!(output, b"01234".to_vec());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_rejects_corrupted_payload() {
+        let server = start_mock_server();
+        let _metadata_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "json");
+            then.status(200).json_body(json!({
+                "name": "report",
+                "bucket": "bucket",
+                "size": "9",
+                "crc32c": "4waSgw=="
+            }));
+        });
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(206).body("123456780");
+        });
+
+        let reference = build_reference(&server).await;
+        let mut output = Vec::new();
+        let err = reference.download_to_writer(&mut output).await.unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::ChecksumMismatch);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_bytes_verifies_reported_hash() {
+        let server = start_mock_server();
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(200)
+                .header("x-goog-hash", "crc32c=4waSgw==")
+                .body("123456780");
+        });
+
+        let reference = build_reference(&server).await;
+        let err = reference.get_bytes(None).await.unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::ChecksumMismatch);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_bytes_falls_back_to_metadata_checksums() {
+        let server = start_mock_server();
+        let metadata_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "json");
+            then.status(200)
+                .json_body(json!({ "name": "report", "bucket": "bucket", "size": "9", "crc32c": "4waSgw==" }));
+        });
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(200).body("123456789");
+        });
+
+        let reference = build_reference(&server).await;
+        reference
+            .storage()
+            .set_integrity_options(IntegrityOptions::default().with_metadata_fallback(true));
+        assert_eq!(reference.get_bytes(None).await.unwrap(), b"123456789".to_vec());
+        metadata_mock.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_bytes_without_reported_hash_skips_metadata_by_default() {
+        let server = start_mock_server();
+        let metadata_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "json");
+            then.status(403);
+        });
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(200).body("123456789");
+        });
+
+        let reference = build_reference(&server).await;
+        assert_eq!(reference.get_bytes(None).await.unwrap(), b"123456789".to_vec());
+        metadata_mock.assert_hits(0);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_bytes_ignores_failed_metadata_fallback() {
+        let server = start_mock_server();
+        let metadata_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "json");
+            then.status(403);
+        });
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(200).body("123456789");
+        });
+
+        let reference = build_reference(&server).await;
+        reference
+            .storage()
+            .set_integrity_options(IntegrityOptions::default().with_metadata_fallback(true));
+        assert_eq!(reference.get_bytes(None).await.unwrap(), b"123456789".to_vec());
+        let response = reference.get_stream(None).await.unwrap();
+        let mut reader = response.reader;
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).await.unwrap();
+        assert_eq!(output, b"123456789".to_vec());
+        // Each 403 is retried once with refreshed credentials before the fallback gives up.
+        metadata_mock.assert_hits(4);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_stream_fails_final_read_on_checksum_mismatch() {
+        let server = start_mock_server();
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(200)
+                .header("x-goog-hash", "crc32c=4waSgw==")
+                .body("123456780");
+        });
+
+        let reference = build_reference(&server).await;
+        let mut reader = reference.get_stream(None).await.unwrap().reader;
+        let mut output = Vec::new();
+        let err = reader.read_to_end(&mut output).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let inner = err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<StorageError>())
+            .unwrap();
+        assert_eq!(inner.code, StorageErrorCode::ChecksumMismatch);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_to_file_writes_object() {
+        let server = start_mock_server();
+        let _metadata_mock = mock_metadata(&server, "1");
+        let _media = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o/report")
+                .query_param("alt", "media");
+            then.status(206).body("0123456789");
+        });
+
+        let reference = build_reference(&server).await;
+        let mut path = std::env::temp_dir();
+        path.push(format!("firebase-storage-download-{}.bin", std::process::id()));
+        reference.download_to_file(&path).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789".to_vec());
+        let _ = std::fs::remove_file(&path);
+    }
+}
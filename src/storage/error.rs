@@ -13,6 +13,13 @@ pub enum StorageErrorCode {
     InternalError,
     UnsupportedEnvironment,
     NoDownloadUrl,
+    Canceled,
+    UploadSessionExpired,
+    ObjectChanged,
+    ChecksumMismatch,
+    Unauthenticated,
+    AppCheckFailed,
+    PreconditionFailed,
 }
 
 impl StorageErrorCode {
@@ -28,6 +35,13 @@ impl StorageErrorCode {
             StorageErrorCode::InternalError => "storage/internal-error",
             StorageErrorCode::UnsupportedEnvironment => "storage/unsupported-environment",
             StorageErrorCode::NoDownloadUrl => "storage/no-download-url",
+            StorageErrorCode::Canceled => "storage/canceled",
+            StorageErrorCode::UploadSessionExpired => "storage/upload-session-expired",
+            StorageErrorCode::ObjectChanged => "storage/object-changed",
+            StorageErrorCode::ChecksumMismatch => "storage/checksum-mismatch",
+            StorageErrorCode::Unauthenticated => "storage/unauthenticated",
+            StorageErrorCode::AppCheckFailed => "storage/app-check-failed",
+            StorageErrorCode::PreconditionFailed => "storage/precondition-failed",
         }
     }
 }
@@ -136,3 +150,49 @@ pub fn no_download_url() -> StorageError {
         "The requested object does not expose a download URL.",
     )
 }
+
+pub fn canceled() -> StorageError {
+    StorageError::new(StorageErrorCode::Canceled, "User canceled the upload/download.")
+}
+
+pub fn upload_session_expired(session_url: &str) -> StorageError {
+    StorageError::new(
+        StorageErrorCode::UploadSessionExpired,
+        format!("The resumable upload session {session_url} has expired; start a new upload."),
+    )
+}
+
+pub fn object_changed(path: &str, expected_generation: &str, actual_generation: &str) -> StorageError {
+    StorageError::new(
+        StorageErrorCode::ObjectChanged,
+        format!("Object '{path}' changed during download (generation {expected_generation} -> {actual_generation})."),
+    )
+}
+
+pub fn checksum_mismatch(path: &str, algorithm: &str, expected: &str, actual: &str) -> StorageError {
+    StorageError::new(
+        StorageErrorCode::ChecksumMismatch,
+        format!("Object '{path}' failed {algorithm} verification (server {expected}, client {actual})."),
+    )
+}
+
+pub fn precondition_failed(path: &str) -> StorageError {
+    StorageError::new(
+        StorageErrorCode::PreconditionFailed,
+        format!("A generation or metageneration precondition for '{path}' did not hold."),
+    )
+}
+
+pub fn unauthenticated() -> StorageError {
+    StorageError::new(
+        StorageErrorCode::Unauthenticated,
+        "User is not authenticated, please authenticate using Firebase Authentication and try again.",
+    )
+}
+
+pub fn app_check_failed() -> StorageError {
+    StorageError::new(
+        StorageErrorCode::AppCheckFailed,
+        "The App Check token was rejected by the Storage backend, even after refreshing it.",
+    )
+}
@@ -1,9 +1,46 @@
+use std::collections::VecDeque;
+
 use crate::storage::error::{internal_error, StorageResult};
 use crate::storage::location::Location;
+use crate::storage::metadata::serde::ObjectMetadata;
 use crate::storage::reference::StorageReference;
 use crate::storage::service::FirebaseStorageImpl;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::stream::BoxStream;
+#[cfg(target_arch = "wasm32")]
+use futures::stream::LocalBoxStream;
+use futures::stream::{self, Stream, StreamExt};
 use serde::Deserialize;
 
+/// Default number of concurrent metadata lookups performed by [`StorageReference::walk`].
+pub const DEFAULT_WALK_METADATA_CONCURRENCY: usize = 8;
+
+#[cfg(target_arch = "wasm32")]
+pub type ListEntryStream = LocalBoxStream<'static, StorageResult<ListEntry>>;
+#[cfg(not(target_arch = "wasm32"))]
+pub type ListEntryStream = BoxStream<'static, StorageResult<ListEntry>>;
+
+#[cfg(target_arch = "wasm32")]
+pub type WalkStream = LocalBoxStream<'static, StorageResult<WalkEntry>>;
+#[cfg(not(target_arch = "wasm32"))]
+pub type WalkStream = BoxStream<'static, StorageResult<WalkEntry>>;
+
+#[cfg(target_arch = "wasm32")]
+fn box_stream<S, T>(stream: S) -> LocalBoxStream<'static, T>
+where
+    S: Stream<Item = T> + 'static,
+{
+    stream.boxed_local()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn box_stream<S, T>(stream: S) -> BoxStream<'static, T>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    stream.boxed()
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ListOptions {
     pub max_results: Option<u32>,
@@ -80,6 +117,228 @@ pub fn parse_list_result(
     Ok(result)
 }
 
+/// A single result of [`StorageReference::list_stream`].
+#[derive(Clone)]
+pub enum ListEntry {
+    Prefix(StorageReference),
+    Item(StorageReference),
+}
+
+impl ListEntry {
+    pub fn reference(&self) -> &StorageReference {
+        match self {
+            ListEntry::Prefix(reference) | ListEntry::Item(reference) => reference,
+        }
+    }
+
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, ListEntry::Prefix(_))
+    }
+}
+
+/// Controls the traversal performed by [`StorageReference::walk`].
+#[derive(Clone, Debug)]
+pub struct WalkOptions {
+    /// Only yields items whose path relative to the walk root matches this glob. `*` and `?`
+    /// stay within one path segment, `**` spans segments.
+    pub glob: Option<String>,
+    /// Maximum number of prefix levels to descend; `Some(0)` yields only direct children.
+    pub max_depth: Option<usize>,
+    /// Fetches [`ObjectMetadata`] for every yielded item.
+    pub fetch_metadata: bool,
+    /// Number of metadata lookups in flight when `fetch_metadata` is set.
+    pub metadata_concurrency: usize,
+    /// Page size requested from the list endpoint.
+    pub page_size: Option<u32>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            glob: None,
+            max_depth: None,
+            fetch_metadata: false,
+            metadata_concurrency: DEFAULT_WALK_METADATA_CONCURRENCY,
+            page_size: None,
+        }
+    }
+}
+
+impl WalkOptions {
+    pub fn with_glob(mut self, glob: impl Into<String>) -> Self {
+        self.glob = Some(glob.into());
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_metadata(mut self, concurrency: usize) -> Self {
+        self.fetch_metadata = true;
+        self.metadata_concurrency = concurrency;
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+}
+
+/// An object found by [`StorageReference::walk`].
+#[derive(Clone)]
+pub struct WalkEntry {
+    pub reference: StorageReference,
+    /// Number of prefixes between the walk root and this item.
+    pub depth: usize,
+    pub metadata: Option<ObjectMetadata>,
+}
+
+struct ListStreamState {
+    reference: StorageReference,
+    max_results: Option<u32>,
+    page_token: Option<String>,
+    buffered: VecDeque<ListEntry>,
+    finished: bool,
+}
+
+/// Lazily pages through `reference`, yielding each page's prefixes before its items.
+pub(crate) fn list_entry_stream(reference: StorageReference, options: ListOptions) -> ListEntryStream {
+    let state = ListStreamState {
+        reference,
+        max_results: options.max_results,
+        page_token: options.page_token,
+        buffered: VecDeque::new(),
+        finished: false,
+    };
+    box_stream(stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(entry) = state.buffered.pop_front() {
+                return Some((Ok(entry), state));
+            }
+            if state.finished {
+                return None;
+            }
+            let options = ListOptions {
+                max_results: state.max_results,
+                page_token: state.page_token.take(),
+            };
+            match state.reference.list(Some(options)).await {
+                Ok(page) => {
+                    state.buffered.extend(page.prefixes.into_iter().map(ListEntry::Prefix));
+                    state.buffered.extend(page.items.into_iter().map(ListEntry::Item));
+                    state.page_token = page.next_page_token;
+                    state.finished = state.page_token.is_none();
+                }
+                Err(err) => {
+                    state.finished = true;
+                    return Some((Err(err), state));
+                }
+            }
+        }
+    }))
+}
+
+struct WalkState {
+    root_path: String,
+    glob: Option<Vec<char>>,
+    max_depth: Option<usize>,
+    page_size: Option<u32>,
+    stack: Vec<(ListEntryStream, usize)>,
+}
+
+/// Depth-first traversal: a prefix is fully walked before the next entry of its parent page.
+pub(crate) fn walk_stream(root: StorageReference, options: WalkOptions) -> WalkStream {
+    let page_options = ListOptions {
+        max_results: options.page_size,
+        page_token: None,
+    };
+    let state = WalkState {
+        root_path: root.full_path().to_string(),
+        glob: options.glob.as_ref().map(|glob| glob.chars().collect()),
+        max_depth: options.max_depth,
+        page_size: options.page_size,
+        stack: vec![(list_entry_stream(root, page_options), 0)],
+    };
+
+    let entries = stream::unfold(state, |mut state| async move {
+        loop {
+            let (stream, depth) = state.stack.last_mut()?;
+            let depth = *depth;
+            match stream.next().await {
+                None => {
+                    state.stack.pop();
+                }
+                Some(Err(err)) => {
+                    state.stack.clear();
+                    return Some((Err(err), state));
+                }
+                Some(Ok(ListEntry::Prefix(prefix))) => {
+                    if state.max_depth.map_or(true, |max| depth < max) {
+                        let options = ListOptions {
+                            max_results: state.page_size,
+                            page_token: None,
+                        };
+                        state.stack.push((list_entry_stream(prefix, options), depth + 1));
+                    }
+                }
+                Some(Ok(ListEntry::Item(reference))) => {
+                    let matches = state.glob.as_deref().map_or(true, |glob| {
+                        let relative = relative_path(&state.root_path, reference.full_path());
+                        glob_matches(glob, &relative.chars().collect::<Vec<_>>())
+                    });
+                    if matches {
+                        let entry = WalkEntry {
+                            reference,
+                            depth,
+                            metadata: None,
+                        };
+                        return Some((Ok(entry), state));
+                    }
+                }
+            }
+        }
+    });
+
+    if !options.fetch_metadata {
+        return box_stream(entries);
+    }
+    box_stream(
+        entries
+            .map(|entry| async move {
+                let mut entry = entry?;
+                entry.metadata = Some(entry.reference.get_metadata().await?);
+                Ok(entry)
+            })
+            .buffered(options.metadata_concurrency.max(1)),
+    )
+}
+
+fn relative_path<'a>(root: &str, path: &'a str) -> &'a str {
+    path.strip_prefix(root).unwrap_or(path).trim_start_matches('/')
+}
+
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) if rest.first() == Some(&'*') => {
+            let rest = &rest[1..];
+            // `**/` also matches zero directories.
+            let rest_after_slash = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len())
+                .any(|skip| glob_matches(rest, &text[skip..]) || glob_matches(rest_after_slash, &text[skip..]))
+        }
+        Some(('*', rest)) => {
+            let segment_end = text.iter().position(|ch| *ch == '/').unwrap_or(text.len());
+            (0..=segment_end).any(|skip| glob_matches(rest, &text[skip..]))
+        }
+        Some(('?', rest)) => text.first().is_some_and(|ch| *ch != '/') && glob_matches(rest, &text[1..]),
+        Some((literal, rest)) => text.first() == Some(literal) && glob_matches(rest, &text[1..]),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,4 +382,102 @@ mod tests {
         assert_eq!(result.items.len(), 2);
         assert_eq!(result.next_page_token.as_deref(), Some("abc"));
     }
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        glob_matches(&pattern.chars().collect::<Vec<_>>(), &text.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn glob_matching_respects_segments() {
+        assert!(matches("*.jpg", "cat.jpg"));
+        assert!(!matches("*.jpg", "2024/cat.jpg"));
+        assert!(matches("**/*.jpg", "2024/cat.jpg"));
+        assert!(matches("**/*.jpg", "cat.jpg"));
+        assert!(matches("2024/??t.jpg", "2024/cat.jpg"));
+        assert!(!matches("**/*.png", "2024/cat.jpg"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn list_stream_fetches_pages_lazily() {
+        use crate::storage::testing::FakeStorageServer;
+
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = build_storage().await;
+        server.connect(&storage).unwrap();
+        for path in ["photos/a.jpg", "photos/b.jpg", "photos/c.jpg", "photos/2024/d.jpg"] {
+            server.insert_object("my-bucket", path, b"x".to_vec(), None);
+        }
+
+        let photos = storage.root_reference().unwrap().child("photos");
+        let mut entries = photos.list_stream(Some(ListOptions {
+            max_results: Some(2),
+            page_token: None,
+        }));
+        let first = entries.next().await.unwrap().unwrap();
+        assert!(first.is_prefix());
+        assert_eq!(first.reference().full_path(), "photos/2024");
+        let requests_after_first = server.request_count();
+
+        let rest: Vec<_> = entries
+            .map(|entry| entry.unwrap().reference().full_path().to_string())
+            .collect()
+            .await;
+        assert_eq!(rest, vec!["photos/a.jpg", "photos/b.jpg", "photos/c.jpg"]);
+        assert_eq!(requests_after_first, 1);
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn walk_recurses_with_glob_depth_and_metadata() {
+        use crate::storage::testing::FakeStorageServer;
+
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = build_storage().await;
+        server.connect(&storage).unwrap();
+        for path in [
+            "media/a.jpg",
+            "media/notes.txt",
+            "media/2024/b.jpg",
+            "media/2024/06/c.jpg",
+            "media/z.jpg",
+        ] {
+            server.insert_object("my-bucket", path, b"xyz".to_vec(), None);
+        }
+        let media = storage.root_reference().unwrap().child("media");
+
+        let all: Vec<_> = media
+            .walk(WalkOptions::default().with_page_size(1))
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.reference.full_path().to_string(), entry.depth)
+            })
+            .collect()
+            .await;
+        assert_eq!(
+            all,
+            vec![
+                ("media/2024/06/c.jpg".to_string(), 2),
+                ("media/2024/b.jpg".to_string(), 1),
+                ("media/a.jpg".to_string(), 0),
+                ("media/notes.txt".to_string(), 0),
+                ("media/z.jpg".to_string(), 0),
+            ]
+        );
+
+        let shallow: Vec<_> = media
+            .walk(
+                WalkOptions::default()
+                    .with_glob("**/*.jpg")
+                    .with_max_depth(1)
+                    .with_metadata(2),
+            )
+            .map(|entry| entry.unwrap())
+            .collect()
+            .await;
+        let paths: Vec<_> = shallow.iter().map(|entry| entry.reference.full_path()).collect();
+        assert_eq!(paths, vec!["media/2024/b.jpg", "media/a.jpg", "media/z.jpg"]);
+        assert!(shallow
+            .iter()
+            .all(|entry| entry.metadata.as_ref().and_then(|m| m.size_bytes()) == Some(3)));
+    }
 }
@@ -74,7 +74,7 @@ impl ObjectMetadata {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetMetadataRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
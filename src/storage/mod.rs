@@ -1,6 +1,8 @@
 #![doc = include_str!("README.md")]
 mod api;
+mod checksum;
 mod constants;
+mod download;
 mod error;
 mod list;
 mod location;
@@ -11,6 +13,10 @@ mod request;
 mod service;
 mod stream;
 mod string;
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "testing")))]
+pub mod testing;
+#[cfg(not(target_arch = "wasm32"))]
+mod transfer;
 mod upload;
 mod util;
 #[cfg(all(feature = "wasm-web", target_arch = "wasm32"))]
@@ -27,15 +33,25 @@ pub use constants::{
     DEFAULT_HOST, DEFAULT_MAX_OPERATION_RETRY_TIME_MS, DEFAULT_MAX_UPLOAD_RETRY_TIME_MS, DEFAULT_PROTOCOL, STORAGE_TYPE,
 };
 
+#[doc(inline)]
+pub use checksum::{IntegrityOptions, ObjectChecksums};
+
+#[doc(inline)]
+pub use download::DownloadProgress;
+
 #[doc(inline)]
 pub use error::{
-    app_deleted, internal_error, invalid_argument, invalid_default_bucket, invalid_root_operation, invalid_url,
-    no_default_bucket, no_download_url, unknown_error, unsupported_environment, StorageError, StorageErrorCode,
-    StorageResult,
+    app_check_failed, app_deleted, canceled, checksum_mismatch, internal_error, invalid_argument,
+    invalid_default_bucket, invalid_root_operation, invalid_url, no_default_bucket, no_download_url, object_changed,
+    precondition_failed, unauthenticated, unknown_error, unsupported_environment, upload_session_expired, StorageError,
+    StorageErrorCode, StorageResult,
 };
 
 #[doc(inline)]
-pub use list::{build_list_options, parse_list_result, ListOptions, ListResult};
+pub use list::{
+    build_list_options, parse_list_result, ListEntry, ListEntryStream, ListOptions, ListResult, WalkEntry, WalkOptions,
+    WalkStream, DEFAULT_WALK_METADATA_CONCURRENCY,
+};
 
 #[doc(inline)]
 pub use location::Location;
@@ -54,11 +70,12 @@ pub use reference::StreamingDownload;
 
 #[doc(inline)]
 pub use request::{
-    continue_resumable_upload_request, create_resumable_upload_request, delete_object_request, download_bytes_request,
+    cancel_resumable_upload_request, continue_resumable_upload_request, create_resumable_upload_request,
+    delete_object_request, download_bytes_request, download_range_request, download_url_for_token,
     download_url_request, get_metadata_request, get_resumable_upload_status_request, list_request,
-    multipart_upload_request, update_metadata_request, BackoffConfig, BackoffState, ErrorHandler, HttpClient,
-    RequestBody, RequestError, RequestInfo, ResponseHandler, ResponsePayload, ResumableUploadStatus,
-    RESUMABLE_UPLOAD_CHUNK_SIZE,
+    multipart_upload_request, update_download_tokens_request, update_metadata_request, BackoffConfig, BackoffState,
+    ErrorHandler, HttpClient, Preconditions, RequestBody, RequestError, RequestInfo, ResponseHandler, ResponsePayload,
+    ResumableUploadStatus, DOWNLOAD_TOKENS_METADATA_KEY, RESUMABLE_UPLOAD_CHUNK_SIZE,
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -74,8 +91,18 @@ pub use stream::UploadAsyncRead;
 #[doc(inline)]
 pub use string::{prepare_string_upload, PreparedString, StringFormat};
 
+#[cfg(not(target_arch = "wasm32"))]
 #[doc(inline)]
-pub use upload::{UploadProgress, UploadTask, UploadTaskState};
+pub use transfer::{
+    FileTransferResult, TransferManager, TransferOptions, TransferOutcome, TransferProgress, TransferReport,
+    DEFAULT_TRANSFER_CONCURRENCY,
+};
+
+#[doc(inline)]
+pub use upload::{
+    ResumableUploadSession, UploadProgress, UploadTask, UploadTaskHandle, UploadTaskSnapshot, UploadTaskSnapshotStream,
+    UploadTaskState,
+};
 
 #[doc(inline)]
 pub use util::{is_retry_status_code, is_url};
@@ -1,23 +1,49 @@
-use crate::storage::error::{internal_error, invalid_argument, invalid_root_operation, no_download_url, StorageResult};
-use crate::storage::list::{parse_list_result, ListOptions, ListResult};
+use crate::storage::checksum::{
+    reported_checksums, verify_checksums, ChecksumHasher, IntegrityOptions, ObjectChecksums,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::download;
+use crate::storage::download::DownloadProgress;
+use crate::storage::error::{
+    internal_error, invalid_argument, invalid_root_operation, no_download_url, upload_session_expired,
+    StorageErrorCode, StorageResult,
+};
+use crate::storage::list::{
+    list_entry_stream, parse_list_result, walk_stream, ListEntryStream, ListOptions, ListResult, WalkOptions,
+    WalkStream,
+};
 use crate::storage::location::Location;
 use crate::storage::metadata::serde::ObjectMetadata;
 use crate::storage::path::{child, last_component, parent};
+use crate::storage::request::builders::download_media_request;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::storage::request::StreamingResponse;
 use crate::storage::request::{
     continue_resumable_upload_request, create_resumable_upload_request, delete_object_request, download_bytes_request,
-    download_url_request, get_metadata_request, list_request, multipart_upload_request, update_metadata_request,
-    RESUMABLE_UPLOAD_CHUNK_SIZE,
+    download_range_request, download_url_for_token, download_url_request, get_metadata_request,
+    get_resumable_upload_status_request, list_request, multipart_upload_request, update_download_tokens_request,
+    update_metadata_request, Preconditions, RequestInfo, RESUMABLE_UPLOAD_CHUNK_SIZE,
 };
 use crate::storage::service::FirebaseStorageImpl;
 use crate::storage::stream::UploadAsyncRead;
 use crate::storage::string::{prepare_string_upload, StringFormat};
-use crate::storage::upload::{UploadProgress, UploadTask};
+use crate::storage::upload::{ResumableUploadSession, UploadProgress, UploadTask};
+use crate::storage::util::generate_download_token;
 #[cfg(all(feature = "wasm-web", target_arch = "wasm32"))]
 use crate::storage::wasm;
 use crate::storage::{SettableMetadata, UploadMetadata};
+use futures::io::AsyncSeek;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::io::SeekFrom;
+use std::ops::Range;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::AsyncWrite;
+
+/// Read-modify-write attempts for download token updates that lose a precondition race.
+const DOWNLOAD_TOKEN_UPDATE_ATTEMPTS: usize = 3;
 
 #[derive(Clone)]
 pub struct StorageReference {
@@ -107,6 +133,22 @@ impl StorageReference {
         parse_list_result(&self.storage, self.location.bucket(), json)
     }
 
+    /// Streams the prefixes and items under this reference, fetching pages only as the stream is
+    /// polled.
+    ///
+    /// `options.max_results` sets the page size and `options.page_token` resumes from a previous
+    /// page. Each page yields its prefixes before its items; the stream ends after the first error.
+    pub fn list_stream(&self, options: Option<ListOptions>) -> ListEntryStream {
+        list_entry_stream(self.clone(), options.unwrap_or_default())
+    }
+
+    /// Recursively walks every object beneath this reference depth-first.
+    ///
+    /// See [`WalkOptions`] for glob filtering, depth limits and optional metadata lookups.
+    pub fn walk(&self, options: WalkOptions) -> WalkStream {
+        walk_stream(self.clone(), options)
+    }
+
     /// Recursively lists all objects beneath this reference.
     ///
     /// This mirrors the Firebase Web SDK `listAll` helper and repeatedly calls [`list`](Self::list)
@@ -139,8 +181,24 @@ impl StorageReference {
     /// Returns [`storage/invalid-root-operation`](crate::storage::StorageErrorCode::InvalidRootOperation)
     /// when invoked on the bucket root.
     pub async fn update_metadata(&self, metadata: SettableMetadata) -> StorageResult<ObjectMetadata> {
+        self.update_metadata_with_preconditions(metadata, &Preconditions::default())
+            .await
+    }
+
+    /// Updates metadata only while `preconditions` hold, e.g. [`Preconditions::matching`] the
+    /// metadata the change was computed from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`storage/precondition-failed`](crate::storage::StorageErrorCode::PreconditionFailed)
+    /// when the object changed since the preconditions were captured.
+    pub async fn update_metadata_with_preconditions(
+        &self,
+        metadata: SettableMetadata,
+        preconditions: &Preconditions,
+    ) -> StorageResult<ObjectMetadata> {
         self.ensure_not_root("update_metadata")?;
-        let request = update_metadata_request(&self.storage, &self.location, metadata);
+        let request = update_metadata_request(&self.storage, &self.location, metadata, preconditions);
         let json = self.storage.run_request(request).await?;
         Ok(ObjectMetadata::from_value(json))
     }
@@ -150,10 +208,27 @@ impl StorageReference {
     /// The optional `max_download_size_bytes` mirrors the Web SDK behaviour: when supplied the
     /// backend is asked for at most that many bytes and the response is truncated if the server
     /// ignores the range header.
+    ///
+    /// Complete objects are checked against the checksums the server reports in the `x-goog-hash`
+    /// response header according to the storage's [`IntegrityOptions`]. Responses without the
+    /// header, and partial responses to a size-limited request, are not verified unless
+    /// [`IntegrityOptions::metadata_fallback`] is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns `storage/checksum-mismatch` when the downloaded bytes do not match.
     pub async fn get_bytes(&self, max_download_size_bytes: Option<u64>) -> StorageResult<Vec<u8>> {
         self.ensure_not_root("get_bytes")?;
-        let request = download_bytes_request(&self.storage, &self.location, max_download_size_bytes);
-        let mut bytes = self.storage.run_request(request).await?;
+        let request = download_media_request(&self.storage, &self.location, max_download_size_bytes);
+        let payload = self.storage.run_request(request).await?;
+        let mut bytes = payload.body;
+        if payload.status.as_u16() == 200 {
+            if let Some(expected) = self.expected_checksums(&payload.headers).await {
+                let options = self.storage.integrity_options();
+                let computed = ObjectChecksums::compute(&bytes, options.verify_md5);
+                verify_checksums(&options, &computed, &expected)?;
+            }
+        }
 
         if let Some(limit) = max_download_size_bytes {
             let limit_usize = usize::try_from(limit)
@@ -172,6 +247,11 @@ impl StorageReference {
     /// Returns a [`StreamingResponse`] whose [`StorageByteStream`] can be consumed using the
     /// standard `tokio::io::AsyncRead` interfaces.
     ///
+    /// A complete object is checked against the server-reported checksums like
+    /// [`get_bytes`](Self::get_bytes): on a mismatch the final read fails with
+    /// [`std::io::ErrorKind::InvalidData`] wrapping the `storage/checksum-mismatch` error. Partial
+    /// responses to a size-limited request cannot be verified.
+    ///
     /// # Examples
     ///
     /// ```rust,ignore
@@ -190,7 +270,106 @@ impl StorageReference {
     pub async fn get_stream(&self, max_download_size_bytes: Option<u64>) -> StorageResult<StreamingResponse> {
         self.ensure_not_root("get_stream")?;
         let request = download_bytes_request(&self.storage, &self.location, max_download_size_bytes);
-        self.storage.run_streaming_request(request).await
+        let response = self.storage.run_streaming_request(request).await?;
+        if response.status.as_u16() != 200 {
+            return Ok(response);
+        }
+        match self.expected_checksums(&response.headers).await {
+            Some(expected) => Ok(download::verify_stream(response, self.storage.integrity_options(), expected)),
+            None => Ok(response),
+        }
+    }
+
+    /// Checksums a full-object download should match: the `x-goog-hash` response header, or the
+    /// object metadata when the response carries none and the metadata fallback is enabled.
+    /// `None` when verification is disabled, no checksums are available, or the object changed
+    /// since it was served.
+    async fn expected_checksums(&self, headers: &HashMap<String, String>) -> Option<ObjectMetadata> {
+        let options = self.storage.integrity_options();
+        if !options.verify_crc32c && !options.verify_md5 {
+            return None;
+        }
+        if let Some(reported) = reported_checksums(headers, self.full_path()) {
+            return Some(reported);
+        }
+        if !options.metadata_fallback {
+            return None;
+        }
+        let metadata = self.get_metadata().await.ok()?;
+        let served_generation = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("x-goog-generation"))
+            .map(|(_, value)| value.as_str());
+        if served_generation.is_some() && served_generation != metadata.generation.as_deref() {
+            return None;
+        }
+        Some(metadata)
+    }
+
+    /// Downloads the bytes in `range` using an HTTP `Range` request.
+    ///
+    /// Checksums cover whole objects, so the bytes of a range are not verified.
+    /// # Errors
+    ///
+    /// Returns `storage/invalid-argument` when the range is empty.
+    pub async fn get_range(&self, range: Range<u64>) -> StorageResult<Vec<u8>> {
+        self.ensure_not_root("get_range")?;
+        if range.start >= range.end {
+            return Err(invalid_argument(format!("invalid byte range {}..{}", range.start, range.end)));
+        }
+        let request = download_range_request(&self.storage, &self.location, range.start, Some(range.end));
+        self.storage.run_request(request).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Streams the object into `writer`, resuming from the last written byte after transient failures.
+    ///
+    /// Returns the object metadata captured before the download started. The download aborts with
+    /// `storage/object-changed` if the object is replaced while it is being read.
+    pub async fn download_to_writer<W>(&self, writer: W) -> StorageResult<ObjectMetadata>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.download_to_writer_with_progress(writer, |_| {}).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Streams the object into `writer` while reporting download progress.
+    pub async fn download_to_writer_with_progress<W, F>(
+        &self,
+        mut writer: W,
+        mut progress: F,
+    ) -> StorageResult<ObjectMetadata>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(DownloadProgress),
+    {
+        self.ensure_not_root("download_to_writer")?;
+        download::download_to_writer(self, &mut writer, &mut progress).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Downloads the object into the file at `path`, creating or truncating it.
+    pub async fn download_to_file(&self, path: impl AsRef<Path>) -> StorageResult<ObjectMetadata> {
+        self.download_to_file_with_progress(path, |_| {}).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Downloads the object into the file at `path` while reporting download progress.
+    pub async fn download_to_file_with_progress<F>(
+        &self,
+        path: impl AsRef<Path>,
+        progress: F,
+    ) -> StorageResult<ObjectMetadata>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        self.ensure_not_root("download_to_file")?;
+        let path = path.as_ref();
+        let file = tokio::fs::File::create(path)
+            .await
+            .map_err(|err| internal_error(format!("failed to create {}: {err}", path.display())))?;
+        self.download_to_writer_with_progress(file, progress).await
     }
 
     /// Returns a signed download URL for the object.
@@ -201,10 +380,96 @@ impl StorageReference {
         url.ok_or_else(no_download_url)
     }
 
+    /// Returns the download tokens currently attached to the object.
+    pub async fn list_download_tokens(&self) -> StorageResult<Vec<String>> {
+        let metadata = self.get_metadata().await?;
+        Ok(metadata.download_tokens().map(<[String]>::to_vec).unwrap_or_default())
+    }
+
+    /// Issues a new download token for the object and returns it.
+    ///
+    /// Existing tokens stay valid. The metadata update is guarded by generation preconditions and
+    /// retried when another writer changed the object in between.
+    pub async fn create_download_token(&self) -> StorageResult<String> {
+        self.ensure_not_root("create_download_token")?;
+        let token = generate_download_token();
+        self.update_download_tokens(|tokens| {
+            tokens.push(token.clone());
+            Ok(())
+        })
+        .await?;
+        Ok(token)
+    }
+
+    /// Revokes `token`, invalidating every download URL that embeds it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `storage/invalid-argument` when the token is not attached to the object.
+    pub async fn revoke_download_token(&self, token: &str) -> StorageResult<()> {
+        self.ensure_not_root("revoke_download_token")?;
+        self.update_download_tokens(|tokens| {
+            let before = tokens.len();
+            tokens.retain(|candidate| candidate != token);
+            if tokens.len() == before {
+                return Err(invalid_argument(format!(
+                    "download token is not registered for '{}'",
+                    self.full_path()
+                )));
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the download URL that embeds `token`, which must be attached to the object.
+    pub async fn get_download_url_with_token(&self, token: &str) -> StorageResult<String> {
+        self.ensure_not_root("get_download_url_with_token")?;
+        let tokens = self.list_download_tokens().await?;
+        if !tokens.iter().any(|candidate| candidate == token) {
+            return Err(invalid_argument(format!(
+                "download token is not registered for '{}'",
+                self.full_path()
+            )));
+        }
+        Ok(download_url_for_token(&self.storage, &self.location, token))
+    }
+
+    async fn update_download_tokens<F>(&self, mut update: F) -> StorageResult<ObjectMetadata>
+    where
+        F: FnMut(&mut Vec<String>) -> StorageResult<()>,
+    {
+        let mut attempt = 1;
+        loop {
+            let current = self.get_metadata().await?;
+            let mut tokens = current.download_tokens().map(<[String]>::to_vec).unwrap_or_default();
+            update(&mut tokens)?;
+
+            let preconditions = Preconditions::matching(&current);
+            let request = update_download_tokens_request(&self.storage, &self.location, &tokens, &preconditions);
+            match self.storage.run_request(request).await {
+                Ok(json) => return Ok(ObjectMetadata::from_value(json)),
+                Err(err)
+                    if err.code == StorageErrorCode::PreconditionFailed && attempt < DOWNLOAD_TOKEN_UPDATE_ATTEMPTS =>
+                {
+                    attempt += 1
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
     /// Permanently deletes the object referenced by this path.
     pub async fn delete_object(&self) -> StorageResult<()> {
+        self.delete_object_with_preconditions(&Preconditions::default()).await
+    }
+
+    /// Deletes the object only while `preconditions` hold, failing with
+    /// [`storage/precondition-failed`](crate::storage::StorageErrorCode::PreconditionFailed) otherwise.
+    pub async fn delete_object_with_preconditions(&self, preconditions: &Preconditions) -> StorageResult<()> {
         self.ensure_not_root("delete_object")?;
-        let request = delete_object_request(&self.storage, &self.location);
+        let request = delete_object_request(&self.storage, &self.location, preconditions);
         self.storage.run_request(request).await
     }
 
@@ -233,10 +498,42 @@ impl StorageReference {
         &self,
         data: impl Into<Vec<u8>>,
         metadata: Option<UploadMetadata>,
+    ) -> StorageResult<ObjectMetadata> {
+        self.upload_bytes_with_preconditions(data, metadata, &Preconditions::default())
+            .await
+    }
+
+    /// Uploads a small blob only while `preconditions` hold.
+    ///
+    /// Use `Preconditions::new().if_generation_match(0)` to create the object only if it does not
+    /// exist yet, or [`Preconditions::matching`] to overwrite exactly the version that was read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`storage/precondition-failed`](crate::storage::StorageErrorCode::PreconditionFailed)
+    /// when the stored object does not satisfy the preconditions.
+    pub async fn upload_bytes_with_preconditions(
+        &self,
+        data: impl Into<Vec<u8>>,
+        metadata: Option<UploadMetadata>,
+        preconditions: &Preconditions,
     ) -> StorageResult<ObjectMetadata> {
         self.ensure_not_root("upload_bytes")?;
-        let request = multipart_upload_request(&self.storage, &self.location, data.into(), metadata);
-        self.storage.run_upload_request(request).await
+        let data = data.into();
+        let options = self.storage.integrity_options();
+        let checksums = options
+            .is_enabled()
+            .then(|| ObjectChecksums::compute(&data, options.verify_md5));
+        let metadata = match &checksums {
+            Some(checksums) if options.send_client_hashes => Some(checksums.apply_to(metadata)),
+            _ => metadata,
+        };
+        let request = multipart_upload_request(&self.storage, &self.location, data, metadata, preconditions);
+        let uploaded = self.storage.run_upload_request(request).await?;
+        if let Some(checksums) = checksums {
+            verify_checksums(&options, &checksums, &uploaded)?;
+        }
+        Ok(uploaded)
     }
 
     /// Creates a resumable upload task that can be advanced chunk by chunk or run to completion.
@@ -340,7 +637,7 @@ impl StorageReference {
     /// Streams data from an [`AsyncRead`](futures::io::AsyncRead) source while reporting chunk progress.
     pub async fn upload_reader_resumable_with_progress<R, F>(
         &self,
-        mut reader: R,
+        reader: R,
         total_size: u64,
         metadata: Option<UploadMetadata>,
         mut progress: F,
@@ -349,28 +646,146 @@ impl StorageReference {
         R: UploadAsyncRead,
         F: FnMut(UploadProgress),
     {
-        use futures::io::AsyncReadExt;
-
         self.ensure_not_root("upload_reader_resumable")?;
 
         let storage = self.storage();
         let request = create_resumable_upload_request(&storage, self.location(), metadata, total_size);
         let upload_url = storage.run_upload_request(request).await?;
+        self.upload_reader_from_offset(&upload_url, reader, 0, total_size, &mut progress)
+            .await
+    }
+
+    /// Starts a resumable upload session without sending any data.
+    ///
+    /// The returned [`ResumableUploadSession`] can be persisted before the upload begins and then
+    /// driven with [`resume_upload`](Self::resume_upload), so an interrupted upload never has to
+    /// restart from byte zero.
+    pub async fn create_upload_session(
+        &self,
+        total_size: u64,
+        metadata: Option<UploadMetadata>,
+    ) -> StorageResult<ResumableUploadSession> {
+        self.ensure_not_root("create_upload_session")?;
+        let request = create_resumable_upload_request(&self.storage, &self.location, metadata.clone(), total_size);
+        let session_url = self.storage.run_upload_request(request).await?;
+        Ok(ResumableUploadSession {
+            session_url,
+            bucket: self.bucket().to_string(),
+            full_path: self.full_path().to_string(),
+            metadata,
+            total_bytes: total_size,
+            offset: 0,
+        })
+    }
+
+    /// Continues a previously exported resumable upload session from `reader`.
+    ///
+    /// The session status is queried to find the committed offset, `reader` is seeked to that
+    /// position, and the remaining bytes are uploaded. Expired sessions fail with
+    /// `storage/upload-session-expired`.
+    pub async fn resume_upload<R>(&self, session: &ResumableUploadSession, reader: R) -> StorageResult<ObjectMetadata>
+    where
+        R: UploadAsyncRead + AsyncSeek,
+    {
+        self.resume_upload_with_progress(session, reader, |_| {}).await
+    }
+
+    /// Continues a resumable upload session while reporting chunk progress.
+    pub async fn resume_upload_with_progress<R, F>(
+        &self,
+        session: &ResumableUploadSession,
+        mut reader: R,
+        mut progress: F,
+    ) -> StorageResult<ObjectMetadata>
+    where
+        R: UploadAsyncRead + AsyncSeek,
+        F: FnMut(UploadProgress),
+    {
+        use futures::io::AsyncSeekExt;
+
+        self.ensure_not_root("resume_upload")?;
+        if session.bucket != self.bucket() || session.full_path != self.full_path() {
+            return Err(invalid_argument(format!(
+                "upload session targets gs://{}/{}, not {}",
+                session.bucket,
+                session.full_path,
+                self.to_gs_url()
+            )));
+        }
+
+        let request = get_resumable_upload_status_request(
+            &self.storage,
+            &self.location,
+            &session.session_url,
+            session.total_bytes,
+        );
+        let status = match self.storage.run_upload_request(request).await {
+            Ok(status) => status,
+            Err(err) if matches!(err.status, Some(404 | 410)) => {
+                return Err(upload_session_expired(&session.session_url));
+            }
+            Err(err) => return Err(err),
+        };
+
+        if status.finalized {
+            progress(UploadProgress::new(session.total_bytes, session.total_bytes));
+            return self.get_metadata().await;
+        }
+        if status.current > session.total_bytes {
+            return Err(internal_error("upload session reports more bytes than the declared total"));
+        }
+
+        reader
+            .seek(SeekFrom::Start(status.current))
+            .await
+            .map_err(|err| internal_error(format!("failed to seek upload source: {err}")))?;
+        progress(UploadProgress::new(status.current, session.total_bytes));
+        self.upload_reader_from_offset(&session.session_url, reader, status.current, session.total_bytes, &mut progress)
+            .await
+    }
+
+    async fn upload_reader_from_offset<R, F>(
+        &self,
+        upload_url: &str,
+        mut reader: R,
+        start_offset: u64,
+        total_size: u64,
+        progress: &mut F,
+    ) -> StorageResult<ObjectMetadata>
+    where
+        R: UploadAsyncRead,
+        F: FnMut(UploadProgress),
+    {
+        use futures::io::AsyncReadExt;
+
+        let storage = self.storage();
+        let options = storage.integrity_options();
+        // Only a session uploaded from byte zero has seen every byte needed for a whole-object hash.
+        let mut hasher = if start_offset == 0 {
+            ChecksumHasher::for_options(&options)
+        } else {
+            None
+        };
 
         if total_size == 0 {
             let request =
-                continue_resumable_upload_request(&storage, self.location(), &upload_url, 0, 0, Vec::new(), true);
+                continue_resumable_upload_request(&storage, self.location(), upload_url, 0, 0, Vec::new(), true);
+            let checksums = hasher.take().map(ChecksumHasher::finish);
+            let request = with_client_hashes(request, &options, checksums.clone());
             let status = storage.run_upload_request(request).await?;
             progress(UploadProgress::new(0, 0));
             let metadata = status
                 .metadata
                 .ok_or_else(|| internal_error("resumable upload completed without metadata"))?;
+            if let Some(checksums) = checksums {
+                verify_checksums(&options, &checksums, &metadata)?;
+            }
             return Ok(metadata);
         }
 
         let chunk_size = RESUMABLE_UPLOAD_CHUNK_SIZE as usize;
         let mut buffer = vec![0u8; chunk_size];
-        let mut offset = 0u64;
+        let mut offset = start_offset;
 
         while offset < total_size {
             let remaining = (total_size - offset) as usize;
@@ -394,16 +809,25 @@ impl StorageReference {
 
             let finalize = offset + read_total as u64 == total_size;
             let chunk = buffer[..read_total].to_vec();
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(&chunk);
+            }
 
             let request = continue_resumable_upload_request(
                 &storage,
                 self.location(),
-                &upload_url,
+                upload_url,
                 offset,
                 total_size,
                 chunk,
                 finalize,
             );
+            let checksums = if finalize {
+                hasher.take().map(ChecksumHasher::finish)
+            } else {
+                None
+            };
+            let request = with_client_hashes(request, &options, checksums.clone());
             let status = storage.run_upload_request(request).await?;
             offset = status.current;
             progress(UploadProgress::new(offset, total_size));
@@ -412,6 +836,9 @@ impl StorageReference {
                 let metadata = status
                     .metadata
                     .ok_or_else(|| internal_error("resumable upload completed without metadata"))?;
+                if let Some(checksums) = checksums {
+                    verify_checksums(&options, &checksums, &metadata)?;
+                }
                 return Ok(metadata);
             }
         }
@@ -419,7 +846,7 @@ impl StorageReference {
         let request = continue_resumable_upload_request(
             &storage,
             self.location(),
-            &upload_url,
+            upload_url,
             offset,
             total_size,
             Vec::new(),
@@ -434,6 +861,20 @@ impl StorageReference {
     }
 }
 
+/// Attaches an `X-Goog-Hash` header to the finalizing request when client hashes are enabled.
+fn with_client_hashes<T>(
+    mut request: RequestInfo<T>,
+    options: &IntegrityOptions,
+    checksums: Option<ObjectChecksums>,
+) -> RequestInfo<T> {
+    if let Some(checksums) = checksums.filter(|_| options.send_client_hashes) {
+        request
+            .headers
+            .insert("X-Goog-Hash".to_string(), checksums.header_value());
+    }
+    request
+}
+
 fn merge_metadata(metadata: Option<UploadMetadata>, inferred_content_type: Option<String>) -> Option<UploadMetadata> {
     match (metadata, inferred_content_type) {
         (Some(mut metadata), Some(content_type)) => {
@@ -508,4 +949,145 @@ mod tests {
         let merged = merge_metadata(None, Some("text/plain".to_string())).unwrap();
         assert_eq!(merged.content_type.as_deref(), Some("text/plain"));
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_tokens_can_be_created_and_revoked() {
+        use crate::storage::testing::FakeStorageServer;
+
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = build_storage().await;
+        server.connect(&storage).unwrap();
+        server.insert_object("my-bucket", "shared/report.pdf", b"pdf".to_vec(), None);
+        let reference = storage.root_reference().unwrap().child("shared/report.pdf");
+
+        let original = reference.list_download_tokens().await.unwrap();
+        assert_eq!(original.len(), 1);
+        let created = reference.create_download_token().await.unwrap();
+        assert_eq!(
+            reference.list_download_tokens().await.unwrap(),
+            vec![original[0].clone(), created.clone()]
+        );
+
+        let url = reference.get_download_url_with_token(&created).await.unwrap();
+        assert!(url.ends_with(&format!("alt=media&token={created}")));
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+
+        reference.revoke_download_token(&original[0]).await.unwrap();
+        assert_eq!(reference.list_download_tokens().await.unwrap(), vec![created.clone()]);
+        let revoked_url = download_url_for_token(&storage, reference.location(), &original[0]);
+        assert_eq!(reqwest::get(&revoked_url).await.unwrap().status().as_u16(), 403);
+
+        let err = reference.revoke_download_token(&original[0]).await.unwrap_err();
+        assert_eq!(err.code, crate::storage::StorageErrorCode::InvalidArgument);
+        assert!(reference.get_download_url_with_token("unknown").await.is_err());
+        assert_eq!(
+            server
+                .object_metadata("my-bucket", "shared/report.pdf")
+                .unwrap()
+                .metageneration
+                .as_deref(),
+            Some("3")
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_token_update_retries_after_concurrent_edit() {
+        use crate::storage::testing::FakeStorageServer;
+
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = build_storage().await;
+        server.connect(&storage).unwrap();
+        server.insert_object("my-bucket", "shared/report.pdf", b"pdf".to_vec(), None);
+        let reference = storage.root_reference().unwrap().child("shared/report.pdf");
+        let original = reference.list_download_tokens().await.unwrap();
+
+        // Another client adds a token between our read and our patch, so the first patch fails its
+        // metageneration precondition and the update is recomputed from fresh metadata.
+        let mut calls = 0;
+        let updated = reference
+            .update_download_tokens(|tokens| {
+                calls += 1;
+                if calls == 1 {
+                    assert!(server.add_download_token("my-bucket", "shared/report.pdf", "concurrent"));
+                }
+                tokens.push("ours".to_string());
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(calls, 2);
+        let expected = vec![original[0].clone(), "concurrent".to_string(), "ours".to_string()];
+        assert_eq!(updated.download_tokens().map(<[String]>::to_vec), Some(expected.clone()));
+        assert_eq!(reference.list_download_tokens().await.unwrap(), expected);
+        assert_eq!(
+            server
+                .object_metadata("my-bucket", "shared/report.pdf")
+                .unwrap()
+                .metageneration
+                .as_deref(),
+            Some("3")
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "current_thread")]
+    async fn preconditions_guard_concurrent_writers() {
+        use crate::storage::testing::FakeStorageServer;
+        use crate::storage::StorageErrorCode;
+
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = build_storage().await;
+        server.connect(&storage).unwrap();
+        let reference = storage.root_reference().unwrap().child("notes/todo.txt");
+
+        let create_only = Preconditions::new().if_generation_match(0);
+        let created = reference
+            .upload_bytes_with_preconditions(b"v1".to_vec(), None, &create_only)
+            .await
+            .unwrap();
+        let err = reference
+            .upload_bytes_with_preconditions(b"v1-again".to_vec(), None, &create_only)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::PreconditionFailed);
+        assert_eq!(err.status, Some(412));
+
+        // A second editor overwrites the object after the first one read it.
+        let stale = Preconditions::matching(&created);
+        reference.upload_bytes(b"v2".to_vec(), None).await.unwrap();
+
+        let mut update = SettableMetadata::new();
+        update.content_type = Some("text/plain".into());
+        let err = reference
+            .update_metadata_with_preconditions(update.clone(), &stale)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::PreconditionFailed);
+        let err = reference.delete_object_with_preconditions(&stale).await.unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::PreconditionFailed);
+        assert_eq!(server.object_data("my-bucket", "notes/todo.txt"), Some(b"v2".to_vec()));
+
+        let current = reference.get_metadata().await.unwrap();
+        let updated = reference
+            .update_metadata_with_preconditions(update, &Preconditions::matching(&current))
+            .await
+            .unwrap();
+        assert_eq!(updated.content_type.as_deref(), Some("text/plain"));
+
+        let not_current = Preconditions::new().if_generation_not_match(current.generation.unwrap().parse().unwrap());
+        let err = reference
+            .delete_object_with_preconditions(&not_current)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::PreconditionFailed);
+        reference
+            .delete_object_with_preconditions(&Preconditions::matching(&updated))
+            .await
+            .unwrap();
+        assert!(server.object_data("my-bucket", "notes/todo.txt").is_none());
+    }
 }
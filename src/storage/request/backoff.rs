@@ -69,6 +69,13 @@ impl BackoffState {
         self.attempt < self.config.max_attempts && self.has_time_remaining()
     }
 
+    /// Starts over with a fresh attempt count and time budget, e.g. once a retried operation
+    /// has made progress.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+        self.deadline = Instant::now() + self.config.total_timeout;
+    }
+
     pub fn next_delay(&mut self) -> Duration {
         if self.attempt == 0 {
             self.attempt += 1;
@@ -114,4 +121,14 @@ mod tests {
         let d2 = backoff.next_delay();
         assert!(d2 >= d1);
     }
+
+    #[test]
+    fn reset_restarts_attempts() {
+        let mut backoff = BackoffState::new(BackoffConfig::standard_operation());
+        backoff.next_delay();
+        backoff.next_delay();
+        backoff.reset();
+        assert_eq!(backoff.attempts(), 0);
+        assert_eq!(backoff.next_delay(), Duration::from_millis(0));
+    }
 }
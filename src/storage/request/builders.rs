@@ -7,14 +7,63 @@ use reqwest::Method;
 use serde_json::{Map, Value};
 use url::form_urlencoded;
 
-use crate::storage::error::internal_error;
+use crate::storage::error::{internal_error, precondition_failed};
 use crate::storage::list::{build_list_options, ListOptions};
 use crate::storage::location::Location;
 use crate::storage::metadata::serde::ObjectMetadata;
 use crate::storage::service::FirebaseStorageImpl;
 use crate::storage::{SetMetadataRequest, UploadMetadata};
 
-use super::{RequestBody, RequestInfo, ResponseHandler};
+use super::{ErrorHandler, RequestBody, RequestInfo, ResponseHandler, ResponsePayload};
+
+/// Generation and metageneration conditions attached to a mutating request.
+///
+/// The backend evaluates them atomically and answers `412`, surfaced as
+/// [`storage/precondition-failed`](crate::storage::StorageErrorCode::PreconditionFailed), when one
+/// does not hold. A generation of `0` matches only when the object does not exist yet.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Preconditions {
+    pub if_generation_match: Option<u64>,
+    pub if_generation_not_match: Option<u64>,
+    pub if_metageneration_match: Option<u64>,
+}
+
+impl Preconditions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Conditions that only hold while the object still has the generation and metageneration
+    /// reported by `metadata`.
+    pub fn matching(metadata: &ObjectMetadata) -> Self {
+        Self {
+            if_generation_match: metadata.generation.as_deref().and_then(|value| value.parse().ok()),
+            if_generation_not_match: None,
+            if_metageneration_match: metadata.metageneration.as_deref().and_then(|value| value.parse().ok()),
+        }
+    }
+
+    pub fn if_generation_match(mut self, generation: u64) -> Self {
+        self.if_generation_match = Some(generation);
+        self
+    }
+
+    pub fn if_generation_not_match(mut self, generation: u64) -> Self {
+        self.if_generation_not_match = Some(generation);
+        self
+    }
+
+    pub fn if_metageneration_match(mut self, metageneration: u64) -> Self {
+        self.if_metageneration_match = Some(metageneration);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.if_generation_match.is_none()
+            && self.if_generation_not_match.is_none()
+            && self.if_metageneration_match.is_none()
+    }
+}
 
 pub fn get_metadata_request(storage: &FirebaseStorageImpl, location: &Location) -> RequestInfo<Value> {
     let base_url = format!("{}/v0{}", storage.host(), location.full_server_url());
@@ -33,6 +82,7 @@ pub fn update_metadata_request(
     storage: &FirebaseStorageImpl,
     location: &Location,
     metadata: SetMetadataRequest,
+    preconditions: &Preconditions,
 ) -> RequestInfo<Value> {
     let base_url = format!("{}/v0{}", storage.host(), location.full_server_url());
     let timeout = Duration::from_millis(storage.max_operation_retry_time());
@@ -41,12 +91,13 @@ pub fn update_metadata_request(
         serde_json::from_slice(&payload.body).map_err(|err| internal_error(format!("failed to parse metadata: {err}")))
     });
 
-    RequestInfo::new(base_url, Method::PATCH, timeout, handler)
+    let request = RequestInfo::new(base_url, Method::PATCH, timeout, handler)
         .with_query_param("alt", "json")
         .with_headers(default_json_headers())
         .with_body(RequestBody::Text(
             serde_json::to_string(&metadata).expect("metadata serialization should never fail"),
-        ))
+        ));
+    apply_preconditions(request, location, preconditions)
 }
 
 pub fn list_request(storage: &FirebaseStorageImpl, location: &Location, options: &ListOptions) -> RequestInfo<Value> {
@@ -73,11 +124,28 @@ pub fn download_bytes_request(
     location: &Location,
     max_download_size_bytes: Option<u64>,
 ) -> RequestInfo<Vec<u8>> {
+    media_request(storage, location, max_download_size_bytes, Arc::new(|payload| Ok(payload.body)))
+}
+
+/// Like [`download_bytes_request`] but keeps the status and headers, which carry the
+/// `x-goog-hash` checksums of the object.
+pub(crate) fn download_media_request(
+    storage: &FirebaseStorageImpl,
+    location: &Location,
+    max_download_size_bytes: Option<u64>,
+) -> RequestInfo<ResponsePayload> {
+    media_request(storage, location, max_download_size_bytes, Arc::new(Ok))
+}
+
+fn media_request<O>(
+    storage: &FirebaseStorageImpl,
+    location: &Location,
+    max_download_size_bytes: Option<u64>,
+    handler: ResponseHandler<O>,
+) -> RequestInfo<O> {
     let base_url = format!("{}/v0{}", storage.host(), location.full_server_url());
     let timeout = Duration::from_millis(storage.max_operation_retry_time());
 
-    let handler: ResponseHandler<Vec<u8>> = Arc::new(|payload| Ok(payload.body));
-
     let mut request = RequestInfo::new(base_url, Method::GET, timeout, handler);
     request.query_params.insert("alt".to_string(), "media".to_string());
 
@@ -89,12 +157,45 @@ pub fn download_bytes_request(
     request
 }
 
+/// Builds a media download limited to `start..end` (or `start..` when `end` is `None`).
+///
+/// Servers that ignore the `Range` header answer with the full object; the handler trims such
+/// responses so callers always receive exactly the requested bytes.
+pub fn download_range_request(
+    storage: &FirebaseStorageImpl,
+    location: &Location,
+    start: u64,
+    end: Option<u64>,
+) -> RequestInfo<Vec<u8>> {
+    let base_url = format!("{}/v0{}", storage.host(), location.full_server_url());
+    let timeout = Duration::from_millis(storage.max_operation_retry_time());
+
+    let handler: ResponseHandler<Vec<u8>> = Arc::new(move |payload| {
+        if payload.status.as_u16() == 206 {
+            return Ok(payload.body);
+        }
+        let len = payload.body.len() as u64;
+        let from = start.min(len) as usize;
+        let to = end.unwrap_or(len).min(len) as usize;
+        Ok(payload.body[from..to].to_vec())
+    });
+
+    let range = match end {
+        Some(end) => format!("bytes={start}-{}", end.saturating_sub(1)),
+        None => format!("bytes={start}-"),
+    };
+
+    let mut request = RequestInfo::new(base_url, Method::GET, timeout, handler).with_query_param("alt", "media");
+    request.headers.insert("Range".to_string(), range);
+    request.success_codes = vec![200, 206];
+    request
+}
+
 pub fn download_url_request(storage: &FirebaseStorageImpl, location: &Location) -> RequestInfo<Option<String>> {
-    let url_part = location.full_server_url();
-    let base_url = format!("{}/v0{}", storage.host(), url_part.clone());
+    let base_url = format!("{}/v0{}", storage.host(), location.full_server_url());
     let timeout = Duration::from_millis(storage.max_operation_retry_time());
 
-    let download_base = format!("{}://{}/v0{}", storage.protocol(), storage.host(), url_part);
+    let download_base = download_base_url(storage, location);
 
     let handler: ResponseHandler<Option<String>> = Arc::new(move |payload| {
         let value: Value = serde_json::from_slice(&payload.body)
@@ -106,8 +207,7 @@ pub fn download_url_request(storage: &FirebaseStorageImpl, location: &Location)
             .filter(|s| !s.is_empty())
         {
             if let Some(token) = tokens.split(',').find(|segment| !segment.is_empty()) {
-                let encoded_token: String = form_urlencoded::byte_serialize(token.as_bytes()).collect();
-                return Ok(Some(format!("{download_base}?alt=media&token={encoded_token}")));
+                return Ok(Some(format_download_url(&download_base, token)));
             }
         }
 
@@ -119,7 +219,31 @@ pub fn download_url_request(storage: &FirebaseStorageImpl, location: &Location)
     request
 }
 
-pub fn delete_object_request(storage: &FirebaseStorageImpl, location: &Location) -> RequestInfo<()> {
+/// Formats the public download URL for `location` that embeds `token`.
+pub fn download_url_for_token(storage: &FirebaseStorageImpl, location: &Location, token: &str) -> String {
+    format_download_url(&download_base_url(storage, location), token)
+}
+
+/// Replaces the download tokens of an object.
+///
+/// Pass [`Preconditions::matching`] for the metadata the tokens were read from so that the server
+/// rejects the update when the object or its metadata changed in the meantime.
+pub fn update_download_tokens_request(
+    storage: &FirebaseStorageImpl,
+    location: &Location,
+    tokens: &[String],
+    preconditions: &Preconditions,
+) -> RequestInfo<Value> {
+    let mut metadata = SetMetadataRequest::new();
+    metadata.insert_custom_metadata(DOWNLOAD_TOKENS_METADATA_KEY, tokens.join(","));
+    update_metadata_request(storage, location, metadata, preconditions)
+}
+
+pub fn delete_object_request(
+    storage: &FirebaseStorageImpl,
+    location: &Location,
+    preconditions: &Preconditions,
+) -> RequestInfo<()> {
     let base_url = format!("{}/v0{}", storage.host(), location.full_server_url());
     let timeout = Duration::from_millis(storage.max_operation_retry_time());
 
@@ -127,11 +251,14 @@ pub fn delete_object_request(storage: &FirebaseStorageImpl, location: &Location)
 
     let mut request = RequestInfo::new(base_url, Method::DELETE, timeout, handler);
     request.success_codes = vec![200, 204];
-    request
+    apply_preconditions(request, location, preconditions)
 }
 
 pub const RESUMABLE_UPLOAD_CHUNK_SIZE: usize = 256 * 1024;
 
+/// Custom metadata key under which Firebase Storage keeps the comma-separated download tokens.
+pub const DOWNLOAD_TOKENS_METADATA_KEY: &str = "firebaseStorageDownloadTokens";
+
 #[derive(Clone, Debug, Default)]
 pub struct ResumableUploadStatus {
     pub current: u64,
@@ -156,6 +283,7 @@ pub fn multipart_upload_request(
     location: &Location,
     data: Vec<u8>,
     metadata: Option<UploadMetadata>,
+    preconditions: &Preconditions,
 ) -> RequestInfo<ObjectMetadata> {
     let base_url = format!("{}/v0{}", storage.host(), location.bucket_only_server_url());
     let timeout = Duration::from_millis(storage.max_upload_retry_time());
@@ -194,7 +322,7 @@ pub fn multipart_upload_request(
         .headers
         .insert("X-Goog-Upload-Protocol".to_string(), "multipart".to_string());
 
-    request
+    apply_preconditions(request, location, preconditions)
 }
 
 pub fn create_resumable_upload_request(
@@ -349,6 +477,69 @@ pub fn continue_resumable_upload_request(
     request
 }
 
+pub fn cancel_resumable_upload_request(
+    storage: &FirebaseStorageImpl,
+    _location: &Location,
+    upload_url: &str,
+) -> RequestInfo<()> {
+    let timeout = Duration::from_millis(storage.max_upload_retry_time());
+    let handler: ResponseHandler<()> = Arc::new(|_| Ok(()));
+
+    let mut request = RequestInfo::new(upload_url, Method::POST, timeout, handler);
+    request
+        .headers
+        .insert("X-Goog-Upload-Command".to_string(), "cancel".to_string());
+    request
+        .headers
+        .insert("X-Goog-Upload-Protocol".to_string(), "resumable".to_string());
+    request.success_codes = vec![200, 204, 499];
+    request
+}
+
+fn download_base_url(storage: &FirebaseStorageImpl, location: &Location) -> String {
+    format!("{}://{}/v0{}", storage.protocol(), storage.host(), location.full_server_url())
+}
+
+fn format_download_url(download_base: &str, token: &str) -> String {
+    let encoded_token: String = form_urlencoded::byte_serialize(token.as_bytes()).collect();
+    format!("{download_base}?alt=media&token={encoded_token}")
+}
+
+/// Adds the precondition query parameters and maps the backend's `412` onto
+/// `storage/precondition-failed`, keeping the status for callers that retry on it.
+fn apply_preconditions<O>(
+    mut request: RequestInfo<O>,
+    location: &Location,
+    preconditions: &Preconditions,
+) -> RequestInfo<O> {
+    if preconditions.is_empty() {
+        return request;
+    }
+    let params = [
+        ("ifGenerationMatch", preconditions.if_generation_match),
+        ("ifGenerationNotMatch", preconditions.if_generation_not_match),
+        ("ifMetagenerationMatch", preconditions.if_metageneration_match),
+    ];
+    for (name, value) in params {
+        if let Some(value) = value {
+            request = request.with_query_param(name, value.to_string());
+        }
+    }
+
+    let path = location.path().to_string();
+    let handler: ErrorHandler = Arc::new(move |payload, error| {
+        if payload.status.as_u16() != 412 {
+            return error;
+        }
+        let mapped = precondition_failed(&path).with_status(412);
+        match error.server_response {
+            Some(response) => mapped.with_server_response(response),
+            None => mapped,
+        }
+    });
+    request.with_error_handler(handler)
+}
+
 fn default_json_headers() -> HashMap<String, String> {
     let mut headers = HashMap::new();
     headers.insert("Accept".to_string(), "application/json".to_string());
@@ -442,6 +633,7 @@ mod tests {
     use super::*;
     use crate::app::initialize_app;
     use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::storage::error::StorageErrorCode;
     use crate::storage::metadata::serde::{SetMetadataRequest, UploadMetadata};
     use crate::storage::request::{RequestBody, ResponsePayload};
     use reqwest::StatusCode;
@@ -486,7 +678,7 @@ mod tests {
         let location = Location::new("my-bucket", "docs/file.txt");
         let mut metadata = SetMetadataRequest::default();
         metadata.content_type = Some("text/plain".into());
-        let request = update_metadata_request(&storage, &location, metadata);
+        let request = update_metadata_request(&storage, &location, metadata, &Preconditions::default());
         assert_eq!(request.method, Method::PATCH);
         assert_eq!(
             request.url,
@@ -527,6 +719,25 @@ mod tests {
         assert_eq!(request.success_codes, vec![200, 206]);
     }
 
+    #[tokio::test]
+    async fn download_range_request_trims_full_responses() {
+        let storage = build_storage().await;
+        let location = Location::new("my-bucket", "docs/file.txt");
+        let request = download_range_request(&storage, &location, 2, Some(5));
+        assert_eq!(request.headers.get("Range"), Some(&"bytes=2-4".to_string()));
+
+        let payload = ResponsePayload {
+            status: StatusCode::OK,
+            headers: HashMap::new(),
+            body: b"abcdefg".to_vec(),
+        };
+        let handler = request.response_handler.clone();
+        assert_eq!(handler(payload).unwrap(), b"cde".to_vec());
+
+        let open_ended = download_range_request(&storage, &location, 4, None);
+        assert_eq!(open_ended.headers.get("Range"), Some(&"bytes=4-".to_string()));
+    }
+
     #[tokio::test]
     async fn download_url_request_builds_signed_url() {
         let storage = build_storage().await;
@@ -553,9 +764,49 @@ mod tests {
     async fn delete_object_request_accepts_empty_response() {
         let storage = build_storage().await;
         let location = Location::new("my-bucket", "docs/file.txt");
-        let request = delete_object_request(&storage, &location);
+        let request = delete_object_request(&storage, &location, &Preconditions::default());
         assert_eq!(request.method, Method::DELETE);
         assert!(request.success_codes.contains(&204));
+        assert!(request.query_params.is_empty());
+        assert!(request.error_handler.is_none());
+    }
+
+    #[tokio::test]
+    async fn preconditions_add_query_params_and_map_412() {
+        let storage = build_storage().await;
+        let location = Location::new("my-bucket", "docs/file.txt");
+        let preconditions = Preconditions::new().if_generation_match(0).if_generation_not_match(4);
+        let request = delete_object_request(&storage, &location, &preconditions);
+
+        assert_eq!(request.query_params.get("ifGenerationMatch"), Some(&"0".to_string()));
+        assert_eq!(request.query_params.get("ifGenerationNotMatch"), Some(&"4".to_string()));
+        assert!(!request.query_params.contains_key("ifMetagenerationMatch"));
+
+        let handler = request.error_handler.expect("error handler");
+        let payload = |status: u16| ResponsePayload {
+            status: StatusCode::from_u16(status).unwrap(),
+            headers: HashMap::new(),
+            body: Vec::new(),
+        };
+        let mapped = handler(payload(412), internal_error("failed").with_status(412));
+        assert_eq!(mapped.code, StorageErrorCode::PreconditionFailed);
+        assert_eq!(mapped.status, Some(412));
+        let untouched = handler(payload(404), internal_error("failed").with_status(404));
+        assert_eq!(untouched.code, StorageErrorCode::InternalError);
+    }
+
+    #[test]
+    fn preconditions_match_metadata_generations() {
+        let metadata = ObjectMetadata {
+            generation: Some("12".into()),
+            metageneration: Some("3".into()),
+            ..Default::default()
+        };
+        let preconditions = Preconditions::matching(&metadata);
+        assert_eq!(preconditions.if_generation_match, Some(12));
+        assert_eq!(preconditions.if_metageneration_match, Some(3));
+        assert_eq!(preconditions.if_generation_not_match, None);
+        assert!(Preconditions::new().is_empty());
     }
 
     #[tokio::test]
@@ -568,7 +819,8 @@ mod tests {
         metadata.insert_custom_metadata("role", "cover");
         let bytes = vec![1_u8, 2, 3, 4, 5];
 
-        let request = multipart_upload_request(&storage, &location, bytes.clone(), Some(metadata));
+        let request =
+            multipart_upload_request(&storage, &location, bytes.clone(), Some(metadata), &Preconditions::default());
         assert_eq!(request.method, Method::POST);
         assert_eq!(request.query_params.get("uploadType"), Some(&"multipart".to_string()));
         assert_eq!(request.query_params.get("name"), Some(&"photos/dog.jpg".to_string()));
@@ -584,6 +836,31 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn update_download_tokens_request_sets_preconditions() {
+        let storage = build_storage().await;
+        let location = Location::new("my-bucket", "docs/report.pdf");
+        let tokens = vec!["first".to_string(), "second".to_string()];
+        let preconditions = Preconditions::new().if_generation_match(12).if_metageneration_match(3);
+        let request = update_download_tokens_request(&storage, &location, &tokens, &preconditions);
+
+        assert_eq!(request.method, Method::PATCH);
+        assert_eq!(request.query_params.get("ifGenerationMatch"), Some(&"12".to_string()));
+        assert_eq!(request.query_params.get("ifMetagenerationMatch"), Some(&"3".to_string()));
+        match &request.body {
+            RequestBody::Text(body) => {
+                let value: Value = serde_json::from_str(body).unwrap();
+                assert_eq!(value["metadata"][DOWNLOAD_TOKENS_METADATA_KEY], "first,second");
+            }
+            other => panic!("unexpected request body: {other:?}"),
+        }
+        let url = download_url_for_token(&storage, &location, "a b");
+        assert_eq!(
+            url,
+            "https://firebasestorage.googleapis.com/v0/b/my%2Dbucket/o/docs%2Freport%2Epdf?alt=media&token=a+b"
+        );
+    }
+
     #[tokio::test]
     async fn create_resumable_upload_request_extracts_upload_url() {
         let storage = build_storage().await;
@@ -635,6 +912,16 @@ mod tests {
         assert!(!status.finalized);
     }
 
+    #[tokio::test]
+    async fn cancel_resumable_upload_request_sets_command() {
+        let storage = build_storage().await;
+        let location = Location::new("my-bucket", "videos/clip.mp4");
+        let request = cancel_resumable_upload_request(&storage, &location, "https://example.com/upload/session");
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.url, "https://example.com/upload/session");
+        assert_eq!(request.headers.get("X-Goog-Upload-Command"), Some(&"cancel".to_string()));
+    }
+
     #[tokio::test]
     async fn continue_resumable_upload_handles_final_response() {
         let storage = build_storage().await;
@@ -5,9 +5,11 @@ pub mod transport;
 
 pub use backoff::{BackoffConfig, BackoffState};
 pub use builders::{
-    continue_resumable_upload_request, create_resumable_upload_request, delete_object_request, download_bytes_request,
+    cancel_resumable_upload_request, continue_resumable_upload_request, create_resumable_upload_request,
+    delete_object_request, download_bytes_request, download_range_request, download_url_for_token,
     download_url_request, get_metadata_request, get_resumable_upload_status_request, list_request,
-    multipart_upload_request, update_metadata_request, ResumableUploadStatus, RESUMABLE_UPLOAD_CHUNK_SIZE,
+    multipart_upload_request, update_download_tokens_request, update_metadata_request, Preconditions,
+    ResumableUploadStatus, DOWNLOAD_TOKENS_METADATA_KEY, RESUMABLE_UPLOAD_CHUNK_SIZE,
 };
 pub use info::{ErrorHandler, RequestBody, RequestInfo, ResponseHandler};
 
@@ -82,7 +82,7 @@ impl HttpClient {
         })
     }
 
-    pub async fn execute<O>(&self, info: RequestInfo<O>) -> StorageResult<O> {
+    pub async fn execute<O>(&self, info: &RequestInfo<O>) -> StorageResult<O> {
         let mut backoff = BackoffState::new(self.backoff.clone());
 
         loop {
@@ -95,7 +95,7 @@ impl HttpClient {
                 runtime::sleep(delay).await;
             }
 
-            let result = self.try_once(&info).await;
+            let result = self.try_once(info).await;
 
             match result {
                 Ok(payload) => {
@@ -103,11 +103,11 @@ impl HttpClient {
                         return (info.response_handler)(payload);
                     }
 
-                    if should_retry(payload.status, &info) && backoff.can_retry() {
+                    if should_retry(payload.status, info) && backoff.can_retry() {
                         continue;
                     }
 
-                    return Err(map_failure(payload, &info));
+                    return Err(map_failure(payload, info));
                 }
                 Err(RequestError::Fatal(err)) => return Err(err),
                 Err(RequestError::Timeout) => {
@@ -162,7 +162,7 @@ impl HttpClient {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    pub async fn execute_streaming<O>(&self, info: RequestInfo<O>) -> StorageResult<StreamingResponse> {
+    pub async fn execute_streaming<O>(&self, info: &RequestInfo<O>) -> StorageResult<StreamingResponse> {
         let mut backoff = BackoffState::new(self.backoff.clone());
 
         loop {
@@ -175,7 +175,7 @@ impl HttpClient {
                 runtime::sleep(delay).await;
             }
 
-            match self.try_stream_once(&info).await {
+            match self.try_stream_once(info).await {
                 Ok(response) => return Ok(response),
                 Err(RequestError::Fatal(err)) => return Err(err),
                 Err(RequestError::Timeout) => {
@@ -1,19 +1,28 @@
+use std::future::Future;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use serde_json::Value;
+
 use crate::app::FirebaseApp;
 use crate::app_check::FirebaseAppCheckInternal;
 use crate::auth::Auth;
 use crate::component::Provider;
+use crate::platform::token::AsyncTokenProvider;
+use crate::storage::checksum::IntegrityOptions;
 use crate::storage::constants::{
     DEFAULT_HOST, DEFAULT_MAX_OPERATION_RETRY_TIME_MS, DEFAULT_MAX_UPLOAD_RETRY_TIME_MS, DEFAULT_PROTOCOL,
 };
-use crate::storage::error::{internal_error, no_default_bucket, StorageResult};
+use crate::storage::error::{
+    app_check_failed, internal_error, no_default_bucket, unauthenticated, StorageError, StorageResult,
+};
 use crate::storage::location::Location;
 use crate::storage::reference::StorageReference;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::storage::request::StreamingResponse;
 use crate::storage::request::{BackoffConfig, HttpClient, RequestInfo};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::transfer::{TransferManager, TransferOptions};
 use crate::storage::util::is_url;
 
 #[derive(Clone)]
@@ -23,6 +32,7 @@ pub struct FirebaseStorageImpl {
     app_check_provider: Provider,
     firebase_version: Option<String>,
     url_override: Option<String>,
+    auth_token_provider: Option<Arc<dyn AsyncTokenProvider>>,
     state: Arc<Mutex<FirebaseStorageState>>,
 }
 
@@ -32,6 +42,7 @@ struct FirebaseStorageState {
     protocol: String,
     max_operation_retry_time_ms: u64,
     max_upload_retry_time_ms: u64,
+    integrity_options: IntegrityOptions,
     override_auth_token: Option<String>,
     is_using_emulator: bool,
 }
@@ -58,6 +69,7 @@ impl FirebaseStorageImpl {
             protocol: DEFAULT_PROTOCOL.to_string(),
             max_operation_retry_time_ms: DEFAULT_MAX_OPERATION_RETRY_TIME_MS,
             max_upload_retry_time_ms: DEFAULT_MAX_UPLOAD_RETRY_TIME_MS,
+            integrity_options: IntegrityOptions::default(),
             override_auth_token: None,
             is_using_emulator: false,
         };
@@ -68,10 +80,21 @@ impl FirebaseStorageImpl {
             app_check_provider,
             firebase_version,
             url_override,
+            auth_token_provider: None,
             state: Arc::new(Mutex::new(state)),
         })
     }
 
+    /// Returns a handle that authenticates requests with `provider` instead of the app's `Auth`.
+    ///
+    /// The returned instance shares bucket, host and retry settings with `self`. Pass an
+    /// `Arc<UserSession>` to run Storage calls on behalf of a specific user.
+    pub fn with_auth_token_provider(&self, provider: Arc<dyn AsyncTokenProvider>) -> Self {
+        let mut storage = self.clone();
+        storage.auth_token_provider = Some(provider);
+        storage
+    }
+
     pub fn app(&self) -> &FirebaseApp {
         &self.app
     }
@@ -116,6 +139,22 @@ impl FirebaseStorageImpl {
         self.state.lock().unwrap().max_operation_retry_time_ms = millis;
     }
 
+    /// Returns the checksum settings applied to uploads and downloads.
+    pub fn integrity_options(&self) -> IntegrityOptions {
+        self.state.lock().unwrap().integrity_options
+    }
+
+    /// Replaces the checksum settings applied to uploads and downloads.
+    pub fn set_integrity_options(&self, options: IntegrityOptions) {
+        self.state.lock().unwrap().integrity_options = options;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Creates a [`TransferManager`] for mirroring directories to and from this bucket.
+    pub fn transfer_manager(&self, options: TransferOptions) -> TransferManager {
+        TransferManager::new(self.clone(), options)
+    }
+
     pub fn is_using_emulator(&self) -> bool {
         self.state.lock().unwrap().is_using_emulator
     }
@@ -183,34 +222,63 @@ impl FirebaseStorageImpl {
         HttpClient::new(self.is_using_emulator(), config)
     }
 
+    /// Executes a metadata/control request, retrying once with force-refreshed Auth and App Check
+    /// tokens when the backend rejects the credentials with 401/403.
     pub async fn run_request<O>(&self, info: RequestInfo<O>) -> StorageResult<O> {
         let client = self.http_client()?;
-        let info = self.prepare_request(info).await?;
-        client.execute(info).await
+        let client = &client;
+        self.run_with_credentials(info, |info| async move { client.execute(&info).await })
+            .await
     }
 
     pub async fn run_upload_request<O>(&self, info: RequestInfo<O>) -> StorageResult<O> {
         let client = self.upload_http_client()?;
-        let info = self.prepare_request(info).await?;
-        client.execute(info).await
+        let client = &client;
+        self.run_with_credentials(info, |info| async move { client.execute(&info).await })
+            .await
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn run_streaming_request<O>(&self, info: RequestInfo<O>) -> StorageResult<StreamingResponse> {
         let client = self.http_client()?;
-        let info = self.prepare_request(info).await?;
-        client.execute_streaming(info).await
+        let client = &client;
+        self.run_with_credentials(info, |info| async move { client.execute_streaming(&info).await })
+            .await
     }
 
-    async fn prepare_request<O>(&self, mut info: RequestInfo<O>) -> StorageResult<RequestInfo<O>> {
-        if let Some(token) = self.auth_token().await? {
+    /// Attaches the current tokens to `info` and runs it through `execute`. When the backend rejects
+    /// the credentials with 401/403 the request is sent once more with force-refreshed tokens, and a
+    /// second rejection is classified by [`classify_credential_error`].
+    async fn run_with_credentials<O, T, F, Fut>(&self, info: RequestInfo<O>, execute: F) -> StorageResult<T>
+    where
+        F: Fn(Arc<RequestInfo<O>>) -> Fut,
+        Fut: Future<Output = StorageResult<T>>,
+    {
+        let info = Arc::new(self.prepare_request(info, false).await?);
+        match execute(Arc::clone(&info)).await {
+            Err(err) if is_credential_rejection(&err) => {
+                let info = Arc::try_unwrap(info).unwrap_or_else(|info| (*info).clone());
+                let info = self.prepare_request(info, true).await?;
+                execute(Arc::new(info)).await.map_err(classify_credential_error)
+            }
+            result => result,
+        }
+    }
+
+    async fn prepare_request<O>(&self, mut info: RequestInfo<O>, force_refresh: bool) -> StorageResult<RequestInfo<O>> {
+        if force_refresh {
+            info.headers.remove("Authorization");
+            info.headers.remove("X-Firebase-AppCheck");
+        }
+
+        if let Some(token) = self.auth_token(force_refresh).await? {
             if !token.is_empty() {
                 info.headers
                     .insert("Authorization".to_string(), format!("Firebase {token}"));
             }
         }
 
-        if let Some(headers) = self.app_check_headers().await? {
+        if let Some(headers) = self.app_check_headers(force_refresh).await? {
             if !headers.token.is_empty() {
                 info.headers.insert("X-Firebase-AppCheck".to_string(), headers.token);
             }
@@ -235,7 +303,7 @@ impl FirebaseStorageImpl {
         Ok(info)
     }
 
-    async fn auth_token(&self) -> StorageResult<Option<String>> {
+    async fn auth_token(&self, force_refresh: bool) -> StorageResult<Option<String>> {
         if let Some(token) = {
             let state = self.state.lock().unwrap();
             state.override_auth_token.clone()
@@ -243,13 +311,21 @@ impl FirebaseStorageImpl {
             return Ok(Some(token));
         }
 
+        if let Some(provider) = self.auth_token_provider.as_ref() {
+            return match provider.get_token(force_refresh).await {
+                Ok(Some(token)) if token.is_empty() => Ok(None),
+                Ok(token) => Ok(token),
+                Err(err) => Err(internal_error(format!("failed to obtain auth token: {err}"))),
+            };
+        }
+
         let auth = match self.auth_provider.get_immediate_with_options::<Auth>(None, true) {
             Ok(Some(auth)) => auth,
             Ok(None) => return Ok(None),
             Err(err) => return Err(internal_error(format!("failed to resolve auth provider: {err}"))),
         };
 
-        match auth.get_token(false).await {
+        match auth.get_token(force_refresh).await {
             Ok(Some(token)) if token.is_empty() => Ok(None),
             Ok(Some(token)) => Ok(Some(token)),
             Ok(None) => Ok(None),
@@ -257,7 +333,7 @@ impl FirebaseStorageImpl {
         }
     }
 
-    async fn app_check_headers(&self) -> StorageResult<Option<AppCheckHeaders>> {
+    async fn app_check_headers(&self, force_refresh: bool) -> StorageResult<Option<AppCheckHeaders>> {
         let app_check = match self
             .app_check_provider
             .get_immediate_with_options::<FirebaseAppCheckInternal>(None, true)
@@ -267,7 +343,7 @@ impl FirebaseStorageImpl {
             Err(err) => return Err(internal_error(format!("failed to resolve app check provider: {err}"))),
         };
 
-        let token = match app_check.get_token(false).await {
+        let token = match app_check.get_token(force_refresh).await {
             Ok(result) => result.token,
             Err(err) => {
                 if let Some(cached) = err.cached_token() {
@@ -296,6 +372,36 @@ struct AppCheckHeaders {
     heartbeat: Option<String>,
 }
 
+fn is_credential_rejection(err: &StorageError) -> bool {
+    matches!(err.status, Some(401) | Some(403))
+}
+
+/// Prefix of the `error.message` the Storage backend returns when it rejects an App Check token,
+/// e.g. `{"error":{"code":401,"message":"Firebase App Check token is invalid."}}`.
+const APP_CHECK_REJECTION_PREFIX: &str = "Firebase App Check token";
+
+/// Maps a 401/403 that survived the token refresh onto a dedicated error code, keeping the status
+/// and server payload for diagnostics. The App Check case is read from the JSON error body; a 403
+/// that is not about App Check is a security rules denial and is returned unchanged.
+fn classify_credential_error(err: StorageError) -> StorageError {
+    let app_check_rejected = err
+        .server_response
+        .as_deref()
+        .and_then(|body| serde_json::from_str::<Value>(body).ok())
+        .and_then(|body| body["error"]["message"].as_str().map(str::to_owned))
+        .is_some_and(|message| message.starts_with(APP_CHECK_REJECTION_PREFIX));
+    let classified = match err.status {
+        Some(401) | Some(403) if app_check_rejected => app_check_failed(),
+        Some(401) => unauthenticated(),
+        _ => return err,
+    };
+    let classified = classified.with_status(err.status.unwrap_or_default());
+    match err.server_response {
+        Some(response) => classified.with_server_response(response),
+        None => classified,
+    }
+}
+
 fn extract_bucket(host: &str, app: &FirebaseApp) -> StorageResult<Option<Location>> {
     let options = app.options();
     match options.storage_bucket {
@@ -315,8 +421,12 @@ mod tests {
     use crate::app_check::{clear_registry, clear_state_for_tests, initialize_app_check, test_guard, token_with_ttl};
     use crate::component::types::{ComponentError, DynService, InstanceFactoryOptions};
     use crate::component::{Component, ComponentType};
+    use crate::storage::error::StorageErrorCode;
     use crate::storage::request::{RequestInfo, ResponseHandler};
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
     use reqwest::Method;
+    use serde_json::json;
     use std::future::Future;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
@@ -367,7 +477,7 @@ mod tests {
             .connect_emulator("localhost", 9199, Some("mock-token".into()))
             .unwrap();
 
-        let prepared = storage.prepare_request(test_request()).await.unwrap();
+        let prepared = storage.prepare_request(test_request(), false).await.unwrap();
 
         assert_eq!(prepared.headers.get("Authorization"), Some(&"Firebase mock-token".to_string()));
 
@@ -379,6 +489,30 @@ mod tests {
         assert!(prepared.headers.get("X-Firebase-AppCheck").is_none());
     }
 
+    struct StaticAuthTokenProvider;
+
+    #[async_trait::async_trait]
+    impl AsyncTokenProvider for StaticAuthTokenProvider {
+        async fn get_token(&self, _force_refresh: bool) -> Result<Option<String>, crate::platform::token::TokenError> {
+            Ok(Some("session-token".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn prepare_request_uses_custom_auth_token_provider() {
+        let storage = build_storage_with(|_| async {}).await;
+        let scoped = storage.with_auth_token_provider(Arc::new(StaticAuthTokenProvider));
+
+        let prepared = scoped.prepare_request(test_request(), false).await.unwrap();
+        assert_eq!(
+            prepared.headers.get("Authorization"),
+            Some(&"Firebase session-token".to_string())
+        );
+
+        let unscoped = storage.prepare_request(test_request(), false).await.unwrap();
+        assert!(!unscoped.headers.contains_key("Authorization"));
+    }
+
     #[derive(Clone)]
     struct StaticAppCheckProvider;
 
@@ -419,7 +553,7 @@ mod tests {
             async move { register_app_check(&app).await }
         })
         .await;
-        let prepared = storage.prepare_request(test_request()).await.unwrap();
+        let prepared = storage.prepare_request(test_request(), false).await.unwrap();
 
         assert_eq!(
             prepared.headers.get("X-Firebase-AppCheck"),
@@ -429,4 +563,130 @@ mod tests {
         clear_state_for_tests();
         clear_registry();
     }
+
+    #[derive(Default)]
+    struct RotatingAuthTokenProvider {
+        issued: AtomicUsize,
+        forced: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncTokenProvider for RotatingAuthTokenProvider {
+        async fn get_token(&self, force_refresh: bool) -> Result<Option<String>, crate::platform::token::TokenError> {
+            if force_refresh {
+                self.forced.fetch_add(1, Ordering::SeqCst);
+            }
+            let issued = self.issued.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Some(format!("token-{issued}")))
+        }
+    }
+
+    async fn storage_for_mock(server: &MockServer, provider: Arc<RotatingAuthTokenProvider>) -> FirebaseStorageImpl {
+        let storage = build_storage_with(|_| async {}).await;
+        storage.connect_emulator("127.0.0.1", server.port(), None).unwrap();
+        storage.with_auth_token_provider(provider)
+    }
+
+    fn mock_request(server: &MockServer, method: Method) -> RequestInfo<()> {
+        let handler: ResponseHandler<()> = Arc::new(|_| Ok(()));
+        RequestInfo::new(server.url("/v0/b/my-bucket/o/file"), method, Duration::from_secs(5), handler)
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_request_retries_with_refreshed_token_after_401() {
+        let server = start_mock_server();
+        let rejected = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/my-bucket/o/file")
+                .header("Authorization", "Firebase token-1");
+            then.status(401).body("token expired");
+        });
+        let accepted = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/my-bucket/o/file")
+                .header("Authorization", "Firebase token-2");
+            then.status(200);
+        });
+
+        let provider = Arc::new(RotatingAuthTokenProvider::default());
+        let storage = storage_for_mock(&server, provider.clone()).await;
+        storage.run_request(mock_request(&server, Method::GET)).await.unwrap();
+
+        rejected.assert();
+        accepted.assert();
+        assert_eq!(provider.forced.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_upload_request_retries_after_403() {
+        let server = start_mock_server();
+        let rejected = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/my-bucket/o/file")
+                .header("Authorization", "Firebase token-1");
+            then.status(403);
+        });
+        let accepted = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/my-bucket/o/file")
+                .header("Authorization", "Firebase token-2");
+            then.status(200);
+        });
+
+        let provider = Arc::new(RotatingAuthTokenProvider::default());
+        let storage = storage_for_mock(&server, provider).await;
+        storage
+            .run_upload_request(mock_request(&server, Method::POST))
+            .await
+            .unwrap();
+
+        rejected.assert();
+        accepted.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn persistent_rejections_map_to_dedicated_codes() {
+        let server = start_mock_server();
+        let mut unauthenticated = server.mock(|when, then| {
+            when.method(GET).path("/v0/b/my-bucket/o/file");
+            then.status(401).body("token expired");
+        });
+        let provider = Arc::new(RotatingAuthTokenProvider::default());
+        let storage = storage_for_mock(&server, provider).await;
+
+        let err = storage
+            .run_request(mock_request(&server, Method::GET))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::Unauthenticated);
+        assert_eq!(err.status, Some(401));
+        unauthenticated.assert_hits(2);
+        unauthenticated.delete();
+
+        let mut app_check = server.mock(|when, then| {
+            when.method(GET).path("/v0/b/my-bucket/o/file");
+            then.status(401)
+                .json_body(json!({ "error": { "code": 401, "message": "Firebase App Check token is invalid." } }));
+        });
+        let err = storage
+            .run_request(mock_request(&server, Method::GET))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::AppCheckFailed);
+        app_check.assert_hits(2);
+        app_check.delete();
+
+        let _denied = server.mock(|when, then| {
+            when.method(GET).path("/v0/b/my-bucket/o/file");
+            then.status(403).json_body(json!({
+                "error": { "code": 403, "message": "Permission denied. Could not perform this operation" }
+            }));
+        });
+        let err = storage
+            .run_request(mock_request(&server, Method::GET))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::InternalError);
+        assert_eq!(err.status, Some(403));
+    }
 }
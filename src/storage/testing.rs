@@ -0,0 +1,943 @@
+//! In-process fake of the Firebase Storage REST API for offline tests.
+//!
+//! [`FakeStorageServer`] listens on a loopback port and serves the request shapes produced by
+//! the storage request builders from an in-memory object store: multipart and resumable uploads,
+//! metadata reads and patches, paginated listing, ranged downloads, deletes and download tokens.
+//! Point a [`FirebaseStorageImpl`] at it with [`FakeStorageServer::connect`] (which calls
+//! [`connect_storage_emulator`]) and whole upload/download flows run without network access.
+//!
+//! Enable the `testing` cargo feature (typically in `[dev-dependencies]`) to use it from other
+//! crates.
+//!
+//! ```rust,ignore
+//! let server = FakeStorageServer::start().await?;
+//! server.connect(&storage)?;
+//! storage.root_reference()?.child("notes.txt").upload_string("hi", StringFormat::Raw, None).await?;
+//! assert_eq!(server.object_data("my-bucket", "notes.txt").as_deref(), Some(&b"hi"[..]));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use chrono::{SecondsFormat, Utc};
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::storage::api::connect_storage_emulator;
+use crate::storage::checksum::ObjectChecksums;
+use crate::storage::error::{internal_error, StorageResult};
+use crate::storage::metadata::serde::ObjectMetadata;
+use crate::storage::request::DOWNLOAD_TOKENS_METADATA_KEY;
+use crate::storage::service::FirebaseStorageImpl;
+use crate::storage::util::generate_download_token;
+
+const DEFAULT_PAGE_SIZE: usize = 1000;
+const EDITABLE_FIELDS: [&str; 5] = [
+    "cacheControl",
+    "contentDisposition",
+    "contentEncoding",
+    "contentLanguage",
+    "contentType",
+];
+
+/// A loopback HTTP server that emulates Firebase Storage on an in-memory object store.
+///
+/// The server stops accepting connections when dropped.
+pub struct FakeStorageServer {
+    address: SocketAddr,
+    state: Arc<Mutex<FakeState>>,
+    accept_task: JoinHandle<()>,
+}
+
+impl FakeStorageServer {
+    /// Binds an ephemeral loopback port and starts serving requests on the current tokio runtime.
+    pub async fn start() -> StorageResult<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|err| internal_error(format!("failed to bind fake storage server: {err}")))?;
+        let address = listener
+            .local_addr()
+            .map_err(|err| internal_error(format!("failed to read fake storage server address: {err}")))?;
+        let state = Arc::new(Mutex::new(FakeState::new(format!("http://{address}"))));
+
+        let accept_state = Arc::clone(&state);
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, Arc::clone(&accept_state)));
+            }
+        });
+
+        Ok(Self {
+            address,
+            state,
+            accept_task,
+        })
+    }
+
+    pub fn host(&self) -> String {
+        self.address.ip().to_string()
+    }
+
+    pub fn port(&self) -> u16 {
+        self.address.port()
+    }
+
+    /// Routes `storage` to this server through [`connect_storage_emulator`].
+    pub fn connect(&self, storage: &FirebaseStorageImpl) -> StorageResult<()> {
+        connect_storage_emulator(storage, &self.host(), self.port(), None)
+    }
+
+    /// Seeds an object directly into the store, bypassing the upload endpoints.
+    pub fn insert_object(&self, bucket: &str, path: &str, data: impl Into<Vec<u8>>, content_type: Option<&str>) {
+        let mut resource = Map::new();
+        if let Some(content_type) = content_type {
+            resource.insert("contentType".to_string(), Value::String(content_type.to_string()));
+        }
+        self.state
+            .lock()
+            .unwrap()
+            .store_object(bucket, path, data.into(), resource);
+    }
+
+    pub fn object_data(&self, bucket: &str, path: &str) -> Option<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        state
+            .objects
+            .get(&(bucket.to_string(), path.to_string()))
+            .map(|object| object.data.clone())
+    }
+
+    pub fn object_metadata(&self, bucket: &str, path: &str) -> Option<ObjectMetadata> {
+        let state = self.state.lock().unwrap();
+        state
+            .objects
+            .get(&(bucket.to_string(), path.to_string()))
+            .map(|object| ObjectMetadata::from_value(object.to_json(bucket, path)))
+    }
+
+    /// Returns the paths of all objects in `bucket`, sorted.
+    pub fn object_paths(&self, bucket: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .objects
+            .keys()
+            .filter(|(object_bucket, _)| object_bucket == bucket)
+            .map(|(_, path)| path.clone())
+            .collect()
+    }
+
+    /// Appends a download token the way a concurrent editor would, bumping the metageneration.
+    /// Returns `false` when the object does not exist.
+    pub fn add_download_token(&self, bucket: &str, path: &str, token: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(object) = state.objects.get_mut(&(bucket.to_string(), path.to_string())) else {
+            return false;
+        };
+        object.download_tokens.push(token.to_string());
+        object.metageneration += 1;
+        true
+    }
+
+    pub fn remove_object(&self, bucket: &str, path: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.objects.remove(&(bucket.to_string(), path.to_string())).is_some()
+    }
+
+    /// Number of HTTP requests served so far.
+    pub fn request_count(&self) -> usize {
+        self.state.lock().unwrap().request_count
+    }
+
+    /// Drops every object and pending upload session.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.objects.clear();
+        state.sessions.clear();
+    }
+}
+
+impl Drop for FakeStorageServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+struct FakeObject {
+    data: Vec<u8>,
+    resource: Map<String, Value>,
+    custom_metadata: BTreeMap<String, String>,
+    download_tokens: Vec<String>,
+    generation: u64,
+    metageneration: u64,
+    time_created: String,
+    updated: String,
+}
+
+impl FakeObject {
+    fn to_json(&self, bucket: &str, path: &str) -> Value {
+        let checksums = ObjectChecksums::compute(&self.data, true);
+        let mut value = self.resource.clone();
+        value.insert("name".to_string(), json!(path));
+        value.insert("bucket".to_string(), json!(bucket));
+        value.insert("generation".to_string(), json!(self.generation.to_string()));
+        value.insert("metageneration".to_string(), json!(self.metageneration.to_string()));
+        value.insert("size".to_string(), json!(self.data.len().to_string()));
+        value.insert("timeCreated".to_string(), json!(self.time_created));
+        value.insert("updated".to_string(), json!(self.updated));
+        value.insert("crc32c".to_string(), json!(checksums.crc32c));
+        value.insert("md5Hash".to_string(), json!(checksums.md5_hash));
+        value.insert(
+            "etag".to_string(),
+            json!(format!("{}/{}", self.generation, self.metageneration)),
+        );
+        value
+            .entry("contentType".to_string())
+            .or_insert_with(|| json!("application/octet-stream"));
+        if !self.custom_metadata.is_empty() {
+            value.insert("metadata".to_string(), json!(self.custom_metadata));
+        }
+        if !self.download_tokens.is_empty() {
+            value.insert("downloadTokens".to_string(), json!(self.download_tokens.join(",")));
+        }
+        Value::Object(value)
+    }
+}
+
+struct UploadSession {
+    bucket: String,
+    path: String,
+    resource: Map<String, Value>,
+    data: Vec<u8>,
+    finalized: bool,
+}
+
+struct FakeState {
+    base_url: String,
+    objects: BTreeMap<(String, String), FakeObject>,
+    sessions: HashMap<u64, UploadSession>,
+    next_session: u64,
+    next_generation: u64,
+    request_count: usize,
+}
+
+struct FakeRequest {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl FakeRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+}
+
+struct FakeResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl FakeResponse {
+    fn empty(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    fn json(status: u16, value: Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "error": { "code": status, "message": message } }))
+    }
+
+    fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+}
+
+impl FakeState {
+    fn new(base_url: String) -> Self {
+        Self {
+            base_url,
+            objects: BTreeMap::new(),
+            sessions: HashMap::new(),
+            next_session: 1,
+            next_generation: 1,
+            request_count: 0,
+        }
+    }
+
+    fn handle(&mut self, request: &FakeRequest) -> FakeResponse {
+        self.request_count += 1;
+        let segments: Vec<String> = request
+            .path
+            .trim_start_matches('/')
+            .split('/')
+            .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["v0", "b", bucket, "o"]) => self.list(bucket, request),
+            ("POST", ["v0", "b", bucket, "o"]) => self.start_upload(bucket, request),
+            ("GET", ["v0", "b", bucket, "o", path]) => self.get_object(bucket, path, request),
+            ("PATCH", ["v0", "b", bucket, "o", path]) => self.patch_object(bucket, path, request),
+            ("DELETE", ["v0", "b", bucket, "o", path]) => self.delete_object(bucket, path, request),
+            ("POST", ["upload", "resumable", id]) => match id.parse::<u64>() {
+                Ok(id) => self.continue_upload(id, request),
+                Err(_) => FakeResponse::error(404, "Unknown upload session."),
+            },
+            _ => FakeResponse::error(404, "Unsupported fake storage route."),
+        }
+    }
+
+    fn list(&self, bucket: &str, request: &FakeRequest) -> FakeResponse {
+        let prefix = request.query("prefix").unwrap_or_default();
+        let delimiter = request.query("delimiter").filter(|delimiter| !delimiter.is_empty());
+        let offset = match request.query("pageToken").map(str::parse::<usize>) {
+            Some(Ok(offset)) => offset,
+            Some(Err(_)) => return FakeResponse::error(400, "Invalid page token."),
+            None => 0,
+        };
+        let page_size = request
+            .query("maxResults")
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+
+        // Prefixes and items share one sorted sequence so page tokens stay stable offsets.
+        let mut entries = BTreeSet::new();
+        for (object_bucket, path) in self.objects.keys() {
+            if object_bucket != bucket {
+                continue;
+            }
+            let Some(rest) = path.strip_prefix(prefix) else {
+                continue;
+            };
+            match delimiter.and_then(|delimiter| rest.find(delimiter).map(|index| (index, delimiter))) {
+                Some((index, delimiter)) => {
+                    entries.insert((format!("{prefix}{}{delimiter}", &rest[..index]), true));
+                }
+                None => {
+                    entries.insert((path.clone(), false));
+                }
+            }
+        }
+
+        let mut prefixes = Vec::new();
+        let mut items = Vec::new();
+        for (name, is_prefix) in entries.iter().skip(offset).take(page_size) {
+            if *is_prefix {
+                prefixes.push(json!(name));
+            } else {
+                items.push(json!({ "name": name, "bucket": bucket }));
+            }
+        }
+
+        let mut body = json!({ "prefixes": prefixes, "items": items });
+        if offset + page_size < entries.len() {
+            body["nextPageToken"] = json!((offset + page_size).to_string());
+        }
+        FakeResponse::json(200, body)
+    }
+
+    fn get_object(&self, bucket: &str, path: &str, request: &FakeRequest) -> FakeResponse {
+        let Some(object) = self.objects.get(&(bucket.to_string(), path.to_string())) else {
+            return FakeResponse::error(404, "Not Found.");
+        };
+        if request.query("alt") != Some("media") {
+            return FakeResponse::json(200, object.to_json(bucket, path));
+        }
+
+        if let Some(token) = request.query("token") {
+            if !object.download_tokens.iter().any(|candidate| candidate == token) {
+                return FakeResponse::error(403, "Permission denied. Invalid download token.");
+            }
+        }
+
+        let content_type = object
+            .resource
+            .get("contentType")
+            .and_then(Value::as_str)
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let len = object.data.len() as u64;
+        let (status, body) = match request.header("range").map(|range| parse_range(range, len)) {
+            Some(Some((start, end))) => (206, object.data[start as usize..end as usize].to_vec()),
+            Some(None) => return FakeResponse::error(416, "Requested range not satisfiable."),
+            None => (200, object.data.clone()),
+        };
+        let mut response = FakeResponse {
+            status,
+            headers: vec![
+                ("Content-Type".to_string(), content_type),
+                ("x-goog-generation".to_string(), object.generation.to_string()),
+            ],
+            body,
+        };
+        // Like GCS, only complete objects report their hashes.
+        if status == 200 {
+            let checksums = ObjectChecksums::compute(&object.data, true);
+            response = response.with_header("x-goog-hash", checksums.header_value());
+        }
+        response
+    }
+
+    fn patch_object(&mut self, bucket: &str, path: &str, request: &FakeRequest) -> FakeResponse {
+        let patch: Map<String, Value> = match serde_json::from_slice(&request.body) {
+            Ok(patch) => patch,
+            Err(_) => return FakeResponse::error(400, "Invalid metadata payload."),
+        };
+        let updated = timestamp();
+        let Some(object) = self.objects.get_mut(&(bucket.to_string(), path.to_string())) else {
+            return FakeResponse::error(404, "Not Found.");
+        };
+        if let Some(response) = check_preconditions(Some(&*object), request) {
+            return response;
+        }
+
+        for field in EDITABLE_FIELDS {
+            match patch.get(field) {
+                Some(Value::Null) => {
+                    object.resource.remove(field);
+                }
+                Some(value) => {
+                    object.resource.insert(field.to_string(), value.clone());
+                }
+                None => {}
+            }
+        }
+        if let Some(Value::Object(custom)) = patch.get("metadata") {
+            apply_custom_metadata(object, custom);
+        }
+        object.metageneration += 1;
+        object.updated = updated;
+        FakeResponse::json(200, object.to_json(bucket, path))
+    }
+
+    fn delete_object(&mut self, bucket: &str, path: &str, request: &FakeRequest) -> FakeResponse {
+        let key = (bucket.to_string(), path.to_string());
+        let Some(object) = self.objects.get(&key) else {
+            return FakeResponse::error(404, "Not Found.");
+        };
+        if let Some(response) = check_preconditions(Some(object), request) {
+            return response;
+        }
+        self.objects.remove(&key);
+        FakeResponse::empty(204)
+    }
+
+    fn start_upload(&mut self, bucket: &str, request: &FakeRequest) -> FakeResponse {
+        match request.query("uploadType") {
+            Some("multipart") => {
+                let Some((resource, data)) = parse_multipart(request) else {
+                    return FakeResponse::error(400, "Malformed multipart upload.");
+                };
+                let Some(path) = upload_path(request, &resource) else {
+                    return FakeResponse::error(400, "Upload is missing an object name.");
+                };
+                if let Some(response) = verify_client_hashes(&resource, None, &data) {
+                    return response;
+                }
+                let existing = self.objects.get(&(bucket.to_string(), path.clone()));
+                if let Some(response) = check_preconditions(existing, request) {
+                    return response;
+                }
+                let value = self.store_object(bucket, &path, data, resource);
+                FakeResponse::json(200, value)
+            }
+            Some("resumable") => {
+                if request.header("x-goog-upload-command") != Some("start") {
+                    return FakeResponse::error(400, "Resumable uploads must start with the start command.");
+                }
+                let resource: Map<String, Value> = if request.body.is_empty() {
+                    Map::new()
+                } else {
+                    match serde_json::from_slice(&request.body) {
+                        Ok(resource) => resource,
+                        Err(_) => return FakeResponse::error(400, "Invalid upload metadata."),
+                    }
+                };
+                let Some(path) = upload_path(request, &resource) else {
+                    return FakeResponse::error(400, "Upload is missing an object name.");
+                };
+                let id = self.next_session;
+                self.next_session += 1;
+                self.sessions.insert(
+                    id,
+                    UploadSession {
+                        bucket: bucket.to_string(),
+                        path,
+                        resource,
+                        data: Vec::new(),
+                        finalized: false,
+                    },
+                );
+                FakeResponse::empty(200)
+                    .with_header("X-Goog-Upload-Status", "active")
+                    .with_header("X-Goog-Upload-URL", format!("{}/upload/resumable/{id}", self.base_url))
+            }
+            _ => FakeResponse::error(400, "Unsupported uploadType."),
+        }
+    }
+
+    fn continue_upload(&mut self, id: u64, request: &FakeRequest) -> FakeResponse {
+        let command = request
+            .header("x-goog-upload-command")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let commands: Vec<&str> = command.split(',').map(str::trim).collect();
+
+        if commands.contains(&"cancel") {
+            return match self.sessions.remove(&id) {
+                Some(_) => FakeResponse::empty(200).with_header("X-Goog-Upload-Status", "cancelled"),
+                None => FakeResponse::error(404, "Unknown upload session."),
+            };
+        }
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return FakeResponse::error(404, "Unknown upload session.");
+        };
+
+        if commands.contains(&"query") {
+            let status = if session.finalized { "final" } else { "active" };
+            return FakeResponse::empty(200)
+                .with_header("X-Goog-Upload-Status", status)
+                .with_header("X-Goog-Upload-Size-Received", session.data.len().to_string());
+        }
+        if session.finalized {
+            return FakeResponse::error(400, "Upload session is already finalized.");
+        }
+
+        if commands.contains(&"upload") {
+            let offset = request
+                .header("x-goog-upload-offset")
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(session.data.len());
+            if offset > session.data.len() {
+                return FakeResponse::error(400, "Upload offset is beyond the received data.");
+            }
+            // A retried chunk overwrites whatever was received after its offset.
+            session.data.truncate(offset);
+            session.data.extend_from_slice(&request.body);
+        }
+
+        if !commands.contains(&"finalize") {
+            return FakeResponse::empty(200)
+                .with_header("X-Goog-Upload-Status", "active")
+                .with_header("X-Goog-Upload-Size-Received", session.data.len().to_string());
+        }
+
+        if let Some(response) = verify_client_hashes(&session.resource, request.header("x-goog-hash"), &session.data) {
+            self.sessions.remove(&id);
+            return response;
+        }
+        session.finalized = true;
+        let bucket = session.bucket.clone();
+        let path = session.path.clone();
+        let data = session.data.clone();
+        let resource = session.resource.clone();
+        let value = self.store_object(&bucket, &path, data, resource);
+        FakeResponse::json(200, value).with_header("X-Goog-Upload-Status", "final")
+    }
+
+    fn store_object(&mut self, bucket: &str, path: &str, data: Vec<u8>, resource: Map<String, Value>) -> Value {
+        let now = timestamp();
+        let generation = self.next_generation;
+        self.next_generation += 1;
+
+        let mut object = FakeObject {
+            data,
+            resource: Map::new(),
+            custom_metadata: BTreeMap::new(),
+            download_tokens: vec![generate_download_token()],
+            generation,
+            metageneration: 1,
+            time_created: now.clone(),
+            updated: now,
+        };
+        for field in EDITABLE_FIELDS {
+            if let Some(value) = resource.get(field).filter(|value| !value.is_null()) {
+                object.resource.insert(field.to_string(), value.clone());
+            }
+        }
+        if let Some(Value::Object(custom)) = resource.get("metadata") {
+            apply_custom_metadata(&mut object, custom);
+        }
+
+        let value = object.to_json(bucket, path);
+        self.objects.insert((bucket.to_string(), path.to_string()), object);
+        value
+    }
+}
+
+/// Applies a custom metadata patch; `null` removes a key and the reserved download-token key
+/// replaces the object's tokens instead of being stored.
+fn apply_custom_metadata(object: &mut FakeObject, custom: &Map<String, Value>) {
+    for (key, value) in custom {
+        if key == DOWNLOAD_TOKENS_METADATA_KEY {
+            object.download_tokens = value
+                .as_str()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(str::to_string)
+                .collect();
+            continue;
+        }
+        match value {
+            Value::Null => {
+                object.custom_metadata.remove(key);
+            }
+            Value::String(text) => {
+                object.custom_metadata.insert(key.clone(), text.clone());
+            }
+            other => {
+                object.custom_metadata.insert(key.clone(), other.to_string());
+            }
+        }
+    }
+}
+
+/// Evaluates `ifGenerationMatch`/`ifMetagenerationMatch` (and their `NotMatch` variants) the way
+/// the backend does, answering `412` when one fails. A missing object has generation `0`.
+fn check_preconditions(object: Option<&FakeObject>, request: &FakeRequest) -> Option<FakeResponse> {
+    let generation = object.map_or(0, |object| object.generation);
+    let metageneration = object.map_or(0, |object| object.metageneration);
+    let checks = [
+        ("ifGenerationMatch", generation, true),
+        ("ifGenerationNotMatch", generation, false),
+        ("ifMetagenerationMatch", metageneration, true),
+        ("ifMetagenerationNotMatch", metageneration, false),
+    ];
+    for (name, actual, must_match) in checks {
+        let Some(expected) = request.query(name) else {
+            continue;
+        };
+        let Ok(expected) = expected.parse::<u64>() else {
+            return Some(FakeResponse::error(400, &format!("Invalid {name} value.")));
+        };
+        if (expected == actual) != must_match {
+            return Some(FakeResponse::error(412, "Precondition Failed"));
+        }
+    }
+    None
+}
+
+fn upload_path(request: &FakeRequest, resource: &Map<String, Value>) -> Option<String> {
+    request
+        .query("name")
+        .map(str::to_string)
+        .or_else(|| resource.get("name").and_then(Value::as_str).map(str::to_string))
+        .filter(|path| !path.is_empty())
+}
+
+/// Rejects uploads whose declared checksums (resource fields or `X-Goog-Hash`) do not match.
+fn verify_client_hashes(resource: &Map<String, Value>, hash_header: Option<&str>, data: &[u8]) -> Option<FakeResponse> {
+    let mut declared: Vec<(&str, String)> = Vec::new();
+    for (field, algorithm) in [("crc32c", "crc32c"), ("md5Hash", "md5")] {
+        if let Some(value) = resource.get(field).and_then(Value::as_str) {
+            declared.push((algorithm, value.to_string()));
+        }
+    }
+    for entry in hash_header.unwrap_or_default().split(',') {
+        if let Some((algorithm, value)) = entry.trim().split_once('=') {
+            if let Some(algorithm) = ["crc32c", "md5"].into_iter().find(|known| *known == algorithm) {
+                declared.push((algorithm, value.to_string()));
+            }
+        }
+    }
+    if declared.is_empty() {
+        return None;
+    }
+
+    let computed = ObjectChecksums::compute(data, true);
+    let mismatch = declared.iter().any(|(algorithm, value)| match *algorithm {
+        "crc32c" => *value != computed.crc32c,
+        _ => Some(value.as_str()) != computed.md5_hash.as_deref(),
+    });
+    mismatch.then(|| FakeResponse::error(400, "Provided checksum does not match the uploaded data."))
+}
+
+fn parse_multipart(request: &FakeRequest) -> Option<(Map<String, Value>, Vec<u8>)> {
+    let content_type = request.header("content-type")?;
+    let boundary = content_type
+        .split(';')
+        .filter_map(|part| part.trim().strip_prefix("boundary="))
+        .next()?
+        .trim_matches('"');
+    let delimiter = format!("--{boundary}");
+
+    let mut parts = Vec::new();
+    let mut rest = request.body.as_slice();
+    while let Some(start) = find(rest, delimiter.as_bytes()) {
+        rest = &rest[start + delimiter.len()..];
+        if rest.starts_with(b"--") {
+            break;
+        }
+        let rest_after_crlf = rest.strip_prefix(b"\r\n")?;
+        let next = find(rest_after_crlf, delimiter.as_bytes())?;
+        let part = &rest_after_crlf[..next];
+        let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+        let header_end = find(part, b"\r\n\r\n")?;
+        parts.push(part[header_end + 4..].to_vec());
+        rest = &rest_after_crlf[next..];
+    }
+
+    let mut parts = parts.into_iter();
+    let resource = serde_json::from_slice(&parts.next()?).ok()?;
+    let data = parts.next()?;
+    Some((resource, data))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Parses `bytes=start-end` / `bytes=start-` into an exclusive `start..end` range.
+fn parse_range(header: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+    let start: u64 = start.parse().ok()?;
+    let end = match end {
+        "" => len,
+        end => end.parse::<u64>().ok()?.saturating_add(1).min(len),
+    };
+    (start < len && start < end).then_some((start, end))
+}
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+async fn serve_connection(stream: TcpStream, state: Arc<Mutex<FakeState>>) {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    while let Ok(Some(request)) = read_request(&mut reader).await {
+        let response = state.lock().unwrap().handle(&request);
+        if write_response(&mut write_half, response).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn read_request<R>(reader: &mut R) -> std::io::Result<Option<FakeRequest>>
+where
+    R: AsyncBufReadExt + Unpin,
+{
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+    let method = method.to_string();
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_string();
+    let query = url::form_urlencoded::parse(query_string.as_bytes())
+        .into_owned()
+        .collect();
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Some(FakeRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    }))
+}
+
+async fn write_response<W>(writer: &mut W, response: FakeResponse) -> std::io::Result<()>
+where
+    W: AsyncWriteExt + Unpin,
+{
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason_phrase(response.status));
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", response.body.len()));
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(&response.body).await?;
+    writer.flush().await
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        206 => "Partial Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        412 => "Precondition Failed",
+        416 => "Range Not Satisfiable",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::storage::list::ListOptions;
+    use crate::storage::metadata::serde::SettableMetadata;
+    use crate::storage::UploadMetadata;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn unique_settings() -> FirebaseAppSettings {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("storage-fake-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn connected_storage(server: &FakeStorageServer) -> FirebaseStorageImpl {
+        let options = FirebaseOptions {
+            storage_bucket: Some("fake-bucket".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let container = app.container();
+        let auth_provider = container.get_provider("auth-internal");
+        let app_check_provider = container.get_provider("app-check-internal");
+        let storage = FirebaseStorageImpl::new(app, auth_provider, app_check_provider, None, None).unwrap();
+        server.connect(&storage).unwrap();
+        storage
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn multipart_upload_metadata_and_delete_round_trip() {
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = connected_storage(&server).await;
+        let reference = storage.root_reference().unwrap().child("docs/readme.txt");
+
+        let metadata = UploadMetadata::new().with_content_type("text/plain");
+        let uploaded = reference.upload_bytes(b"hello".to_vec(), Some(metadata)).await.unwrap();
+        assert_eq!(uploaded.size_bytes(), Some(5));
+        assert_eq!(uploaded.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(server.object_data("fake-bucket", "docs/readme.txt").unwrap(), b"hello");
+
+        assert_eq!(reference.get_bytes(None).await.unwrap(), b"hello".to_vec());
+        assert_eq!(reference.get_range(1..4).await.unwrap(), b"ell".to_vec());
+        let url = reference.get_download_url().await.unwrap();
+        assert!(url.contains("token="));
+
+        let mut update = SettableMetadata::new();
+        update.cache_control = Some("no-cache".into());
+        update.insert_custom_metadata("owner", "tests");
+        let updated = reference.update_metadata(update).await.unwrap();
+        assert_eq!(updated.cache_control.as_deref(), Some("no-cache"));
+        assert_eq!(updated.metageneration.as_deref(), Some("2"));
+        assert_eq!(updated.custom_metadata.unwrap().get("owner").map(String::as_str), Some("tests"));
+
+        reference.delete_object().await.unwrap();
+        let err = reference.get_metadata().await.unwrap_err();
+        assert_eq!(err.status, Some(404));
+        assert!(server.object_paths("fake-bucket").is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn resumable_upload_and_streamed_download_round_trip() {
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = connected_storage(&server).await;
+        let reference = storage.root_reference().unwrap().child("videos/clip.bin");
+        let payload: Vec<u8> = (0..600 * 1024).map(|index| (index % 251) as u8).collect();
+
+        let metadata = reference
+            .upload_reader_resumable(futures::io::Cursor::new(payload.clone()), payload.len() as u64, None)
+            .await
+            .unwrap();
+        assert_eq!(metadata.size_bytes(), Some(payload.len() as u64));
+
+        let mut output = Vec::new();
+        reference.download_to_writer(&mut output).await.unwrap();
+        assert_eq!(output, payload);
+        assert!(server.request_count() > 4);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn list_returns_prefixes_and_pages() {
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = connected_storage(&server).await;
+        for path in ["photos/a.jpg", "photos/b.jpg", "photos/c.jpg", "photos/2024/d.jpg"] {
+            server.insert_object("fake-bucket", path, b"x".to_vec(), Some("image/jpeg"));
+        }
+        let photos = storage.root_reference().unwrap().child("photos");
+
+        let first = photos
+            .list(Some(ListOptions {
+                max_results: Some(2),
+                page_token: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(first.prefixes.len(), 1);
+        assert_eq!(first.prefixes[0].full_path(), "photos/2024");
+        assert_eq!(first.items.len(), 1);
+        assert!(first.next_page_token.is_some());
+
+        let all = photos.list_all().await.unwrap();
+        let names: Vec<_> = all.items.iter().map(|item| item.full_path().to_string()).collect();
+        assert_eq!(names, vec!["photos/a.jpg", "photos/b.jpg", "photos/c.jpg"]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rejects_uploads_with_mismatched_client_hashes() {
+        let server = FakeStorageServer::start().await.unwrap();
+        let storage = connected_storage(&server).await;
+        let reference = storage.root_reference().unwrap().child("blob");
+        let metadata = UploadMetadata::new().with_crc32c("AAAAAA==");
+        let err = reference
+            .upload_bytes(b"data".to_vec(), Some(metadata))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status, Some(400));
+        assert!(server.object_data("fake-bucket", "blob").is_none());
+    }
+}
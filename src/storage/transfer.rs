@@ -0,0 +1,717 @@
+use std::collections::VecDeque;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use futures::stream::{self, StreamExt};
+use tokio::io::AsyncReadExt;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+use crate::storage::checksum::ChecksumHasher;
+use crate::storage::error::{internal_error, invalid_argument, StorageError, StorageResult};
+use crate::storage::metadata::serde::ObjectMetadata;
+use crate::storage::reference::StorageReference;
+use crate::storage::service::FirebaseStorageImpl;
+use crate::storage::UploadMetadata;
+
+/// Number of files transferred at the same time when no explicit limit is configured.
+pub const DEFAULT_TRANSFER_CONCURRENCY: usize = 4;
+
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Settings shared by every file of a directory transfer.
+#[derive(Clone, Debug)]
+pub struct TransferOptions {
+    /// Maximum number of files in flight at once (values below one are treated as one).
+    pub concurrency: usize,
+    /// Skips files whose size and CRC32C already match on the other side.
+    pub skip_unchanged: bool,
+    /// Metadata applied to every uploaded object.
+    pub metadata: Option<UploadMetadata>,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_TRANSFER_CONCURRENCY,
+            skip_unchanged: true,
+            metadata: None,
+        }
+    }
+}
+
+impl TransferOptions {
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_skip_unchanged(mut self, skip_unchanged: bool) -> Self {
+        self.skip_unchanged = skip_unchanged;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: UploadMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// Aggregate progress across all files of a directory transfer.
+///
+/// Skipped files count towards `bytes_transferred` so the totals converge once the job finishes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub files_total: usize,
+    pub files_finished: usize,
+    pub bytes_transferred: u64,
+    pub bytes_total: u64,
+}
+
+/// What happened to a single file of a directory transfer.
+#[derive(Clone, Debug)]
+pub enum TransferOutcome {
+    /// The file was copied; carries the object metadata after the transfer.
+    Transferred(Box<ObjectMetadata>),
+    /// Both sides already held identical content.
+    Skipped,
+    /// The file failed without affecting the rest of the job.
+    Failed(StorageError),
+}
+
+#[derive(Clone, Debug)]
+pub struct FileTransferResult {
+    pub local_path: PathBuf,
+    pub remote_path: String,
+    pub outcome: TransferOutcome,
+}
+
+/// Per-file results of a directory transfer, in a stable path order.
+#[derive(Clone, Debug, Default)]
+pub struct TransferReport {
+    pub results: Vec<FileTransferResult>,
+}
+
+impl TransferReport {
+    pub fn transferred_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, TransferOutcome::Transferred(_)))
+            .count()
+    }
+
+    pub fn skipped_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, TransferOutcome::Skipped))
+            .count()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &FileTransferResult> {
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, TransferOutcome::Failed(_)))
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+/// Mirrors local directory trees to bucket prefixes and back with bounded concurrency.
+///
+/// Obtain one from [`FirebaseStorageImpl::transfer_manager`]. Only listing and directory walking
+/// errors abort a job; individual file failures are reported in the returned [`TransferReport`].
+#[derive(Clone)]
+pub struct TransferManager {
+    storage: FirebaseStorageImpl,
+    options: TransferOptions,
+}
+
+struct FileJob {
+    local_path: PathBuf,
+    reference: StorageReference,
+    size: u64,
+}
+
+struct ProgressTracker<F> {
+    state: Mutex<(TransferProgress, F)>,
+}
+
+impl<F> ProgressTracker<F>
+where
+    F: FnMut(TransferProgress),
+{
+    fn new(files_total: usize, bytes_total: u64, mut callback: F) -> Self {
+        let progress = TransferProgress {
+            files_total,
+            bytes_total,
+            ..Default::default()
+        };
+        callback(progress);
+        Self {
+            state: Mutex::new((progress, callback)),
+        }
+    }
+
+    fn advance(&self, bytes: u64, file_finished: bool) {
+        let mut guard = self.state.lock().unwrap();
+        let (progress, callback) = &mut *guard;
+        progress.bytes_transferred += bytes;
+        if file_finished {
+            progress.files_finished += 1;
+        }
+        callback(*progress);
+    }
+}
+
+impl TransferManager {
+    pub(crate) fn new(storage: FirebaseStorageImpl, options: TransferOptions) -> Self {
+        Self { storage, options }
+    }
+
+    pub fn storage(&self) -> &FirebaseStorageImpl {
+        &self.storage
+    }
+
+    pub fn options(&self) -> &TransferOptions {
+        &self.options
+    }
+
+    /// Uploads every file beneath `local_dir` to the matching path under `destination`.
+    pub async fn upload_directory(
+        &self,
+        local_dir: impl AsRef<Path>,
+        destination: &StorageReference,
+    ) -> StorageResult<TransferReport> {
+        self.upload_directory_with_progress(local_dir, destination, |_| {})
+            .await
+    }
+
+    /// Uploads a directory tree while reporting aggregate progress.
+    pub async fn upload_directory_with_progress<F>(
+        &self,
+        local_dir: impl AsRef<Path>,
+        destination: &StorageReference,
+        progress: F,
+    ) -> StorageResult<TransferReport>
+    where
+        F: FnMut(TransferProgress) + Send,
+    {
+        let local_dir = local_dir.as_ref();
+        let jobs: Vec<FileJob> = collect_local_files(local_dir)
+            .await?
+            .into_iter()
+            .map(|(local_path, relative, size)| FileJob {
+                reference: destination.child(&relative),
+                local_path,
+                size,
+            })
+            .collect();
+
+        let tracker = ProgressTracker::new(jobs.len(), jobs.iter().map(|job| job.size).sum(), progress);
+        let tracker = &tracker;
+        let results = stream::iter(jobs)
+            .map(|job| async move {
+                let outcome = match self.upload_file(&job, tracker).await {
+                    Ok(Some(metadata)) => TransferOutcome::Transferred(Box::new(metadata)),
+                    Ok(None) => TransferOutcome::Skipped,
+                    Err(err) => TransferOutcome::Failed(err),
+                };
+                tracker.advance(0, true);
+                FileTransferResult {
+                    remote_path: job.reference.full_path().to_string(),
+                    local_path: job.local_path,
+                    outcome,
+                }
+            })
+            .buffered(self.options.concurrency.max(1))
+            .collect()
+            .await;
+        Ok(TransferReport { results })
+    }
+
+    /// Downloads every object beneath `source` (recursively) into `local_dir`.
+    pub async fn download_prefix(
+        &self,
+        source: &StorageReference,
+        local_dir: impl AsRef<Path>,
+    ) -> StorageResult<TransferReport> {
+        self.download_prefix_with_progress(source, local_dir, |_| {}).await
+    }
+
+    /// Downloads a bucket prefix while reporting aggregate progress.
+    pub async fn download_prefix_with_progress<F>(
+        &self,
+        source: &StorageReference,
+        local_dir: impl AsRef<Path>,
+        progress: F,
+    ) -> StorageResult<TransferReport>
+    where
+        F: FnMut(TransferProgress) + Send,
+    {
+        let local_dir = local_dir.as_ref();
+        let references = collect_remote_objects(source).await?;
+        let concurrency = self.options.concurrency.max(1);
+
+        // Sizes are needed up front for aggregate progress; a failed lookup only fails that file.
+        let lookups: Vec<(StorageReference, StorageResult<ObjectMetadata>)> = stream::iter(references)
+            .map(|reference| async move {
+                let metadata = reference.get_metadata().await;
+                (reference, metadata)
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        let bytes_total = lookups
+            .iter()
+            .filter_map(|(_, metadata)| metadata.as_ref().ok().and_then(ObjectMetadata::size_bytes))
+            .sum();
+        let tracker = ProgressTracker::new(lookups.len(), bytes_total, progress);
+        let tracker = &tracker;
+        let results = stream::iter(lookups)
+            .map(|(reference, metadata)| async move {
+                let (local_path, outcome) = match local_path_for(local_dir, source, &reference) {
+                    Ok(local_path) => {
+                        let outcome = match metadata {
+                            Ok(metadata) => {
+                                match self.download_file(&reference, &local_path, &metadata, tracker).await {
+                                    Ok(Some(metadata)) => TransferOutcome::Transferred(Box::new(metadata)),
+                                    Ok(None) => TransferOutcome::Skipped,
+                                    Err(err) => TransferOutcome::Failed(err),
+                                }
+                            }
+                            Err(err) => TransferOutcome::Failed(err),
+                        };
+                        (local_path, outcome)
+                    }
+                    // Never written; the joined path only identifies the object in the report.
+                    Err(err) => (local_dir.join(relative_path(source, &reference)), TransferOutcome::Failed(err)),
+                };
+                tracker.advance(0, true);
+                FileTransferResult {
+                    local_path,
+                    remote_path: reference.full_path().to_string(),
+                    outcome,
+                }
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+        Ok(TransferReport { results })
+    }
+
+    async fn upload_file<F>(&self, job: &FileJob, tracker: &ProgressTracker<F>) -> StorageResult<Option<ObjectMetadata>>
+    where
+        F: FnMut(TransferProgress),
+    {
+        if self.options.skip_unchanged {
+            match job.reference.get_metadata().await {
+                Ok(remote) => {
+                    if local_matches(&job.local_path, job.size, &remote).await? {
+                        tracker.advance(job.size, false);
+                        return Ok(None);
+                    }
+                }
+                Err(err) if err.status == Some(404) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let file = tokio::fs::File::open(&job.local_path)
+            .await
+            .map_err(|err| io_error(&job.local_path, err))?;
+        let mut reported = 0;
+        let metadata = job
+            .reference
+            .upload_reader_resumable_with_progress(file.compat(), job.size, self.options.metadata.clone(), |progress| {
+                tracker.advance(progress.bytes_transferred.saturating_sub(reported), false);
+                reported = reported.max(progress.bytes_transferred);
+            })
+            .await?;
+        Ok(Some(metadata))
+    }
+
+    async fn download_file<F>(
+        &self,
+        reference: &StorageReference,
+        local_path: &Path,
+        remote: &ObjectMetadata,
+        tracker: &ProgressTracker<F>,
+    ) -> StorageResult<Option<ObjectMetadata>>
+    where
+        F: FnMut(TransferProgress),
+    {
+        let size = remote.size_bytes().unwrap_or_default();
+        if self.options.skip_unchanged {
+            if let Ok(local) = tokio::fs::metadata(local_path).await {
+                if local.is_file() && local_matches(local_path, local.len(), remote).await? {
+                    tracker.advance(size, false);
+                    return Ok(None);
+                }
+            }
+        }
+
+        if let Some(parent) = local_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| io_error(parent, err))?;
+        }
+        let mut reported = 0;
+        let metadata = reference
+            .download_to_file_with_progress(local_path, |progress| {
+                tracker.advance(progress.bytes_transferred.saturating_sub(reported), false);
+                reported = reported.max(progress.bytes_transferred);
+            })
+            .await?;
+        Ok(Some(metadata))
+    }
+}
+
+/// Walks `root` and returns `(path, slash-separated relative path, size)` for every regular file.
+async fn collect_local_files(root: &Path) -> StorageResult<Vec<(PathBuf, String, u64)>> {
+    let mut pending = vec![root.to_path_buf()];
+    let mut files = Vec::new();
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await.map_err(|err| io_error(&dir, err))?;
+        while let Some(entry) = entries.next_entry().await.map_err(|err| io_error(&dir, err))? {
+            let path = entry.path();
+            let file_type = entry.file_type().await.map_err(|err| io_error(&path, err))?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let size = entry.metadata().await.map_err(|err| io_error(&path, err))?.len();
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((path, relative, size));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Lists every object beneath `source`, following nested prefixes and pagination.
+async fn collect_remote_objects(source: &StorageReference) -> StorageResult<Vec<StorageReference>> {
+    let mut pending = VecDeque::from([source.clone()]);
+    let mut items = Vec::new();
+    while let Some(prefix) = pending.pop_front() {
+        let page = prefix.list_all().await?;
+        pending.extend(page.prefixes);
+        items.extend(page.items);
+    }
+    items.sort_by(|a, b| a.full_path().cmp(b.full_path()));
+    Ok(items)
+}
+
+fn relative_path<'a>(source: &StorageReference, reference: &'a StorageReference) -> &'a str {
+    reference
+        .full_path()
+        .strip_prefix(source.full_path())
+        .unwrap_or(reference.full_path())
+}
+
+/// Maps an object to its file beneath `local_dir`. Object names are untrusted, so segments that
+/// would leave the directory (`..`, `.`, roots, drive prefixes) are rejected.
+fn local_path_for(local_dir: &Path, source: &StorageReference, reference: &StorageReference) -> StorageResult<PathBuf> {
+    let mut path = local_dir.to_path_buf();
+    for segment in relative_path(source, reference)
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+    {
+        let mut components = Path::new(segment).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => path.push(segment),
+            _ => {
+                return Err(invalid_argument(format!(
+                    "object '{}' does not map to a file inside {}",
+                    reference.full_path(),
+                    local_dir.display()
+                )));
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Returns true when the local file has the remote size and CRC32C. Objects without a reported
+/// CRC32C are never considered unchanged.
+async fn local_matches(path: &Path, size: u64, remote: &ObjectMetadata) -> StorageResult<bool> {
+    let Some(expected) = remote.crc32c.as_deref() else {
+        return Ok(false);
+    };
+    if remote.size_bytes() != Some(size) {
+        return Ok(false);
+    }
+
+    let mut file = tokio::fs::File::open(path).await.map_err(|err| io_error(path, err))?;
+    let mut hasher = ChecksumHasher::new(false);
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await.map_err(|err| io_error(path, err))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finish().crc32c == expected)
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> StorageError {
+    internal_error(format!("failed to access {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::storage::checksum::ObjectChecksums;
+    use crate::storage::error::StorageErrorCode;
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn unique_settings() -> FirebaseAppSettings {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("storage-transfer-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn build_storage(server: &MockServer) -> FirebaseStorageImpl {
+        let options = FirebaseOptions {
+            storage_bucket: Some("bucket".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let container = app.container();
+        let auth_provider = container.get_provider("auth-internal");
+        let app_check_provider = container.get_provider("app-check-internal");
+        let storage = FirebaseStorageImpl::new(app, auth_provider, app_check_provider, None, None).unwrap();
+        storage.connect_emulator("127.0.0.1", server.port(), None).unwrap();
+        storage
+    }
+
+    fn temp_dir(label: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "firebase-storage-{label}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn object_json(name: &str, body: &str) -> serde_json::Value {
+        json!({
+            "name": name,
+            "bucket": "bucket",
+            "size": body.len().to_string(),
+            "crc32c": ObjectChecksums::compute(body.as_bytes(), false).crc32c
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn upload_directory_skips_unchanged_and_reports_failures() {
+        let server = start_mock_server();
+        let dir = temp_dir("upload");
+        std::fs::write(dir.join("alpha"), "hello").unwrap();
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("beta"), "world!").unwrap();
+        std::fs::write(dir.join("gamma"), "denied").unwrap();
+
+        let _alpha = server.mock(|when, then| {
+            when.method(GET).path_contains("alpha").query_param("alt", "json");
+            then.status(200).json_body(object_json("mirror/alpha", "hello"));
+        });
+        let _beta = server.mock(|when, then| {
+            when.method(GET).path_contains("beta").query_param("alt", "json");
+            then.status(404);
+        });
+        let _gamma = server.mock(|when, then| {
+            when.method(GET).path_contains("gamma").query_param("alt", "json");
+            then.status(403);
+        });
+        let session_url = server.url("/upload/session");
+        let start = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/bucket/o")
+                .query_param("uploadType", "resumable")
+                .query_param("name", "mirror/sub/beta");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "active")
+                .header("X-Goog-Upload-URL", session_url.as_str());
+        });
+        let finalize = server.mock(|when, then| {
+            when.method(POST).path("/upload/session").body("world!");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "final")
+                .json_body(object_json("mirror/sub/beta", "world!"));
+        });
+
+        let storage = build_storage(&server).await;
+        let destination = storage.root_reference().unwrap().child("mirror");
+        let mut updates = Vec::new();
+        let report = storage
+            .transfer_manager(TransferOptions::default().with_concurrency(2))
+            .upload_directory_with_progress(&dir, &destination, |progress| updates.push(progress))
+            .await
+            .unwrap();
+
+        start.assert();
+        finalize.assert();
+        let paths: Vec<_> = report
+            .results
+            .iter()
+            .map(|result| result.remote_path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["mirror/alpha", "mirror/gamma", "mirror/sub/beta"]);
+        assert!(matches!(report.results[0].outcome, TransferOutcome::Skipped));
+        match &report.results[1].outcome {
+            TransferOutcome::Failed(err) => assert_eq!(err.status, Some(403)),
+            other => panic!("unexpected outcome: {other:?}"),
+        }
+        assert!(matches!(report.results[2].outcome, TransferOutcome::Transferred(_)));
+        assert_eq!(report.transferred_count(), 1);
+        assert_eq!(report.skipped_count(), 1);
+        assert!(!report.is_success());
+
+        let last = *updates.last().unwrap();
+        assert_eq!(last.files_total, 3);
+        assert_eq!(last.files_finished, 3);
+        assert_eq!(last.bytes_total, 17);
+        assert_eq!(last.bytes_transferred, 11);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_prefix_follows_pages_and_prefixes() {
+        let server = start_mock_server();
+        let dir = temp_dir("download");
+        std::fs::write(dir.join("alpha"), "hello").unwrap();
+
+        let _second_page = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o")
+                .query_param("prefix", "mirror/")
+                .query_param("pageToken", "next");
+            then.status(200)
+                .json_body(json!({ "items": [{ "name": "mirror/gamma", "bucket": "bucket" }] }));
+        });
+        let _first_page = server.mock(|when, then| {
+            when.method(GET).path("/v0/b/bucket/o").query_param("prefix", "mirror/");
+            then.status(200).json_body(json!({
+                "prefixes": ["mirror/sub/"],
+                "items": [{ "name": "mirror/alpha", "bucket": "bucket" }],
+                "nextPageToken": "next"
+            }));
+        });
+        let _nested = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v0/b/bucket/o")
+                .query_param("prefix", "mirror/sub/");
+            then.status(200)
+                .json_body(json!({ "items": [{ "name": "mirror/sub/beta", "bucket": "bucket" }] }));
+        });
+        for (name, body) in [("alpha", "hello"), ("beta", "world!"), ("gamma", "again")] {
+            let object = if name == "beta" {
+                format!("mirror/sub/{name}")
+            } else {
+                format!("mirror/{name}")
+            };
+            server.mock(|when, then| {
+                when.method(GET).path_contains(name).query_param("alt", "json");
+                then.status(200).json_body(object_json(&object, body));
+            });
+            server.mock(|when, then| {
+                when.method(GET).path_contains(name).query_param("alt", "media");
+                then.status(206).body(body);
+            });
+        }
+
+        let storage = build_storage(&server).await;
+        let source = storage.root_reference().unwrap().child("mirror");
+        let report = storage
+            .transfer_manager(TransferOptions::default())
+            .download_prefix(&source, &dir)
+            .await
+            .unwrap();
+
+        assert!(report.is_success());
+        assert_eq!(report.skipped_count(), 1);
+        assert_eq!(report.transferred_count(), 2);
+        assert_eq!(std::fs::read_to_string(dir.join("sub").join("beta")).unwrap(), "world!");
+        assert_eq!(std::fs::read_to_string(dir.join("gamma")).unwrap(), "again");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn download_prefix_rejects_object_names_leaving_the_directory() {
+        let server = start_mock_server();
+        let root = temp_dir("traversal");
+        let dir = root.join("target");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let _list = server.mock(|when, then| {
+            when.method(GET).path("/v0/b/bucket/o").query_param("prefix", "mirror/");
+            then.status(200).json_body(json!({
+                "items": [
+                    { "name": "mirror/../escaped", "bucket": "bucket" },
+                    { "name": "mirror/safe", "bucket": "bucket" }
+                ]
+            }));
+        });
+        for (name, body) in [("escaped", "evil"), ("safe", "fine")] {
+            server.mock(|when, then| {
+                when.method(GET).path_contains(name).query_param("alt", "json");
+                then.status(200).json_body(object_json(name, body));
+            });
+            server.mock(|when, then| {
+                when.method(GET).path_contains(name).query_param("alt", "media");
+                then.status(206).body(body);
+            });
+        }
+
+        let storage = build_storage(&server).await;
+        let source = storage.root_reference().unwrap().child("mirror");
+        let report = storage
+            .transfer_manager(TransferOptions::default())
+            .download_prefix(&source, &dir)
+            .await
+            .unwrap();
+
+        assert_eq!(report.results.len(), 2);
+        match &report.results[0].outcome {
+            TransferOutcome::Failed(err) => assert_eq!(err.code, StorageErrorCode::InvalidArgument),
+            _ => panic!("traversal object should fail"),
+        }
+        assert!(matches!(report.results[1].outcome, TransferOutcome::Transferred(_)));
+        assert!(!root.join("escaped").exists());
+        assert_eq!(std::fs::read_to_string(dir.join("safe")).unwrap(), "fine");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn upload_directory_fails_for_missing_directory() {
+        let server = start_mock_server();
+        let storage = build_storage(&server).await;
+        let destination = storage.root_reference().unwrap().child("mirror");
+        let missing = std::env::temp_dir().join("firebase-storage-transfer-missing-dir");
+        let err = storage
+            .transfer_manager(TransferOptions::default())
+            .upload_directory(&missing, &destination)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::InternalError);
+    }
+}
@@ -1,13 +1,22 @@
 use std::cmp;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
 
-use crate::storage::error::{internal_error, invalid_argument, StorageError, StorageResult};
+use async_channel::{Receiver, Sender};
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+
+use crate::platform::runtime;
+use crate::storage::error::{canceled, internal_error, StorageError, StorageResult};
 use crate::storage::metadata::serde::ObjectMetadata;
 use crate::storage::reference::StorageReference;
 use crate::storage::request::{
-    continue_resumable_upload_request, create_resumable_upload_request, multipart_upload_request,
-    RESUMABLE_UPLOAD_CHUNK_SIZE,
+    cancel_resumable_upload_request, continue_resumable_upload_request, create_resumable_upload_request,
+    multipart_upload_request, Preconditions, RESUMABLE_UPLOAD_CHUNK_SIZE,
 };
 use crate::storage::UploadMetadata;
+use crate::util::{PartialObserver, Unsubscribe};
 
 const MAX_RESUMABLE_CHUNK_SIZE: usize = 32 * 1024 * 1024;
 
@@ -16,11 +25,22 @@ const MAX_RESUMABLE_CHUNK_SIZE: usize = 32 * 1024 * 1024;
 pub enum UploadTaskState {
     Pending,
     Running,
+    Paused,
     Completed,
     Error,
     Canceled,
 }
 
+impl UploadTaskState {
+    /// Returns `true` once the task can no longer make progress.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            UploadTaskState::Completed | UploadTaskState::Error | UploadTaskState::Canceled
+        )
+    }
+}
+
 /// Progress information emitted while uploading large blobs.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct UploadProgress {
@@ -37,44 +57,364 @@ impl UploadProgress {
     }
 }
 
+/// Point-in-time view of an [`UploadTask`], mirroring the Web SDK's `UploadTaskSnapshot`.
+#[derive(Clone)]
+pub struct UploadTaskSnapshot {
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+    pub state: UploadTaskState,
+    /// Object metadata, available once the upload has completed.
+    pub metadata: Option<ObjectMetadata>,
+    pub reference: StorageReference,
+}
+
+/// Serializable description of a resumable upload session.
+///
+/// Persist it (for example as JSON) while an upload is in flight and pass it to
+/// [`StorageReference::resume_upload`](crate::storage::StorageReference::resume_upload) after a
+/// restart to continue from the last committed byte instead of starting over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumableUploadSession {
+    pub session_url: String,
+    pub bucket: String,
+    pub full_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<UploadMetadata>,
+    pub total_bytes: u64,
+    /// Bytes known to be committed when the session was exported.
+    pub offset: u64,
+}
+
+struct TaskShared {
+    reference: StorageReference,
+    total_bytes: u64,
+    inner: Mutex<TaskInner>,
+    // Written once, when the task reaches a terminal state, so accessors can hand out references.
+    metadata: OnceLock<ObjectMetadata>,
+    error: OnceLock<StorageError>,
+    wake_tx: Sender<()>,
+    wake_rx: Receiver<()>,
+}
+
+struct TaskInner {
+    state: UploadTaskState,
+    transferred: u64,
+    /// Resumable session to cancel when [`UploadTaskHandle::cancel`] is called.
+    session_url: Option<String>,
+    next_observer_id: u64,
+    observers: Vec<(u64, PartialObserver<UploadTaskSnapshot>)>,
+    streams: Vec<Sender<UploadTaskSnapshot>>,
+}
+
+/// Cloneable handle used to pause, resume, cancel or observe an [`UploadTask`] from another task.
+///
+/// [`UploadTask::run_to_completion`] consumes the task, so grab a handle with
+/// [`UploadTask::handle`] first when the upload needs to be controlled while it runs.
+#[derive(Clone)]
+pub struct UploadTaskHandle {
+    shared: Arc<TaskShared>,
+}
+
+impl UploadTaskHandle {
+    /// Returns the current snapshot of the task.
+    pub fn snapshot(&self) -> UploadTaskSnapshot {
+        let inner = self.shared.inner.lock().unwrap();
+        self.shared.snapshot_of(&inner)
+    }
+
+    /// Pauses a pending or running task. Returns `false` if the task cannot be paused.
+    ///
+    /// A chunk already in flight completes before the task stops; the driving future then waits
+    /// until [`resume`](Self::resume) or [`cancel`](Self::cancel) is called.
+    pub fn pause(&self) -> bool {
+        self.shared.transition(
+            |inner| matches!(inner.state, UploadTaskState::Pending | UploadTaskState::Running),
+            UploadTaskState::Paused,
+        )
+    }
+
+    /// Resumes a paused task. Returns `false` if the task was not paused.
+    pub fn resume(&self) -> bool {
+        let resumed = self
+            .shared
+            .transition(|inner| inner.state == UploadTaskState::Paused, UploadTaskState::Running);
+        if resumed {
+            let _ = self.shared.wake_tx.try_send(());
+        }
+        resumed
+    }
+
+    /// Cancels the task. Returns `false` if the task has already finished.
+    ///
+    /// The task moves to [`UploadTaskState::Canceled`] immediately, even when nothing is driving
+    /// it, and the resumable session (if any) is canceled in the background. A chunk already in
+    /// flight is discarded and the driving future fails with `storage/canceled`.
+    pub fn cancel(&self) -> bool {
+        let session_url = {
+            let mut inner = self.shared.inner.lock().unwrap();
+            if inner.state.is_terminal() {
+                return false;
+            }
+            inner.state = UploadTaskState::Canceled;
+            let _ = self.shared.error.set(canceled());
+            inner.session_url.clone()
+        };
+        self.shared.notify();
+        let _ = self.shared.wake_tx.try_send(());
+        if let Some(session_url) = session_url {
+            let reference = self.shared.reference.clone();
+            runtime::spawn_detached(async move { cancel_session(&reference, &session_url).await });
+        }
+        true
+    }
+
+    /// Registers an observer for `state_changed` events and returns a closure that removes it.
+    ///
+    /// The observer receives the current snapshot immediately, every progress update and state
+    /// change afterwards, and finally `complete` or `error` once the task finishes.
+    pub fn on_state_changed(&self, observer: PartialObserver<UploadTaskSnapshot>) -> Unsubscribe {
+        let (id, snapshot, error) = {
+            let mut inner = self.shared.inner.lock().unwrap();
+            let id = inner.next_observer_id;
+            inner.next_observer_id += 1;
+            let snapshot = self.shared.snapshot_of(&inner);
+            if !snapshot.state.is_terminal() {
+                inner.observers.push((id, observer.clone()));
+            }
+            (id, snapshot, self.shared.error.get().cloned())
+        };
+
+        let terminal = snapshot.state;
+        if let Some(next) = &observer.next {
+            next(&snapshot);
+        }
+        if terminal.is_terminal() {
+            notify_terminal(&observer, terminal, error.as_ref());
+        }
+
+        let shared = Arc::downgrade(&self.shared);
+        Box::new(move || {
+            if let Some(shared) = shared.upgrade() {
+                shared
+                    .inner
+                    .lock()
+                    .unwrap()
+                    .observers
+                    .retain(|(observer_id, _)| *observer_id != id);
+            }
+        })
+    }
+
+    /// Returns a stream of snapshots that ends once the task completes, fails or is canceled.
+    pub fn snapshots(&self) -> UploadTaskSnapshotStream {
+        let (tx, rx) = async_channel::unbounded();
+        let mut inner = self.shared.inner.lock().unwrap();
+        let _ = tx.try_send(self.shared.snapshot_of(&inner));
+        if !inner.state.is_terminal() {
+            inner.streams.push(tx);
+        }
+        UploadTaskSnapshotStream { inner: rx }
+    }
+}
+
+/// Stream of [`UploadTaskSnapshot`] values returned by [`UploadTaskHandle::snapshots`].
+pub struct UploadTaskSnapshotStream {
+    inner: Receiver<UploadTaskSnapshot>,
+}
+
+impl Stream for UploadTaskSnapshotStream {
+    type Item = UploadTaskSnapshot;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl TaskShared {
+    fn snapshot_of(&self, inner: &TaskInner) -> UploadTaskSnapshot {
+        UploadTaskSnapshot {
+            bytes_transferred: inner.transferred,
+            total_bytes: self.total_bytes,
+            state: inner.state,
+            metadata: self.metadata.get().cloned(),
+            reference: self.reference.clone(),
+        }
+    }
+
+    fn transition<F>(&self, allowed: F, state: UploadTaskState) -> bool
+    where
+        F: FnOnce(&TaskInner) -> bool,
+    {
+        let changed = {
+            let mut inner = self.inner.lock().unwrap();
+            if allowed(&inner) {
+                inner.state = state;
+                true
+            } else {
+                false
+            }
+        };
+        if changed {
+            self.notify();
+        }
+        changed
+    }
+
+    /// Applies `update` and notifies observers and streams with the resulting snapshot.
+    fn update<F>(&self, update: F)
+    where
+        F: FnOnce(&mut TaskInner),
+    {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            update(&mut inner);
+        }
+        self.notify();
+    }
+
+    /// Moves the task to a terminal state unless it already reached one (e.g. it was canceled
+    /// while a request was in flight). Returns `false` in that case.
+    fn finish(&self, state: UploadTaskState, transferred: u64, outcome: Result<ObjectMetadata, StorageError>) -> bool {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.state.is_terminal() {
+                return false;
+            }
+            inner.state = state;
+            inner.transferred = transferred;
+            match outcome {
+                Ok(metadata) => {
+                    let _ = self.metadata.set(metadata);
+                }
+                Err(error) => {
+                    let _ = self.error.set(error);
+                }
+            }
+        }
+        self.notify();
+        true
+    }
+
+    /// Error of a failed or canceled task.
+    fn terminal_error(&self) -> StorageError {
+        self.error
+            .get()
+            .cloned()
+            .unwrap_or_else(|| internal_error("upload task failed"))
+    }
+
+    fn notify(&self) {
+        let (snapshot, observers, error) = {
+            let mut inner = self.inner.lock().unwrap();
+            let snapshot = self.snapshot_of(&inner);
+            inner.streams.retain(|tx| tx.try_send(snapshot.clone()).is_ok());
+            let observers: Vec<_> = inner.observers.iter().map(|(_, observer)| observer.clone()).collect();
+            if snapshot.state.is_terminal() {
+                inner.observers.clear();
+                inner.streams.clear();
+            }
+            (snapshot, observers, self.error.get().cloned())
+        };
+
+        for observer in observers {
+            if let Some(next) = &observer.next {
+                next(&snapshot);
+            }
+            if snapshot.state.is_terminal() {
+                notify_terminal(&observer, snapshot.state, error.as_ref());
+            }
+        }
+    }
+}
+
+/// Cancels a resumable session. Best effort: the task is canceled locally even if the backend is
+/// unreachable.
+async fn cancel_session(reference: &StorageReference, session_url: &str) {
+    let storage = reference.storage();
+    let request = cancel_resumable_upload_request(&storage, reference.location(), session_url);
+    let _ = storage.run_upload_request(request).await;
+}
+
+fn notify_terminal(
+    observer: &PartialObserver<UploadTaskSnapshot>,
+    state: UploadTaskState,
+    error: Option<&StorageError>,
+) {
+    match (state, error) {
+        (UploadTaskState::Completed, _) => {
+            if let Some(complete) = &observer.complete {
+                complete();
+            }
+        }
+        (_, Some(error)) => {
+            if let Some(callback) = &observer.error {
+                callback(error);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Stateful helper that mirrors the Firebase Web SDK's resumable upload behaviour.
 ///
 /// A task is created via [`StorageReference::upload_bytes_resumable`](crate::storage::StorageReference::upload_bytes_resumable)
 /// and can then be polled chunk-by-chunk (`upload_next`) or allowed to run to completion (`run_to_completion`).
 /// Small payloads are uploaded with a single multipart request, whereas larger blobs utilise the resumable REST API.
+///
+/// Like the Web SDK task, uploads can be paused, resumed and canceled, and observed through
+/// [`on_state_changed`](UploadTaskHandle::on_state_changed) or a [`Stream`] of [`UploadTaskSnapshot`]s.
 pub struct UploadTask {
     reference: StorageReference,
     data: Vec<u8>,
     metadata: Option<UploadMetadata>,
     total_bytes: u64,
-    transferred: u64,
     resumable: bool,
     upload_url: Option<String>,
-    state: UploadTaskState,
-    last_error: Option<StorageError>,
-    result_metadata: Option<ObjectMetadata>,
     chunk_multiplier: usize,
+    handle: UploadTaskHandle,
 }
 
 impl UploadTask {
     pub(crate) fn new(reference: StorageReference, data: Vec<u8>, metadata: Option<UploadMetadata>) -> Self {
         let total_bytes = data.len() as u64;
         let resumable = total_bytes as usize > RESUMABLE_UPLOAD_CHUNK_SIZE;
+        let (wake_tx, wake_rx) = async_channel::bounded(1);
+        let shared = TaskShared {
+            reference: reference.clone(),
+            total_bytes,
+            inner: Mutex::new(TaskInner {
+                state: UploadTaskState::Pending,
+                transferred: 0,
+                session_url: None,
+                next_observer_id: 0,
+                observers: Vec::new(),
+                streams: Vec::new(),
+            }),
+            metadata: OnceLock::new(),
+            error: OnceLock::new(),
+            wake_tx,
+            wake_rx,
+        };
         Self {
             reference,
             data,
             metadata,
             total_bytes,
-            transferred: 0,
             resumable,
             upload_url: None,
-            state: UploadTaskState::Pending,
-            last_error: None,
-            result_metadata: None,
             chunk_multiplier: 1,
+            handle: UploadTaskHandle {
+                shared: Arc::new(shared),
+            },
         }
     }
 
+    /// Returns a cloneable handle that can control and observe the task while it runs elsewhere.
+    pub fn handle(&self) -> UploadTaskHandle {
+        self.handle.clone()
+    }
+
     /// Returns the total number of bytes that will be uploaded.
     pub fn total_bytes(&self) -> u64 {
         self.total_bytes
@@ -82,22 +422,22 @@ impl UploadTask {
 
     /// Returns the number of bytes that have been successfully uploaded so far.
     pub fn bytes_transferred(&self) -> u64 {
-        self.transferred
+        self.inner().transferred
     }
 
     /// Current task state.
     pub fn state(&self) -> UploadTaskState {
-        self.state
+        self.inner().state
     }
 
     /// Last error reported by the task, if any.
     pub fn last_error(&self) -> Option<&StorageError> {
-        self.last_error.as_ref()
+        self.handle.shared.error.get()
     }
 
     /// Resulting object metadata after a successful upload.
     pub fn metadata(&self) -> Option<&ObjectMetadata> {
-        self.result_metadata.as_ref()
+        self.handle.shared.metadata.get()
     }
 
     /// The resumable session URL, if one has been established.
@@ -105,42 +445,84 @@ impl UploadTask {
         self.upload_url.as_deref()
     }
 
+    /// Exports the resumable session so the upload can continue after a restart.
+    ///
+    /// Returns `None` until the first resumable chunk has started the session; multipart uploads
+    /// never produce one.
+    pub fn resumable_session(&self) -> Option<ResumableUploadSession> {
+        let session_url = self.upload_url.clone()?;
+        Some(ResumableUploadSession {
+            session_url,
+            bucket: self.reference.bucket().to_string(),
+            full_path: self.reference.full_path().to_string(),
+            metadata: self.metadata.clone(),
+            total_bytes: self.total_bytes,
+            offset: self.bytes_transferred(),
+        })
+    }
+
+    /// Returns the current snapshot of the task.
+    pub fn snapshot(&self) -> UploadTaskSnapshot {
+        self.handle.snapshot()
+    }
+
+    /// Pauses the task; see [`UploadTaskHandle::pause`].
+    pub fn pause(&self) -> bool {
+        self.handle.pause()
+    }
+
+    /// Resumes a paused task; see [`UploadTaskHandle::resume`].
+    pub fn resume(&self) -> bool {
+        self.handle.resume()
+    }
+
+    /// Cancels the task; see [`UploadTaskHandle::cancel`].
+    pub fn cancel(&self) -> bool {
+        self.handle.cancel()
+    }
+
+    /// Registers a `state_changed` observer; see [`UploadTaskHandle::on_state_changed`].
+    pub fn on_state_changed(&self, observer: PartialObserver<UploadTaskSnapshot>) -> Unsubscribe {
+        self.handle.on_state_changed(observer)
+    }
+
+    /// Returns a stream of snapshots; see [`UploadTaskHandle::snapshots`].
+    pub fn snapshots(&self) -> UploadTaskSnapshotStream {
+        self.handle.snapshots()
+    }
+
     /// Uploads the next chunk and invokes the provided progress callback.
     ///
     /// Returns `Ok(Some(metadata))` when the upload finishes and the remote metadata is available.
+    /// While the task is paused this waits until it is resumed or canceled.
     pub async fn upload_next_with_progress<F>(&mut self, mut progress: F) -> StorageResult<Option<ObjectMetadata>>
     where
         F: FnMut(UploadProgress),
     {
-        match self.state {
-            UploadTaskState::Completed => {
-                return Ok(self.result_metadata.clone());
-            }
-            UploadTaskState::Error => {
-                return Err(self
-                    .last_error
-                    .clone()
-                    .unwrap_or_else(|| internal_error("upload task failed")));
-            }
-            UploadTaskState::Canceled => {
-                return Err(invalid_argument("upload task was canceled"));
-            }
+        match self.state() {
+            UploadTaskState::Completed => return Ok(self.metadata().cloned()),
+            UploadTaskState::Error | UploadTaskState::Canceled => return Err(self.handle.shared.terminal_error()),
             _ => {}
         }
 
+        self.wait_until_runnable().await?;
+
         if !self.resumable {
             return self.upload_multipart(progress).await;
         }
 
-        self.ensure_resumable_session().await?;
-        self.state = UploadTaskState::Running;
+        if let Err(err) = self.ensure_resumable_session().await {
+            return self.fail(err);
+        }
+        // The session request may have raced with a pause or cancel.
+        self.wait_until_runnable().await?;
 
         let storage = self.reference.storage();
         let upload_url = self
             .upload_url
             .clone()
             .ok_or_else(|| internal_error("resumable session url missing"))?;
-        let start_offset = self.transferred;
+        let start_offset = self.bytes_transferred();
         let chunk_size = self.current_chunk_size() as u64;
         let end_offset = cmp::min(self.total_bytes, start_offset + chunk_size);
         let finalize = end_offset == self.total_bytes;
@@ -167,17 +549,20 @@ impl UploadTask {
             }
         };
 
-        self.transferred = status.current;
-        progress(UploadProgress::new(self.transferred, self.total_bytes));
+        let transferred = status.current;
+        progress(UploadProgress::new(transferred, self.total_bytes));
 
         if status.finalized {
-            let metadata = status
-                .metadata
-                .ok_or_else(|| internal_error("resumable upload completed without metadata"))?;
-            self.state = UploadTaskState::Completed;
-            self.result_metadata = Some(metadata.clone());
-            Ok(Some(metadata))
+            let metadata = match status.metadata {
+                Some(metadata) => metadata,
+                None => return self.fail(internal_error("resumable upload completed without metadata")),
+            };
+            self.complete(transferred, metadata)
         } else {
+            if self.state().is_terminal() {
+                return Err(self.handle.shared.terminal_error());
+            }
+            self.handle.shared.update(|inner| inner.transferred = transferred);
             self.bump_multiplier();
             Ok(None)
         }
@@ -206,6 +591,32 @@ impl UploadTask {
         self.run_to_completion_with_progress(|_| {}).await
     }
 
+    fn inner(&self) -> std::sync::MutexGuard<'_, TaskInner> {
+        self.handle.shared.inner.lock().unwrap()
+    }
+
+    /// Blocks while the task is paused and fails once it has been canceled.
+    async fn wait_until_runnable(&mut self) -> StorageResult<()> {
+        loop {
+            let state = self.state();
+            match state {
+                UploadTaskState::Canceled | UploadTaskState::Error => {
+                    return Err(self.handle.shared.terminal_error());
+                }
+                UploadTaskState::Paused => {
+                    let _ = self.handle.shared.wake_rx.recv().await;
+                }
+                UploadTaskState::Pending => {
+                    self.handle
+                        .shared
+                        .update(|inner| inner.state = UploadTaskState::Running);
+                    return Ok(());
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
     async fn ensure_resumable_session(&mut self) -> StorageResult<()> {
         if !self.resumable || self.upload_url.is_some() {
             return Ok(());
@@ -218,7 +629,20 @@ impl UploadTask {
             self.total_bytes,
         );
         let url = storage.run_upload_request(request).await?;
-        self.upload_url = Some(url);
+        self.upload_url = Some(url.clone());
+        // Publish the session for `cancel`, or cancel it here if that already happened.
+        let canceled = {
+            let mut inner = self.inner();
+            if inner.state == UploadTaskState::Canceled {
+                true
+            } else {
+                inner.session_url = Some(url.clone());
+                false
+            }
+        };
+        if canceled {
+            cancel_session(&self.reference, &url).await;
+        }
         Ok(())
     }
 
@@ -226,22 +650,22 @@ impl UploadTask {
     where
         F: FnMut(UploadProgress),
     {
-        if self.state == UploadTaskState::Completed {
-            return Ok(self.result_metadata.clone());
-        }
-
-        self.state = UploadTaskState::Running;
         let storage = self.reference.storage();
-        let request =
-            multipart_upload_request(&storage, self.reference.location(), self.data.clone(), self.metadata.clone());
+        let request = multipart_upload_request(
+            &storage,
+            self.reference.location(),
+            self.data.clone(),
+            self.metadata.clone(),
+            &Preconditions::default(),
+        );
 
         match storage.run_upload_request(request).await {
             Ok(metadata) => {
-                self.transferred = self.total_bytes;
-                self.state = UploadTaskState::Completed;
-                self.result_metadata = Some(metadata.clone());
-                progress(UploadProgress::new(self.transferred, self.total_bytes));
-                Ok(Some(metadata))
+                let result = self.complete(self.total_bytes, metadata);
+                if result.is_ok() {
+                    progress(UploadProgress::new(self.total_bytes, self.total_bytes));
+                }
+                result
             }
             Err(err) => self.fail(err),
         }
@@ -262,9 +686,368 @@ impl UploadTask {
         self.chunk_multiplier = 1;
     }
 
+    fn complete(&mut self, transferred: u64, metadata: ObjectMetadata) -> StorageResult<Option<ObjectMetadata>> {
+        let shared = &self.handle.shared;
+        if shared.finish(UploadTaskState::Completed, transferred, Ok(metadata.clone())) {
+            Ok(Some(metadata))
+        } else {
+            Err(shared.terminal_error())
+        }
+    }
+
     fn fail<T>(&mut self, error: StorageError) -> StorageResult<T> {
-        self.state = UploadTaskState::Error;
-        self.last_error = Some(error.clone());
-        Err(error)
+        let shared = &self.handle.shared;
+        let transferred = self.bytes_transferred();
+        shared.finish(UploadTaskState::Error, transferred, Err(error));
+        Err(shared.terminal_error())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::app::initialize_app;
+    use crate::app::{FirebaseAppSettings, FirebaseOptions};
+    use crate::storage::checksum::IntegrityOptions;
+    use crate::storage::error::StorageErrorCode;
+    use crate::storage::service::FirebaseStorageImpl;
+    use crate::test_support::start_mock_server;
+    use futures::StreamExt;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn unique_settings() -> FirebaseAppSettings {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        FirebaseAppSettings {
+            name: Some(format!("storage-upload-{}", COUNTER.fetch_add(1, Ordering::SeqCst))),
+            ..Default::default()
+        }
+    }
+
+    async fn build_reference(server: &MockServer) -> StorageReference {
+        let options = FirebaseOptions {
+            storage_bucket: Some("bucket".into()),
+            ..Default::default()
+        };
+        let app = initialize_app(options, Some(unique_settings())).await.unwrap();
+        let container = app.container();
+        let auth_provider = container.get_provider("auth-internal");
+        let app_check_provider = container.get_provider("app-check-internal");
+        let storage = FirebaseStorageImpl::new(app, auth_provider, app_check_provider, None, None).unwrap();
+        storage.connect_emulator("127.0.0.1", server.port(), None).unwrap();
+        storage.root_reference().unwrap().child("uploads/file.bin")
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pause_resume_and_cancel_resumable_upload() {
+        let server = start_mock_server();
+        let session_url = server.url("/upload/session");
+        let start_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/bucket/o")
+                .query_param("uploadType", "resumable");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "active")
+                .header("X-Goog-Upload-URL", session_url.as_str());
+        });
+        let chunk_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "upload");
+            then.status(200).header("X-Goog-Upload-Status", "active");
+        });
+        let cancel_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "cancel");
+            then.status(200);
+        });
+
+        let reference = build_reference(&server).await;
+        let mut task = reference
+            .upload_bytes_resumable(vec![7_u8; RESUMABLE_UPLOAD_CHUNK_SIZE * 3], None)
+            .unwrap();
+
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let observer = {
+            let states = states.clone();
+            let errors = errors.clone();
+            PartialObserver::new()
+                .with_next(move |snapshot: &UploadTaskSnapshot| states.lock().unwrap().push(snapshot.state))
+                .with_error(move |err| errors.lock().unwrap().push(err.to_string()))
+        };
+        let _unsubscribe = task.on_state_changed(observer);
+
+        assert!(task.upload_next().await.unwrap().is_none());
+        assert_eq!(task.bytes_transferred(), RESUMABLE_UPLOAD_CHUNK_SIZE as u64);
+
+        let session = task.resumable_session().expect("session established");
+        assert_eq!(session.offset, RESUMABLE_UPLOAD_CHUNK_SIZE as u64);
+        assert_eq!(session.full_path, "uploads/file.bin");
+        let restored: ResumableUploadSession = serde_json::from_str(&serde_json::to_string(&session).unwrap()).unwrap();
+        assert_eq!(restored, session);
+
+        assert!(task.pause());
+        assert!(!task.pause());
+        assert_eq!(task.snapshot().state, UploadTaskState::Paused);
+        assert!(task.resume());
+        assert_eq!(task.state(), UploadTaskState::Running);
+
+        assert!(task.cancel());
+        assert_eq!(task.state(), UploadTaskState::Canceled);
+        assert_eq!(task.last_error().map(|err| &err.code), Some(&StorageErrorCode::Canceled));
+        let err = task.upload_next().await.unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::Canceled);
+        assert!(!task.cancel());
+
+        wait_for_hits(&cancel_mock, 1).await;
+        start_mock.assert();
+        chunk_mock.assert_hits(1);
+        cancel_mock.assert();
+
+        let states = states.lock().unwrap().clone();
+        assert_eq!(states.first(), Some(&UploadTaskState::Pending));
+        assert!(states.contains(&UploadTaskState::Paused));
+        assert_eq!(states.last(), Some(&UploadTaskState::Canceled));
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+
+    async fn wait_for_hits(mock: &httpmock::Mock<'_>, hits: usize) {
+        for _ in 0..200 {
+            if mock.hits() >= hits {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn cancel_without_driver_cancels_session() {
+        let server = start_mock_server();
+        let session_url = server.url("/upload/session");
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/bucket/o")
+                .query_param("uploadType", "resumable");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "active")
+                .header("X-Goog-Upload-URL", session_url.as_str());
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "upload");
+            then.status(200).header("X-Goog-Upload-Status", "active");
+        });
+        let cancel_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "cancel");
+            then.status(200);
+        });
+
+        let reference = build_reference(&server).await;
+        let mut task = reference
+            .upload_bytes_resumable(vec![7_u8; RESUMABLE_UPLOAD_CHUNK_SIZE * 2], None)
+            .unwrap();
+        assert!(task.upload_next().await.unwrap().is_none());
+        let handle = task.handle();
+        let snapshots = handle.snapshots();
+        drop(task);
+
+        assert!(handle.cancel());
+        assert_eq!(handle.snapshot().state, UploadTaskState::Canceled);
+        assert!(!handle.cancel());
+        let states: Vec<_> = snapshots.map(|snapshot| snapshot.state).collect().await;
+        assert_eq!(states.last(), Some(&UploadTaskState::Canceled));
+
+        wait_for_hits(&cancel_mock, 1).await;
+        cancel_mock.assert();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn paused_task_waits_for_resume_and_streams_snapshots() {
+        let server = start_mock_server();
+        let upload_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/bucket/o")
+                .query_param("uploadType", "multipart");
+            then.status(200)
+                .json_body(json!({ "name": "uploads/file.bin", "bucket": "bucket", "size": "3" }));
+        });
+
+        let reference = build_reference(&server).await;
+        let task = reference.upload_bytes_resumable(vec![1_u8, 2, 3], None).unwrap();
+        let handle = task.handle();
+        let snapshots = handle.snapshots();
+        assert!(handle.pause());
+
+        let upload = tokio::spawn(task.run_to_completion());
+        tokio::task::yield_now().await;
+        assert!(!upload.is_finished());
+        assert_eq!(handle.snapshot().state, UploadTaskState::Paused);
+
+        assert!(handle.resume());
+        let metadata = upload.await.unwrap().unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("uploads/file.bin"));
+        upload_mock.assert();
+
+        let states: Vec<_> = snapshots.map(|snapshot| snapshot.state).collect().await;
+        assert_eq!(
+            states,
+            vec![
+                UploadTaskState::Pending,
+                UploadTaskState::Paused,
+                UploadTaskState::Running,
+                UploadTaskState::Completed
+            ]
+        );
+        let snapshot = handle.snapshot();
+        assert_eq!(snapshot.bytes_transferred, 3);
+        assert!(snapshot.metadata.is_some());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn resume_upload_continues_from_committed_offset() {
+        let server = start_mock_server();
+        let total = RESUMABLE_UPLOAD_CHUNK_SIZE as u64 + 100;
+        let query_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "query");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "active")
+                .header("X-Goog-Upload-Size-Received", RESUMABLE_UPLOAD_CHUNK_SIZE.to_string());
+        });
+        let finalize_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "upload, finalize")
+                .header("X-Goog-Upload-Offset", RESUMABLE_UPLOAD_CHUNK_SIZE.to_string());
+            then.status(200)
+                .header("X-Goog-Upload-Status", "final")
+                .json_body(json!({ "name": "uploads/file.bin", "bucket": "bucket" }));
+        });
+
+        let reference = build_reference(&server).await;
+        let session = ResumableUploadSession {
+            session_url: server.url("/upload/session"),
+            bucket: "bucket".into(),
+            full_path: "uploads/file.bin".into(),
+            metadata: None,
+            total_bytes: total,
+            offset: 0,
+        };
+        let reader = futures::io::Cursor::new(vec![5_u8; total as usize]);
+        let mut reported = Vec::new();
+        let metadata = reference
+            .resume_upload_with_progress(&session, reader, |progress| reported.push(progress.bytes_transferred))
+            .await
+            .unwrap();
+
+        query_mock.assert();
+        finalize_mock.assert();
+        assert_eq!(metadata.name.as_deref(), Some("uploads/file.bin"));
+        assert_eq!(reported, vec![RESUMABLE_UPLOAD_CHUNK_SIZE as u64, total]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn resume_upload_reports_expired_sessions() {
+        let server = start_mock_server();
+        let query_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "query");
+            then.status(404);
+        });
+
+        let reference = build_reference(&server).await;
+        let session = ResumableUploadSession {
+            session_url: server.url("/upload/session"),
+            bucket: "bucket".into(),
+            full_path: "uploads/file.bin".into(),
+            metadata: None,
+            total_bytes: 10,
+            offset: 4,
+        };
+        let err = reference
+            .resume_upload(&session, futures::io::Cursor::new(vec![0_u8; 10]))
+            .await
+            .unwrap_err();
+        query_mock.assert();
+        assert_eq!(err.code, StorageErrorCode::UploadSessionExpired);
+
+        let other = reference.root().child("elsewhere.bin");
+        let err = other
+            .resume_upload(&session, futures::io::Cursor::new(vec![0_u8; 10]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, StorageErrorCode::InvalidArgument);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn upload_bytes_sends_client_hashes_and_detects_mismatch() {
+        let server = start_mock_server();
+        let upload_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/bucket/o")
+                .query_param("uploadType", "multipart")
+                .body_contains("\"crc32c\":\"4waSgw==\"");
+            then.status(200).json_body(json!({
+                "name": "uploads/file.bin",
+                "bucket": "bucket",
+                "size": "9",
+                "crc32c": "AAAAAA=="
+            }));
+        });
+
+        let reference = build_reference(&server).await;
+        let storage = reference.storage();
+        storage.set_integrity_options(IntegrityOptions::default().with_client_hashes(true));
+        let err = reference.upload_bytes(b"123456789".to_vec(), None).await.unwrap_err();
+        upload_mock.assert();
+        assert_eq!(err.code, StorageErrorCode::ChecksumMismatch);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn streamed_upload_hashes_final_chunk() {
+        let server = start_mock_server();
+        let session_url = server.url("/upload/session");
+        let _start_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v0/b/bucket/o")
+                .query_param("uploadType", "resumable");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "active")
+                .header("X-Goog-Upload-URL", session_url.as_str());
+        });
+        let finalize_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/upload/session")
+                .header("X-Goog-Upload-Command", "upload, finalize")
+                .header("X-Goog-Hash", "crc32c=4waSgw==,md5=JfnnlDI7RTiF9RgfG2JNCw==");
+            then.status(200)
+                .header("X-Goog-Upload-Status", "final")
+                .json_body(json!({
+                    "name": "uploads/file.bin",
+                    "bucket": "bucket",
+                    "size": "9",
+                    "crc32c": "4waSgw==",
+                    "md5Hash": "JfnnlDI7RTiF9RgfG2JNCw=="
+                }));
+        });
+
+        let reference = build_reference(&server).await;
+        reference
+            .storage()
+            .set_integrity_options(IntegrityOptions::default().with_md5(true).with_client_hashes(true));
+        let metadata = reference
+            .upload_reader_resumable(futures::io::Cursor::new(b"123456789".to_vec()), 9, None)
+            .await
+            .unwrap();
+        finalize_mock.assert();
+        assert_eq!(metadata.crc32c.as_deref(), Some("4waSgw=="));
     }
 }
@@ -1,3 +1,5 @@
+use rand::Rng;
+
 pub fn is_url(path: &str) -> bool {
     if let Some(index) = path.find("://") {
         path[..index].chars().all(|ch| ch.is_ascii_alphabetic()) && index > 0
@@ -10,6 +12,13 @@ pub fn is_retry_status_code(status: u16, additional: &[u16]) -> bool {
     (500..600).contains(&status) || matches!(status, 408 | 429) || additional.contains(&status)
 }
 
+/// Generates a random UUID-formatted token, matching the tokens issued by Firebase Storage.
+pub(crate) fn generate_download_token() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -29,4 +38,12 @@ mod tests {
         assert!(!is_retry_status_code(404, &[]));
         assert!(is_retry_status_code(499, &[499]));
     }
+
+    #[test]
+    fn download_tokens_are_uuid_shaped() {
+        let token = generate_download_token();
+        let lengths: Vec<_> = token.split('-').map(str::len).collect();
+        assert_eq!(lengths, vec![8, 4, 4, 4, 12]);
+        assert_ne!(token, generate_download_token());
+    }
 }
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use reqwest::header::CACHE_CONTROL;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{BigUint, RsaPublicKey};
+use serde::Deserialize;
+use serde_json::Value;
+use x509_cert::der::{DecodePem, Encode};
+use x509_cert::Certificate;
+
+use crate::util::base64::base64_decode_bytes;
+use crate::util::jwt::TokenVerificationError;
+
+/// RSA public keys indexed by their key ID (`kid`).
+#[derive(Debug, Clone, Default)]
+pub struct PublicKeySet {
+    keys: HashMap<String, RsaPublicKey>,
+}
+
+#[derive(Deserialize)]
+struct JwksDocument {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kid: Option<String>,
+    kty: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+impl PublicKeySet {
+    /// Creates an empty key set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces the key registered under `kid`.
+    pub fn insert(&mut self, kid: impl Into<String>, key: RsaPublicKey) {
+        self.keys.insert(kid.into(), key);
+    }
+
+    /// Returns the key registered under `kid`.
+    pub fn get(&self, kid: &str) -> Option<&RsaPublicKey> {
+        self.keys.get(kid)
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` when the set holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Parses either a JWKS document (`{"keys": [...]}`) or a `kid` to PEM certificate map.
+    ///
+    /// Google publishes Firebase ID token keys in both shapes; App Check only offers JWKS.
+    pub fn parse(body: &str) -> Result<Self, TokenVerificationError> {
+        let value: Value =
+            serde_json::from_str(body).map_err(|err| TokenVerificationError::KeyFetch(err.to_string()))?;
+        if value.get("keys").is_some() {
+            Self::from_jwks(value)
+        } else {
+            Self::from_x509_map(value)
+        }
+    }
+
+    fn from_jwks(value: Value) -> Result<Self, TokenVerificationError> {
+        let document: JwksDocument =
+            serde_json::from_value(value).map_err(|err| TokenVerificationError::KeyFetch(err.to_string()))?;
+        let mut set = Self::new();
+        for jwk in document.keys {
+            if jwk.kty.as_deref().is_some_and(|kty| kty != "RSA") {
+                continue;
+            }
+            let (Some(kid), Some(n), Some(e)) = (jwk.kid, jwk.n, jwk.e) else {
+                continue;
+            };
+            let n = base64_decode_bytes(&n)
+                .map_err(|_| TokenVerificationError::KeyFetch(format!("invalid modulus for key {kid}")))?;
+            let e = base64_decode_bytes(&e)
+                .map_err(|_| TokenVerificationError::KeyFetch(format!("invalid exponent for key {kid}")))?;
+            let key = RsaPublicKey::new(BigUint::from_bytes_be(&n), BigUint::from_bytes_be(&e))
+                .map_err(|err| TokenVerificationError::KeyFetch(format!("invalid RSA key {kid}: {err}")))?;
+            set.insert(kid, key);
+        }
+        Ok(set)
+    }
+
+    fn from_x509_map(value: Value) -> Result<Self, TokenVerificationError> {
+        let Value::Object(entries) = value else {
+            return Err(TokenVerificationError::KeyFetch(
+                "expected a JWKS document or a map of certificates".into(),
+            ));
+        };
+        let mut set = Self::new();
+        for (kid, pem) in entries {
+            let pem = pem
+                .as_str()
+                .ok_or_else(|| TokenVerificationError::KeyFetch(format!("certificate {kid} is not a string")))?;
+            let key = public_key_from_certificate(pem)
+                .map_err(|err| TokenVerificationError::KeyFetch(format!("certificate {kid}: {err}")))?;
+            set.insert(kid, key);
+        }
+        Ok(set)
+    }
+}
+
+fn public_key_from_certificate(pem: &str) -> Result<RsaPublicKey, String> {
+    let certificate = Certificate::from_pem(pem.as_bytes()).map_err(|err| err.to_string())?;
+    let spki = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|err| err.to_string())?;
+    RsaPublicKey::from_public_key_der(&spki).map_err(|err| err.to_string())
+}
+
+/// Supplies the public keys used to verify signed tokens.
+///
+/// The default [`HttpPublicKeySource`] downloads Google's published keys; tests and offline
+/// deployments can inject a fixed [`PublicKeySet`] instead.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait PublicKeySource: Send + Sync {
+    async fn public_keys(&self) -> Result<PublicKeySet, TokenVerificationError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl PublicKeySource for PublicKeySet {
+    async fn public_keys(&self) -> Result<PublicKeySet, TokenVerificationError> {
+        Ok(self.clone())
+    }
+}
+
+/// Downloads a JWKS or x509 key document and caches it for the `Cache-Control` max-age.
+pub struct HttpPublicKeySource {
+    url: String,
+    client: reqwest::Client,
+    cache: Mutex<Option<CachedKeys>>,
+}
+
+struct CachedKeys {
+    keys: PublicKeySet,
+    expires_at: SystemTime,
+}
+
+impl HttpPublicKeySource {
+    /// Creates a source that fetches keys from `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Returns the URL the keys are fetched from.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn cached(&self) -> Option<PublicKeySet> {
+        let cache = self.cache.lock().unwrap();
+        cache
+            .as_ref()
+            .filter(|entry| entry.expires_at > SystemTime::now())
+            .map(|entry| entry.keys.clone())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl PublicKeySource for HttpPublicKeySource {
+    async fn public_keys(&self) -> Result<PublicKeySet, TokenVerificationError> {
+        if let Some(keys) = self.cached() {
+            return Ok(keys);
+        }
+
+        let response = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(|err| TokenVerificationError::KeyFetch(err.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(TokenVerificationError::KeyFetch(format!("{} returned HTTP {status}", self.url)));
+        }
+        let max_age = response
+            .headers()
+            .get(CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_max_age);
+        let body = response
+            .text()
+            .await
+            .map_err(|err| TokenVerificationError::KeyFetch(err.to_string()))?;
+        let keys = PublicKeySet::parse(&body)?;
+
+        if let Some(max_age) = max_age {
+            if let Some(expires_at) = SystemTime::now().checked_add(max_age) {
+                *self.cache.lock().unwrap() = Some(CachedKeys {
+                    keys: keys.clone(),
+                    expires_at,
+                });
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// Extracts the `max-age` directive from a `Cache-Control` header value.
+pub fn parse_max_age(header: &str) -> Option<Duration> {
+    header.split(',').find_map(|directive| {
+        let (name, value) = directive.trim().split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("max-age") {
+            value
+                .trim()
+                .trim_matches('"')
+                .parse::<u64>()
+                .ok()
+                .map(Duration::from_secs)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod test_keys {
+    use super::*;
+    use crate::util::base64::base64_url_encode_trimmed;
+    use rsa::pkcs1v15::SigningKey;
+    use rsa::signature::{SignatureEncoding, Signer};
+    use rsa::RsaPrivateKey;
+    use sha2::Sha256;
+
+    /// Generates a throwaway RSA key pair; 1024 bits keeps debug-mode tests fast.
+    pub(crate) fn test_key_pair() -> (RsaPrivateKey, RsaPublicKey) {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).expect("generate test key");
+        let public_key = private_key.to_public_key();
+        (private_key, public_key)
+    }
+
+    /// Signs `claims` as an RS256 JWT with the given `kid`.
+    pub(crate) fn sign_rs256(private_key: &RsaPrivateKey, kid: &str, claims: &Value) -> String {
+        let header = serde_json::json!({"alg": "RS256", "typ": "JWT", "kid": kid});
+        let signing_input = format!(
+            "{}.{}",
+            base64_url_encode_trimmed(&header.to_string()),
+            base64_url_encode_trimmed(&claims.to_string())
+        );
+        let signature = SigningKey::<Sha256>::new(private_key.clone()).sign(signing_input.as_bytes());
+        let encoded = crate::util::base64::base64_url_encode_bytes(&signature.to_bytes());
+        format!("{signing_input}.{}", encoded.trim_end_matches('.'))
+    }
+
+    /// Encodes `key` as a JWK entry.
+    pub(crate) fn jwk(kid: &str, key: &RsaPublicKey) -> Value {
+        use rsa::traits::PublicKeyParts;
+        let n = crate::util::base64::base64_url_encode_bytes(&key.n().to_bytes_be());
+        let e = crate::util::base64::base64_url_encode_bytes(&key.e().to_bytes_be());
+        serde_json::json!({
+            "kid": kid,
+            "kty": "RSA",
+            "alg": "RS256",
+            "use": "sig",
+            "n": n.trim_end_matches('.'),
+            "e": e.trim_end_matches('.'),
+        })
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::test_keys::{jwk, test_key_pair};
+    use super::*;
+    use crate::test_support::start_mock_server;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_max_age_reads_directive() {
+        assert_eq!(
+            parse_max_age("public, max-age=19302, must-revalidate, no-transform"),
+            Some(Duration::from_secs(19302))
+        );
+        assert_eq!(parse_max_age("no-cache"), None);
+    }
+
+    #[test]
+    fn parse_reads_jwks_documents() {
+        let (_, public_key) = test_key_pair();
+        let body = json!({"keys": [jwk("a", &public_key), {"kid": "ec", "kty": "EC"}]}).to_string();
+        let set = PublicKeySet::parse(&body).unwrap();
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.get("a"), Some(&public_key));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_certificates() {
+        let body = json!({"kid": "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n"}).to_string();
+        assert!(matches!(PublicKeySet::parse(&body), Err(TokenVerificationError::KeyFetch(_))));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn http_source_caches_for_max_age() {
+        let server = start_mock_server();
+        let (_, public_key) = test_key_pair();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/jwks");
+            then.status(200)
+                .header("cache-control", "public, max-age=3600")
+                .json_body(json!({"keys": [jwk("a", &public_key)]}));
+        });
+
+        let source = HttpPublicKeySource::new(server.url("/jwks"));
+        assert_eq!(source.public_keys().await.unwrap().len(), 1);
+        assert_eq!(source.public_keys().await.unwrap().len(), 1);
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn http_source_refetches_without_max_age() {
+        let server = start_mock_server();
+        let (_, public_key) = test_key_pair();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/jwks");
+            then.status(200).json_body(json!({"keys": [jwk("a", &public_key)]}));
+        });
+
+        let source = HttpPublicKeySource::new(server.url("/jwks"));
+        source.public_keys().await.unwrap();
+        source.public_keys().await.unwrap();
+        mock.assert_hits(2);
+    }
+}
@@ -1,6 +1,11 @@
-use crate::util::base64::base64_decode;
+use crate::util::base64::{base64_decode, base64_decode_bytes};
 use crate::util::json::json_eval;
+use crate::util::jwks::PublicKeySet;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::signature::Verifier;
 use serde_json::{Map, Value};
+use sha2::Sha256;
+use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Default)]
@@ -75,6 +80,178 @@ pub fn is_admin_token(token: &str) -> bool {
     }
 }
 
+/// Reasons a signed JWT can be rejected by [`verify_rs256`] or a service-specific verifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenVerificationError {
+    /// The token is not a three-part JWT with JSON header and claims.
+    Malformed(String),
+    /// The header declares an algorithm other than `RS256`.
+    UnsupportedAlgorithm(String),
+    /// The header carries no `kid`, so no public key can be selected.
+    MissingKeyId,
+    /// No public key matches the header `kid`.
+    UnknownKeyId(String),
+    /// The RS256 signature does not match the token contents.
+    InvalidSignature,
+    /// The `exp` claim lies in the past.
+    Expired { expired_at: i64 },
+    /// The `iat` (or `auth_time`) claim lies in the future.
+    IssuedInFuture { claim: &'static str, issued_at: i64 },
+    /// A registered claim is missing or has an unexpected value.
+    InvalidClaim { claim: &'static str, message: String },
+    /// The public keys could not be fetched or parsed.
+    KeyFetch(String),
+}
+
+impl fmt::Display for TokenVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenVerificationError::Malformed(message) => write!(f, "Malformed token: {message}"),
+            TokenVerificationError::UnsupportedAlgorithm(alg) => {
+                write!(f, "Unsupported token algorithm \"{alg}\"; expected RS256")
+            }
+            TokenVerificationError::MissingKeyId => write!(f, "Token header has no \"kid\" claim"),
+            TokenVerificationError::UnknownKeyId(kid) => write!(f, "No public key found for kid \"{kid}\""),
+            TokenVerificationError::InvalidSignature => write!(f, "Token signature is invalid"),
+            TokenVerificationError::Expired { expired_at } => write!(f, "Token expired at {expired_at}"),
+            TokenVerificationError::IssuedInFuture { claim, issued_at } => {
+                write!(f, "Token \"{claim}\" claim ({issued_at}) is in the future")
+            }
+            TokenVerificationError::InvalidClaim { claim, message } => {
+                write!(f, "Invalid \"{claim}\" claim: {message}")
+            }
+            TokenVerificationError::KeyFetch(message) => write!(f, "Failed to load public keys: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TokenVerificationError {}
+
+impl TokenVerificationError {
+    pub(crate) fn invalid_claim(claim: &'static str, message: impl Into<String>) -> Self {
+        TokenVerificationError::InvalidClaim {
+            claim,
+            message: message.into(),
+        }
+    }
+}
+
+/// Header and claims of a decoded JWT.
+#[derive(Debug, Clone)]
+pub struct VerifiedJwt {
+    pub header: Map<String, Value>,
+    pub claims: Map<String, Value>,
+}
+
+/// Checks the RS256 signature of `token` against the key named by its `kid` header.
+///
+/// Only the signature and header are validated; callers apply their own claim rules.
+pub fn verify_rs256(token: &str, keys: &PublicKeySet) -> Result<VerifiedJwt, TokenVerificationError> {
+    let (header_part, claims_part, signature_part) = split_segments(token)?;
+    let header = decode_object(header_part, "header")?;
+    let claims = decode_object(claims_part, "claims")?;
+
+    match header.get("alg").and_then(Value::as_str) {
+        Some("RS256") => {}
+        Some(other) => return Err(TokenVerificationError::UnsupportedAlgorithm(other.to_string())),
+        None => return Err(TokenVerificationError::UnsupportedAlgorithm(String::new())),
+    }
+
+    let kid = header
+        .get("kid")
+        .and_then(Value::as_str)
+        .ok_or(TokenVerificationError::MissingKeyId)?;
+    let key = keys
+        .get(kid)
+        .ok_or_else(|| TokenVerificationError::UnknownKeyId(kid.to_string()))?;
+
+    let signature_bytes = base64_decode_bytes(signature_part)
+        .map_err(|_| TokenVerificationError::Malformed("signature is not valid base64url".into()))?;
+    let signature =
+        Signature::try_from(signature_bytes.as_slice()).map_err(|_| TokenVerificationError::InvalidSignature)?;
+    let signed_content = &token[..header_part.len() + 1 + claims_part.len()];
+    VerifyingKey::<Sha256>::new(key.clone())
+        .verify(signed_content.as_bytes(), &signature)
+        .map_err(|_| TokenVerificationError::InvalidSignature)?;
+
+    Ok(VerifiedJwt { header, claims })
+}
+
+/// Decodes `token` without checking its signature, as required for Auth Emulator tokens.
+///
+/// Emulator-issued tokens use `alg: "none"` and an empty signature segment; never use this for
+/// tokens from production backends.
+pub fn decode_unsigned(token: &str) -> Result<VerifiedJwt, TokenVerificationError> {
+    let (header_part, claims_part, _) = split_segments(token)?;
+    Ok(VerifiedJwt {
+        header: decode_object(header_part, "header")?,
+        claims: decode_object(claims_part, "claims")?,
+    })
+}
+
+fn split_segments(token: &str) -> Result<(&str, &str, &str), TokenVerificationError> {
+    let mut parts = token.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(claims), Some(signature), None) => Ok((header, claims, signature)),
+        _ => Err(TokenVerificationError::Malformed(
+            "expected three dot-separated segments".into(),
+        )),
+    }
+}
+
+pub(crate) fn now_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+pub(crate) fn string_claim<'a>(
+    claims: &'a Map<String, Value>,
+    claim: &'static str,
+) -> Result<&'a str, TokenVerificationError> {
+    claims
+        .get(claim)
+        .and_then(Value::as_str)
+        .ok_or_else(|| TokenVerificationError::invalid_claim(claim, "missing or not a string"))
+}
+
+/// Rejects expired tokens and tokens whose `issued_claims` lie in the future, allowing `skew` seconds.
+pub(crate) fn check_time_claims(
+    claims: &Map<String, Value>,
+    now: i64,
+    skew: i64,
+    issued_claims: &[&'static str],
+) -> Result<(), TokenVerificationError> {
+    let exp = integer_claim(claims, "exp")?;
+    if exp + skew <= now {
+        return Err(TokenVerificationError::Expired { expired_at: exp });
+    }
+    for claim in issued_claims {
+        let issued_at = integer_claim(claims, claim)?;
+        if issued_at - skew > now {
+            return Err(TokenVerificationError::IssuedInFuture { claim, issued_at });
+        }
+    }
+    Ok(())
+}
+
+fn integer_claim(claims: &Map<String, Value>, claim: &'static str) -> Result<i64, TokenVerificationError> {
+    claims
+        .get(claim)
+        .and_then(value_as_i64)
+        .ok_or_else(|| TokenVerificationError::invalid_claim(claim, "missing or not a number"))
+}
+
+fn decode_object(part: &str, name: &str) -> Result<Map<String, Value>, TokenVerificationError> {
+    let decoded =
+        base64_decode(part).map_err(|_| TokenVerificationError::Malformed(format!("{name} is not valid base64url")))?;
+    match json_eval::<Value>(&decoded) {
+        Ok(Value::Object(map)) => Ok(map),
+        _ => Err(TokenVerificationError::Malformed(format!("{name} is not a JSON object"))),
+    }
+}
+
 fn decode_part(part: &str) -> Value {
     if part.is_empty() {
         return Value::Object(Map::new());
@@ -130,6 +307,38 @@ mod tests {
         assert!(!is_valid_format(&token));
     }
 
+    #[test]
+    fn verify_rs256_checks_signature_and_kid() {
+        use crate::util::jwks::test_keys::{sign_rs256, test_key_pair};
+
+        let (private_key, public_key) = test_key_pair();
+        let mut keys = PublicKeySet::new();
+        keys.insert("key-1", public_key);
+
+        let token = sign_rs256(&private_key, "key-1", &json!({"sub": "abc"}));
+        let verified = verify_rs256(&token, &keys).expect("signature should verify");
+        assert_eq!(verified.claims["sub"], json!("abc"));
+
+        let mut tampered = token.clone();
+        tampered.replace_range(tampered.len() - 4.., "AAAA");
+        assert_eq!(
+            verify_rs256(&tampered, &keys).unwrap_err(),
+            TokenVerificationError::InvalidSignature
+        );
+
+        let unknown = sign_rs256(&private_key, "key-2", &json!({"sub": "abc"}));
+        assert_eq!(
+            verify_rs256(&unknown, &keys).unwrap_err(),
+            TokenVerificationError::UnknownKeyId("key-2".into())
+        );
+
+        let unsigned = build_token(&json!({"sub": "abc"}));
+        assert!(matches!(
+            verify_rs256(&unsigned, &keys),
+            Err(TokenVerificationError::UnsupportedAlgorithm(alg)) if alg == "none"
+        ));
+    }
+
     #[test]
     fn admin_detection() {
         let token = build_token(&json!({"iat": 1, "admin": true}));
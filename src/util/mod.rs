@@ -8,6 +8,7 @@ pub mod environment;
 pub mod errors;
 pub mod formatters;
 pub mod json;
+pub mod jwks;
 pub mod jwt;
 pub mod obj;
 pub mod runtime;
@@ -30,9 +31,11 @@ pub use environment::{
 pub use errors::{ErrorData, ErrorFactory, ErrorMap, FirebaseError};
 pub use formatters::ordinal;
 pub use json::{json_eval, stringify};
+pub use jwks::{HttpPublicKeySource, PublicKeySet, PublicKeySource};
 pub use jwt::{
-    decode_jwt, is_admin_token, is_valid_format as jwt_is_valid_format, is_valid_timestamp as jwt_is_valid_timestamp,
-    issued_at_time as jwt_issued_at_time, DecodedToken,
+    decode_jwt, decode_unsigned, is_admin_token, is_valid_format as jwt_is_valid_format,
+    is_valid_timestamp as jwt_is_valid_timestamp, issued_at_time as jwt_issued_at_time, verify_rs256, DecodedToken,
+    TokenVerificationError, VerifiedJwt,
 };
 pub use obj::{deep_equal, is_empty, map_values};
 pub use runtime::block_on;